@@ -1,7 +1,14 @@
 use quote::ToTokens;
-use syn::{spanned::Spanned, LitBool, LitInt, Pat, PatType};
+use syn::{
+    ext::IdentExt, spanned::Spanned, Attribute, ExprLit, Lit, LitBool, Pat, PatType, ReturnType,
+    Visibility, WhereClause,
+};
 
-use crate::parsing::{OapiOptions, Responses, Security, StrArray};
+use crate::parsing::{
+    Accepts, Aliases, Examples, ExprArray, Extensions, Hooks, Idempotency, InjectedArgs, Localized,
+    MultipartFields, OapiOptions, ParamDocs, ParamOptions, Parameters, PathPattern, PathStruct,
+    RequestBody, Responses, RouteOptions, Security, Sitemap, StrArray, Transform, TypeArray,
+};
 
 use self::parsing::PathParam;
 
@@ -11,61 +18,93 @@ pub struct CompiledRoute {
     pub method: Method,
     #[allow(clippy::type_complexity)]
     pub path_params: Vec<(Slash, PathParam)>,
+    /// Parameters with a type in the route, but without a function argument.
+    pub unused_params: Vec<Ident>,
     pub query_params: Vec<(Ident, Box<Type>)>,
     pub state: Type,
     pub route_lit: LitStr,
+    pub options: RouteOptions,
     pub oapi_options: Option<OapiOptions>,
+    /// The indices of the arguments annotated with `#[api_ignore]`, and of the `Extension<T>`
+    /// arguments with `infer_extensions`.
+    pub api_ignored: Vec<usize>,
+    /// The types `T` of the `Extension<T>` arguments with `infer_extensions`.
+    pub extensions: Vec<Type>,
+    /// The type `T` of a `Form<T>` argument of an `api_route`, documented as the request body.
+    pub form_body: Option<Type>,
+    /// The `#[route_param(..)]` options of the path- and query-parameters.
+    pub param_options: HashMap<Ident, ParamOptions>,
+    /// The `#[serde(..)]` and `#[schemars(..)]` attributes of the query parameters, which are
+    /// put on their fields of the query struct.
+    pub field_attrs: HashMap<Ident, Vec<Attribute>>,
+    /// The argument receiving the path parameters with `path_struct: <TYPE>`.
+    pub path_struct_arg: Option<Ident>,
+    /// The fields of the `multipart(..)` clause, extracted from the body.
+    pub multipart_fields: Vec<(Ident, Type)>,
+    /// The arguments of the `inject(..)` clause with their values, which aren't extracted.
+    pub injected: Vec<(Ident, Expr)>,
+    /// The patterns of the path parameters, as in `/:slug(regex = "[a-z0-9-]+")`.
+    pub path_patterns: Vec<PathPattern>,
 }
 
 impl CompiledRoute {
     pub fn to_axum_path_string(&self) -> String {
-        let mut path = String::new();
-
-        for (_slash, param) in &self.path_params {
-            path.push('/');
-            match param {
-                PathParam::Capture(lit, _brace_1, _, _, _brace_2) => {
-                    path.push('{');
-                    path.push_str(&lit.value());
-                    path.push('}');
-                }
-                PathParam::WildCard(lit, _brace_1, _, _, _, _brace_2) => {
-                    path.push('{');
-                    path.push('*');
-                    path.push_str(&lit.value());
-                    path.push('}');
-                }
-                PathParam::Static(lit) => path.push_str(&lit.value()),
-            }
-            // if colon.is_some() {
-            //     path.push(':');
-            // }
-            // path.push_str(&ident.value());
-        }
+        axum_path_string(&self.path_params)
+    }
 
-        path
+    /// The path returned by the generated function. With a `base`, the path is formatted once,
+    /// since the base is only known at runtime.
+    pub fn path_expr(&self) -> TokenStream2 {
+        let path = self.to_axum_path_string();
+        match &self.options.base {
+            Some((_ident, base)) => quote! {{
+                static __PATH__: ::std::sync::OnceLock<::std::string::String> =
+                    ::std::sync::OnceLock::new();
+                __PATH__.get_or_init(|| ::std::format!("{}{}", #base, #path)).as_str()
+            }},
+            None => quote!(#path),
+        }
     }
 
     /// Removes the arguments in `route` from `args`, and merges them in the output.
-    pub fn from_route(mut route: Route, function: &ItemFn, with_aide: bool) -> syn::Result<Self> {
+    ///
+    /// The `#[api_ignore]` and `#[route_param(..)]` attributes are removed from the arguments of
+    /// `function`.
+    pub fn from_route(
+        mut route: Route,
+        function: &mut ItemFn,
+        with_aide: bool,
+    ) -> syn::Result<Self> {
         if !with_aide && route.oapi_options.is_some() {
             return Err(syn::Error::new(
                 Span::call_site(),
                 "Use `api_route` instead of `route` to use OpenAPI options",
             ));
         } else if with_aide && route.oapi_options.is_none() {
-            route.oapi_options = Some(OapiOptions {
-                summary: None,
-                description: None,
-                id: None,
-                hidden: None,
-                tags: None,
-                security: None,
-                responses: None,
-                transform: None,
-            });
+            route.oapi_options = Some(OapiOptions::default());
         }
 
+        let mut api_ignored = strip_api_ignore(function);
+        let mut param_options = strip_param_options(function)?;
+        // `?ids(csv)` is a shorthand for `#[route_param(explode = false)]`.
+        for ident in &route.csv_params {
+            let options = param_options.entry(ident.clone()).or_default();
+            let conflict = options.explode.as_ref().map(|(ident, _lit)| ident);
+            let style = options.style.as_ref().filter(|_| options.deep_object());
+            let conflict = conflict.or(style.map(|(ident, _style)| ident));
+            if let Some(conflict) = conflict {
+                return Err(syn::Error::new(
+                    conflict.span(),
+                    format!("`{conflict}` can't be used together with `{ident}(csv)`"),
+                ));
+            }
+            let explode = Ident::new("explode", ident.span());
+            options.explode = Some((explode, LitBool::new(false, ident.span())));
+        }
+        let field_attrs = strip_field_attrs(function)?;
+        if let (Some(state), Some(bounds)) = (&route.state, &route.state_bounds) {
+            add_state_generic(function, state, bounds)?;
+        }
         let sig = &function.sig;
         let mut arg_map = sig
             .inputs
@@ -79,137 +118,2338 @@ impl CompiledRoute {
                 _ => None,
             })
             .collect::<HashMap<_, _>>();
+        // Destructured arguments can't be path- or query-parameters, but are fine for other
+        // extractors, e.g. `Json(item): Json<Item>`.
+        let patterns = sig
+            .inputs
+            .iter()
+            .filter_map(|item| match item {
+                syn::FnArg::Typed(pat_type) => Some(&*pat_type.pat),
+                syn::FnArg::Receiver(_) => None,
+            })
+            .filter(|pat| !matches!(pat, syn::Pat::Ident(_)))
+            .collect::<Vec<_>>();
 
-        for (_slash, path_param) in &mut route.path_params {
-            match path_param {
-                PathParam::Capture(_lit, _, ident, ty, _) => {
-                    let (new_ident, new_ty) = arg_map.remove_entry(ident).ok_or_else(|| {
+        // With `path_struct: <TYPE>`, the captures are fields of the argument of that type. They
+        // keep the annotated types for the helpers, and are strings otherwise.
+        if let Some((ident, _path_struct)) = &route.options.path_struct {
+            if !route.path_params.iter().any(|(_, param)| param.captures()) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`path_struct` can only be used on routes with path parameters",
+                ));
+            }
+        }
+        let path_struct_arg = match &route.options.path_struct {
+            Some((ident, PathStruct::Type(ty))) => {
+                let ty_string = ty.to_token_stream().to_string();
+                let arg = arg_map
+                    .iter()
+                    .find(|(_arg, arg_ty)| arg_ty.to_token_stream().to_string() == ty_string)
+                    .map(|(arg, _arg_ty)| arg.clone())
+                    .ok_or_else(|| {
                         syn::Error::new(
                             ident.span(),
-                            format!("path parameter `{}` not found in function arguments", ident),
+                            format!("expected an argument of type `{ty_string}` for the path parameters"),
                         )
                     })?;
-                    *ident = new_ident;
-                    *ty = new_ty;
+                arg_map.remove(&arg);
+                Some(arg)
+            }
+            _ => None,
+        };
+
+        let mut unused_params = Vec::new();
+        for (_slash, path_param) in &mut route.path_params {
+            let (ident, ty, annotation) = match path_param {
+                PathParam::Capture(_lit, _, ident, ty, _, annotation) => (ident, ty, annotation),
+                PathParam::WildCard(_lit, _, _star, ident, ty, _, annotation) => {
+                    (ident, ty, annotation)
                 }
-                PathParam::WildCard(_lit, _, _star, ident, ty, _) => {
-                    let (new_ident, new_ty) = arg_map.remove_entry(ident).ok_or_else(|| {
-                        syn::Error::new(
-                            ident.span(),
-                            format!("path parameter `{}` not found in function arguments", ident),
-                        )
-                    })?;
+                PathParam::Static(_lit) => continue,
+            };
+            if let Some(arg) = &path_struct_arg {
+                if let Some((arg_ident, _ty)) = arg_map.get_key_value(ident) {
+                    return Err(syn::Error::new(
+                        arg_ident.span(),
+                        format!("path parameter `{ident}` is extracted into `{arg}`, remove this argument"),
+                    ));
+                }
+                *ty = annotation
+                    .clone()
+                    .unwrap_or_else(|| Box::new(parse_quote!(::std::string::String)));
+                continue;
+            }
+            match take_param(
+                &mut arg_map,
+                &patterns,
+                ident,
+                annotation.as_deref(),
+                "path",
+            )? {
+                Some((new_ident, new_ty)) => {
                     *ident = new_ident;
                     *ty = new_ty;
                 }
-                PathParam::Static(_lit) => {}
+                None => {
+                    *ty = annotation.clone().unwrap();
+                    unused_params.push(ident.clone());
+                }
             }
         }
 
         let mut query_params = Vec::new();
-        for ident in route.query_params {
-            let (ident, ty) = arg_map.remove_entry(&ident).ok_or_else(|| {
-                syn::Error::new(
+        for (ident, annotation) in route.query_params {
+            match take_param(
+                &mut arg_map,
+                &patterns,
+                &ident,
+                annotation.as_deref(),
+                "query",
+            )? {
+                Some((ident, ty)) => query_params.push((ident, ty)),
+                None => {
+                    unused_params.push(ident.clone());
+                    query_params.push((ident, annotation.unwrap()));
+                }
+            }
+        }
+
+        let mut multipart_fields = Vec::new();
+        if let Some((multipart, MultipartFields(fields))) = route.multipart {
+            if !cfg!(feature = "multipart") {
+                return Err(syn::Error::new(
+                    multipart.span,
+                    "`multipart` requires the `multipart` feature",
+                ));
+            }
+            for (ident, ty) in fields {
+                let is_param = query_params.iter().any(|(query, _ty)| *query == ident)
+                    || route.path_params.iter().any(|(_slash, path_param)| {
+                        path_param
+                            .capture()
+                            .is_some_and(|(path_ident, _ty)| *path_ident == ident)
+                    });
+                if is_param {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("multipart field `{ident}` is also a path- or query-parameter"),
+                    ));
+                }
+                if take_param(&mut arg_map, &patterns, &ident, Some(&ty), "multipart")?.is_none() {
+                    unused_params.push(ident.clone());
+                }
+                multipart_fields.push((ident, ty));
+            }
+            if let Some((ident, lit)) = &route.options.generate_test {
+                if lit.value() {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`generate_test` can't be used together with `multipart`",
+                    ));
+                }
+            }
+        }
+
+        // The parameters have been removed from `arg_map`, so an ignored argument that isn't in
+        // there is a path- or query-parameter.
+        for &index in &api_ignored {
+            if let FnArg::Typed(pat_type) = &sig.inputs[index] {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    if with_aide && !arg_map.contains_key(&pat_ident.ident) {
+                        return Err(syn::Error::new(
+                            pat_ident.ident.span(),
+                            "`#[api_ignore]` can't be used on path- or query-parameters",
+                        ));
+                    }
+                }
+            }
+        }
+
+        // The parameters have been removed from `arg_map` as well, so an injected argument that
+        // isn't in there is a parameter or doesn't exist.
+        let mut injected = Vec::new();
+        for (ident, value) in route
+            .inject
+            .into_iter()
+            .flat_map(|(_, InjectedArgs(args))| args)
+        {
+            let is_param = route.path_params.iter().any(|(_slash, path_param)| {
+                path_param
+                    .capture()
+                    .is_some_and(|(path_ident, _ty)| path_ident == &ident)
+            }) || query_params.iter().any(|(param, _ty)| *param == ident)
+                || multipart_fields.iter().any(|(field, _ty)| *field == ident)
+                || path_struct_arg.as_ref() == Some(&ident);
+            if is_param {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("injected argument `{ident}` is also extracted from the request"),
+                ));
+            }
+            if arg_map.remove(&ident).is_none() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("injected argument `{ident}` not found in function arguments"),
+                ));
+            }
+            injected.push((ident, value));
+        }
+
+        for (ident, attrs) in &field_attrs {
+            if !query_params
+                .iter()
+                .any(|(query_ident, _ty)| query_ident == ident)
+            {
+                return Err(syn::Error::new_spanned(
+                    &attrs[0],
+                    "`#[serde]` and `#[schemars]` attributes can only be used on query parameters",
+                ));
+            }
+        }
+
+        for (ident, options) in &param_options {
+            let is_query = query_params
+                .iter()
+                .any(|(query_ident, _ty)| query_ident == ident);
+            let is_path = route.path_params.iter().any(|(_slash, path_param)| {
+                path_param
+                    .capture()
+                    .is_some_and(|(path_ident, _ty)| path_ident == ident)
+            });
+            if !is_query && !is_path {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`#[route_param]` can only be used on path- or query-parameters",
+                ));
+            }
+            // These options all decide how the parameter is deserialized.
+            let conversions = [
+                options.flatten.as_ref(),
+                options
+                    .deserialize_with
+                    .as_ref()
+                    .map(|(ident, _path)| ident),
+                options.try_from.as_ref().map(|(ident, _ty)| ident),
+                options.from_str.as_ref(),
+            ];
+            if let Some(second) = conversions.into_iter().flatten().nth(1) {
+                return Err(syn::Error::new(
+                    second.span(),
+                    "only one of `flatten`, `deserialize_with`, `try_from` and `from_str` can be used",
+                ));
+            }
+            if let Some(flatten) = &options.flatten {
+                if !is_query {
+                    return Err(syn::Error::new(
+                        flatten.span(),
+                        "only query parameters can be flattened",
+                    ));
+                }
+                if let Some((deny, _value)) = &route.options.deny_unknown_query {
+                    return Err(syn::Error::new(
+                        deny.span(),
+                        "`deny_unknown_query` can't be used together with flattened query parameters",
+                    ));
+                }
+            }
+            let style = options.style.as_ref().map(|(ident, _style)| ident);
+            let explode = options.explode.as_ref().map(|(ident, _lit)| ident);
+            let schema = options.schema_option();
+            if let (false, Some(option)) = (is_query, style.or(explode).or(schema)) {
+                return Err(syn::Error::new(
+                    option.span(),
+                    format!("`{option}` can only be used on query parameters"),
+                ));
+            }
+            if let (Some((schema_with, _path)), Some(_skip)) =
+                (&options.schema_with, &options.skip_schema)
+            {
+                return Err(syn::Error::new(
+                    schema_with.span(),
+                    "only one of `schema_with` and `skip_schema` can be used",
+                ));
+            }
+            if let (Some(schema), Some(_flatten)) = (schema, &options.flatten) {
+                return Err(syn::Error::new(
+                    schema.span(),
+                    format!("`{schema}` can't be used on flattened query parameters"),
+                ));
+            }
+            if options.deep_object() {
+                let (style, _) = options.style.as_ref().unwrap();
+                if let Some((explode, lit)) = &options.explode {
+                    if !lit.value() {
+                        return Err(syn::Error::new(
+                            explode.span(),
+                            "`deepObject` parameters are always exploded",
+                        ));
+                    }
+                }
+                if let Some(flatten) = &options.flatten {
+                    return Err(syn::Error::new(
+                        flatten.span(),
+                        "`deepObject` parameters can't be flattened",
+                    ));
+                }
+                if !cfg!(feature = "deep-object") {
+                    return Err(syn::Error::new(
+                        style.span(),
+                        "`style = deepObject` requires the `deep-object` feature",
+                    ));
+                }
+            }
+            if options.comma_separated() {
+                let (explode, _) = options.explode.as_ref().unwrap();
+                if options.flatten.is_some()
+                    || options.deserialize_with.is_some()
+                    || options.try_from.is_some()
+                    || options.from_str.is_some()
+                {
+                    return Err(syn::Error::new(
+                        explode.span(),
+                        "`explode = false` can't be used together with `flatten`, `deserialize_with`, `try_from` or `from_str`",
+                    ));
+                }
+            }
+        }
+
+        if let Some((_ident, Accepts(encodings))) = &route.options.accepts {
+            let msgpack = encodings.iter().find(|encoding| *encoding == "msgpack");
+            if let (Some(msgpack), false) = (msgpack, cfg!(feature = "msgpack")) {
+                return Err(syn::Error::new(
+                    msgpack.span(),
+                    "`msgpack` requires the `msgpack` feature",
+                ));
+            }
+        }
+
+        if let Some((ident, lit)) = &route.options.lenient_query {
+            if lit.value() && query_params.is_empty() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`lenient_query` can only be used on routes with query parameters",
+                ));
+            }
+            if let (true, Some((deny, _value))) = (lit.value(), &route.options.deny_unknown_query) {
+                return Err(syn::Error::new(
+                    deny.span(),
+                    "`deny_unknown_query` can't be used together with `lenient_query`",
+                ));
+            }
+            let required = query_params
+                .iter()
+                .find(|(_ident, ty)| single_generic_argument(ty, "Option").is_none());
+            if let (true, Some((required, _ty))) = (lit.value(), required) {
+                return Err(syn::Error::new(
+                    required.span(),
+                    format!("`lenient_query` requires all query parameters to be optional, but `{required}` isn't an `Option`"),
+                ));
+            }
+        }
+
+        if let Some((ident, _cache)) = &route.options.cache {
+            if !matches!(route.method, Method::Get(_)) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`cache` can only be used on GET routes",
+                ));
+            }
+        }
+
+        if let Some((ident, _value)) = &route.options.head {
+            if !matches!(route.method, Method::Get(_)) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`head: auto` can only be used on GET routes",
+                ));
+            }
+        }
+
+        if let Some((ident, version)) = &route.options.version {
+            let format = match &route.options.version_format {
+                Some((_ident, format)) => format.value(),
+                None => "/v{n}".to_string(),
+            };
+            let prefix = format.replace("{n}", version.base10_digits());
+            let span = ident.span();
+            let segments = prefix
+                .split('/')
+                .skip(1)
+                .map(|segment| (Slash(span), PathParam::Static(LitStr::new(segment, span))));
+            route.path_params.splice(0..0, segments);
+            route.route_lit = LitStr::new(&(prefix + &route.route_lit.value()), span);
+
+            if let Some(oapi_options) = &mut route.oapi_options {
+                let tag = LitStr::new(&format!("v{}", version.base10_digits()), span);
+                let tag = parse_quote!(#tag);
+                match &mut oapi_options.tags {
+                    Some((_ident, tags)) => tags.0.push(tag),
+                    None => oapi_options.tags = Some((ident.clone(), ExprArray(vec![tag]))),
+                }
+            }
+        } else if let Some((ident, _format)) = &route.options.version_format {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`version_format` can only be used together with `version`",
+            ));
+        }
+
+        // Extensions are provided by middleware, so like the state they aren't part of the
+        // operation input.
+        let mut extensions = Vec::new();
+        if matches!(&route.options.infer_extensions, Some((_ident, lit)) if lit.value()) {
+            for (index, arg) in sig.inputs.iter().enumerate() {
+                let FnArg::Typed(pat_type) = arg else {
+                    continue;
+                };
+                if let Some(ty) = single_generic_argument(&pat_type.ty, "Extension") {
+                    extensions.push(ty.clone());
+                    if !api_ignored.contains(&index) {
+                        api_ignored.push(index);
+                    }
+                }
+            }
+        }
+
+        // The raw extractors read the same query and path as the parameters, which are already
+        // documented, and `RawPathParams` isn't an `OperationInput`.
+        if with_aide {
+            for (index, arg) in sig.inputs.iter().enumerate() {
+                let FnArg::Typed(pat_type) = arg else {
+                    continue;
+                };
+                if is_raw_extractor(&pat_type.ty) && !api_ignored.contains(&index) {
+                    api_ignored.push(index);
+                }
+            }
+        }
+
+        // aide only documents `Form<T>` with its `axum-form` feature, so the argument is ignored
+        // and its body is documented by the operation instead.
+        let mut form_body = None;
+        if with_aide {
+            for (index, arg) in sig.inputs.iter().enumerate() {
+                let FnArg::Typed(pat_type) = arg else {
+                    continue;
+                };
+                if api_ignored.contains(&index) {
+                    continue;
+                }
+                if let Some(ty) = single_generic_argument(&pat_type.ty, "Form") {
+                    form_body = Some(ty.clone());
+                    api_ignored.push(index);
+                }
+            }
+        }
+
+        if let (true, Method::Custom(method)) = (with_aide, &route.method) {
+            return Err(syn::Error::new(
+                method.span(),
+                "custom methods can't be documented with OpenAPI, use `route` instead of `api_route`",
+            ));
+        }
+
+        if let Some((ident, _sunset)) = &route.options.sunset {
+            if !matches!(&route.options.deprecated, Some((_ident, lit)) if lit.value()) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`sunset` can only be used on routes with `deprecated: true`",
+                ));
+            }
+        }
+
+        if let (true, Some((ident, lit))) = (with_aide, &route.options.erased) {
+            if lit.value() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`erased` can't be used with `api_route`, since the documentation needs the response type of the handler",
+                ));
+            }
+        }
+
+        if let Some((ident, lit)) = &route.options.generate_test {
+            if lit.value() && matches!(&route.options.in_impl, Some((_ident, lit)) if lit.value()) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`generate_test` can't be used together with `in_impl`",
+                ));
+            }
+            if let (true, Some(param)) = (lit.value(), param_options.keys().next()) {
+                return Err(syn::Error::new(
+                    param.span(),
+                    "`generate_test` can't sample parameters with `#[route_param(..)]` options",
+                ));
+            }
+        }
+
+        let module_options = [&route.options.module, &route.options.in_module];
+        for (ident, lit) in module_options.into_iter().flatten() {
+            let webhook = route
+                .oapi_options
+                .as_ref()
+                .and_then(|oapi| oapi.webhook.as_ref());
+            let unsupported = if !lit.value() {
+                None
+            } else if matches!(&route.options.in_impl, Some((_ident, lit)) if lit.value()) {
+                Some("`in_impl`, since an `impl` block can't contain modules")
+            } else {
+                webhook.map(|_| "`api_webhook`, since webhooks don't have a route module")
+            };
+            if let Some(unsupported) = unsupported {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("`{ident}` can't be used with {unsupported}"),
+                ));
+            }
+        }
+
+        if let Some((ident, _target)) = &route.options.moved_to {
+            if route
+                .oapi_options
+                .as_ref()
+                .is_some_and(|oapi| oapi.webhook.is_some())
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`moved_to` can't be used with `api_webhook`, since webhooks aren't routed",
+                ));
+            }
+        }
+
+        if let Some((ident, lit)) = &route.options.debug_handler {
+            let unsupported = if !lit.value() {
+                None
+            } else if matches!(&route.options.in_impl, Some((_ident, lit)) if lit.value()) {
+                Some("`in_impl`")
+            } else if matches!(&route.options.erased, Some((_ident, lit)) if lit.value()) {
+                Some("`erased`")
+            } else if !sig.generics.params.is_empty() {
+                Some("generic handlers")
+            } else {
+                sig.asyncness
+                    .is_none()
+                    .then_some("handlers that aren't `async`")
+            };
+            if let Some(unsupported) = unsupported {
+                return Err(syn::Error::new(
                     ident.span(),
+                    format!("`debug_handler` can't be used with {unsupported}"),
+                ));
+            }
+        }
+
+        if let Some((ident, sitemap)) = &route.options.sitemap {
+            if !matches!(route.method, Method::Get(_)) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`sitemap` can only be used on `GET` routes",
+                ));
+            }
+            let captures = route.path_params.iter().any(|(_, param)| param.captures());
+            if let (true, Sitemap::Entry { .. }) = (captures, sitemap) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "routes with path parameters can't be listed in the sitemap",
+                ));
+            }
+        }
+
+        if let (Some((ident, _cors)), Method::Options(_)) = (&route.options.cors, &route.method) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`cors` can't be used on `OPTIONS` routes, which answer the preflight requests",
+            ));
+        }
+
+        if let (Some((ident, _deadline)), false) =
+            (&route.options.deadline, cfg!(feature = "deadline"))
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`deadline` requires the `deadline` feature",
+            ));
+        }
+
+        for PathPattern { name, pattern } in &route.path_patterns {
+            if !cfg!(feature = "regex") {
+                return Err(syn::Error::new(
+                    pattern.span(),
                     format!(
-                        "query parameter `{}` not found in function arguments",
-                        ident
+                        "the pattern of path parameter `{}` requires the `regex` feature",
+                        name.value()
                     ),
-                )
-            })?;
-            query_params.push((ident, ty));
+                ));
+            }
+            #[cfg(feature = "regex")]
+            if let Err(err) = regex::Regex::new(&format!("^(?:{})$", pattern.value())) {
+                return Err(syn::Error::new(
+                    pattern.span(),
+                    format!(
+                        "invalid pattern of path parameter `{}`: {err}",
+                        name.value()
+                    ),
+                ));
+            }
+        }
+
+        for permission in route.options.permissions.iter().flat_map(|(_, p)| &p.0) {
+            if permission.value().is_empty() {
+                return Err(syn::Error::new(
+                    permission.span(),
+                    "a permission can't be empty",
+                ));
+            }
+        }
+
+        if let Some((ident, Idempotency::Required | Idempotency::Replayed { .. })) =
+            &route.options.idempotent
+        {
+            if matches!(route.method, Method::Get(_) | Method::Head(_)) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`idempotent` is for routes with side effects, it can't be used on `GET` or `HEAD` routes",
+                ));
+            }
+        }
+
+        if let Some((ident, cached)) = &route.options.cached {
+            if !matches!(route.method, Method::Get(_)) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`cached` can only be used on `GET` routes",
+                ));
+            }
+            // The cache is keyed on the parameters, not the encoding negotiated with `Accept`.
+            if route.options.accepts.is_some() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "`cached` can't be used together with `accepts`",
+                ));
+            }
+            // Without a `key`, the responses are only keyed on the path- and query-parameters,
+            // so another extractor, e.g. of the user, would share a response between requests
+            // it tells apart. The state is the same for all requests.
+            let other_extractor = function.sig.inputs.iter().find(|arg| match arg {
+                FnArg::Typed(pat_type) => {
+                    let extracted = match &*pat_type.pat {
+                        syn::Pat::Ident(pat_ident) => !arg_map.contains_key(&pat_ident.ident),
+                        _ => false,
+                    };
+                    !extracted && !is_state_type(&pat_type.ty)
+                }
+                FnArg::Receiver(_) => false,
+            });
+            if let (None, Some(arg)) = (&cached.key, other_extractor) {
+                return Err(syn::Error::new(
+                    arg.span(),
+                    "`cached` needs a `key` when the handler takes other arguments than path- and query-parameters and the `State`, which must include them if the response depends on them",
+                ));
+            }
+            for param in cached.key.iter().flatten() {
+                // With `path_struct: <TYPE>`, the captures are only fields of its argument.
+                let is_capture = route.path_params.iter().any(|(_slash, path_param)| {
+                    path_param
+                        .capture()
+                        .is_some_and(|(path_ident, _ty)| path_ident == param)
+                });
+                let is_param = query_params.iter().any(|(query, _ty)| query == param)
+                    || (is_capture && path_struct_arg.is_none())
+                    || path_struct_arg.as_ref() == Some(param);
+                // The other arguments of the handler bound to an identifier, e.g. `user: User`.
+                if !is_param && !arg_map.contains_key(param) {
+                    return Err(syn::Error::new(
+                        param.span(),
+                        format!(
+                            "cache key `{param}` is not a parameter or an argument of the handler"
+                        ),
+                    ));
+                }
+                if unused_params.contains(param) {
+                    return Err(syn::Error::new(
+                        param.span(),
+                        format!("cache key `{param}` must be an argument of the handler"),
+                    ));
+                }
+            }
         }
 
         if let Some(options) = route.oapi_options.as_mut() {
             options.merge_with_fn(function)
         }
 
-        Ok(Self {
+        let compiled = Self {
             route_lit: route.route_lit,
+            options: route.options,
             method: route.method,
             path_params: route.path_params,
+            unused_params,
             query_params,
             state: route.state.unwrap_or_else(|| guess_state_type(sig)),
             oapi_options: route.oapi_options,
-        })
+            api_ignored,
+            extensions,
+            form_body,
+            param_options,
+            field_attrs,
+            path_struct_arg,
+            multipart_fields,
+            injected,
+            path_patterns: route.path_patterns,
+        };
+
+        let extracted = compiled.extracted_idents();
+        let hooks = [&compiled.options.before, &compiled.options.after];
+        for (_ident, Hooks(hooks)) in hooks.into_iter().flatten() {
+            for param in hooks.iter().flat_map(|hook| hook.params.iter().flatten()) {
+                if !extracted.contains(param) {
+                    return Err(syn::Error::new(
+                        param.span(),
+                        format!(
+                            "`{param}` is not a path- or query-parameter passed to the handler"
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(compiled)
+    }
+
+    /// Wraps the `call` of the handler with the `before` and `after` hooks, which are awaited
+    /// with references to their parameters. The parameters of the `after` hooks are cloned
+    /// before the handler takes them.
+    pub fn hooked_call(&self, call: TokenStream2) -> TokenStream2 {
+        let before = self.hooks(&self.options.before);
+        let after = self.hooks(&self.options.after);
+        if before.is_empty() && after.is_empty() {
+            return call;
+        }
+
+        let mut cloned = Vec::new();
+        for param in after.iter().flat_map(|(_function, params)| params) {
+            if !cloned.contains(param) {
+                cloned.push(param.clone());
+            }
+        }
+        let clone = |param: &Ident| format_ident!("__after_{}__", param);
+        let clones = cloned.iter().map(|param| {
+            let clone = clone(param);
+            quote! { let #clone = ::core::clone::Clone::clone(&#param); }
+        });
+        let before = before.iter().map(|(function, params)| {
+            quote! { #function(#(&#params),*).await; }
+        });
+        let after = after.iter().map(|(function, params)| {
+            let params = params.iter().map(clone);
+            quote! { #function(#(&#params),*).await; }
+        });
+        quote! {{
+            #(#before)*
+            #(#clones)*
+            let __output__ = #call;
+            #(#after)*
+            __output__
+        }}
+    }
+
+    /// The functions of `hooks`, with the parameters they're called with.
+    fn hooks<'a>(&self, hooks: &'a Option<(Ident, Hooks)>) -> Vec<(&'a syn::Path, Vec<Ident>)> {
+        let hooks = hooks.iter().flat_map(|(_ident, Hooks(hooks))| hooks);
+        hooks
+            .map(|hook| {
+                let params = hook.params.clone();
+                (
+                    &hook.function,
+                    params.unwrap_or_else(|| self.extracted_idents()),
+                )
+            })
+            .collect()
     }
 
     pub fn path_extractor(&self) -> Option<TokenStream2> {
         if !self.path_params.iter().any(|(_, param)| param.captures()) {
             return None;
         }
+        let extractor = match &self.options.path_extractor {
+            Some((_ident, extractor)) => quote!(#extractor),
+            None => quote!(::axum::extract::Path),
+        };
+        if let Some(ty) = self.path_struct_type() {
+            let arg = &self.path_struct_arg;
+            return Some(self.with_rejection(quote!(#extractor(#arg)), quote!(#extractor<#ty>)));
+        }
+        if self.generates_path_struct() {
+            let fields = self
+                .path_params
+                .iter()
+                .filter_map(|(_slash, path_param)| path_param.capture())
+                .map(|(ident, _ty)| match self.unused_params.contains(ident) {
+                    true => quote!(#ident: _),
+                    false => quote!(#ident),
+                });
+            return Some(self.with_rejection(
+                quote!(#extractor(__PathParams__ { #(#fields,)* })),
+                quote!(#extractor<__PathParams__>),
+            ));
+        }
 
         let path_iter = self
             .path_params
             .iter()
             .filter_map(|(_slash, path_param)| path_param.capture());
-        let idents = path_iter.clone().map(|item| item.0);
-        let types = path_iter.clone().map(|item| item.1);
-        Some(quote! {
-            ::axum::extract::Path((#(#idents,)*)): ::axum::extract::Path<(#(#types,)*)>,
-        })
+        let idents = path_iter.clone().map(|(ident, _ty)| {
+            if self.unused_params.contains(ident) {
+                quote!(_)
+            } else if self.deserialize_with(ident).is_some() {
+                let wrapper = path_param_wrapper(ident);
+                quote!(#wrapper(#ident))
+            } else {
+                quote!(#ident)
+            }
+        });
+        let types = path_iter.clone().map(|(ident, ty)| {
+            if self.deserialize_with(ident).is_some() {
+                path_param_wrapper(ident).into_token_stream()
+            } else {
+                ty.into_token_stream()
+            }
+        });
+        Some(self.with_rejection(
+            quote!(#extractor((#(#idents,)*))),
+            quote!(#extractor<(#(#types,)*)>),
+        ))
     }
 
-    pub fn query_extractor(&self) -> Option<TokenStream2> {
-        if self.query_params.is_empty() {
-            return None;
+    /// Whether the captures are extracted into a generated struct with `path_struct: true`.
+    fn generates_path_struct(&self) -> bool {
+        matches!(
+            &self.options.path_struct,
+            Some((_ident, PathStruct::Generated(lit))) if lit.value()
+        )
+    }
+
+    /// The type of `path_struct: <TYPE>`.
+    fn path_struct_type(&self) -> Option<&Type> {
+        match &self.options.path_struct {
+            Some((_ident, PathStruct::Type(ty))) => Some(ty),
+            _ => None,
         }
+    }
 
-        let idents = self.query_params.iter().map(|item| &item.0);
-        Some(quote! {
-            ::axum::extract::Query(__QueryParams__ {
-                #(#idents,)*
-            }): ::axum::extract::Query<__QueryParams__>,
-        })
+    /// Whether the query is extracted. This is the case when there are query parameters, or
+    /// when unknown query parameters are denied.
+    fn extracts_query(&self) -> bool {
+        !self.query_params.is_empty() || self.deny_unknown_query()
     }
 
-    pub fn query_params_struct(&self, with_aide: bool) -> Option<TokenStream2> {
-        match self.query_params.is_empty() {
-            true => None,
-            false => {
-                let idents = self.query_params.iter().map(|item| &item.0);
-                let types = self.query_params.iter().map(|item| &item.1);
-                let derive = match with_aide {
-                    true => quote! { #[derive(::serde::Deserialize, ::schemars::JsonSchema)] },
-                    false => quote! { #[derive(::serde::Deserialize)] },
-                };
-                Some(quote! {
-                    #derive
-                    struct __QueryParams__ {
-                        #(#idents: #types,)*
-                    }
+    fn deny_unknown_query(&self) -> bool {
+        matches!(&self.options.deny_unknown_query, Some((_ident, lit)) if lit.value())
+    }
+
+    fn lenient_query(&self) -> bool {
+        matches!(&self.options.lenient_query, Some((_ident, lit)) if lit.value())
+    }
+
+    /// The query parameter of a route with a single one, without options or attributes, which
+    /// is extracted with the shared `SingleQueryParam` instead of a generated struct. With
+    /// `api_route`, the struct is still generated for its `JsonSchema`.
+    fn single_query_param(&self) -> Option<(&Ident, &Type)> {
+        let [(ident, ty)] = self.query_params.as_slice() else {
+            return None;
+        };
+        self.plain_query().then_some((ident, &**ty))
+    }
+
+    /// The query parameters of a route with 2 to 8 of them, without options or attributes,
+    /// which are extracted as a tuple with the shared `QueryParams` instead of a generated
+    /// struct, like a [single one](Self::single_query_param).
+    fn shared_query_params(&self) -> Option<&[(Ident, Box<Type>)]> {
+        let shared = (2..=8).contains(&self.query_params.len()) && self.plain_query();
+        shared.then_some(&self.query_params)
+    }
+
+    /// Whether the query parameters are deserialized like a struct with their fields, without
+    /// any options, attributes or `JsonSchema`.
+    fn plain_query(&self) -> bool {
+        self.oapi_options.is_none()
+            && !self.deny_unknown_query()
+            && self.query_params.iter().all(|(ident, _ty)| {
+                !self.param_options.contains_key(ident) && !self.field_attrs.contains_key(ident)
+            })
+    }
+
+    pub fn query_extractor(&self) -> Option<TokenStream2> {
+        if !self.extracts_query() {
+            return None;
+        }
+        if let Some((ident, ty)) = self.single_query_param() {
+            let pat = match self.unused_params.contains(ident) {
+                true => quote!(_),
+                false => quote!(#ident),
+            };
+            return Some(self.with_query_rejection(
+                quote! {
+                    ::axum::extract::Query(
+                        ::axum_typed_routing::__private::SingleQueryParam(#pat, _)
+                    )
+                },
+                quote! {
+                    ::axum::extract::Query<
+                        ::axum_typed_routing::__private::SingleQueryParam<__QueryParamName__, #ty>
+                    >
+                },
+            ));
+        }
+
+        if let Some(params) = self.shared_query_params() {
+            let pats = params
+                .iter()
+                .map(|(ident, _ty)| match self.unused_params.contains(ident) {
+                    true => quote!(_),
+                    false => quote!(#ident),
+                });
+            let types = params.iter().map(|(_ident, ty)| ty);
+            return Some(self.with_query_rejection(
+                quote! {
+                    ::axum::extract::Query(
+                        ::axum_typed_routing::__private::QueryParams((#(#pats,)*), _)
+                    )
+                },
+                quote! {
+                    ::axum::extract::Query<
+                        ::axum_typed_routing::__private::QueryParams<__QueryParamNames__, (#(#types,)*)>
+                    >
+                },
+            ));
+        }
+
+        let idents = self.query_params.iter().map(|(ident, _ty)| {
+            if self.unused_params.contains(ident) {
+                quote!(#ident: _)
+            } else {
+                quote!(#ident)
+            }
+        });
+        // `deepObject` parameters need a deserializer that understands `filter[name]=x`.
+        let extractor = match self.param_options.values().any(ParamOptions::deep_object) {
+            true => quote!(::axum_typed_routing::__private::DeepObjectQuery),
+            false => quote!(::axum::extract::Query),
+        };
+        Some(self.with_query_rejection(
+            quote!(#extractor(__QueryParams__ { #(#idents,)* })),
+            quote!(#extractor<__QueryParams__>),
+        ))
+    }
+
+    /// Same as [`Self::with_rejection`], but a `lenient_query` is wrapped in `LenientQuery`
+    /// instead, which never rejects the request.
+    fn with_query_rejection(&self, pat: TokenStream2, ty: TokenStream2) -> TokenStream2 {
+        match self.lenient_query() {
+            true => quote! {
+                ::axum_typed_routing::__private::LenientQuery(#pat):
+                    ::axum_typed_routing::__private::LenientQuery<#ty>,
+            },
+            false => self.with_rejection(pat, ty),
+        }
+    }
+
+    /// The argument `#pat: #ty,`, wrapped in `WithRejection` if a `rejection` is specified.
+    fn with_rejection(&self, pat: TokenStream2, ty: TokenStream2) -> TokenStream2 {
+        match &self.options.rejection {
+            Some((_ident, rejection)) => quote! {
+                ::axum_extra::extract::WithRejection(#pat, _):
+                    ::axum_extra::extract::WithRejection<#ty, #rejection>,
+            },
+            None => quote! { #pat: #ty, },
+        }
+    }
+
+    /// The axum paths of the `alias` option. These must have the same path parameters as the
+    /// route, since the path parameters are extracted by position.
+    pub fn aliases(&self) -> syn::Result<Vec<String>> {
+        let Some((_ident, Aliases(aliases))) = &self.options.alias else {
+            return Ok(Vec::new());
+        };
+        let mut paths = Vec::new();
+        for (lit, path_params) in aliases {
+            paths.push(self.same_captures_path(lit, path_params, "an alias")?);
+        }
+        Ok(paths)
+    }
+
+    /// The `(locale, axum path)` of the paths of the `localized` option, which have the same
+    /// path parameters as the route, like aliases.
+    pub fn localized_paths(&self) -> syn::Result<Vec<(String, String)>> {
+        let Some((ident, Localized(localized))) = &self.options.localized else {
+            return Ok(Vec::new());
+        };
+        if self.options.locale.is_none() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`localized` requires the `locale` of the path of the route, e.g. `locale: \"en\"`",
+            ));
+        }
+        let mut paths = Vec::new();
+        for (locale, lit, path_params) in localized {
+            let path = self.same_captures_path(lit, path_params, "a localized path")?;
+            paths.push((locale.value(), path));
+        }
+        Ok(paths)
+    }
+
+    /// The axum path of `path_params`, checking that it has the same path parameters as the
+    /// route, since the path parameters are extracted by position.
+    fn same_captures_path(
+        &self,
+        lit: &LitStr,
+        path_params: &[(Slash, PathParam)],
+        what: &str,
+    ) -> syn::Result<String> {
+        let capture_names = |path_params: &[(Slash, PathParam)]| {
+            path_params
+                .iter()
+                .filter_map(|(_slash, param)| param.capture())
+                .map(|(ident, _ty)| ident.to_string())
+                .collect::<Vec<_>>()
+        };
+        let expected = capture_names(&self.path_params);
+        if capture_names(path_params) != expected {
+            return Err(syn::Error::new(
+                lit.span(),
+                format!(
+                    "{what} must have the same path parameters as the route: ({})",
+                    expected.join(", ")
+                ),
+            ));
+        }
+        Ok(axum_path_string(path_params))
+    }
+
+    /// The layer providing the `locale` of the route to the `Locale` extractor. Localized paths
+    /// add their own locale in an outer layer, which takes precedence.
+    pub fn locale_layer(&self) -> Option<TokenStream2> {
+        let (_ident, locale) = self.options.locale.as_ref()?;
+        Some(quote! {
+            .layer(::axum::middleware::map_request(
+                |request: ::axum::extract::Request| {
+                    ::axum_typed_routing::__private::default_locale(request, #locale)
+                },
+            ))
+        })
+    }
+
+    /// With the `matched-route` feature, the layer inserting the `MatchedTypedRoute` of the route
+    /// into the extensions of its requests and responses. This is the outermost layer, so the
+    /// other layers and guards can read it. It's the same function for all routes, so it doesn't
+    /// add instantiations to `erased` routes.
+    pub fn matched_route_layer(&self, fn_name: &Ident) -> Option<TokenStream2> {
+        if !cfg!(feature = "matched-route") {
+            return None;
+        }
+        let fn_name = fn_name.to_string();
+        let path = self.path_expr();
+        let method = self.method.name();
+        let permissions = self.permissions();
+        Some(quote! {
+            .layer(::axum::middleware::from_fn_with_state(
+                ::axum_typed_routing::MatchedTypedRoute {
+                    name: ::core::concat!(::core::module_path!(), "::", #fn_name),
+                    template: #path,
+                    method: #method,
+                    permissions: &[#(#permissions),*],
+                },
+                ::axum_typed_routing::__private::matched_typed_route,
+            ))
+        })
+    }
+
+    /// The permissions of the `permissions` option, or none without it.
+    fn permissions(&self) -> &[LitStr] {
+        match &self.options.permissions {
+            Some((_ident, StrArray(permissions))) => permissions,
+            None => &[],
+        }
+    }
+
+    /// Documents the permissions of the route as the `x-permissions` extension of the operation.
+    pub fn get_oapi_permissions(&self) -> Option<TokenStream2> {
+        let (_ident, StrArray(permissions)) = self.options.permissions.as_ref()?;
+        Some(quote! {
+            let mut __op__ = __op__;
+            ::axum_typed_routing::__private::set_extension(
+                __op__.inner_mut(),
+                "x-permissions",
+                [#(#permissions),*],
+            );
+        })
+    }
+
+    /// Marks the operation with the named document of `api_doc`, which `finish_named_api` moves
+    /// it to.
+    pub fn get_oapi_api_doc(&self) -> Option<TokenStream2> {
+        let (_ident, name) = self.oapi_options.as_ref()?.api_doc.as_ref()?;
+        Some(quote! {
+            let mut __op__ = __op__;
+            ::axum_typed_routing::__private::set_extension(__op__.inner_mut(), "x-api-doc", #name);
+        })
+    }
+
+    fn deprecated(&self) -> bool {
+        matches!(&self.options.deprecated, Some((_ident, lit)) if lit.value())
+    }
+
+    /// The layer adding the `Deprecation` and `Sunset` headers to the responses of a deprecated
+    /// route.
+    pub fn deprecation_layer(&self) -> Option<TokenStream2> {
+        if !self.deprecated() {
+            return None;
+        }
+        let sunset = match &self.options.sunset {
+            Some((_ident, sunset)) => quote!(::core::option::Option::Some(#sunset)),
+            None => quote!(::core::option::Option::None),
+        };
+        Some(quote! {
+            .layer(::axum::middleware::map_response(
+                |response: ::axum::response::Response| {
+                    ::axum_typed_routing::__private::deprecation_headers(response, #sunset)
+                },
+            ))
+        })
+    }
+
+    /// The `guards` of the route, as a `route_layer` running each extractor before the handler,
+    /// which rejects the request if it fails. The `host` of the route is checked first.
+    pub fn guard_layers(&self) -> TokenStream2 {
+        let guards = self
+            .options
+            .guards
+            .iter()
+            .flat_map(|(_ident, TypeArray(guards))| guards);
+        let host = self.options.host.as_ref().map(|(_ident, host)| {
+            quote! {
+                .route_layer(::axum::middleware::map_request(
+                    |request: ::axum::extract::Request| {
+                        ::axum_typed_routing::__private::require_host(request, #host)
+                    },
+                ))
+            }
+        });
+        quote! {
+            #(.route_layer(::axum::middleware::from_extractor::<#guards>()))*
+            #host
+        }
+    }
+
+    /// The layer adding the `Cache-Control` header of the `cache` option.
+    pub fn cache_layer(&self) -> Option<TokenStream2> {
+        let (_ident, cache) = self.options.cache.as_ref()?;
+        let value = cache.header_value();
+        Some(quote! {
+            .layer(::axum::middleware::map_response(
+                |response: ::axum::response::Response| {
+                    ::axum_typed_routing::__private::cache_control(response, #value)
+                },
+            ))
+        })
+    }
+
+    /// The `CorsPolicy` of the `cors` option as a static `__CORS__`, which is shared by the layer
+    /// of the route and the preflight route added by the `TypedRouter`.
+    pub fn cors_policy(&self) -> Option<TokenStream2> {
+        let (_ident, cors) = self.options.cors.as_ref()?;
+        let origins = &cors.origins;
+        let headers = cors
+            .headers
+            .iter()
+            .map(|header| header.value().to_lowercase());
+        let max_age = match &cors.max_age {
+            Some(max_age) => quote!(::core::option::Option::Some(#max_age)),
+            None => quote!(::core::option::Option::None),
+        };
+        let credentials = cors.credentials.as_ref().is_some_and(LitBool::value);
+        Some(quote! {
+            static __CORS__: ::axum_typed_routing::__private::CorsPolicy =
+                ::axum_typed_routing::__private::CorsPolicy {
+                    origins: &[#(#origins),*],
+                    headers: &[#(#headers),*],
+                    max_age: #max_age,
+                    credentials: #credentials,
+                };
+        })
+    }
+
+    /// The layer adding the CORS headers of the `cors` option to the responses.
+    pub fn cors_layer(&self) -> Option<TokenStream2> {
+        self.options.cors.as_ref()?;
+        Some(quote! {
+            .layer(::axum::middleware::from_fn(
+                |request: ::axum::extract::Request, next: ::axum::middleware::Next| {
+                    ::axum_typed_routing::__private::cors_headers(&__CORS__, request, next)
+                },
+            ))
+        })
+    }
+
+    /// The `DeadlinePolicy` of the `deadline` option as a static `__DEADLINE__`, which is shared by
+    /// the layer and the documentation of the route.
+    pub fn deadline_policy(&self) -> Option<TokenStream2> {
+        let (_ident, deadline) = self.options.deadline.as_ref()?;
+        let header = deadline.header.value().to_lowercase();
+        let millis = |millis: Option<u64>| match millis {
+            Some(millis) => quote!(::core::option::Option::Some(#millis)),
+            None => quote!(::core::option::Option::None),
+        };
+        let default = millis(deadline.default_millis);
+        let max = millis(deadline.max_millis);
+        Some(quote! {
+            static __DEADLINE__: ::axum_typed_routing::__private::DeadlinePolicy =
+                ::axum_typed_routing::__private::DeadlinePolicy {
+                    header: #header,
+                    default_millis: #default,
+                    max_millis: #max,
+                };
+        })
+    }
+
+    /// The layer reading the `Deadline` of a request and timing out its response.
+    pub fn deadline_layer(&self) -> Option<TokenStream2> {
+        self.options.deadline.as_ref()?;
+        Some(quote! {
+            .layer(::axum::middleware::from_fn(
+                |request: ::axum::extract::Request, next: ::axum::middleware::Next| {
+                    ::axum_typed_routing::__private::deadline(&__DEADLINE__, request, next)
+                },
+            ))
+        })
+    }
+
+    /// The path of the route of `moved_to` as `__moved_to__`, which is looked up when the
+    /// generated function is called, before the route is described.
+    pub fn moved_to_path(&self) -> Option<TokenStream2> {
+        let (_ident, target) = self.options.moved_to.as_ref()?;
+        let path = self.path_expr();
+        Some(quote! {
+            let __moved_to__: &'static str =
+                ::axum_typed_routing::__private::moved_to_path(#path, #target);
+        })
+    }
+
+    /// The layer answering every request of a route with `moved_to` with a redirect, instead of
+    /// calling the handler.
+    pub fn moved_to_layer(&self) -> Option<TokenStream2> {
+        self.options.moved_to.as_ref()?;
+        Some(quote! {
+            .layer(::axum::middleware::from_fn(
+                move |request: ::axum::extract::Request, _next: ::axum::middleware::Next| {
+                    ::axum_typed_routing::__private::moved_to(__moved_to__, request)
+                },
+            ))
+        })
+    }
+
+    /// Documents the redirect of `moved_to` as the only response of the operation.
+    pub fn get_oapi_moved_to(&self) -> Option<TokenStream2> {
+        self.options.moved_to.as_ref()?;
+        Some(quote! {
+            let mut __op__ = __op__;
+            ::axum_typed_routing::__private::document_moved_to(__op__.inner_mut(), __moved_to__);
+        })
+    }
+
+    /// Documents the header of the `deadline` option, and the response to an expired deadline.
+    /// Webhooks aren't served, so their deadline isn't documented.
+    pub fn get_oapi_deadline(&self) -> Option<TokenStream2> {
+        if self.webhook().is_some() {
+            return None;
+        }
+        self.options.deadline.as_ref()?;
+        Some(quote! {
+            let mut __op__ = __op__;
+            ::axum_typed_routing::__private::document_deadline(__op__.inner_mut(), &__DEADLINE__);
+        })
+    }
+
+    /// The patterns of the path parameters as a static `__PATH_PATTERNS__`, which is shared by the
+    /// layer and the documentation of the route.
+    pub fn path_patterns(&self) -> Option<TokenStream2> {
+        if self.path_patterns.is_empty() {
+            return None;
+        }
+        let len = self.path_patterns.len();
+        let patterns = self.path_patterns.iter().map(|PathPattern { name, pattern }| {
+            quote!(::axum_typed_routing::__private::PathPattern::new(#name, #pattern))
+        });
+        Some(quote! {
+            static __PATH_PATTERNS__: [::axum_typed_routing::__private::PathPattern; #len] =
+                [#(#patterns),*];
+        })
+    }
+
+    /// The layer rejecting the requests whose path parameters don't match their patterns.
+    pub fn path_pattern_layer(&self) -> Option<TokenStream2> {
+        if self.path_patterns.is_empty() {
+            return None;
+        }
+        Some(quote! {
+            .layer(::axum::middleware::from_fn(
+                |request: ::axum::extract::Request, next: ::axum::middleware::Next| {
+                    ::axum_typed_routing::__private::path_patterns(&__PATH_PATTERNS__, request, next)
+                },
+            ))
+        })
+    }
+
+    /// Documents the patterns of the path parameters, and the response to a mismatch.
+    pub fn get_oapi_path_patterns(&self) -> Option<TokenStream2> {
+        if self.path_patterns.is_empty() {
+            return None;
+        }
+        Some(quote! {
+            let mut __op__ = __op__;
+            ::axum_typed_routing::__private::document_path_patterns(
+                __op__.inner_mut(),
+                &__PATH_PATTERNS__,
+            );
+        })
+    }
+
+    /// The layer excluding the responses of `compression: off` from the compression layer of the
+    /// application.
+    pub fn compression_layer(&self) -> Option<TokenStream2> {
+        self.options.compression.as_ref()?;
+        Some(quote! {
+            .layer(::axum::middleware::map_response(
+                ::axum_typed_routing::__private::disable_compression,
+            ))
+        })
+    }
+
+    /// Whether the route is registered in type-erased form, with `erased: true`.
+    pub fn erased(&self) -> bool {
+        matches!(&self.options.erased, Some((_ident, lit)) if lit.value())
+    }
+
+    /// The layers of an `erased` route as a static `__LAYERS__`, which are applied by a function
+    /// shared by all routes with the same state, instead of the `layer` calls above.
+    pub fn erased_layers(&self) -> TokenStream2 {
+        let option = |value: Option<TokenStream2>| match value {
+            Some(value) => quote!(::core::option::Option::Some(#value)),
+            None => quote!(::core::option::Option::None),
+        };
+        let deprecation = option(self.deprecated().then(|| {
+            option(
+                self.options
+                    .sunset
+                    .as_ref()
+                    .map(|(_ident, sunset)| quote!(#sunset)),
+            )
+        }));
+        let cache_control = option(self.options.cache.as_ref().map(|(_ident, cache)| {
+            let value = cache.header_value();
+            quote!(#value)
+        }));
+        let disable_compression = self.options.compression.is_some();
+        let cors = option(self.options.cors.as_ref().map(|_cors| quote!(&__CORS__)));
+        // The field only exists with the `deadline` feature, which is required by the option.
+        let deadline = cfg!(feature = "deadline").then(|| {
+            let deadline = option(
+                self.options
+                    .deadline
+                    .as_ref()
+                    .map(|_| quote!(&__DEADLINE__)),
+            );
+            quote!(deadline: #deadline,)
+        });
+        let locale = option(
+            self.options
+                .locale
+                .as_ref()
+                .map(|(_ident, locale)| quote!(#locale)),
+        );
+        quote! {
+            static __LAYERS__: ::axum_typed_routing::__private::RouteLayers =
+                ::axum_typed_routing::__private::RouteLayers {
+                    deprecation: #deprecation,
+                    cache_control: #cache_control,
+                    disable_compression: #disable_compression,
+                    cors: #cors,
+                    #deadline
+                    locale: #locale,
+                };
+        }
+    }
+
+    /// Marks the operation of a deprecated route as deprecated.
+    pub fn get_oapi_deprecated(&self) -> Option<TokenStream2> {
+        self.deprecated().then(|| {
+            quote! {
+                let mut __op__ = __op__;
+                __op__.inner_mut().deprecated = true;
+            }
+        })
+    }
+
+    fn deprecate_alias(&self) -> bool {
+        matches!(&self.options.deprecate_alias, Some((_ident, lit)) if lit.value())
+    }
+
+    /// The description of the route for the `TypedRouter`, returned by the generated function
+    /// after the path and the method router. Without options the `TypedRouter` needs to know
+    /// about, this is only the method and the name of the route, so a route without options
+    /// doesn't depend on `axum_typed_routing`.
+    pub fn route_description(&self, fn_name: &Ident) -> syn::Result<TokenStream2> {
+        let method = self.method.name();
+        let fn_name = fn_name.to_string();
+        let handler = quote!(::core::concat!(::core::module_path!(), "::", #fn_name));
+        let aliases = self.aliases()?;
+        let aliases = (!aliases.is_empty()).then(|| quote!(aliases: &[#(#aliases),*],));
+        let deprecate_alias = self
+            .deprecate_alias()
+            .then(|| quote!(deprecate_alias: true,));
+        let localized = self.localized_paths()?;
+        let localized = (!localized.is_empty()).then(|| {
+            let locales = localized.iter().map(|(locale, _path)| locale);
+            let paths = localized.iter().map(|(_locale, path)| path);
+            quote!(localized: &[#((#locales, #paths)),*],)
+        });
+        let enabled = self
+            .options
+            .enabled_if
+            .as_ref()
+            .map(|(_ident, enabled)| quote!(enabled: #enabled,));
+        let operation_id = self
+            .operation_id_expr()
+            .map(|operation_id| quote!(operation_id: ::core::option::Option::Some(#operation_id),));
+        let tags = self.get_oapi_tags();
+        // Tags that aren't literals may be evaluated at runtime, so they're collected once.
+        let (tags_static, tags) = match tags.iter().all(|tag| matches!(tag, Expr::Lit(_))) {
+            _ if tags.is_empty() => (None, None),
+            true => (None, Some(quote!(tags: &[#(#tags),*],))),
+            false => (
+                Some(quote! {
+                    static __TAGS__: ::std::sync::OnceLock<::std::vec::Vec<&'static str>> =
+                        ::std::sync::OnceLock::new();
+                }),
+                Some(quote!(tags: __TAGS__.get_or_init(|| ::std::vec![#(#tags),*]),)),
+            ),
+        };
+        let option = |value: Option<String>| match value {
+            Some(value) => quote!(::core::option::Option::Some(#value)),
+            None => quote!(::core::option::Option::None),
+        };
+        let sitemap = self
+            .options
+            .sitemap
+            .as_ref()
+            .and_then(|(_ident, sitemap)| match sitemap {
+                Sitemap::Exclude(_lit) => Some(quote!(sitemap: ::core::option::Option::None,)),
+                Sitemap::Entry {
+                    priority: None,
+                    changefreq: None,
+                } => None,
+                Sitemap::Entry {
+                    priority,
+                    changefreq,
+                } => {
+                    let priority = option(priority.as_ref().map(|lit| lit.base10_digits().into()));
+                    let changefreq = option(changefreq.as_ref().map(Ident::to_string));
+                    Some(quote! {
+                        sitemap: ::core::option::Option::Some((#priority, #changefreq)),
+                    })
+                }
+            });
+        let cors = self
+            .options
+            .cors
+            .as_ref()
+            .map(|_cors| quote!(cors: ::core::option::Option::Some(&__CORS__),));
+        let permissions = self.permissions();
+        let permissions =
+            (!permissions.is_empty()).then(|| quote!(permissions: &[#(#permissions),*],));
+
+        let fields = [
+            aliases,
+            deprecate_alias,
+            localized,
+            enabled,
+            operation_id,
+            tags,
+            sitemap,
+            cors,
+            permissions,
+        ];
+        if fields.iter().all(Option::is_none) {
+            return Ok(quote! {
+                ::std::boxed::Box::new((#method, #handler)) as ::std::boxed::Box<dyn ::core::any::Any>
+            });
+        }
+        Ok(quote! {{
+            #tags_static
+            ::std::boxed::Box::new(::axum_typed_routing::__private::RouteDescription {
+                #(#fields)*
+                ..::axum_typed_routing::__private::RouteDescription::new(#method, #handler)
+            }) as ::std::boxed::Box<dyn ::core::any::Any>
+        }})
+    }
+
+    /// Whether the handler is kept as a regular function, with `keep_fn`, `in_module` or
+    /// `in_impl`.
+    pub fn keep_fn(&self) -> bool {
+        matches!(&self.options.keep_fn, Some((_ident, lit)) if lit.value())
+            || self.in_module()
+            || self.in_impl()
+    }
+
+    /// Whether the generated function is put into the [`route_module`](Self::route_module).
+    pub fn in_module(&self) -> bool {
+        matches!(&self.options.in_module, Some((_ident, lit)) if lit.value())
+    }
+
+    /// Whether the [`route_module`](Self::route_module) is generated: with the `module` option,
+    /// or the options and features putting something else into it.
+    pub fn has_module(&self) -> bool {
+        matches!(&self.options.module, Some((_ident, lit)) if lit.value())
+            || matches!(&self.options.generate_test, Some((_ident, lit)) if lit.value())
+            || self.in_module()
+            || cfg!(any(
+                feature = "templates",
+                feature = "fuzz",
+                feature = "client"
+            ))
+    }
+
+    /// The visibility of the generated function and of the route module: the `vis` option, or
+    /// the visibility `vis` of the handler. In the route module, the function is `pub`, since the
+    /// module limits its visibility.
+    pub fn route_fn_vis(&self, vis: &Visibility) -> (Visibility, Visibility) {
+        let vis = match &self.options.vis {
+            Some((_ident, vis)) => vis.clone(),
+            None => vis.clone(),
+        };
+        match self.in_module() {
+            true => (parse_quote!(pub), vis),
+            false => (vis.clone(), vis),
+        }
+    }
+
+    /// With `debug_handler: true`, the `axum::debug_handler` attribute of the inner function, with
+    /// the state of the route.
+    pub fn debug_handler(&self) -> Option<TokenStream2> {
+        if !matches!(&self.options.debug_handler, Some((_ident, lit)) if lit.value()) {
+            return None;
+        }
+        let state = &self.state;
+        Some(quote! { #[::axum_typed_routing::__private::debug_handler(state = #state)] })
+    }
+
+    /// Whether the inner function can return the future of the handler as it is, instead of
+    /// awaiting it in a future of its own: without hooks or options wrapping its output, and
+    /// without `debug_handler`, which only accepts `async` functions.
+    pub fn forwards_future(&self, output: &ReturnType) -> bool {
+        self.hooks(&self.options.before).is_empty()
+            && self.hooks(&self.options.after).is_empty()
+            && self.encodings().is_none()
+            && self.options.status.is_none()
+            && (self.oapi_options.is_none() || self.result_output(output).is_none())
+            && self.options.cached.is_none()
+            && !matches!(
+                &self.options.idempotent,
+                Some((_ident, Idempotency::Replayed { .. }))
+            )
+            && self.debug_handler().is_none()
+    }
+
+    /// Whether the handler is an associated function of an `impl` block, with `in_impl`.
+    pub fn in_impl(&self) -> bool {
+        matches!(&self.options.in_impl, Some((_ident, lit)) if lit.value())
+    }
+
+    /// The name of the webhook of an `api_webhook`, which isn't routed, only documented.
+    pub fn webhook(&self) -> Option<&LitStr> {
+        let oapi_options = self.oapi_options.as_ref()?;
+        oapi_options.webhook.as_ref().map(|(_ident, name)| name)
+    }
+
+    /// The name of the generated function: the `name` option, `<FN>_route` if the handler is
+    /// kept, or the name of the handler.
+    pub fn route_fn_name(&self, fn_name: &Ident) -> Ident {
+        match &self.options.name {
+            Some((_ident, name)) => name.clone(),
+            None if self.in_module() => format_ident!("route"),
+            None if self.keep_fn() => format_ident!("{}_route", fn_name),
+            None => fn_name.clone(),
+        }
+    }
+
+    /// Whether the handler also answers `HEAD` requests, because of `head: auto`.
+    pub fn auto_head(&self) -> bool {
+        self.options.head.is_some()
+    }
+
+    /// The return type of the inner function and the expression returning `output` from it,
+    /// which is encoded with the `accepts` option, paired with the `status` if one is
+    /// specified, and looked up in the response cache with the `cached` option.
+    pub fn inner_output(
+        &self,
+        fn_name: &Ident,
+        output: &ReturnType,
+        value: TokenStream2,
+    ) -> (TokenStream2, TokenStream2) {
+        let ty = match output {
+            ReturnType::Default => quote!(()),
+            ReturnType::Type(_, ty) => quote!(#ty),
+        };
+        let (ty, value) = match self.encodings() {
+            Some(encodings) => self.negotiated_output(output, encodings, ty, value),
+            None => (ty, value),
+        };
+        let (ty, value) = match (&self.options.status, self.result_output(output)) {
+            (Some((_ident, status)), Some((ok, err))) => (
+                quote! { ::core::result::Result<#ok, #err> },
+                quote! {
+                    ::core::result::Result::map(#value, |value| {
+                        (::axum::http::StatusCode::from_u16(#status).unwrap(), value)
+                    })
+                },
+            ),
+            (Some((_ident, status)), None) => (
+                quote! { (::axum::http::StatusCode, #ty) },
+                quote! { (::axum::http::StatusCode::from_u16(#status).unwrap(), #value) },
+            ),
+            (None, _) => (ty, value),
+        };
+        let (ty, value) = match (&self.oapi_options, self.result_output(output)) {
+            (Some(_), Some((ok, err))) => (
+                quote! { ::axum_typed_routing::__private::ApiResult<#ok, #err> },
+                quote! { ::axum_typed_routing::__private::ApiResult(#value) },
+            ),
+            _ => (ty, value),
+        };
+        let (ty, value) = self
+            .cached_output(fn_name, &ty, &value)
+            .or_else(|| self.idempotent_output(fn_name, &ty, &value))
+            .unwrap_or((ty, value));
+        (quote!(-> #ty), value)
+    }
+
+    /// The `Ok` and `Err` types of a `Result` output, with the `Ok` value negotiated and sent with
+    /// the `status` of the route. With `api_route`, the `Result` is wrapped in an `ApiResult`,
+    /// which documents the `ErrorResponses` of the `Err` type.
+    fn result_output<'a>(&self, output: &'a ReturnType) -> Option<(TokenStream2, &'a Type)> {
+        let ReturnType::Type(_, ty) = output else {
+            return None;
+        };
+        let (ok, err) = result_types(ty)?;
+        let ok = match self.encodings() {
+            Some(_) => quote! { ::axum_typed_routing::__private::Negotiated<#ok> },
+            None => quote!(#ok),
+        };
+        let ok = match self.options.status {
+            Some(_) => quote! { (::axum::http::StatusCode, #ok) },
+            None => ok,
+        };
+        Some((ok, err))
+    }
+
+    /// The output of the inner function with the `cached` option: the cached response, or the
+    /// response of the handler, which is cached for the `ttl` if it's successful.
+    fn cached_output(
+        &self,
+        fn_name: &Ident,
+        ty: &TokenStream2,
+        value: &TokenStream2,
+    ) -> Option<(TokenStream2, TokenStream2)> {
+        let (_ident, cached) = self.options.cached.as_ref()?;
+        let handler = fn_name.unraw().to_string();
+        let extracted = self.extracted_idents();
+        let params = self.cache_key_params().into_iter().map(|mut ident| {
+            let name = ident.unraw().to_string();
+            // The other arguments are passed to the inner function with mixed-site hygiene.
+            if !extracted.contains(&ident) {
+                ident.set_span(Span::mixed_site().located_at(ident.span()));
+            }
+            quote! { (#name, &#ident as &dyn ::core::fmt::Debug) }
+        });
+        let ttl = cached.ttl_millis;
+        Some((
+            quote! { ::axum_typed_routing::__private::Cached<#ty> },
+            quote! {{
+                let __key__ = ::axum_typed_routing::__private::cache_key(
+                    ::core::concat!(::core::module_path!(), "::", #handler),
+                    &[#(#params),*],
+                );
+                ::axum_typed_routing::__private::Cached::new(match __cache__.get(&__key__).await {
+                    ::core::option::Option::Some(__cached__) => {
+                        ::axum::response::IntoResponse::into_response(__cached__)
+                    }
+                    ::core::option::Option::None => {
+                        let __response__ = ::axum::response::IntoResponse::into_response(#value);
+                        __cache__
+                            .insert(
+                                __key__,
+                                __response__,
+                                ::core::time::Duration::from_millis(#ttl),
+                            )
+                            .await
+                    }
+                })
+            }},
+        ))
+    }
+
+    /// The output of the inner function with `idempotent: { ttl: .. }`: the stored response to
+    /// the `Idempotency-Key` of the request, or the response of the handler, which is stored for
+    /// the `ttl` unless it's a server error.
+    fn idempotent_output(
+        &self,
+        fn_name: &Ident,
+        ty: &TokenStream2,
+        value: &TokenStream2,
+    ) -> Option<(TokenStream2, TokenStream2)> {
+        let Some((_ident, Idempotency::Replayed { ttl_millis })) = &self.options.idempotent else {
+            return None;
+        };
+        let handler = fn_name.unraw().to_string();
+        Some((
+            quote! { ::axum_typed_routing::__private::Cached<#ty> },
+            quote! {{
+                let __key__ = ::axum_typed_routing::__private::idempotency_key(
+                    ::core::concat!(::core::module_path!(), "::", #handler),
+                    &__idempotency_key__,
+                );
+                ::axum_typed_routing::__private::Cached::new(match __idempotency__.get(&__key__).await {
+                    ::core::option::Option::Some(__replayed__) => __replayed__,
+                    ::core::option::Option::None => {
+                        let __response__ = ::axum::response::IntoResponse::into_response(#value);
+                        __idempotency__
+                            .insert(
+                                __key__,
+                                __response__,
+                                ::core::time::Duration::from_millis(#ttl_millis),
+                            )
+                            .await
+                    }
+                })
+            }},
+        ))
+    }
+
+    /// The extractors of the `idempotent` option: the `Idempotency-Key` of the request, and the
+    /// idempotency store of the state if its responses are replayed.
+    pub fn idempotency_extractor(&self) -> Option<TokenStream2> {
+        let key = quote! { __idempotency_key__: ::axum_typed_routing::IdempotencyKey, };
+        let state = &self.state;
+        match &self.options.idempotent {
+            Some((_ident, Idempotency::Required)) => Some(key),
+            Some((_ident, Idempotency::Replayed { .. })) => Some(quote! {
+                #key
+                __idempotency__: ::axum_typed_routing::__private::IdempotencyState<#state>,
+            }),
+            Some((_, Idempotency::Disabled)) | None => None,
+        }
+    }
+
+    /// The parameters keying the responses of the `cached` option: its `key`, or the path- and
+    /// query-parameters passed to the handler, which then takes no other extractors.
+    fn cache_key_params(&self) -> Vec<Ident> {
+        match self.options.cached.as_ref() {
+            Some((_ident, cached)) => match &cached.key {
+                Some(key) => key.clone(),
+                None => self
+                    .extracted_idents()
+                    .into_iter()
+                    .filter(|ident| {
+                        !self
+                            .multipart_fields
+                            .iter()
+                            .any(|(field, _ty)| field == ident)
+                    })
+                    .collect(),
+            },
+            None => Vec::new(),
+        }
+    }
+
+    /// The extractor of the response cache of the state for the `cached` option.
+    pub fn cache_extractor(&self) -> Option<TokenStream2> {
+        self.options.cached.as_ref()?;
+        let state = &self.state;
+        Some(quote! { __cache__: ::axum_typed_routing::__private::CacheState<#state>, })
+    }
+
+    /// The encodings of the `accepts` option.
+    fn encodings(&self) -> Option<&[Ident]> {
+        let (_ident, Accepts(encodings)) = self.options.accepts.as_ref()?;
+        Some(encodings)
+    }
+
+    /// The output of the inner function with the `accepts` option: the return value of the
+    /// handler wrapped in `Negotiated`, or its `Ok` value for a `Result`.
+    fn negotiated_output(
+        &self,
+        output: &ReturnType,
+        encodings: &[Ident],
+        ty: TokenStream2,
+        value: TokenStream2,
+    ) -> (TokenStream2, TokenStream2) {
+        let encodings = encodings.iter().map(|encoding| match encoding == "json" {
+            true => quote!(::axum_typed_routing::__private::Encoding::Json),
+            false => quote!(::axum_typed_routing::__private::Encoding::MsgPack),
+        });
+        let negotiated = quote! {
+            |value| ::axum_typed_routing::__private::Negotiated::new(
+                __accept__,
+                &[#(#encodings),*],
+                value,
+            )
+        };
+        let result = match output {
+            ReturnType::Type(_, ty) => result_types(ty),
+            ReturnType::Default => None,
+        };
+        match result {
+            Some((ok, err)) => (
+                quote! {
+                    ::core::result::Result<::axum_typed_routing::__private::Negotiated<#ok>, #err>
+                },
+                quote! { ::core::result::Result::map(#value, #negotiated) },
+            ),
+            None => (
+                quote! { ::axum_typed_routing::__private::Negotiated<#ty> },
+                quote! { (#negotiated)(#value) },
+            ),
+        }
+    }
+
+    /// The extractor checking the `feature_flag` of the route with the `FeatureGate` of the
+    /// state, before the other arguments are extracted.
+    pub fn feature_extractor(&self) -> Option<TokenStream2> {
+        self.options.feature_flag.as_ref()?;
+        Some(quote! {
+            _: ::axum_typed_routing::__private::RequireFeature<__FeatureFlag__>,
+        })
+    }
+
+    /// The marker type naming the `feature_flag` for the [`Self::feature_extractor`].
+    pub fn feature_flag_struct(&self) -> Option<TokenStream2> {
+        let (_ident, flag) = self.options.feature_flag.as_ref()?;
+        Some(quote! {
+            struct __FeatureFlag__;
+            impl ::axum_typed_routing::__private::FeatureFlagName for __FeatureFlag__ {
+                const NAME: &'static str = #flag;
+            }
+        })
+    }
+
+    /// The extractor of the `multipart(..)` clause. It reads the body, so it comes after the
+    /// other arguments.
+    pub fn multipart_extractor(&self) -> Option<TokenStream2> {
+        if self.multipart_fields.is_empty() {
+            return None;
+        }
+        let fields = self.multipart_fields.iter().map(|(ident, _ty)| {
+            match self.unused_params.contains(ident) {
+                true => quote!(#ident: _),
+                false => quote!(#ident),
+            }
+        });
+        Some(quote! {
+            __MultipartFields__ { #(#fields,)* }: __MultipartFields__,
+        })
+    }
+
+    /// The struct of the [`Self::multipart_extractor`], reading the fields of the body into
+    /// their types with `FromMultipartField`. A missing or repeated field is rejected with
+    /// `400 Bad Request`, and unknown fields are skipped. With `api_route`, its
+    /// `OperationInput` documents the `multipart/form-data` body.
+    pub fn multipart_struct(&self, with_aide: bool) -> Option<TokenStream2> {
+        if self.multipart_fields.is_empty() {
+            return None;
+        }
+        let idents = self
+            .multipart_fields
+            .iter()
+            .map(|(ident, _ty)| ident)
+            .collect::<Vec<_>>();
+        let types = self.multipart_fields.iter().map(|(_ident, ty)| ty);
+        let names = idents
+            .iter()
+            .map(|ident| ident.unraw().to_string())
+            .collect::<Vec<_>>();
+        let optional = self
+            .multipart_fields
+            .iter()
+            .map(|(_ident, ty)| single_generic_argument(ty, "Option"));
+        let slot_types = optional
+            .clone()
+            .zip(&self.multipart_fields)
+            .map(|(inner, (_ident, ty))| inner.unwrap_or(ty))
+            .collect::<Vec<_>>();
+        let required = optional
+            .clone()
+            .zip(idents.iter().zip(&names))
+            .filter(|(inner, _field)| inner.is_none())
+            .map(|(_inner, (ident, name))| {
+                quote! {
+                    let ::core::option::Option::Some(#ident) = #ident else {
+                        return ::core::result::Result::Err(
+                            ::axum_typed_routing::__private::missing_multipart_field(#name),
+                        );
+                    };
+                }
+            });
+
+        let operation_input = with_aide.then(|| {
+            let fields = optional.zip(slot_types.iter().zip(&names)).map(
+                |(inner, (ty, name))| {
+                    let required = inner.is_none();
+                    let binary = single_generic_argument(ty, "Vec")
+                        .is_some_and(|item| item.to_token_stream().to_string() == "u8")
+                        || matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(
+                            |segment| segment.ident == "Bytes" || segment.ident == "MultipartFile",
+                        ));
+                    match binary {
+                        true => quote! {
+                            ::axum_typed_routing::__private::multipart_binary_field(#name, #required)
+                        },
+                        false => quote! {
+                            ::axum_typed_routing::__private::multipart_input_field::<#ty>(
+                                __ctx__, #name, #required,
+                            )
+                        },
+                    }
+                },
+            );
+            quote! {
+                impl ::axum_typed_routing::aide::OperationInput for __MultipartFields__ {
+                    fn operation_input(
+                        __ctx__: &mut ::axum_typed_routing::aide::generate::GenContext,
+                        __operation__: &mut ::axum_typed_routing::aide::openapi::Operation,
+                    ) {
+                        ::axum_typed_routing::__private::set_multipart_body(
+                            __operation__,
+                            ::std::vec![#(#fields),*],
+                        );
+                    }
+                }
+            }
+        });
+
+        Some(quote! {
+            struct __MultipartFields__ {
+                #(#idents: #types,)*
+            }
+
+            impl<__S__: ::core::marker::Send + ::core::marker::Sync>
+                ::axum::extract::FromRequest<__S__> for __MultipartFields__
+            {
+                type Rejection = ::axum::response::Response;
+
+                async fn from_request(
+                    __request__: ::axum::extract::Request,
+                    __state__: &__S__,
+                ) -> ::core::result::Result<Self, Self::Rejection> {
+                    let mut __multipart__ =
+                        ::axum_typed_routing::__private::multipart(__request__, __state__).await?;
+                    #(let mut #idents = ::core::option::Option::<#slot_types>::None;)*
+                    while let ::core::option::Option::Some(__field__) =
+                        ::axum_typed_routing::__private::next_multipart_field(&mut __multipart__)
+                            .await?
+                    {
+                        match __field__.name() {
+                            #(
+                                ::core::option::Option::Some(#names) => {
+                                    ::axum_typed_routing::__private::read_multipart_field(
+                                        &mut #idents,
+                                        __field__,
+                                    )
+                                    .await?
+                                }
+                            )*
+                            _ => {}
+                        }
+                    }
+                    #(#required)*
+                    ::core::result::Result::Ok(Self { #(#idents,)* })
+                }
+            }
+
+            #operation_input
+        })
+    }
+
+    /// The extractor of the `Accept` header for the `accepts` option.
+    pub fn accept_extractor(&self) -> Option<TokenStream2> {
+        self.encodings()?;
+        Some(quote! { __accept__: ::axum_typed_routing::__private::AcceptHeader, })
+    }
+
+    pub fn query_params_struct(&self, with_aide: bool) -> Option<TokenStream2> {
+        if let Some((ident, _ty)) = self.single_query_param() {
+            let name = ident.unraw().to_string();
+            return Some(quote! {
+                struct __QueryParamName__;
+                impl ::axum_typed_routing::__private::QueryParamName for __QueryParamName__ {
+                    const NAME: &'static str = #name;
+                }
+            });
+        }
+        if let Some(params) = self.shared_query_params() {
+            let names = params.iter().map(|(ident, _ty)| ident.unraw().to_string());
+            return Some(quote! {
+                struct __QueryParamNames__;
+                impl ::axum_typed_routing::__private::QueryParamNames for __QueryParamNames__ {
+                    const NAMES: &'static [&'static str] = &[#(#names),*];
+                }
+            });
+        }
+        match self.extracts_query() {
+            false => None,
+            true => {
+                let idents = self.query_params.iter().map(|item| &item.0);
+                let types = self.query_params.iter().map(|item| &item.1);
+                let attrs = self.query_params.iter().map(|(ident, ty)| {
+                    let options = self.param_options.get(ident)?;
+                    let flatten = options
+                        .flatten
+                        .as_ref()
+                        .map(|_| quote! { #[serde(flatten)] });
+                    let deserialize_with = self.deserialize_with_attrs(ident, with_aide);
+                    let comma_separated = options.comma_separated().then(|| {
+                        match single_generic_argument(ty, "Option") {
+                            Some(_) => quote! {
+                                #[serde(deserialize_with = "::axum_typed_routing::__private::comma_separated_option")]
+                            },
+                            None => quote! {
+                                #[serde(deserialize_with = "::axum_typed_routing::__private::comma_separated")]
+                            },
+                        }
+                    });
+                    // Without `default`, a missing optional parameter would be an error.
+                    let default = ((deserialize_with.is_some() || comma_separated.is_some())
+                        && single_generic_argument(ty, "Option").is_some())
+                    .then(|| quote! { #[serde(default)] });
+                    let schema = match (&options.schema_with, &options.skip_schema) {
+                        (Some((_ident, path)), _) if with_aide => {
+                            Some(quote! { #[schemars(schema_with = #path)] })
+                        }
+                        (_, Some(_skip)) if with_aide => Some(quote! { #[schemars(skip)] }),
+                        _ => None,
+                    };
+                    Some(quote! { #flatten #deserialize_with #comma_separated #default #schema })
+                });
+                // `#[schemars]` is only known with the `JsonSchema` derive of `api_route`.
+                let field_attrs = self.query_params.iter().map(|(ident, _ty)| {
+                    let attrs = self.field_attrs.get(ident).into_iter().flatten();
+                    let attrs = attrs.filter(|attr| with_aide || !attr.path().is_ident("schemars"));
+                    quote! { #(#attrs)* }
+                });
+                let derive = match with_aide {
+                    true => quote! {
+                        #[derive(::serde::Deserialize, ::axum_typed_routing::schemars::JsonSchema)]
+                        #[schemars(crate = "::axum_typed_routing::schemars")]
+                    },
+                    false => quote! { #[derive(::serde::Deserialize)] },
+                };
+                let deny_unknown_fields = self
+                    .deny_unknown_query()
+                    .then(|| quote! { #[serde(deny_unknown_fields)] });
+                // A rejected `lenient_query` is replaced by the default, without any parameters.
+                let default = self
+                    .lenient_query()
+                    .then(|| quote! { #[derive(::core::default::Default)] });
+                Some(quote! {
+                    #derive
+                    #default
+                    #deny_unknown_fields
+                    struct __QueryParams__ {
+                        #(#attrs #field_attrs #idents: #types,)*
+                    }
+                })
+            }
+        }
+    }
+
+    /// The function deserializing a path- or query-parameter, given with `deserialize_with`, or
+    /// converting it with `try_from` or `from_str`. A wildcard of segments is split at the
+    /// slashes.
+    fn deserialize_with(&self, ident: &Ident) -> Option<LitStr> {
+        if self.splits_segments(ident) {
+            let path = "::axum_typed_routing::__private::path_segments";
+            return Some(LitStr::new(path, ident.span()));
+        }
+        let options = self.param_options.get(ident)?;
+        if let Some((_ident, path)) = &options.deserialize_with {
+            return Some(path.clone());
+        }
+        if let Some(from_str) = &options.from_str {
+            let path = match self.is_optional_query_param(ident) {
+                true => "::axum_typed_routing::__private::from_str_param_option",
+                false => "::axum_typed_routing::__private::from_str_param",
+            };
+            return Some(LitStr::new(path, from_str.span()));
+        }
+        let (_ident, raw) = options.try_from.as_ref()?;
+        let function = match self.is_optional_query_param(ident) {
+            true => "try_from_param_option",
+            false => "try_from_param",
+        };
+        let path = format!(
+            "::axum_typed_routing::__private::{function}::<{}, _, _>",
+            raw.to_token_stream()
+        );
+        Some(LitStr::new(&path, raw.span()))
+    }
+
+    /// The type a parameter with `deserialize_with`, `try_from` or `from_str` is documented as:
+    /// a string, since that's what `deserialize_with` functions and `FromStr` parse, or the raw
+    /// type of `try_from`.
+    fn documented_type(&self, ident: &Ident) -> Option<Type> {
+        if self.splits_segments(ident) {
+            return Some(parse_quote!(::std::string::String));
+        }
+        let options = self.param_options.get(ident)?;
+        if options.deserialize_with.is_some() || options.from_str.is_some() {
+            return Some(parse_quote!(::std::string::String));
+        }
+        let (_ident, raw) = options.try_from.as_ref()?;
+        match self.is_optional_query_param(ident) {
+            true => Some(parse_quote!(::core::option::Option<#raw>)),
+            false => Some(raw.clone()),
+        }
+    }
+
+    /// Whether `ident` is a wildcard of segments, which isn't converted otherwise.
+    fn splits_segments(&self, ident: &Ident) -> bool {
+        let converts = self
+            .param_options
+            .get(ident)
+            .is_some_and(ParamOptions::converts);
+        !converts
+            && self.path_params.iter().any(|(_slash, path_param)| {
+                matches!(path_param, PathParam::WildCard(_, _, _, wildcard, ty, _, _)
+                    if wildcard == ident && is_segments_type(ty))
+            })
+    }
+
+    /// Whether `ident` is a query parameter of type `Option<T>`.
+    fn is_optional_query_param(&self, ident: &Ident) -> bool {
+        self.query_params.iter().any(|(query_ident, ty)| {
+            query_ident == ident && single_generic_argument(ty, "Option").is_some()
+        })
+    }
+
+    /// The field attributes of a parameter with `deserialize_with`, `try_from` or `from_str`.
+    fn deserialize_with_attrs(&self, ident: &Ident, with_aide: bool) -> Option<TokenStream2> {
+        let path = self.deserialize_with(ident)?;
+        // `schema_with` and `skip_schema` replace the documented type.
+        let replaced = self
+            .param_options
+            .get(ident)
+            .is_some_and(|options| options.schema_option().is_some());
+        let schema = self
+            .documented_type(ident)
+            .filter(|_| with_aide && !replaced)
+            .map(|ty| {
+                let ty = ty.to_token_stream().to_string();
+                quote! { #[schemars(with = #ty)] }
+            });
+        Some(quote! { #[serde(deserialize_with = #path)] #schema })
+    }
+
+    /// Newtypes for the path parameters with `deserialize_with`, since the attribute can't be
+    /// put on the elements of the extracted tuple. With `path_struct: true`, this is the struct
+    /// with a named field for every path parameter instead.
+    pub fn path_params_structs(&self, with_aide: bool) -> TokenStream2 {
+        let derive = match with_aide {
+            true => quote! {
+                #[derive(::serde::Deserialize, ::axum_typed_routing::schemars::JsonSchema)]
+                #[schemars(crate = "::axum_typed_routing::schemars")]
+            },
+            false => quote! { #[derive(::serde::Deserialize)] },
+        };
+        if self.generates_path_struct() {
+            let fields = self.path_params.iter().filter_map(|(_slash, path_param)| {
+                let (name, ident, ty) = match path_param {
+                    PathParam::Capture(name, _, ident, ty, _, _) => (name, ident, ty),
+                    PathParam::WildCard(name, _, _, ident, ty, _, _) => (name, ident, ty),
+                    PathParam::Static(_) => return None,
+                };
+                let rename =
+                    (ident.unraw() != name.value()).then(|| quote! { #[serde(rename = #name)] });
+                let deserialize_with = self.deserialize_with_attrs(ident, with_aide);
+                Some(quote! { #rename #deserialize_with #ident: #ty, })
+            });
+            return quote! {
+                #derive
+                struct __PathParams__ {
+                    #(#fields)*
+                }
+            };
+        }
+
+        let structs = self
+            .path_params
+            .iter()
+            .filter_map(|(_slash, path_param)| path_param.capture())
+            .filter_map(|(ident, ty)| {
+                let attrs = self.deserialize_with_attrs(ident, with_aide)?;
+                let wrapper = path_param_wrapper(ident);
+                Some(quote! {
+                    #derive
+                    #[allow(non_camel_case_types)]
+                    struct #wrapper(#attrs #ty);
                 })
+            });
+        quote! { #(#structs)* }
+    }
+
+    /// Checks that the types of the path- and query-parameters can be deserialized, so a wrong
+    /// type gives an error at the parameter, instead of one about the handler.
+    pub fn param_assertions(&self) -> TokenStream2 {
+        let path_types = self
+            .path_params
+            .iter()
+            .filter_map(|(_slash, path_param)| path_param.capture())
+            .filter(|(ident, _ty)| self.deserialize_with(ident).is_none())
+            .map(|(_ident, ty)| ty)
+            .filter(|_ty| self.path_struct_arg.is_none())
+            .chain(self.path_struct_type())
+            .map(|ty| {
+                quote_spanned! {ty.span()=>
+                    ::axum_typed_routing::__private::assert_path_param::<#ty>();
+                }
+            });
+        let query_types = self
+            .query_params
+            .iter()
+            .filter(|(ident, _ty)| self.deserialize_with(ident).is_none())
+            .map(|(_ident, ty)| {
+                quote_spanned! {ty.span()=>
+                    ::axum_typed_routing::__private::assert_query_param::<#ty>();
+                }
+            });
+        quote! { #(#path_types)* #(#query_types)* }
+    }
+
+    /// Checks that every argument of the handler is an extractor, so a wrong argument gives an
+    /// error at its type, instead of one about the handler.
+    ///
+    /// The `remaining` arguments follow the path- and query-extractors, which always implement
+    /// `FromRequestParts`. Finally `handler` is checked to be a `Handler`, for the errors that
+    /// aren't caused by a single argument.
+    pub fn extractor_assertions(
+        &self,
+        remaining: &Punctuated<PatType, Comma>,
+        handler: TokenStream2,
+    ) -> TokenStream2 {
+        let state = &self.state;
+        let count = remaining.len();
+        let assertions = remaining.iter().enumerate().map(|(i, pat_type)| {
+            let ty = &pat_type.ty;
+            match i + 1 == count {
+                true => quote_spanned! {ty.span()=>
+                    ::axum_typed_routing::__private::assert_last_argument::<#ty, #state, _>();
+                },
+                false => quote_spanned! {ty.span()=>
+                    ::axum_typed_routing::__private::assert_parts_argument::<#ty, #state>();
+                },
             }
+        });
+        quote! {
+            #(#assertions)*
+            ::axum_typed_routing::__private::assert_handler::<_, _, #state>(&#handler);
         }
     }
 
+    /// The arguments passed to the handler after the extracted ones: the `remaining` arguments of
+    /// the inner function, with the values of the `inject(..)` clause in between.
+    pub fn remaining_args(
+        &self,
+        args: &Punctuated<FnArg, Comma>,
+        remaining: &Punctuated<PatType, Comma>,
+    ) -> Vec<TokenStream2> {
+        let extracted = self.extracted_idents();
+        let mut remaining = remaining_idents(remaining).into_iter();
+        args.iter()
+            .filter_map(|arg| {
+                let FnArg::Typed(pat_type) = arg else {
+                    return None;
+                };
+                let ident = match &*pat_type.pat {
+                    Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+                    _ => None,
+                };
+                if ident.is_some_and(|ident| extracted.contains(ident)) {
+                    return None;
+                }
+                let injected = self
+                    .injected
+                    .iter()
+                    .find(|(name, _value)| Some(name) == ident);
+                match injected {
+                    Some((_name, value)) => Some(quote!(#value)),
+                    None => remaining.next().map(|pat| quote!(#pat)),
+                }
+            })
+            .collect()
+    }
+
     pub fn extracted_idents(&self) -> Vec<Ident> {
         let mut idents = Vec::new();
+        if let Some(arg) = &self.path_struct_arg {
+            idents.push(arg.clone());
+        }
         for (_slash, path_param) in &self.path_params {
+            if self.path_struct_arg.is_some() {
+                break;
+            }
             if let Some((ident, _ty)) = path_param.capture() {
-                idents.push(ident.clone());
+                if !self.unused_params.contains(ident) {
+                    idents.push(ident.clone());
+                }
             }
             // if let Some((_colon, ident, _ty)) = colon {
             //     idents.push(ident.clone());
             // }
         }
         for (ident, _ty) in &self.query_params {
-            idents.push(ident.clone());
+            if !self.unused_params.contains(ident) {
+                idents.push(ident.clone());
+            }
+        }
+        for (ident, _ty) in &self.multipart_fields {
+            if !self.unused_params.contains(ident) {
+                idents.push(ident.clone());
+            }
         }
         idents
     }
 
-    /// The arguments not used in the route.
-    /// Map the identifier to `___arg___{i}: Type`.
-    pub fn remaining_pattypes_numbered(
+    /// The arguments that aren't path- or query-parameters, passed through to the handler, e.g.
+    /// `state: State<S>`. They keep the names of the handler's arguments, so the diagnostics of
+    /// axum and `debug_handler` refer to them, see [`passthrough_ident`]. The handler `fn_name`
+    /// is nested in the inner function, where it would shadow an argument of the same name.
+    pub fn remaining_pattypes(
         &self,
+        fn_name: &Ident,
         args: &Punctuated<FnArg, Comma>,
     ) -> Punctuated<PatType, Comma> {
+        let mut taken = self.extracted_idents();
+        taken.push(fn_name.clone());
         args.iter()
             .enumerate()
             .filter_map(|(i, item)| {
                 if let FnArg::Typed(pat_type) = item {
                     if let syn::Pat::Ident(pat_ident) = &*pat_type.pat {
+                        if self.path_struct_arg.as_ref() == Some(&pat_ident.ident)
+                            || self
+                                .injected
+                                .iter()
+                                .any(|(name, _)| *name == pat_ident.ident)
+                        {
+                            return None;
+                        }
                         if self.path_params.iter().any(|(_slash, path_param)| {
                             if let Some((path_ident, _ty)) = path_param.capture() {
                                 path_ident == &pat_ident.ident
@@ -220,20 +2460,678 @@ impl CompiledRoute {
                             .query_params
                             .iter()
                             .any(|(query_ident, _)| query_ident == &pat_ident.ident)
+                            || self
+                                .multipart_fields
+                                .iter()
+                                .any(|(field_ident, _)| field_ident == &pat_ident.ident)
                         {
                             return None;
                         }
                     }
 
-                    let mut new_pat_type = pat_type.clone();
-                    let ident = format_ident!("___arg___{}", i);
-                    new_pat_type.pat = Box::new(parse_quote!(#ident));
-                    Some(new_pat_type)
-                } else {
-                    unimplemented!("Self type is not supported")
-                }
-            })
-            .collect()
+                    let mut new_pat_type = pat_type.clone();
+                    let ident = passthrough_ident(&pat_type.pat, i, &taken);
+                    taken.push(ident.clone());
+                    new_pat_type.pat = Box::new(parse_quote!(#ident));
+                    if self.oapi_options.is_some() && self.api_ignored.contains(&i) {
+                        let ty = &pat_type.ty;
+                        new_pat_type.pat =
+                            Box::new(parse_quote!(::axum_typed_routing::aide::NoApi(#ident)));
+                        new_pat_type.ty =
+                            Box::new(parse_quote!(::axum_typed_routing::aide::NoApi<#ty>));
+                    }
+                    Some(new_pat_type)
+                } else {
+                    unimplemented!("Self type is not supported")
+                }
+            })
+            .collect()
+    }
+
+    /// The module with the same name as the handler, if [`has_module`](Self::has_module),
+    /// containing the `NAME` and `ROUTE` of the route, and helpers for linking to it from
+    /// templates when the `templates` feature is enabled. With `in_module`, it also contains the
+    /// generated `route_fn`.
+    pub fn route_module(
+        &self,
+        vis: &Visibility,
+        fn_name: &Ident,
+        route_fn: Option<TokenStream2>,
+    ) -> TokenStream2 {
+        // The path of a route with a `base` is only known at runtime.
+        let route = self.options.base.is_none().then(|| {
+            let method = self.method.name();
+            let path = self.to_axum_path_string();
+            let permissions = self.permissions();
+            quote! {
+                /// The method, path and handler of the route, e.g. for `route_index!`.
+                pub const ROUTE: ::axum_typed_routing::RouteInfo = ::axum_typed_routing::RouteInfo {
+                    method: #method,
+                    path: #path,
+                    handler: NAME,
+                    permissions: &[#(#permissions),*],
+                };
+            }
+        });
+        let operation_id = self.module_operation_id(fn_name);
+        let operation_id_fn = self.operation_id_fn(fn_name);
+        let template_helpers = self.template_helpers();
+        let extraction_test = self.extraction_test();
+        let param_strategy = self.param_strategy();
+        let client_request = self.client_request();
+        quote! {
+            #operation_id_fn
+
+            #[allow(clippy::ptr_arg)]
+            #vis mod #fn_name {
+                #[allow(unused_imports)]
+                use super::*;
+
+                /// The name of the handler, including its module path, e.g. for logging, tracing
+                /// and metrics. Unlike the path, this doesn't change when the route is moved.
+                pub const NAME: &str = ::core::module_path!();
+
+                #route
+
+                /// The operation id of the route in the OpenAPI documentation, if it has one,
+                /// e.g. for the `OperationId` of `route_index!`.
+                pub fn operation_id() -> ::core::option::Option<&'static str> {
+                    #operation_id
+                }
+
+                #route_fn
+
+                #template_helpers
+                #extraction_test
+                #param_strategy
+                #client_request
+            }
+        }
+    }
+
+    /// The `param_strategy()` of the [`route_module`](Self::route_module) when the `fuzz`
+    /// feature is enabled: a `proptest` strategy for paths of this route with arbitrary path-
+    /// and query-parameters. Like with the [`link_builder`](Self::link_builder), query
+    /// parameters with `deserialize_with` or `flatten` are left out.
+    fn param_strategy(&self) -> Option<TokenStream2> {
+        if !cfg!(feature = "fuzz") {
+            return None;
+        }
+        let (path_idents, path_types): (Vec<_>, Vec<_>) = self
+            .path_params
+            .iter()
+            .filter_map(|(_slash, path_param)| path_param.capture())
+            .unzip();
+        let query_params = self
+            .query_params
+            .iter()
+            .filter(|(ident, _ty)| !self.param_options.contains_key(ident))
+            .map(|(ident, ty)| match single_generic_argument(ty, "Option") {
+                Some(inner) => (ident, inner, quote!(#ident.as_ref())),
+                None => (ident, &**ty, quote!(::core::option::Option::Some(&#ident))),
+            })
+            .collect::<Vec<_>>();
+        let query_idents = query_params.iter().map(|(ident, _ty, _value)| *ident);
+        let query_types = self
+            .query_params
+            .iter()
+            .filter(|(ident, _ty)| !self.param_options.contains_key(ident))
+            .map(|(_ident, ty)| ty);
+        // `()` isn't a strategy, so routes without parameters use `Just(())` instead.
+        let strategies = |types: Vec<TokenStream2>| match types.is_empty() {
+            true => quote!(::axum_typed_routing::__private::proptest::strategy::Just(())),
+            false => {
+                quote!((#(::axum_typed_routing::__private::proptest::arbitrary::any::<#types>(),)*))
+            }
+        };
+        let path_strategies = strategies(path_types.iter().map(|ty| quote!(#ty)).collect());
+        // A wildcard of segments needs one that isn't empty, other parameters can't be empty.
+        let non_empty = path_idents.iter().zip(&path_types).map(|(ident, ty)| {
+            match is_segments_type(ty) {
+                true => quote! {
+                    #ident.iter().any(|segment| {
+                        !::axum_typed_routing::PathSegmentEncode::to_path_segment(segment).is_empty()
+                    })
+                },
+                false => quote! {
+                    !::axum_typed_routing::PathSegmentEncode::to_path_segment(#ident).is_empty()
+                },
+            }
+        });
+        let query_types = query_types.collect::<Vec<_>>();
+        let query_strategies = strategies(query_types.iter().map(|ty| quote!(#ty)).collect());
+        let format_bounds = self.format_bounds(
+            &query_params
+                .iter()
+                .map(|(_ident, ty, _value)| *ty)
+                .collect::<Vec<_>>(),
+        );
+        let arbitrary = quote!(::axum_typed_routing::__private::proptest::arbitrary::Arbitrary);
+        let arbitrary_bounds = path_types
+            .iter()
+            .map(|ty| quote!(#ty))
+            .chain(query_types.iter().map(|ty| quote!(#ty)))
+            .map(|ty| quote!(for<'__a> #ty: #arbitrary,));
+
+        let mut pushes = Vec::new();
+        if let Some((_ident, base)) = &self.options.base {
+            pushes.push(quote! { __path__.push_str(#base); });
+        }
+        for (_slash, path_param) in &self.path_params {
+            pushes.push(match path_param {
+                PathParam::Capture(_, _, ident, _, _, _) => quote! {
+                    ::axum_typed_routing::__private::push_path_segment(&mut __path__, &#ident);
+                },
+                PathParam::WildCard(_, _, _, ident, ty, _, _) if is_segments_type(ty) => quote! {
+                    ::axum_typed_routing::__private::push_path_segments(&mut __path__, &#ident);
+                },
+                PathParam::WildCard(_, _, _, ident, _, _, _) => quote! {
+                    ::axum_typed_routing::__private::push_path_wildcard(&mut __path__, &#ident);
+                },
+                PathParam::Static(lit) => {
+                    let segment = format!("/{}", lit.value());
+                    quote! { __path__.push_str(#segment); }
+                }
+            });
+        }
+        for (ident, _ty, value) in &query_params {
+            let name = ident.unraw().to_string();
+            pushes.push(quote! {
+                if let ::core::option::Option::Some(value) = #value {
+                    ::axum_typed_routing::__private::push_query_param(&mut __path__, #name, value);
+                }
+            });
+        }
+
+        Some(quote! {
+            /// A `proptest` strategy for paths of this route, with arbitrary (percent-encoded)
+            /// path- and query-parameters of the declared types. Empty path parameters are
+            /// skipped, since they wouldn't match the route.
+            pub fn param_strategy(
+            ) -> impl ::axum_typed_routing::__private::proptest::strategy::Strategy<Value = ::std::string::String>
+            #format_bounds #(#arbitrary_bounds)*
+            {
+                ::axum_typed_routing::__private::proptest::strategy::Strategy::prop_map(
+                    ::axum_typed_routing::__private::proptest::strategy::Strategy::prop_filter(
+                        (#path_strategies, #query_strategies),
+                        "empty path parameters don't match the route",
+                        |((#(#path_idents,)*), _query)| {
+                            true #(&& #non_empty)*
+                        },
+                    ),
+                    |((#(#path_idents,)*), (#(#query_idents,)*))| {
+                        let mut __path__ = ::std::string::String::new();
+                        #(#pushes)*
+                        if __path__.is_empty() {
+                            __path__.push('/');
+                        }
+                        __path__
+                    },
+                )
+            }
+        })
+    }
+
+    /// The test of `generate_test`, which requests this route with sample parameters from a
+    /// router with the path- and query-extractors of the route, and checks that they succeed.
+    fn extraction_test(&self) -> Option<TokenStream2> {
+        if !matches!(&self.options.generate_test, Some((_ident, lit)) if lit.value()) {
+            return None;
+        }
+        let path = self.path_expr();
+        let path_extractor = self.path_extractor();
+        let query_extractor = self.query_extractor();
+        let query_params_struct = self.query_params_struct(false);
+        let path_params_structs = self.path_params_structs(false);
+
+        let mut pushes = Vec::new();
+        if let Some((_ident, base)) = &self.options.base {
+            pushes.push(quote! { __uri__.push_str(#base); });
+        }
+        for (_slash, path_param) in &self.path_params {
+            pushes.push(match path_param {
+                PathParam::Capture(_, _, _, ty, _, _)
+                | PathParam::WildCard(_, _, _, _, ty, _, _) => {
+                    quote_spanned! {ty.span()=>
+                        __uri__.push('/');
+                        __uri__.push_str(&<#ty as ::axum_typed_routing::SampleParam>::sample());
+                    }
+                }
+                PathParam::Static(lit) => {
+                    let segment = format!("/{}", lit.value());
+                    quote! { __uri__.push_str(#segment); }
+                }
+            });
+        }
+        for (i, (ident, ty)) in self.query_params.iter().enumerate() {
+            let prefix = format!("{}{}=", if i == 0 { '?' } else { '&' }, ident.unraw());
+            pushes.push(quote_spanned! {ty.span()=>
+                __uri__.push_str(#prefix);
+                __uri__.push_str(&<#ty as ::axum_typed_routing::SampleParam>::sample());
+            });
+        }
+
+        Some(quote! {
+            #[cfg(test)]
+            #[::tokio::test]
+            #[allow(unused_variables)]
+            async fn extracts_params() {
+                #query_params_struct
+                #path_params_structs
+
+                let __router__ = ::axum::Router::new().route(
+                    #path,
+                    ::axum::routing::any(|#path_extractor #query_extractor| async {}),
+                );
+                let mut __uri__ = ::std::string::String::new();
+                #(#pushes)*
+                ::axum_typed_routing::__private::assert_extracts(__router__, &__uri__).await;
+            }
+        })
+    }
+
+    /// The statements formatting the percent-encoded path of this route into `__path__`, from
+    /// the [`href_args`](Self::href_args).
+    fn href_pushes(&self) -> Vec<TokenStream2> {
+        let mut pushes = Vec::new();
+        if let Some((_ident, base)) = &self.options.base {
+            pushes.push(quote! { __path__.push_str(#base); });
+        }
+        // With `path_struct: <TYPE>`, the helpers take the struct instead of the captures.
+        let value = |ident: &Ident| match &self.path_struct_arg {
+            Some(arg) => quote!(&#arg.#ident),
+            None => quote!(#ident),
+        };
+        for (_slash, path_param) in &self.path_params {
+            pushes.push(match path_param {
+                PathParam::Capture(_, _, ident, _, _, _) => {
+                    let value = value(ident);
+                    quote! {
+                        ::axum_typed_routing::__private::push_path_segment(&mut __path__, #value);
+                    }
+                }
+                PathParam::WildCard(_, _, _, ident, ty, _, _) if is_segments_type(ty) => {
+                    let value = value(ident);
+                    quote! {
+                        ::axum_typed_routing::__private::push_path_segments(&mut __path__, #value);
+                    }
+                }
+                PathParam::WildCard(_, _, _, ident, _, _, _) => {
+                    let value = value(ident);
+                    quote! {
+                        ::axum_typed_routing::__private::push_path_wildcard(&mut __path__, #value);
+                    }
+                }
+                PathParam::Static(lit) => {
+                    let segment = format!("/{}", lit.value());
+                    quote! { __path__.push_str(#segment); }
+                }
+            });
+        }
+        pushes
+    }
+
+    /// The `where` clause of a helper formatting the path parameters, and the query parameters
+    /// of `query_types`. The bounds are higher-ranked, so they aren't checked where the helper is
+    /// defined: the helpers of a route with a parameter type that can't be formatted only fail to
+    /// compile where they're called.
+    fn format_bounds(&self, query_types: &[&Type]) -> TokenStream2 {
+        let encode = quote!(::axum_typed_routing::PathSegmentEncode);
+        let path_bounds =
+            self.path_params
+                .iter()
+                .filter_map(|(_slash, path_param)| match path_param {
+                    PathParam::Capture(_, _, _ident, ty, _, _) => Some(quote!(#ty: #encode)),
+                    PathParam::WildCard(_, _, _, _ident, ty, _, _) if is_segments_type(ty) => {
+                        let item = single_generic_argument(ty, "Vec")?;
+                        Some(quote!(#item: #encode))
+                    }
+                    PathParam::WildCard(_, _, _, _ident, ty, _, _) => Some(quote!(#ty: #encode)),
+                    PathParam::Static(_lit) => None,
+                });
+        let query_bounds = query_types
+            .iter()
+            .map(|ty| quote!(#ty: ::core::fmt::Display));
+        let bounds = path_bounds.chain(query_bounds);
+        quote!(where #(for<'__a> #bounds,)*)
+    }
+
+    /// The arguments of the `href` of this route: the path parameters, or the struct of
+    /// `path_struct: <TYPE>`.
+    fn href_args(&self) -> (Vec<&Ident>, Vec<&Type>) {
+        match (&self.path_struct_arg, self.path_struct_type()) {
+            (Some(arg), Some(ty)) => (vec![arg], vec![ty]),
+            _ => self
+                .path_params
+                .iter()
+                .filter_map(|(_slash, path_param)| path_param.capture())
+                .unzip(),
+        }
+    }
+
+    /// The helpers of the [`route_module`](Self::route_module) for linking to this route from
+    /// templates.
+    fn template_helpers(&self) -> Option<TokenStream2> {
+        if !cfg!(feature = "templates") {
+            return None;
+        }
+        let pushes = self.href_pushes();
+        let (idents, types) = self.href_args();
+        let method = self.method.name();
+        let link_builder = self.link_builder(&idents, &types);
+        let bounds = self.format_bounds(&[]);
+
+        Some(quote! {
+            /// The percent-encoded path of this route for the given path parameters.
+            pub fn href(#(#idents: &#types),*) -> ::std::string::String #bounds {
+                let mut __path__ = ::std::string::String::new();
+                #(#pushes)*
+                __path__
+            }
+
+            /// The `(action, method)` attributes of a form submitting to this route.
+            pub fn attrs(#(#idents: &#types),*) -> (::std::string::String, &'static str) #bounds {
+                (href(#(#idents),*), #method)
+            }
+
+            #link_builder
+        })
+    }
+
+    /// The `link()` builder of the template helpers, for links with query parameters. Query
+    /// parameters with `deserialize_with` or `flatten` can't be formatted, so they are left out.
+    fn link_builder(&self, path_idents: &[&Ident], path_types: &[&Type]) -> TokenStream2 {
+        let query_params = self
+            .query_params
+            .iter()
+            .filter(|(ident, _ty)| !self.param_options.contains_key(ident))
+            .map(|(ident, ty)| match single_generic_argument(ty, "Option") {
+                Some(inner) => (ident, inner, true),
+                None => (ident, &**ty, false),
+            })
+            .collect::<Vec<_>>();
+        let query_idents = query_params.iter().map(|(ident, _ty, _optional)| *ident);
+        let query_types = query_params
+            .iter()
+            .map(|(_ident, ty, _optional)| *ty)
+            .collect::<Vec<_>>();
+        let bounds = self.format_bounds(&query_types);
+        let idents = path_idents
+            .iter()
+            .copied()
+            .chain(query_idents)
+            .collect::<Vec<_>>();
+        let types = path_types
+            .iter()
+            .copied()
+            .chain(query_types)
+            .collect::<Vec<_>>();
+
+        let path_values = path_idents.iter().map(|ident| {
+            let message = format!("missing path parameter `{ident}` of the link");
+            quote! { self.#ident.as_ref().expect(#message) }
+        });
+        let query_pushes = query_params.iter().map(|(ident, _ty, optional)| {
+            let name = ident.unraw().to_string();
+            let value = match optional {
+                true => quote! { self.#ident.as_ref() },
+                false => {
+                    let message = format!("missing query parameter `{name}` of the link");
+                    quote! { ::core::option::Option::Some(self.#ident.as_ref().expect(#message)) }
+                }
+            };
+            quote! {
+                if let ::core::option::Option::Some(value) = #value {
+                    ::axum_typed_routing::__private::push_query_param(&mut __path__, #name, value);
+                }
+            }
+        });
+
+        quote! {
+            /// A builder for a link to this route with query parameters, e.g.
+            /// `link().id(5).amount(3).build()`. The path- and query-parameters are
+            /// percent-encoded.
+            pub fn link() -> Link {
+                ::core::default::Default::default()
+            }
+
+            /// The builder returned by [`link`].
+            #[derive(Default)]
+            pub struct Link {
+                #(#idents: ::core::option::Option<#types>,)*
+            }
+
+            impl Link {
+                #(
+                    pub fn #idents(mut self, #idents: #types) -> Self {
+                        self.#idents = ::core::option::Option::Some(#idents);
+                        self
+                    }
+                )*
+
+                /// The link, panicking if a path parameter or a required query parameter is
+                /// missing.
+                pub fn build(&self) -> ::std::string::String #bounds {
+                    let mut __path__ = href(#(#path_values),*);
+                    #(#query_pushes)*
+                    __path__
+                }
+            }
+        }
+    }
+
+    /// The `request()` builder of the [`route_module`](Self::route_module) when the `client`
+    /// feature is enabled, for requests to this route with any HTTP client. Like with the
+    /// [`link_builder`](Self::link_builder), query parameters with `deserialize_with` or
+    /// `flatten` are left out.
+    fn client_request(&self) -> Option<TokenStream2> {
+        if !cfg!(feature = "client") {
+            return None;
+        }
+        let pushes = self.href_pushes();
+        let (path_idents, path_types) = self.href_args();
+        let method = self.method.name();
+        let host = self
+            .options
+            .host
+            .as_ref()
+            .map(|(_ident, host)| quote! { .header(::axum::http::header::HOST, #host) });
+        let query_params = self
+            .query_params
+            .iter()
+            .filter(|(ident, _ty)| !self.param_options.contains_key(ident))
+            .map(|(ident, ty)| match single_generic_argument(ty, "Option") {
+                Some(inner) => (ident, inner, true),
+                None => (ident, &**ty, false),
+            })
+            .collect::<Vec<_>>();
+        let query_idents = query_params
+            .iter()
+            .map(|(ident, _ty, _optional)| *ident)
+            .collect::<Vec<_>>();
+        let query_types = query_params
+            .iter()
+            .map(|(_ident, ty, _optional)| *ty)
+            .collect::<Vec<_>>();
+        let path_bounds = self.format_bounds(&[]);
+        let query_bounds = self.format_bounds(&query_types);
+        let query_pushes = query_params.iter().map(|(ident, _ty, optional)| {
+            let name = ident.unraw().to_string();
+            let value = match optional {
+                true => quote! { self.#ident.as_ref() },
+                false => {
+                    let message = format!("missing query parameter `{name}` of the request");
+                    quote! { ::core::option::Option::Some(self.#ident.as_ref().expect(#message)) }
+                }
+            };
+            quote! {
+                if let ::core::option::Option::Some(value) = #value {
+                    ::axum_typed_routing::__private::push_query_param(&mut __path__, #name, value);
+                }
+            }
+        });
+
+        Some(quote! {
+            /// A builder for a request to this route, e.g. `request(&5).amount(3).build()`, for
+            /// `reqwest`, `hyper` or `tower::ServiceExt::oneshot`. The path parameters are
+            /// required arguments, and the query parameters are set on the builder.
+            pub fn request(#(#path_idents: &#path_types),*) -> RequestBuilder #path_bounds {
+                let mut __path__ = ::std::string::String::new();
+                #(#pushes)*
+                if __path__.is_empty() {
+                    __path__.push('/');
+                }
+                RequestBuilder {
+                    __path__,
+                    __body__: ::axum::body::Body::empty(),
+                    #(#query_idents: ::core::option::Option::None,)*
+                }
+            }
+
+            /// The builder returned by [`request`].
+            pub struct RequestBuilder {
+                __path__: ::std::string::String,
+                __body__: ::axum::body::Body,
+                #(#query_idents: ::core::option::Option<#query_types>,)*
+            }
+
+            impl RequestBuilder {
+                #(
+                    pub fn #query_idents(mut self, #query_idents: #query_types) -> Self {
+                        self.#query_idents = ::core::option::Option::Some(#query_idents);
+                        self
+                    }
+                )*
+
+                /// Sets the body of the request, which is empty by default.
+                pub fn body(mut self, body: impl ::core::convert::Into<::axum::body::Body>) -> Self {
+                    self.__body__ = body.into();
+                    self
+                }
+
+                /// The request, with a path relative to the server, panicking if a required
+                /// query parameter is missing.
+                pub fn build(self) -> ::axum::http::Request<::axum::body::Body> #query_bounds {
+                    let mut __path__ = self.__path__;
+                    #(#query_pushes)*
+                    ::axum::http::Request::builder()
+                        .method(#method)
+                        .uri(__path__)
+                        #host
+                        .body(self.__body__)
+                        .expect("the method and path of a typed route are valid")
+                }
+            }
+        })
+    }
+
+    /// Registers the route for TypeScript generation when the `ts-client` feature is enabled.
+    pub fn ts_route(&self, function: &ItemFn) -> Option<TokenStream2> {
+        if !cfg!(feature = "ts-client") {
+            return None;
+        }
+
+        let ts_param = |ident: &Ident, ty: &Type| {
+            let name = ident.to_string();
+            let (ty, optional) = match single_generic_argument(ty, "Option") {
+                Some(inner) => (ts_type(inner), true),
+                None => (ts_type(ty), false),
+            };
+            quote! {
+                ::axum_typed_routing::ts_client::TsParam {
+                    name: #name,
+                    ty: #ty,
+                    optional: #optional,
+                }
+            }
+        };
+        let path_params = self
+            .path_params
+            .iter()
+            .filter_map(|(_slash, path_param)| path_param.capture())
+            .map(|(ident, ty)| ts_param(ident, ty));
+        let query_params = self
+            .query_params
+            .iter()
+            .map(|(ident, ty)| ts_param(ident, ty));
+        let body = match json_body_type(&function.sig) {
+            Some(ty) => {
+                let ty = ts_type(ty);
+                quote! { ::std::option::Option::Some(#ty) }
+            }
+            None => quote! { ::std::option::Option::None },
+        };
+        let name = function.sig.ident.to_string();
+        let method = self.method.name();
+        let path = self.to_axum_path_string();
+        let base = match &self.options.base {
+            Some((_ident, base)) => quote!(#base),
+            None => quote!(""),
+        };
+
+        Some(quote! {
+            ::axum_typed_routing::__private::inventory::submit! {
+                ::axum_typed_routing::ts_client::TsRoute {
+                    name: #name,
+                    method: #method,
+                    base: #base,
+                    path: #path,
+                    path_params: &[#(#path_params),*],
+                    query_params: &[#(#query_params),*],
+                    body: #body,
+                }
+            }
+        })
+    }
+
+    /// Hidden constants named after the operation ids of the route, with the
+    /// `unique-operation-ids` feature, so two routes of a module with the same operation id fail
+    /// to compile, with an error pointing at both handlers. Ids with a module prefix are only
+    /// known at runtime, and aren't checked, like the ids of routes in different modules.
+    pub fn unique_operation_ids(&self, fn_name: &Ident) -> Option<TokenStream2> {
+        if !cfg!(feature = "unique-operation-ids") {
+            return None;
+        }
+        let options = self.oapi_options.as_ref()?;
+        let module_prefix =
+            matches!(&options.id_module_prefix, Some((_ident, prefix)) if prefix.value());
+        if (module_prefix && options.derived_id)
+            || self.get_oapi_hidden().is_some_and(|lit| lit.value())
+        {
+            return None;
+        }
+        // Ids that aren't literals are only known at runtime.
+        let Some((
+            _ident,
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(id), ..
+            }),
+        )) = &options.id
+        else {
+            return None;
+        };
+
+        let mut ids = vec![id.value()];
+        if self.auto_head() {
+            ids.push(format!("{}_head", id.value()));
+        }
+        let consts = ids.iter().map(|id| {
+            // Escaping `_` too keeps the names of different ids apart, e.g. `a-b` and `a_2d_b`.
+            let encoded = id
+                .chars()
+                .map(|c| match c.is_ascii_alphanumeric() {
+                    true => c.to_string(),
+                    false => format!("_{:x}_", c as u32),
+                })
+                .collect::<String>();
+            let name = format_ident!("__operation_id__{}", encoded, span = fn_name.span());
+            quote_spanned! {fn_name.span()=>
+                #[doc(hidden)]
+                #[allow(dead_code, non_upper_case_globals)]
+                const #name: () = ();
+            }
+        });
+        Some(quote!(#(#consts)*))
     }
 
     pub fn ide_documentation_for_aide_methods(&self) -> TokenStream2 {
@@ -273,10 +3171,12 @@ impl CompiledRoute {
             quote!( let x = x.#method(|x|x); )
         });
 
+        // Only used for hover documentation in IDEs, so not compiled in release builds.
         quote! {
+            #[cfg(debug_assertions)]
             #[allow(unused)]
             #[allow(clippy::no_effect)]
-            fn ____ide_documentation_for_aide____(x: ::aide::transform::TransformOperation) {
+            fn ____ide_documentation_for_aide____(x: ::axum_typed_routing::aide::transform::TransformOperation) {
                 #summary
                 #description
                 #id
@@ -289,7 +3189,7 @@ impl CompiledRoute {
         }
     }
 
-    pub fn get_oapi_summary(&self) -> Option<LitStr> {
+    pub fn get_oapi_summary(&self) -> Option<Expr> {
         if let Some(oapi_options) = &self.oapi_options {
             if let Some(summary) = &oapi_options.summary {
                 return Some(summary.1.clone());
@@ -298,7 +3198,7 @@ impl CompiledRoute {
         None
     }
 
-    pub fn get_oapi_description(&self) -> Option<LitStr> {
+    pub fn get_oapi_description(&self) -> Option<Expr> {
         if let Some(oapi_options) = &self.oapi_options {
             if let Some(description) = &oapi_options.description {
                 return Some(description.1.clone());
@@ -316,7 +3216,7 @@ impl CompiledRoute {
         None
     }
 
-    pub fn get_oapi_tags(&self) -> Vec<LitStr> {
+    pub fn get_oapi_tags(&self) -> Vec<Expr> {
         if let Some(oapi_options) = &self.oapi_options {
             if let Some(tags) = &oapi_options.tags {
                 return tags.1 .0.clone();
@@ -325,27 +3225,99 @@ impl CompiledRoute {
         Vec::new()
     }
 
-    pub fn get_oapi_id(&self, sig: &Signature) -> Option<LitStr> {
-        if let Some(oapi_options) = &self.oapi_options {
-            if let Some(id) = &oapi_options.id {
-                return Some(id.1.clone());
+    /// The operation id: the `id` option, or the name of the handler converted with the
+    /// `id_strategy`, prefixed with the name of the module if `id_module_prefix` is set.
+    /// The operation id as a `&'static str`, for `assert_no_conflicts!`. With a module prefix,
+    /// the id is formatted once, since the module is only known at runtime.
+    fn operation_id_expr(&self) -> Option<TokenStream2> {
+        let id = self.get_oapi_id()?;
+        let oapi_options = self.oapi_options.as_ref()?;
+        match &oapi_options.id_module_prefix {
+            Some((_ident, prefix)) if prefix.value() && oapi_options.derived_id => Some(quote! {{
+                static __OPERATION_ID__: ::std::sync::OnceLock<::std::string::String> =
+                    ::std::sync::OnceLock::new();
+                __OPERATION_ID__.get_or_init(|| ::std::string::String::from(#id)).as_str()
+            }}),
+            _ => Some(id),
+        }
+    }
+
+    /// An `id` that isn't a literal is evaluated by a function next to the handler, where the
+    /// documentation evaluates it too, since e.g. `module_path!()` differs inside the route
+    /// module.
+    fn operation_id_fn(&self, fn_name: &Ident) -> Option<TokenStream2> {
+        let (_ident, id) = self.oapi_options.as_ref()?.id.as_ref()?;
+        if matches!(id, Expr::Lit(_)) {
+            return None;
+        }
+        let name = format_ident!("__{}_operation_id__", fn_name.unraw());
+        Some(quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            fn #name() -> &'static str {
+                #id
             }
+        })
+    }
+
+    /// The `operation_id()` of the [`route_module`](Self::route_module). The module path is the
+    /// one of the route module, so the module of `id_module_prefix` is taken from its parent.
+    fn module_operation_id(&self, fn_name: &Ident) -> TokenStream2 {
+        let Some(oapi_options) = &self.oapi_options else {
+            return quote!(::core::option::Option::None);
+        };
+        let Some((_ident, id)) = &oapi_options.id else {
+            return quote!(::core::option::Option::None);
+        };
+        if !matches!(id, Expr::Lit(_)) {
+            let name = format_ident!("__{}_operation_id__", fn_name.unraw());
+            return quote!(::core::option::Option::Some(super::#name()));
+        }
+        match &oapi_options.id_module_prefix {
+            Some((_ident, prefix)) if prefix.value() && oapi_options.derived_id => quote! {
+                static __OPERATION_ID__: ::std::sync::OnceLock<::std::string::String> =
+                    ::std::sync::OnceLock::new();
+                let id = __OPERATION_ID__.get_or_init(|| {
+                    let module = NAME.rsplit_once("::").map_or(NAME, |(module, _)| module);
+                    ::axum_typed_routing::__private::module_operation_id(module, #id)
+                });
+                ::core::option::Option::Some(id.as_str())
+            },
+            _ => quote!(::core::option::Option::Some(#id)),
+        }
+    }
+
+    pub fn get_oapi_id(&self) -> Option<TokenStream2> {
+        let oapi_options = self.oapi_options.as_ref()?;
+        let (_ident, id) = oapi_options.id.as_ref()?;
+        match &oapi_options.id_module_prefix {
+            Some((_ident, prefix)) if prefix.value() && oapi_options.derived_id => Some(quote! {
+                &::axum_typed_routing::__private::module_operation_id(::core::module_path!(), #id)
+            }),
+            _ => Some(quote!(#id)),
         }
-        Some(LitStr::new(&sig.ident.to_string(), sig.ident.span()))
     }
 
     pub fn get_oapi_transform(&self) -> syn::Result<Option<TokenStream2>> {
         if let Some(oapi_options) = &self.oapi_options {
-            if let Some(transform) = &oapi_options.transform {
-                if transform.1.inputs.len() != 1 {
+            if let Some((_ident, transform)) = &oapi_options.transform {
+                let transform = match transform {
+                    Transform::Closure(closure) => closure,
+                    Transform::Path(path) => {
+                        return Ok(Some(quote! {
+                            let __op__ = #path(__op__);
+                        }))
+                    }
+                };
+                if transform.inputs.len() != 1 {
                     return Err(syn::Error::new(
-                        transform.1.span(),
+                        transform.span(),
                         "expected a single identifier",
                     ));
                 }
 
-                let pat = transform.1.inputs.first().unwrap();
-                let body = &transform.1.body;
+                let pat = transform.inputs.first().unwrap();
+                let body = &transform.body;
 
                 if let Pat::Ident(pat_ident) = pat {
                     let ident = &pat_ident.ident;
@@ -364,53 +3336,565 @@ impl CompiledRoute {
         Ok(None)
     }
 
-    pub fn get_oapi_responses(&self) -> Vec<(LitInt, Type)> {
-        if let Some(oapi_options) = &self.oapi_options {
-            if let Some((_ident, Responses(responses))) = &oapi_options.responses {
-                return responses.clone();
+    /// Removes the responses inferred by `aide` with `infer_responses: false`, before the
+    /// `status` and `responses` options are documented.
+    pub fn get_oapi_clear_responses(&self) -> Option<TokenStream2> {
+        let (_ident, infer) = self.oapi_options.as_ref()?.infer_responses.as_ref()?;
+        (!infer.value()).then(|| {
+            quote! {
+                let mut __op__ = __op__;
+                __op__.inner_mut().responses = ::core::option::Option::None;
             }
+        })
+    }
+
+    /// The documented `responses`, with `response_with` if they have a transform.
+    pub fn get_oapi_responses(&self) -> Vec<TokenStream2> {
+        let Some((_ident, Responses(responses))) = self
+            .oapi_options
+            .as_ref()
+            .and_then(|oapi_options| oapi_options.responses.as_ref())
+        else {
+            return Vec::new();
+        };
+        // A repeated status code is merged into the response documented before.
+        let mut statuses = std::collections::HashSet::new();
+        responses
+            .iter()
+            .map(|(status, ty, transform)| {
+                let repeated = !statuses.insert(status.key());
+                match (repeated, transform) {
+                    (false, Some(transform)) => {
+                        quote! { .response_with::<#status, #ty, _>(#transform) }
+                    }
+                    (false, None) => quote! { .response::<#status, #ty>() },
+                    (true, transform) => {
+                        let transform = match transform {
+                            Some(transform) => quote!(#transform),
+                            None => quote!(|response| response),
+                        };
+                        quote! {
+                            .with(|__op__| {
+                                ::axum_typed_routing::__private::merge_response::<#status, #ty, _>(
+                                    __op__,
+                                    #transform,
+                                )
+                            })
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The documented response for the `status` option. This is not documented for `impl Trait`
+    /// return types, since they can't be named.
+    pub fn get_oapi_status_response(&self, output: &ReturnType) -> Option<TokenStream2> {
+        let (_ident, status) = self.options.status.as_ref()?;
+        match output {
+            ReturnType::Default => Some(quote! { .response::<#status, ()>() }),
+            ReturnType::Type(_, ty) if matches!(**ty, Type::ImplTrait(_)) => None,
+            ReturnType::Type(_, ty) => match result_types(ty) {
+                Some((Type::ImplTrait(_), _err)) => None,
+                Some((ok, err)) => Some(quote! {
+                    .response::<#status, ::axum_typed_routing::__private::ApiResult<#ok, #err>>()
+                }),
+                None => Some(quote! { .response::<#status, #ty>() }),
+            },
         }
-        Default::default()
     }
 
-    pub fn get_oapi_security(&self) -> Vec<(LitStr, Vec<LitStr>)> {
+    /// Documents the fields of the flattened query parameters, which `aide` doesn't see.
+    pub fn get_oapi_flattened_query(&self) -> TokenStream2 {
+        let types = self
+            .query_params
+            .iter()
+            .filter(|(ident, _ty)| {
+                self.param_options
+                    .get(ident)
+                    .is_some_and(|options| options.flatten.is_some())
+            })
+            .map(|(_ident, ty)| ty);
+        quote! {
+            #(let __op__ = ::axum_typed_routing::__private::flattened_query_params::<#types>(__op__);)*
+        }
+    }
+
+    /// Documents the successful responses with the media types of the `accepts` option.
+    pub fn get_oapi_media_types(&self) -> Option<TokenStream2> {
+        let media_types = self
+            .encodings()?
+            .iter()
+            .map(|encoding| match encoding == "json" {
+                true => "application/json",
+                false => "application/msgpack",
+            });
+        Some(quote! {
+            let mut __op__ = __op__;
+            ::axum_typed_routing::__private::set_response_media_types(
+                __op__.inner_mut(),
+                &[#(#media_types),*],
+            );
+        })
+    }
+
+    /// Sets the `style` and `explode` of the query parameters with those options.
+    pub fn get_oapi_query_styles(&self) -> Option<TokenStream2> {
+        let styles = self.query_params.iter().filter_map(|(ident, _ty)| {
+            let options = self.param_options.get(ident)?;
+            if options.style.is_none() && options.explode.is_none() {
+                return None;
+            }
+            let name = ident.to_string();
+            let deep_object = options.deep_object();
+            let explode = match &options.explode {
+                Some((_ident, lit)) => quote!(::core::option::Option::Some(#lit)),
+                None => quote!(::core::option::Option::None),
+            };
+            Some(quote! {
+                ::axum_typed_routing::__private::set_query_style(
+                    __op__.inner_mut(),
+                    #name,
+                    #deep_object,
+                    #explode,
+                );
+            })
+        });
+        let styles = styles.collect::<Vec<_>>();
+        (!styles.is_empty()).then(|| {
+            quote! {
+                let mut __op__ = __op__;
+                #(#styles)*
+            }
+        })
+    }
+
+    /// Sets the examples and descriptions of the `params` option on the documented parameters.
+    pub fn get_oapi_params(&self) -> syn::Result<Option<TokenStream2>> {
+        let Some(oapi_options) = &self.oapi_options else {
+            return Ok(None);
+        };
+        let Some((_ident, ParamDocs(params))) = &oapi_options.params else {
+            return Ok(None);
+        };
+
+        let mut docs = Vec::new();
+        for (ident, doc) in params {
+            let path_param = self
+                .path_params
+                .iter()
+                .filter_map(|(_slash, param)| param.capture())
+                .find(|(path_ident, _ty)| *path_ident == ident);
+            let query_param = self
+                .query_params
+                .iter()
+                .find(|(query_ident, _ty)| query_ident == ident);
+            let (ty, location) = match (path_param, query_param) {
+                (Some((_ident, ty)), _) => (
+                    ty,
+                    quote!(::axum_typed_routing::aide::operation::ParamLocation::Path),
+                ),
+                (None, Some((_ident, ty))) => (
+                    &**ty,
+                    quote!(::axum_typed_routing::aide::operation::ParamLocation::Query),
+                ),
+                (None, None) => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("`{ident}` is not a path- or query-parameter of the route"),
+                    ))
+                }
+            };
+            if self
+                .param_options
+                .get(ident)
+                .is_some_and(|options| options.flatten.is_some())
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "flattened query parameters can't be documented with `params`, document their fields instead",
+                ));
+            }
+
+            if let (Some(_), Some(required)) = (path_param, &doc.required) {
+                if !required.value() {
+                    return Err(syn::Error::new(
+                        required.span(),
+                        "path parameters are always required",
+                    ));
+                }
+            }
+
+            let options = self.param_options.get(ident);
+            if let Some(skip) = options.and_then(|options| options.skip_schema.as_ref()) {
+                return Err(syn::Error::new(
+                    skip.span(),
+                    "parameters with `skip_schema` aren't documented, so they can't be documented with `params`",
+                ));
+            }
+            // A parameter with `schema_with` is documented from the query struct, so the type
+            // `param_data` would add it with is never used.
+            let ty = match self.documented_type(ident) {
+                _ if options.is_some_and(|options| options.schema_with.is_some()) => quote!(()),
+                Some(documented) => quote!(#documented),
+                None => quote!(#ty),
+            };
+            let name = ident.to_string();
+            let example = doc.example.as_ref().map(|example| {
+                quote! {
+                    __param__.example = ::core::option::Option::Some(
+                        ::axum_typed_routing::__private::json_value(#name, #example),
+                    );
+                }
+            });
+            let description = doc.description.as_ref().map(|description| {
+                quote! {
+                    __param__.description =
+                        ::core::option::Option::Some(::std::string::String::from(#description));
+                }
+            });
+            let required = doc
+                .required
+                .as_ref()
+                .map(|required| quote! { __param__.required = #required; });
+            let deprecated = doc.deprecated.as_ref().map(|deprecated| {
+                quote! { __param__.deprecated = ::core::option::Option::Some(#deprecated); }
+            });
+            docs.push(quote! {
+                {
+                    let __param__ = ::axum_typed_routing::__private::param_data::<#ty>(
+                        __op__.inner_mut(),
+                        #name,
+                        #location,
+                    );
+                    #example
+                    #description
+                    #required
+                    #deprecated
+                }
+            });
+        }
+
+        Ok(Some(quote! {
+            let mut __op__ = __op__;
+            #(#docs)*
+        }))
+    }
+
+    pub fn get_oapi_parameters(&self) -> syn::Result<Option<TokenStream2>> {
+        let Some(oapi_options) = &self.oapi_options else {
+            return Ok(None);
+        };
+        let Some((_ident, Parameters(parameters))) = &oapi_options.parameters else {
+            return Ok(None);
+        };
+
+        let mut docs = Vec::new();
+        for parameter in parameters {
+            let name = parameter.name.value();
+            let is_path = parameter.location == "path";
+            if is_path
+                && !self
+                    .path_params
+                    .iter()
+                    .filter_map(|(_slash, param)| param.capture())
+                    .any(|(ident, _ty)| *ident == name)
+            {
+                return Err(syn::Error::new(
+                    parameter.name.span(),
+                    format!("`{name}` is not a path-parameter of the route"),
+                ));
+            }
+
+            let location = match parameter.location.to_string().as_str() {
+                "path" => quote!(Path),
+                "query" => quote!(Query),
+                "header" => quote!(Header),
+                _ => quote!(Cookie),
+            };
+            let ty = match &parameter.schema {
+                Some(ty) => quote!(#ty),
+                None => quote!(::std::string::String),
+            };
+            let required = match &parameter.required {
+                Some(required) => required.value(),
+                None => is_path,
+            };
+            let example = parameter.example.as_ref().map(|example| {
+                quote! {
+                    __param__.example = ::core::option::Option::Some(
+                        ::axum_typed_routing::__private::json_value(#name, #example),
+                    );
+                }
+            });
+            let description = parameter.description.as_ref().map(|description| {
+                quote! {
+                    __param__.description =
+                        ::core::option::Option::Some(::std::string::String::from(#description));
+                }
+            });
+            let deprecated = parameter.deprecated.as_ref().map(|deprecated| {
+                quote! { __param__.deprecated = ::core::option::Option::Some(#deprecated); }
+            });
+            docs.push(quote! {
+                {
+                    let __param__ = ::axum_typed_routing::__private::set_parameter::<#ty>(
+                        __op__.inner_mut(),
+                        #name,
+                        ::axum_typed_routing::aide::operation::ParamLocation::#location,
+                        #required,
+                    );
+                    #example
+                    #description
+                    #deprecated
+                }
+            });
+        }
+
+        Ok(Some(quote! {
+            let mut __op__ = __op__;
+            #(#docs)*
+        }))
+    }
+
+    pub fn get_oapi_request_body(&self) -> Option<TokenStream2> {
+        let Some((_ident, request_body)) = &self.oapi_options.as_ref()?.request_body else {
+            let ty = self.form_body.as_ref()?;
+            return Some(quote! {
+                let mut __op__ = __op__;
+                ::axum_typed_routing::__private::set_form_body::<#ty>(__op__.inner_mut());
+            });
+        };
+        match request_body {
+            RequestBody::Multipart(fields) => {
+                let fields = fields.iter().map(|(name, ty)| {
+                    let name = name.to_string();
+                    let (ty, required) = match single_generic_argument(ty, "Option") {
+                        Some(inner) => (inner, false),
+                        None => (ty, true),
+                    };
+                    let is_binary = matches!(ty, Type::Path(path) if path.path.is_ident("binary"));
+                    match is_binary {
+                        true => quote! {
+                            ::axum_typed_routing::__private::multipart_binary_field(#name, #required)
+                        },
+                        false => quote! {
+                            ::axum_typed_routing::__private::multipart_field::<#ty>(#name, #required)
+                        },
+                    }
+                });
+                Some(quote! {
+                    let mut __op__ = __op__;
+                    ::axum_typed_routing::__private::set_multipart_body(
+                        __op__.inner_mut(),
+                        vec![#(#fields),*],
+                    );
+                })
+            }
+            RequestBody::Binary(media_types) => {
+                let media_types = match media_types.is_empty() {
+                    true => vec![quote!("application/octet-stream")],
+                    false => media_types.iter().map(|lit| quote!(#lit)).collect(),
+                };
+                Some(quote! {
+                    let mut __op__ = __op__;
+                    ::axum_typed_routing::__private::set_binary_body(
+                        __op__.inner_mut(),
+                        &[#(#media_types),*],
+                    );
+                })
+            }
+        }
+    }
+
+    pub fn get_oapi_servers(&self) -> Option<TokenStream2> {
+        let (_ident, StrArray(servers)) = self.oapi_options.as_ref()?.servers.as_ref()?;
+        Some(quote! {
+            let mut __op__ = __op__;
+            #(
+                __op__.inner_mut().servers.push(::axum_typed_routing::aide::openapi::Server {
+                    url: ::std::string::String::from(#servers),
+                    ..::std::default::Default::default()
+                });
+            )*
+        })
+    }
+
+    pub fn get_oapi_extensions(&self) -> Option<TokenStream2> {
+        let (_ident, Extensions(extensions)) = self.oapi_options.as_ref()?.extensions.as_ref()?;
+        let names = extensions.iter().map(|(name, _value)| name);
+        let values = extensions.iter().map(|(_name, value)| value);
+        Some(quote! {
+            let mut __op__ = __op__;
+            #(
+                ::axum_typed_routing::__private::set_extension(__op__.inner_mut(), #names, #values);
+            )*
+        })
+    }
+
+    /// Adds the `examples` to the request body and to the response of the route's status.
+    pub fn get_oapi_examples(&self) -> Option<TokenStream2> {
+        let (_ident, Examples(examples)) = self.oapi_options.as_ref()?.examples.as_ref()?;
+        let status = match &self.options.status {
+            Some((_ident, status)) => quote!(#status),
+            None => quote!(200),
+        };
+        let examples = examples.iter().map(|(name, example)| {
+            let request = example.request.as_ref().map(|request| {
+                quote! {
+                    ::axum_typed_routing::__private::set_request_example(
+                        __op__.inner_mut(),
+                        #name,
+                        #request,
+                    );
+                }
+            });
+            let response = example.response.as_ref().map(|response| {
+                quote! {
+                    ::axum_typed_routing::__private::set_response_example(
+                        __op__.inner_mut(),
+                        #status,
+                        #name,
+                        #response,
+                    );
+                }
+            });
+            quote!(#request #response)
+        });
+        Some(quote! {
+            let mut __op__ = __op__;
+            #(#examples)*
+        })
+    }
+
+    pub fn get_oapi_security(&self) -> Vec<(Expr, Vec<Expr>)> {
         if let Some(oapi_options) = &self.oapi_options {
             if let Some((_ident, Security(security))) = &oapi_options.security {
                 return security
                     .iter()
-                    .map(|(scheme, StrArray(scopes))| (scheme.clone(), scopes.clone()))
+                    .map(|(scheme, scopes)| (scheme.clone(), scopes.clone()))
                     .collect();
             }
         }
         Default::default()
     }
 
+    /// The path of the route for the example request of the doc comments, with a placeholder for
+    /// every path- and query-parameter, e.g. `/item/<id>?amount=<amount>`.
+    fn example_uri(&self) -> String {
+        let mut uri = match &self.options.base {
+            Some((_ident, base)) => format!("<{}>", base.to_token_stream()),
+            None => String::new(),
+        };
+        for (_slash, path_param) in &self.path_params {
+            let segment = match path_param {
+                PathParam::Capture(_, _, ident, _, _, _)
+                | PathParam::WildCard(_, _, _, ident, _, _, _) => format!("<{}>", ident.unraw()),
+                PathParam::Static(lit) => lit.value(),
+            };
+            uri.push('/');
+            uri.push_str(&segment);
+        }
+        if uri.is_empty() {
+            uri.push('/');
+        }
+        for (i, (ident, _ty)) in self.query_params.iter().enumerate() {
+            let separator = if i == 0 { '?' } else { '&' };
+            uri.push_str(&format!("{separator}{0}=<{0}>", ident.unraw()));
+        }
+        uri
+    }
+
     pub(crate) fn to_doc_comments(&self) -> TokenStream2 {
+        let location = match self.webhook() {
+            Some(name) => format!("Webhook: `{}`", name.value()),
+            None => format!("Path: `{}`", self.route_lit.value()),
+        };
         let mut doc = format!(
             "# Handler information
 - Method: `{}`
-- Path: `{}`
+- {location}
 - State: `{}`",
             self.method.to_axum_method_name(),
-            self.route_lit.value(),
             self.state.to_token_stream(),
         );
 
+        if self.options.infer_extensions.is_some() {
+            let extensions = self
+                .extensions
+                .iter()
+                .map(|ty| format!("`{}`", ty.to_token_stream()))
+                .collect::<Vec<_>>();
+            doc = match extensions.is_empty() {
+                true => format!("{doc}\n- Extensions: None"),
+                false => format!("{doc}\n- Extensions: {}", extensions.join(", ")),
+            };
+        }
+
+        if let Some((_ident, TypeArray(guards))) = &self.options.guards {
+            let guards = guards
+                .iter()
+                .map(|ty| format!("`{}`", ty.to_token_stream()))
+                .collect::<Vec<_>>();
+            doc = format!("{doc}\n- Guards: {}", guards.join(", "));
+        }
+        for (label, hooks) in [
+            ("Before", &self.options.before),
+            ("After", &self.options.after),
+        ] {
+            let hooks = self.hooks(hooks);
+            if hooks.is_empty() {
+                continue;
+            }
+            let hooks = hooks
+                .iter()
+                .map(|(function, _params)| format!("`{}`", function.to_token_stream()))
+                .collect::<Vec<_>>();
+            doc = format!("{doc}\n- {label}: {}", hooks.join(", "));
+        }
+
+        let path_params = self
+            .path_params
+            .iter()
+            .filter_map(|(_slash, path_param)| path_param.capture())
+            .map(|(ident, ty)| (ident, ty, "path"));
+        let query_params = self
+            .query_params
+            .iter()
+            .map(|(ident, ty)| (ident, &**ty, "query"));
+        let rows = path_params
+            .chain(query_params)
+            .map(|(ident, ty, location)| {
+                format!("| `{ident}` | {location} | `{}` |", ty.to_token_stream())
+            })
+            .collect::<Vec<_>>();
+        if !rows.is_empty() {
+            doc = format!(
+                "{doc}\n\n## Parameters\n| Name | In | Type |\n|---|---|---|\n{}",
+                rows.join("\n")
+            );
+        }
+        doc = format!(
+            "{doc}\n\n## Example request\n```sh\ncurl -X {} 'http://localhost:3000{}'\n```",
+            self.method.name(),
+            self.example_uri(),
+        );
+
         if let Some(options) = &self.oapi_options {
             let summary = options
                 .summary
                 .as_ref()
-                .map(|(_, summary)| format!("\"{}\"", summary.value()))
+                .map(|(_, summary)| doc_string(summary))
                 .unwrap_or("None".to_string());
             let description = options
                 .description
                 .as_ref()
-                .map(|(_, description)| format!("\"{}\"", description.value()))
+                .map(|(_, description)| doc_string(description))
                 .unwrap_or("None".to_string());
             let id = options
                 .id
                 .as_ref()
-                .map(|(_, id)| format!("\"{}\"", id.value()))
+                .map(|(_, id)| doc_string(id))
                 .unwrap_or("None".to_string());
             let hidden = options
                 .hidden
@@ -427,6 +3911,11 @@ impl CompiledRoute {
                 .as_ref()
                 .map(|(_, security)| security.to_string())
                 .unwrap_or("{}".to_string());
+            let api_doc = options
+                .api_doc
+                .as_ref()
+                .map(|(_, name)| format!("{:?}", name.value()))
+                .unwrap_or("None".to_string());
 
             doc = format!(
                 "{doc}
@@ -438,16 +3927,300 @@ impl CompiledRoute {
 - Tags: `{tags}`
 - Security: `{security}`
 - Hidden: `{hidden}`
+- API document: `{api_doc}`
 "
             );
         }
 
+        // Like the IDE helpers, the handler information is left out of release builds, since it
+        // adds up for crates with many routes.
         quote!(
-            #[doc = #doc]
+            #[cfg_attr(debug_assertions, doc = #doc)]
         )
     }
 }
 
+/// Formats a string option for the doc comments: a literal as its quoted value, and any other
+/// expression (such as `include_str!(..)`) as written.
+fn doc_string(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => format!("\"{}\"", lit.value()),
+        expr => expr.to_token_stream().to_string(),
+    }
+}
+
+/// The name of the passed-through argument `i` of the handler with the pattern `pat`: its
+/// identifier, or the first one bound by its pattern, e.g. `state` for `State(state)`. The name
+/// has mixed-site hygiene, so it can't shadow the handler or anything else the user's code refers
+/// to. Patterns without an identifier, such as `_`, and names already `taken` by another argument
+/// are numbered instead.
+fn passthrough_ident(pat: &Pat, i: usize, taken: &[Ident]) -> Ident {
+    fn first_binding(pat: &Pat) -> Option<&Ident> {
+        match pat {
+            Pat::Ident(pat) => Some(&pat.ident),
+            Pat::TupleStruct(pat) => pat.elems.iter().find_map(first_binding),
+            Pat::Tuple(pat) => pat.elems.iter().find_map(first_binding),
+            Pat::Struct(pat) => pat
+                .fields
+                .iter()
+                .find_map(|field| first_binding(&field.pat)),
+            Pat::Reference(pat) => first_binding(&pat.pat),
+            Pat::Paren(pat) => first_binding(&pat.pat),
+            Pat::Type(pat) => first_binding(&pat.pat),
+            _ => None,
+        }
+    }
+    match first_binding(pat).filter(|ident| !taken.contains(ident)) {
+        Some(ident) => {
+            let mut ident = ident.clone();
+            ident.set_span(Span::mixed_site().located_at(ident.span()));
+            ident
+        }
+        None => format_ident!("___arg___{}", i),
+    }
+}
+
+/// The identifiers bound by the patterns of [`CompiledRoute::remaining_pattypes`], which are
+/// either an identifier or `::aide::NoApi(<IDENT>)`.
+pub fn remaining_idents(pat_types: &Punctuated<PatType, Comma>) -> Vec<&Pat> {
+    pat_types
+        .iter()
+        .map(|pat_type| match &*pat_type.pat {
+            Pat::TupleStruct(no_api) => &no_api.elems[0],
+            pat => pat,
+        })
+        .collect()
+}
+
+/// Removes the `#[api_ignore]` attributes from the arguments of `function`, returning the indices
+/// of the annotated arguments.
+pub fn strip_api_ignore(function: &mut ItemFn) -> Vec<usize> {
+    let mut ignored = Vec::new();
+    for (i, arg) in function.sig.inputs.iter_mut().enumerate() {
+        if let FnArg::Typed(pat_type) = arg {
+            let len = pat_type.attrs.len();
+            pat_type
+                .attrs
+                .retain(|attr| !attr.path().is_ident("api_ignore"));
+            if pat_type.attrs.len() != len {
+                ignored.push(i);
+            }
+        }
+    }
+    ignored
+}
+
+/// Makes `function` generic over the state of `with S where S: ..` or `with Repo<T> where T: ..`,
+/// with the bounds of the `where` clause, and the bounds axum requires of a state. The handler
+/// can refer to the type parameters without declaring them, but may declare them to add more
+/// bounds, or to choose their order in a turbofish like `handler::<Postgres>`.
+fn add_state_generic(function: &mut ItemFn, state: &Type, bounds: &WhereClause) -> syn::Result<()> {
+    let state_ident = match state {
+        Type::Path(path) if path.qself.is_none() => path.path.get_ident(),
+        _ => None,
+    };
+    let params = match state_ident {
+        Some(ident) => vec![ident.clone()],
+        // The type parameters of the state that are bounded by the `where` clause.
+        None => bounds
+            .predicates
+            .iter()
+            .filter_map(|predicate| match predicate {
+                syn::WherePredicate::Type(predicate) => match &predicate.bounded_ty {
+                    Type::Path(path) if path.qself.is_none() => path.path.get_ident().cloned(),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .filter(|ident| mentions_ident(state.to_token_stream(), ident))
+            .collect(),
+    };
+    if params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            state,
+            "the `where` clause of a state must bound its type parameters, e.g. `with S where S: Trait` or `with Repo<T> where T: Trait`",
+        ));
+    }
+
+    let generics = &mut function.sig.generics;
+    for ident in params {
+        let declared = generics
+            .type_params()
+            .any(|type_param| type_param.ident == ident);
+        if !declared {
+            generics.params.push(parse_quote!(#ident));
+        }
+    }
+    let where_clause = generics.make_where_clause();
+    where_clause
+        .predicates
+        .extend(bounds.predicates.iter().cloned());
+    where_clause.predicates.push(parse_quote!(
+        #state: ::core::clone::Clone + ::core::marker::Send + ::core::marker::Sync + 'static
+    ));
+    Ok(())
+}
+
+/// Whether `tokens` contain the identifier `ident`, e.g. whether `Repo<T>` mentions `T`.
+fn mentions_ident(tokens: TokenStream2, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(other) => other == *ident,
+        TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// The name of the newtype of a path parameter with `deserialize_with`.
+fn path_param_wrapper(ident: &Ident) -> Ident {
+    format_ident!("__PathParam_{}__", ident)
+}
+
+/// Removes the `#[route_param(..)]` attributes from the arguments of `function`, returning the
+/// options by the name of the argument.
+pub fn strip_param_options(function: &mut ItemFn) -> syn::Result<HashMap<Ident, ParamOptions>> {
+    let mut param_options = HashMap::new();
+    for arg in function.sig.inputs.iter_mut() {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let mut attrs = Vec::new();
+        for attr in std::mem::take(&mut pat_type.attrs) {
+            if !attr.path().is_ident("route_param") {
+                attrs.push(attr);
+                continue;
+            }
+            let Pat::Ident(pat_ident) = &*pat_type.pat else {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`#[route_param]` can only be used on path- or query-parameters",
+                ));
+            };
+            param_options.insert(pat_ident.ident.clone(), attr.parse_args()?);
+        }
+        pat_type.attrs = attrs;
+    }
+    Ok(param_options)
+}
+
+/// Removes the `#[serde(..)]` and `#[schemars(..)]` attributes from the arguments of `function`,
+/// returning them by the name of the argument.
+fn strip_field_attrs(function: &mut ItemFn) -> syn::Result<HashMap<Ident, Vec<Attribute>>> {
+    let mut field_attrs = HashMap::new();
+    for arg in function.sig.inputs.iter_mut() {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let (attrs, other_attrs) = std::mem::take(&mut pat_type.attrs)
+            .into_iter()
+            .partition::<Vec<_>, _>(|attr| {
+                attr.path().is_ident("serde") || attr.path().is_ident("schemars")
+            });
+        pat_type.attrs = other_attrs;
+        if attrs.is_empty() {
+            continue;
+        }
+        let Pat::Ident(pat_ident) = &*pat_type.pat else {
+            return Err(syn::Error::new_spanned(
+                &attrs[0],
+                "`#[serde]` and `#[schemars]` attributes can only be used on query parameters",
+            ));
+        };
+        field_attrs.insert(pat_ident.ident.clone(), attrs);
+    }
+    Ok(field_attrs)
+}
+
+/// Removes the argument for a path- or query-parameter from `arg_map`, checking it against the
+/// type declared in the route. Returns `None` if the parameter has a declared type, but no
+/// argument.
+///
+/// Parameters bound inside a destructuring pattern are an error, since the whole argument would
+/// have to be extracted from the single parameter.
+fn take_param(
+    arg_map: &mut HashMap<Ident, Box<Type>>,
+    patterns: &[&Pat],
+    ident: &Ident,
+    annotation: Option<&Type>,
+    kind: &str,
+) -> syn::Result<Option<(Ident, Box<Type>)>> {
+    let entry = arg_map.remove_entry(ident);
+    if entry.is_none() {
+        if let Some(pat) = patterns.iter().find(|pat| binds_ident(pat, ident)) {
+            return Err(syn::Error::new_spanned(
+                pat,
+                format!(
+                    "{} parameter `{}` can't be bound in a destructuring pattern, use a separate argument `{}: <TYPE>` instead",
+                    kind, ident, ident
+                ),
+            ));
+        }
+    }
+    match (entry, annotation) {
+        (Some((ident, ty)), Some(annotation)) => {
+            if ty.to_token_stream().to_string() != annotation.to_token_stream().to_string() {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    format!(
+                        "{} parameter `{}` is declared as `{}` in the route, but has type `{}`",
+                        kind,
+                        ident,
+                        annotation.to_token_stream(),
+                        ty.to_token_stream(),
+                    ),
+                ));
+            }
+            Ok(Some((ident, ty)))
+        }
+        (Some((ident, ty)), None) => Ok(Some((ident, ty))),
+        (None, Some(_annotation)) => Ok(None),
+        (None, None) => Err(syn::Error::new(
+            ident.span(),
+            format!(
+                "{} parameter `{}` not found in function arguments",
+                kind, ident
+            ),
+        )),
+    }
+}
+
+/// Whether the pattern binds a variable called `ident`.
+fn binds_ident(pat: &Pat, ident: &Ident) -> bool {
+    match pat {
+        Pat::Ident(pat_ident) => {
+            pat_ident.ident == *ident
+                || pat_ident
+                    .subpat
+                    .as_ref()
+                    .is_some_and(|(_at, pat)| binds_ident(pat, ident))
+        }
+        Pat::Tuple(tuple) => tuple.elems.iter().any(|pat| binds_ident(pat, ident)),
+        Pat::TupleStruct(tuple) => tuple.elems.iter().any(|pat| binds_ident(pat, ident)),
+        Pat::Struct(strukt) => strukt
+            .fields
+            .iter()
+            .any(|field| binds_ident(&field.pat, ident)),
+        Pat::Slice(slice) => slice.elems.iter().any(|pat| binds_ident(pat, ident)),
+        Pat::Or(or) => or.cases.iter().any(|pat| binds_ident(pat, ident)),
+        Pat::Reference(reference) => binds_ident(&reference.pat, ident),
+        Pat::Paren(paren) => binds_ident(&paren.pat, ident),
+        Pat::Type(pat_type) => binds_ident(&pat_type.pat, ident),
+        _ => false,
+    }
+}
+
+/// Whether `ty` is axum's `State<T>`.
+fn is_state_type(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "State")
+}
+
 fn guess_state_type(sig: &syn::Signature) -> Type {
     for arg in &sig.inputs {
         if let FnArg::Typed(pat_type) = arg {
@@ -469,3 +4242,132 @@ fn guess_state_type(sig: &syn::Signature) -> Type {
 
     parse_quote! { () }
 }
+
+/// Returns `T` if the last segment of the type is exactly `<ident><T>`.
+fn single_generic_argument<'a>(ty: &'a Type, ident: &str) -> Option<&'a Type> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+    let last_segment = ty.path.segments.last()?;
+    if last_segment.ident != ident {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &last_segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(ty)) if args.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
+
+/// Whether a wildcard of type `ty` is split into segments: a `Vec<T>` or `Segments`.
+fn is_segments_type(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    single_generic_argument(ty, "Vec").is_some()
+        || path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Segments" && segment.arguments.is_none())
+}
+
+/// Whether `ty` is one of the extractors of the raw query and path of the request, which are
+/// passed through next to the declared parameters.
+fn is_raw_extractor(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.path.segments.last().is_some_and(|segment| {
+        ["RawQuery", "RawPathParams", "OriginalUri"].contains(&segment.ident.to_string().as_str())
+            && segment.arguments.is_none()
+    })
+}
+
+/// The `Ok` and `Err` types of a `Result<T, E>`.
+fn result_types(ty: &Type) -> Option<(&Type, &Type)> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+    let last_segment = ty.path.segments.last()?;
+    if last_segment.ident != "Result" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &last_segment.arguments else {
+        return None;
+    };
+    match (args.args.first(), args.args.get(1), args.args.len()) {
+        (Some(GenericArgument::Type(ok)), Some(GenericArgument::Type(err)), 2) => Some((ok, err)),
+        _ => None,
+    }
+}
+
+/// The axum path of a route with the given path parameters, e.g. `/item/{id}`.
+pub fn axum_path_string(path_params: &[(Slash, PathParam)]) -> String {
+    let mut path = String::new();
+
+    for (_slash, param) in path_params {
+        path.push('/');
+        match param {
+            PathParam::Capture(lit, _brace_1, _, _, _brace_2, _) => {
+                path.push('{');
+                path.push_str(&lit.value());
+                path.push('}');
+            }
+            PathParam::WildCard(lit, _brace_1, _, _, _, _brace_2, _) => {
+                path.push('{');
+                path.push('*');
+                path.push_str(&lit.value());
+                path.push('}');
+            }
+            PathParam::Static(lit) => path.push_str(&lit.value()),
+        }
+        // if colon.is_some() {
+        //     path.push(':');
+        // }
+        // path.push_str(&ident.value());
+    }
+
+    path
+}
+
+/// Returns `T` for the first argument of type `Json<T>`.
+fn json_body_type(sig: &Signature) -> Option<&Type> {
+    sig.inputs.iter().find_map(|arg| match arg {
+        FnArg::Typed(pat_type) => single_generic_argument(&pat_type.ty, "Json"),
+        FnArg::Receiver(_) => None,
+    })
+}
+
+/// The TypeScript equivalent of a Rust type. Unknown types are referenced by name.
+fn ts_type(ty: &Type) -> String {
+    match ty {
+        Type::Reference(reference) => ts_type(&reference.elem),
+        Type::Slice(slice) => format!("{}[]", ts_type(&slice.elem)),
+        Type::Array(array) => format!("{}[]", ts_type(&array.elem)),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => "null".to_string(),
+        Type::Path(path) => {
+            if let Some(inner) = single_generic_argument(ty, "Vec") {
+                return format!("{}[]", ts_type(inner));
+            }
+            if let Some(inner) = single_generic_argument(ty, "Option") {
+                return format!("{} | null", ts_type(inner));
+            }
+            let Some(last_segment) = path.path.segments.last() else {
+                return "unknown".to_string();
+            };
+            match last_segment.ident.to_string().as_str() {
+                "u8" | "u16" | "u32" | "u64" | "usize" | "i8" | "i16" | "i32" | "i64" | "isize"
+                | "f32" | "f64" => "number".to_string(),
+                "u128" | "i128" => "bigint".to_string(),
+                "String" | "str" | "char" | "Uuid" => "string".to_string(),
+                "bool" => "boolean".to_string(),
+                ident if last_segment.arguments.is_empty() => ident.to_string(),
+                _ => "unknown".to_string(),
+            }
+        }
+        _ => "unknown".to_string(),
+    }
+}
@@ -0,0 +1,104 @@
+//! Crate-wide defaults of [`macro@crate::api_route`], read from environment variables when the
+//! macro is expanded. These are usually set for a whole workspace in the `[env]` section of
+//! `.cargo/config.toml`.
+
+use crate::parsing::{DocFormat, IdStrategy};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{Ident, LitStr};
+
+const TAGS: &str = "AXUM_TYPED_ROUTING_TAGS";
+const ID_STRATEGY: &str = "AXUM_TYPED_ROUTING_ID_STRATEGY";
+const ID_MODULE_PREFIX: &str = "AXUM_TYPED_ROUTING_ID_MODULE_PREFIX";
+const DOC_FORMAT: &str = "AXUM_TYPED_ROUTING_DOC_FORMAT";
+const IDE_HELPERS: &str = "AXUM_TYPED_ROUTING_IDE_HELPERS";
+
+pub struct Config {
+    /// The options inserted at the start of the options block of every `api_route`.
+    pub api_defaults: TokenStream2,
+    /// Whether the IDE helpers of `api_route` are generated, `true` by default.
+    pub ide_helpers: bool,
+}
+
+impl Config {
+    pub fn from_env() -> syn::Result<Self> {
+        let mut api_defaults = TokenStream2::new();
+
+        if let Some(tags) = var(TAGS) {
+            let tags = tags
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(|tag| LitStr::new(tag, Span::call_site()));
+            api_defaults.extend(quote!(tags: [#(#tags),*],));
+        }
+        if let Some(strategy) = var(ID_STRATEGY) {
+            let ident = syn::parse_str::<Ident>(&strategy)
+                .ok()
+                .filter(|ident| syn::parse2::<IdStrategy>(quote!(#ident)).is_ok())
+                .ok_or_else(|| {
+                    invalid(
+                        ID_STRATEGY,
+                        &strategy,
+                        "one of (raw, snake_case, camelCase, PascalCase)",
+                    )
+                })?;
+            api_defaults.extend(quote!(id_strategy: #ident,));
+        }
+        if let Some(prefix) = var(ID_MODULE_PREFIX) {
+            let prefix = parse_bool(ID_MODULE_PREFIX, &prefix)?;
+            api_defaults.extend(quote!(id_module_prefix: #prefix,));
+        }
+        if let Some(format) = var(DOC_FORMAT) {
+            let ident = syn::parse_str::<Ident>(&format)
+                .ok()
+                .filter(|ident| syn::parse2::<DocFormat>(quote!(#ident)).is_ok())
+                .ok_or_else(|| invalid(DOC_FORMAT, &format, "one of (raw, trimmed, unwrapped)"))?;
+            api_defaults.extend(quote!(doc_format: #ident,));
+        }
+        let ide_helpers = match var(IDE_HELPERS) {
+            Some(value) => parse_bool(IDE_HELPERS, &value)?,
+            None => true,
+        };
+
+        Ok(Self {
+            api_defaults,
+            ide_helpers,
+        })
+    }
+}
+
+/// An item reading the environment variables with `option_env!`, so the compiler records them as
+/// dependencies of the crate, and Cargo rebuilds it when they change. Cargo doesn't know which
+/// variables a macro reads with `std::env`.
+pub fn env_dependencies() -> TokenStream2 {
+    let names = [TAGS, ID_STRATEGY, ID_MODULE_PREFIX, DOC_FORMAT, IDE_HELPERS];
+    let len = names.len();
+    quote! {
+        const _: [::core::option::Option<&str>; #len] = [#(::core::option_env!(#names)),*];
+    }
+}
+
+/// The value of the environment variable `name`, if it's set and not empty.
+fn var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn parse_bool(name: &str, value: &str) -> syn::Result<bool> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(invalid(name, value, "`true` or `false`")),
+    }
+}
+
+fn invalid(name: &str, value: &str, expected: &str) -> syn::Error {
+    syn::Error::new(
+        Span::call_site(),
+        format!(
+            "invalid value `{value}` of the environment variable `{name}`, expected {expected}"
+        ),
+    )
+}
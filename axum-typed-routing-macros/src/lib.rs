@@ -1,13 +1,17 @@
 use compilation::CompiledRoute;
-use parsing::{Method, Route};
+use parsing::{
+    ApiDefaults, DocFormat, IdStrategy, IncludeRouteParams, IncludedParams, Includes, Method,
+    Route, RouteIndex, RouteParamsMixins, WebhookAttr,
+};
 use proc_macro::TokenStream;
-use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use proc_macro2::{Delimiter, Group, Ident, Spacing, Span, TokenStream as TokenStream2, TokenTree};
 use std::collections::HashMap;
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     token::{Comma, Slash},
-    FnArg, GenericArgument, ItemFn, LitStr, Meta, PathArguments, Signature, Type,
+    Expr, ExprLit, FnArg, GenericArgument, Item, ItemFn, ItemMod, ItemType, Lit, LitStr, Meta,
+    PathArguments, ReturnType, Signature, Type,
 };
 #[macro_use]
 extern crate quote;
@@ -15,19 +19,261 @@ extern crate quote;
 extern crate syn;
 
 mod compilation;
+mod config;
 mod parsing;
 
 /// A macro that generates statically-typed routes for axum handlers.
 ///
 /// # Syntax
 /// ```ignore
-/// #[route(<METHOD> "<PATH>" [with <STATE>])]
+/// #[route(<METHOD> "<PATH>" [with <STATE> [where <BOUNDS>]] [multipart(<FIELD>: <TYPE>, ..)] [inject(<ARG> = <VALUE>, ..)] [{
+///     path_extractor: <EXTRACTOR>,
+///     path_struct: <bool> | <TYPE>,
+///     rejection: <REJECTION>,
+///     deny_unknown_query: <bool>,
+///     lenient_query: <bool>,
+///     infer_extensions: <bool>,
+///     status: <CODE>,
+///     head: auto,
+///     version: <N>,
+///     version_format: "<PREFIX>",
+///     alias: "<PATH>" | ["<PATH>", ..],
+///     deprecate_alias: <bool>,
+///     locale: "<LOCALE>",
+///     localized: { "<LOCALE>": "<PATH>", .. },
+///     deprecated: <bool>,
+///     sunset: "<HTTP-DATE>",
+///     name: <IDENT>,
+///     keep_fn: <bool>,
+///     vis: <VISIBILITY>,
+///     module: <bool>,
+///     in_module: <bool>,
+///     in_impl: <bool>,
+///     base: <BASE>,
+///     enabled_if: <EXPR>,
+///     moved_to: <ROUTE>,
+///     cache: { max_age: <SECONDS>, public: <bool>, immutable: <bool>, no_store: <bool> },
+///     cached: { ttl: <DURATION>, key: [<PARAM>, ..] },
+///     idempotent: <bool> | { ttl: <DURATION> },
+///     compression: off,
+///     cors: { origins: ["<ORIGIN>", ..], headers: ["<HEADER>", ..], max_age: <SECONDS>, credentials: <bool> },
+///     deadline: { header: "<HEADER>", default: <DURATION>, max: <DURATION> },
+///     generate_test: <bool>,
+///     erased: <bool>,
+///     guards: [<EXTRACTOR>, ..],
+///     before: [<HOOK>[(<PARAM>, ..)], ..],
+///     after: [<HOOK>[(<PARAM>, ..)], ..],
+///     permissions: ["<PERMISSION>", ..],
+///     sitemap: false | { priority: <0.0-1.0>, changefreq: <CHANGEFREQ> },
+///     include: <MIXIN> | [<MIXIN>, ..],
+///     paginated: <bool>,
+///     accepts: [json | msgpack, ..],
+///     host: "<HOST>",
+///     feature_flag: "<FLAG>",
+///     debug_handler: <bool>,
+/// }])]
 /// ```
-/// - `METHOD` is the HTTP method, such as `GET`, `POST`, `PUT`, etc.
+/// - `METHOD` is the HTTP method, such as `GET`, `POST`, `PUT`, etc. Any other uppercase
+///   identifier is a custom method, such as the WebDAV methods `PROPFIND` and `MKCOL`. axum
+///   routes these with a fallback that rejects other methods with `405 Method Not Allowed`, so a
+///   path can only have a single custom method. Custom methods can't be used with
+///   [`macro@api_route`], since OpenAPI doesn't support them.
 /// - `PATH` is the path of the route, with optional path parameters and query parameters,
-///     e.g. `/item/:id?amount&offset`.
+///     e.g. `/item/:id?amount&offset`. Instead of a string literal, this can be a `concat!` of
+///   string literals. Static segments must be valid in a URL, so characters such as spaces
+///   have to be percent-encoded (`%20`). A query parameter annotated with `(csv)`, as in
+///   `/items?ids(csv):Vec<u32>`, is a comma-separated list, like with `explode = false` (see
+///   [Parameter options](#parameter-options)).
 /// - `STATE` is the type of axum-state, passed to the handler. This is optional, and if not
-///    specified, the state type is guessed based on the parameters of the handler.
+///    specified, the state type is guessed based on the parameters of the handler. With
+///   `where <BOUNDS>`, the route is generic over the state, see [State type](#state-type).
+/// - `path_extractor` is the extractor used for the path parameters, instead of
+///   `::axum::extract::Path`. This can be `::axum_extra::extract::Path`, or any tuple struct
+///   `Extractor<T>(T)` that implements `FromRequestParts`, e.g. to customize the rejection.
+/// - `path_struct: true` extracts the path parameters into a generated struct with named fields,
+///   instead of a tuple, so rejections name the parameter that failed to deserialize. With
+///   `path_struct: <TYPE>`, they are extracted into a struct of your own, which is passed to the
+///   argument of that type; the template helpers then take this struct as well. The fields of
+///   the struct must be named like the path parameters, which can have type annotations
+///   (`/:id:u32`) for the TypeScript client, and are strings otherwise.
+/// - `rejection` wraps the generated path- and query-extractors in
+///   `::axum_extra::extract::WithRejection<_, REJECTION>`, so their rejections are converted into
+///   `REJECTION` using its `From` implementation. This requires a dependency on `axum-extra`.
+/// - `deny_unknown_query` rejects requests with query parameters that aren't part of the route
+///   with `400 Bad Request`, instead of ignoring them.
+/// - `lenient_query` extracts all query parameters as `None` if the query string can't be
+///   deserialized, e.g. because a parameter has the wrong type or isn't valid UTF-8, instead of
+///   rejecting the request with `400 Bad Request`. All query parameters must be `Option`s.
+/// - `infer_extensions` treats the `Extension<T>` arguments of the handler like the state: they
+///   are listed in the documentation of the route, and [`macro@api_route`] leaves them out of the
+///   operation input, as if they were annotated with `#[api_ignore]`.
+/// - `status` is the status code of the response, e.g. `201`, instead of `200 OK`. The return
+///   value of the handler is sent with this status code, or its `Ok` value if it returns a
+///   `Result`, so errors keep their own status code.
+/// - `head: auto` registers the handler for `HEAD` requests as well, without the response body.
+///   This can only be used on `GET` routes.
+/// - `version` prefixes the path with the API version, e.g. `version: 2` turns `/item/:id` into
+///   `/v2/item/:id`.
+/// - `version_format` is the prefix used for the `version`, where `{n}` is replaced by the version
+///   number. The default is `"/v{n}"`.
+/// - `alias` are additional paths of the route, e.g. to keep serving a legacy path. An alias must
+///   have the same path parameters as the route, in the same order, and no query parameters.
+/// - `deprecate_alias` adds a `Deprecation: true` header to the responses for the aliases.
+/// - `locale` and `localized` register the handler for localized paths, e.g.
+///   `locale: "en", localized: { "de": "/de/artikel/:id" }` on the route `/en/items/:id`. Like
+///   aliases, the localized paths must have the same path parameters as the route. The handler
+///   receives the locale of the requested path with an `axum_typed_routing::Locale` argument.
+/// - `deprecated` marks the whole route as deprecated, adding a `Deprecation: true` header to its
+///   responses. With `sunset`, e.g. `"Wed, 31 Dec 2025 23:59:59 GMT"`, a `Sunset` header with the
+///   date the route will be removed is added as well.
+/// - `name` is the name of the generated function, instead of the name of the handler.
+/// - `keep_fn` keeps the handler as a regular function, so it can be called directly, e.g. in
+///   unit tests. The generated function is then named `<HANDLER>_route`, unless `name` is given.
+/// - `vis` is the visibility of the generated function and of the [route module](#route-name),
+///   e.g. `vis: pub(crate)`, instead of the visibility of the handler. Together with `keep_fn`,
+///   a crate can export its handlers for testing without exporting routes that can be
+///   registered, or the other way around.
+/// - `module` generates the [route module](#route-name) without a feature that does.
+/// - `in_module` puts the generated function into the route module, named `route` unless `name`
+///   is given, e.g. `router.typed_route(get_item::route)`, and keeps the handler like `keep_fn`.
+///   This can't be used with `in_impl`.
+/// - `in_impl` allows the handler to be an associated function of an inherent `impl` block,
+///   which can be generic over the state, e.g. for plugins exposing their routes as a
+///   `Vec<TypedHandler<S>>`. Like with `keep_fn`, the handler is kept and the generated
+///   associated function is named `<HANDLER>_route`. Since modules can't be declared in an
+///   `impl` block, there are no [template links](#template-links), and the route isn't
+///   registered for the TypeScript client. Trait methods aren't supported, since a trait can't
+///   have extra functions in its implementations, and the futures of its default methods aren't
+///   known to be `Send`.
+/// - `base` is a `&'static str` constant prepended to the path, e.g. `crate::paths::ITEMS`, to
+///   share the root of paths between routes. Since the macro can't read constants, the `PATH`
+///   itself can't refer to them.
+/// - `enabled_if` is a `bool` expression, e.g. `cfg!(feature = "admin")`, evaluated when the route
+///   is added to a router. If it's `false`, the route is left out, see also
+///   `TypedRouter::typed_route_if`.
+/// - `moved_to` is the generated function of the route that replaced this one, e.g.
+///   `moved_to: get_account`. The handler isn't called anymore: requests are redirected with
+///   `308 Permanent Redirect` to the path of the other route, with the values of the path
+///   parameters of the same name and the query of the request. Adding the route to a router
+///   panics if the other route has a path parameter this one doesn't have. Combine it with
+///   `deprecated` and `sunset` to announce when the old path is removed.
+/// - `cache` sets the `Cache-Control` header of successful responses that don't set it
+///   themselves, e.g. `cache: { max_age: 300, public: true }` sends
+///   `Cache-Control: public, max-age=300`. `public: false` sends `private`, and `no_store: true`
+///   sends `no-store`, which can't be combined with the other directives. This can only be used
+///   on `GET` routes.
+/// - `cached` caches the successful responses of a `GET` route in the
+///   `axum_typed_routing::ResponseCache` implemented by the state, for the `ttl` (e.g. `500ms`,
+///   `60s`, `5m` or `1h`). They're keyed by the handler and the `Debug` values of the parameters
+///   and arguments in `key`, e.g. `cached: { ttl: 60s, key: [id, amount, user] }`, or of the path-
+///   and query-parameters passed to the handler without a `key`. A handler taking other arguments
+///   than those and the `State`, e.g. the user, needs a `key` including the ones the response
+///   depends on, so it isn't shared between their requests. Responses that set a cookie aren't
+///   cached. Since the key doesn't include the negotiated encoding, it can't be used together
+///   with `accepts`.
+/// - `idempotent: true` rejects requests without an `Idempotency-Key` header with
+///   `400 Bad Request`, and the handler can take the key as an `axum_typed_routing::IdempotencyKey`
+///   argument. With e.g. `idempotent: { ttl: 24h }`, the response to a key is stored in the
+///   `axum_typed_routing::IdempotencyStore` implemented by the state, and replayed to retries with
+///   the same key for the `ttl`, unless it's a server error. It can't be used on `GET` or `HEAD`
+///   routes.
+/// - `compression: off` excludes the responses of the route from the compression layer of the
+///   application (e.g. `tower_http::compression::CompressionLayer`), such as Server-Sent Events
+///   that must be flushed immediately. The responses are sent with `Content-Encoding: identity`,
+///   which compression layers leave untouched.
+/// - `cors` lets browsers call the route from other origins, without a permissive CORS layer for
+///   the whole application, e.g.
+///   `cors: { origins: ["https://app.example.com"], headers: ["content-type"], max_age: 600 }`.
+///   The responses to requests from the `origins` (or any origin with `"*"`) get the
+///   `Access-Control-Allow-Origin` header, and `credentials: true` allows cookies. An `OPTIONS`
+///   route is added to the path, answering preflight requests with the method of the route and
+///   the allowed `headers`, cached by browsers for `max_age` seconds.
+///
+///   Routes of the same path with `cors` share the `OPTIONS` route when they are added with a
+///   `TypedRouterBuilder` or `merge_method_routers!`. Added one by one to an `axum::Router`, the
+///   preflight requests are answered by the fallback of the path instead, which an `OPTIONS`
+///   route of the path takes precedence over. A path only keeps the fallback of the last route
+///   added to it, so only that route can be called from other origins.
+/// - `deadline` reads the deadline of a request from a header with the time left for it, either
+///   in milliseconds or as a `grpc-timeout` (e.g. `500m` or `5S`), and answers the request with
+///   `504 Gateway Timeout` if the handler hasn't responded when it passes, e.g.
+///   `deadline: { header: "grpc-timeout", default: 5s, max: 30s }`. Requests without the header
+///   time out after the `default`, and no request gets more time than the `max`. A header that
+///   can't be parsed is rejected with `400 Bad Request`. The handler can take an
+///   `axum_typed_routing::Deadline` argument, to pass the time left on to the services it calls.
+///   This requires the `deadline` feature.
+/// - `generate_test` generates the unit test `<HANDLER>::extracts_params`, which requests the
+///   path of the route with sample path- and query-parameters, and checks that the extractors of
+///   the route accept them. This catches parameter types that can't be parsed from the path,
+///   without calling the handler. The samples are taken from `axum_typed_routing::SampleParam`,
+///   which must be implemented for the types of the parameters, such as enums. Parameters with
+///   `#[route_param(..)]` options can't be sampled, and `in_impl` routes have no module for the
+///   test. The test is a `#[tokio::test]`, so `tokio` with its `macros` and `rt` features must be
+///   a dev-dependency.
+/// - `erased` reduces the code generated for every instantiation of a generic handler, to cut
+///   binary size and link times when it's instantiated many times: the future of the handler
+///   is boxed, and the layers of the options above are applied by a function that is shared by
+///   all routes with the same state. This costs an allocation per request. It can't be used with
+///   [`macro@api_route`], since the documentation needs the response type of the handler.
+/// - `guards` are extractors that must succeed before the handler is called, e.g.
+///   `guards: [RequireAuth]`, without being arguments of the handler. Each guard is added with
+///   `route_layer(axum::middleware::from_extractor::<GUARD>())`, so its rejection is the response
+///   when it fails. Since the layers don't have the state, the guards must implement
+///   `FromRequestParts<()>`, which extractors that are generic over the state do.
+/// - `before` and `after` are async functions awaited right before and after the handler, with
+///   references to its typed path- and query-parameters, e.g. `before: [log_entry]` and
+///   `after: [audit(id)]`. A hook without parentheses gets all parameters passed to the handler,
+///   in the order of the path and query, and a hook with them gets only the listed parameters.
+///   They are a lighter alternative to a layer for cross-cutting logic that needs the typed
+///   values, but can't reject the request or change the response. The parameters of the `after`
+///   hooks are cloned before the handler takes them, so they must implement `Clone`.
+///   ```ignore
+///   async fn log_entry(id: &u32, verbose: &bool) {
+///       tracing::info!(id, verbose, "getting item");
+///   }
+///
+///   #[route(GET "/item/:id?verbose" { before: [log_entry], after: [audit(id)] })]
+///   async fn get_item(id: u32, verbose: bool) -> String {
+///       todo!("handle request")
+///   }
+///   ```
+/// - `permissions` are the permissions required by the route, e.g. `permissions: ["items:read"]`,
+///   which aren't checked by the route itself, but by a single authorization middleware. They're
+///   part of the `RouteInfo` of the route, so the middleware can look them up with the
+///   `axum_typed_routing::RoutePermissions` of a `TypedRouterBuilder`, and with the
+///   `matched-route` feature, of the `MatchedTypedRoute` extension of the request.
+/// - `sitemap` configures the entry of a `GET` route in the sitemap of
+///   `TypedRouterBuilder::sitemap`, which lists all `GET` routes without path parameters.
+///   `sitemap: false` leaves the route out, and e.g. `sitemap: { priority: 0.8, changefreq: daily }`
+///   sets the `priority` and `changefreq` of its entry. The change frequency is one of `always`,
+///   `hourly`, `daily`, `weekly`, `monthly`, `yearly` or `never`.
+/// - `include` adds the query parameters of mixins declared with [`route_params!`], e.g.
+///   `include: [pagination, sorting]`.
+/// - `paginated: true` adds the optional query parameters `page` and `per_page` of a list
+///   route, which are read by an `axum_typed_routing::Pagination` argument, and returned with
+///   the page of items in an `axum_typed_routing::Paginated<T>`, with the `pagination` feature.
+/// - `accepts` encodes the return value of the handler with the encoding preferred by the
+///   `Accept` header of the request, e.g. `accepts: [json, msgpack]`, instead of hand-rolling
+///   the negotiation in every handler. The first encoding is used when any is accepted, and
+///   requests accepting none of them are rejected with `406 Not Acceptable`. The return value
+///   must implement `serde::Serialize`, or be a `Result` whose `Ok` value does. `msgpack`
+///   requires the `msgpack` feature. With [`macro@api_route`], the successful responses are
+///   documented with the media types of all encodings.
+/// - `host` only matches requests to the given host, e.g. `host: "internal.example.com"`, and
+///   responds with `404 Not Found` to requests with another `Host` header, to expose internal
+///   endpoints on a separate virtual host of the same listener. The host is compared without
+///   case, and without the port unless it includes one. Since axum routes by path, the other
+///   hosts can't have a route with the same path and method.
+/// - `feature_flag` only serves the route while the given feature flag is enabled, e.g.
+///   `feature_flag: "new-checkout"`, which is checked for every request with the
+///   `FeatureGate` implementation of the state. Requests to a disabled route are rejected with
+///   the `disabled_status` of the gate, `404 Not Found` by default, before the other arguments
+///   are extracted.
+/// - `debug_handler: true` applies `axum::debug_handler` to the handler generated for the route,
+///   with its state type, so a handler that isn't valid is reported with the diagnostics of axum
+///   for each argument and the return type, instead of a trait error about the whole route. Like
+///   `axum::debug_handler`, this only has an effect in debug builds, and can't be used with
+///   generic handlers, `in_impl` or `erased`.
 ///
 /// # Example
 /// ```
@@ -46,6 +292,121 @@ mod parsing;
 /// }
 /// ```
 ///
+/// # Parameter types
+/// The type of a path- or query-parameter can be declared in the route, as in
+/// `/item/:id:u32/*rest:String?amount:u32&offset:Option<u32>`.
+/// If the function has an argument with the same name, its type must match the declared type.
+/// If it doesn't, the parameter is still extracted (and rejected if it fails to parse), but
+/// not passed to the handler:
+/// ```ignore
+/// #[route(GET "/user/:user_id:u32/item/:id:u32?amount:u32&offset:Option<u32>")]
+/// async fn item_handler(id: u32, amount: u32) -> String {
+///     todo!("handle request")
+/// }
+/// ```
+///
+/// # Renaming path parameters
+/// Path parameters that aren't valid Rust identifiers can be renamed with `as`, as in
+/// `/item/:item-id as item_id`. The original name is used in the axum path, and the renamed
+/// identifier refers to the argument of the handler. This can be combined with a declared type,
+/// as in `/item/:item-id as item_id:u32`.
+///
+/// # Parameter patterns
+/// With the `regex` feature, a path parameter can be constrained by a regular expression, as in
+/// `/posts/:slug(regex = "[a-z0-9-]+")`, which has to match its whole (percent-decoded) value.
+/// A request with a value that doesn't match is rejected with `400 Bad Request` before the
+/// handler's arguments are extracted. The pattern is checked when the route is compiled, and with
+/// [`macro@api_route`] it's documented as the `pattern` of the parameter's schema.
+/// ```ignore
+/// #[route(GET "/posts/:slug(regex = \"[a-z0-9-]+\")/:page:u32")]
+/// async fn get_post(slug: String, page: u32) -> String {
+///     todo!("handle request")
+/// }
+/// ```
+///
+/// # Wildcard segments
+/// A wildcard of type `Vec<T>` or `Segments` is split at the slashes, leaving out empty
+/// segments, and every segment is parsed with `FromStr`, as in `/files/*path:Vec<String>`.
+/// The template links, client requests and test paths of the route encode the slashes inside of
+/// a segment. With [`macro@api_route`], the wildcard is documented as a string.
+/// ```ignore
+/// // Matches e.g. `/files/docs/2024/report.pdf`, with `path` as `["docs", "2024", "report.pdf"]`
+/// #[route(GET "/files/*path")]
+/// async fn browse(path: Segments) -> String {
+///     path.join(" > ")
+/// }
+/// ```
+///
+/// # Parameter options
+/// Path- and query-parameters can be customized with `#[route_param(..)]` on their argument:
+/// - `flatten` flattens the fields of a query parameter into the query, using
+///   `#[serde(flatten)]`. This can be used for a tagged enum of mutually exclusive filters.
+///   This can't be combined with `deny_unknown_query`. With [`macro@api_route`], the fields of
+///   all variants are documented as query parameters.
+/// - `deserialize_with = "<FUNCTION>"` parses the parameter with a custom function, as with
+///   `#[serde(deserialize_with = "..")]`, e.g. for comma-separated lists or custom date formats.
+///   With [`macro@api_route`], the parameter is documented as a string.
+/// - `try_from = <TYPE>` deserializes the parameter as `<TYPE>`, and converts it into the type
+///   of the argument with `TryFrom`, rejecting the request with `400 Bad Request` if that fails,
+///   e.g. `#[route_param(try_from = String)] user: UserName`. The argument type doesn't need to
+///   implement `Deserialize`, and with [`macro@api_route`], it's documented as `<TYPE>`.
+/// - `from_str` parses the parameter with `FromStr`, rejecting the request with
+///   `400 Bad Request` if that fails, e.g. for a newtype `#[route_param(from_str)] id: UserId`
+///   that doesn't implement `Deserialize`. With [`macro@api_route`], it's documented as a string.
+/// - `style = deepObject` reads an object parameter from bracketed keys, as in
+///   `filter[name]=x&filter[age]=3`, and documents it with the OpenAPI `deepObject` style. The
+///   query is then extracted with `DeepObjectQuery`, which requires the `deep-object` feature.
+/// - `explode = false` reads a list parameter from comma-separated values, as in `ids=1,2,3`,
+///   and documents it with `explode: false`. `style = form` is the default style. The
+///   `(csv)` annotation of a query parameter in the path is a shorthand for this.
+/// - `schema_with = "<FUNCTION>"` documents a query parameter whose type doesn't implement
+///   `JsonSchema` with the schema of a function `fn(&mut SchemaGenerator) -> Schema`, as with
+///   `#[schemars(schema_with = "..")]`, and `skip_schema` leaves it out of the documentation.
+///   Both only affect [`macro@api_route`].
+///
+/// ```ignore
+/// #[derive(Deserialize)]
+/// #[serde(tag = "sort", rename_all = "snake_case")]
+/// enum SortOrder {
+///     Name,
+///     Date { since: Option<String> },
+/// }
+///
+/// // Matches e.g. `/items?page=2&sort=date&since=2024-01-01&ids=1,2,3`
+/// #[route(GET "/items?page&sort&ids")]
+/// async fn list_items(
+///     page: Option<u32>,
+///     #[route_param(flatten)] sort: SortOrder,
+///     #[route_param(deserialize_with = "from_csv")] ids: Vec<u32>,
+/// ) -> String {
+///     todo!("handle request")
+/// }
+/// ```
+///
+/// Other attributes and `mut` are kept on the arguments of the handler, e.g.
+/// `#[allow(unused_mut)] mut id: u32`.
+///
+/// The `#[serde(..)]` and `#[schemars(..)]` attributes of query parameters are put on their fields
+/// of the generated query struct, e.g. `#[serde(default)]`, or
+/// `#[schemars(range(min = 1, max = 100))]` to document constraints with [`macro@api_route`].
+/// Without `api_route`, the `#[schemars(..)]` attributes are ignored.
+///
+/// # Ignored arguments
+/// Arguments annotated with `#[api_ignore]` are excluded from the OpenApi documentation by
+/// [`macro@api_route`], which is needed for extractors that don't implement `aide`'s
+/// `OperationInput`, such as sessions. [`macro@route`] removes the attribute and ignores it:
+/// ```ignore
+/// #[api_route(GET "/profile")]
+/// async fn profile(#[api_ignore] session: Session) -> String {
+///     todo!("handle request")
+/// }
+/// ```
+///
+/// The raw extractors `RawQuery`, `RawPathParams` and `OriginalUri` can be taken next to the
+/// declared path- and query-parameters, e.g. to forward the query to another service. They're
+/// passed through as they are, and ignored by [`macro@api_route`], since the parameters they
+/// read are already documented.
+///
 /// # State type
 /// Normally, the state-type is guessed based on the parameters of the function:
 /// If the function has a parameter of type `[..]::State<T>`, then `T` is used as the state type.
@@ -55,15 +416,148 @@ mod parsing;
 /// #[route(GET "/item/:id?amount&offset" with String)]
 /// ```
 ///
+/// A route can be generic over its state with a `where` clause, so it can be added to routers
+/// with different states. The state parameter is added to the generics of the handler, with the
+/// given bounds and the bounds axum requires (`Clone + Send + Sync + 'static`):
+/// ```ignore
+/// #[route(GET "/me" with S where S: AuthProvider)]
+/// async fn me(State(auth): State<S>) -> String {
+///     auth.current_user()
+/// }
+///
+/// let router: Router<MyState> = Router::new().typed_route(me);
+/// ```
+///
+/// The state can also be a generic type whose parameters are bounded by the `where` clause, such
+/// as `with Repo<T> where T: Backend`. The parameters are added to the generics of the handler if
+/// it doesn't declare them, and a handler with generics is added to a router with a turbofish:
+/// ```ignore
+/// #[route(GET "/users/:id" with Repo<T> where T: Backend)]
+/// async fn get_user(id: u32, State(repo): State<Repo<T>>) -> String {
+///     repo.user(id).await
+/// }
+///
+/// let router: Router<Repo<Postgres>> = Router::new().typed_route(get_user::<Postgres>);
+/// ```
+///
+/// # Multipart fields
+/// With the `multipart` feature, a `multipart(..)` clause reads the fields of a
+/// `multipart/form-data` body into the arguments with the same names, which come after the
+/// path- and query-parameters. The types implement `axum_typed_routing::FromMultipartField`:
+/// `Bytes`, `Vec<u8>` and `MultipartFile` for files, and `String`, `bool` and the numbers for
+/// text fields. A field of type `Option<T>` is optional. A missing, repeated or invalid field is
+/// rejected with `400 Bad Request`, and unknown fields are skipped. With [`macro@api_route`], the
+/// fields are documented as the request body, unless the route has a `request_body`.
+/// ```ignore
+/// #[route(POST "/avatar" multipart(image: MultipartFile, caption: Option<String>))]
+/// async fn upload_avatar(image: MultipartFile, caption: Option<String>) -> String {
+///     todo!("store the image")
+/// }
+/// ```
+///
+/// # Injected arguments
+/// An `inject(..)` clause passes values to the arguments with the same names, instead of
+/// extracting them from the request, e.g. a literal or a constant. Since a handler has a single
+/// [route module](#route-name), a handler shared by several routes with a different
+/// configuration is an associated function with `in_impl`, and a `name` for each route:
+/// ```ignore
+/// impl Realms {
+///     #[route(GET "/admin/users" inject(realm = "admin", level = 2u8) { in_impl: true, name: admin_users })]
+///     #[route(GET "/guest/users" inject(realm = "guest", level = 0u8) { in_impl: true, name: guest_users })]
+///     async fn users(realm: &'static str, level: u8, State(db): State<Db>) -> String {
+///         db.users(realm, level).await
+///     }
+/// }
+///
+/// let router = Router::new()
+///     .typed_route(Realms::admin_users)
+///     .typed_route(Realms::guest_users);
+/// ```
+/// The values are evaluated for every request, and must have the types of the arguments. Injected
+/// arguments aren't part of the OpenAPI operation of an [`macro@api_route`].
+///
+/// # Route name
+/// With the `module` option, a module with the same name as the handler is generated next to
+/// it, containing the constant `NAME`: the module path and name of the handler, e.g.
+/// `my_crate::items::get_item`. This is a stable identifier of the route for logging, tracing
+/// and metrics, which doesn't change when the path does:
+/// ```ignore
+/// #[route(GET "/item/:id" { module: true })]
+/// async fn item_handler(id: u32) {}
+///
+/// assert_eq!(item_handler::NAME, "my_crate::item_handler");
+/// ```
+/// The module is also generated by `in_module` and `generate_test`, and for every route by the
+/// `templates`, `fuzz` and `client` features, which put their helpers into it. It conflicts with
+/// a module or type with the name of the handler, in which case the handler can be renamed,
+/// keeping the name of the generated function with `name`.
+///
+/// # Template links
+/// With the `templates` feature enabled, the module of the handler also contains helpers to link
+/// to the route from templates (e.g. `maud` or `askama`):
+/// - `href(<PATH PARAMS>..) -> String` returns the percent-encoded path of the route.
+/// - `attrs(<PATH PARAMS>..) -> (String, &'static str)` returns the `action` and `method` of a
+///   form submitting to the route.
+/// - `link()` returns a builder for a link with query parameters, with a setter for each path-
+///   and query-parameter. `build()` panics if a path parameter or a required query parameter is
+///   missing. Query parameters with `#[route_param(..)]` options are left out.
+///
+/// ```ignore
+/// assert_eq!(item_handler::href(&1), "/item/1");
+/// assert_eq!(item_handler::link().id(1).amount(3).build(), "/item/1?amount=3");
+/// ```
+///
+/// The path parameters are formatted with `axum_typed_routing::PathSegmentEncode`, which is
+/// implemented for all `Display` types and can be implemented for custom types. The query
+/// parameters are formatted with `Display`. Both are percent-encoded, so values containing `/`
+/// or spaces can't break the link. The helpers of a route with parameters of other types are
+/// still generated, and only fail to compile where they're called, so enabling the feature
+/// doesn't break these routes.
+///
+/// # Fuzzing
+/// With the `fuzz` feature enabled, the module of the handler also contains
+/// `param_strategy()`, a `proptest` strategy for paths of the route with arbitrary path- and
+/// query-parameters, formatted like the [template links](#template-links). To call it, the
+/// types of the parameters must implement the `Arbitrary` of `proptest`, which the tests use
+/// through their own dependency on it:
+/// ```ignore
+/// proptest! {
+///     #[test]
+///     fn item_handler_never_fails(path in item_handler::param_strategy()) {
+///         let response = block_on(server.get(&path));
+///         prop_assert!(!response.status_code().is_server_error());
+///     }
+/// }
+/// ```
+///
 /// # Internals
-/// The macro expands to a function with signature `fn() -> (&'static str, axum::routing::MethodRouter<S>)`.
-/// The first element of the tuple is the path, and the second is axum's `MethodRouter`.
+/// The macro expands to a function with signature
+/// `fn() -> (&'static str, axum::routing::MethodRouter<S>, Box<dyn Any>)`.
+/// The first element of the tuple is the path, the second is axum's `MethodRouter`, and the third
+/// describes the route for the `TypedRouter` of `axum_typed_routing`. Without the options the
+/// `TypedRouter` needs to know about, such as `alias` or `cors`, this is the `(method, name)` of
+/// the route, so the macro can be used without `axum_typed_routing`:
+/// ```ignore
+/// let (path, method_router, _description) = item_handler();
+/// let router = axum::Router::new().route(path, method_router);
+/// ```
 ///
 /// The path and query are extracted using axum's `extract::Path` and `extract::Query` extractors, as the first
 /// and second parameters of the function. The remaining parameters are the parameters of the handler.
+///
+/// The generated function is documented with the method, path and state of the route, a table of
+/// its path- and query-parameters, and an example `curl` request. To keep
+/// release builds of crates with many routes fast, this documentation (and the IDE helpers of
+/// [`macro@api_route`]) is only generated with `debug_assertions` enabled.
+///
+/// For the same reason, routes with up to 8 query parameters without options or attributes
+/// don't generate a struct deriving `Deserialize` for them. They're extracted as a tuple by a
+/// generic struct of `axum-typed-routing`, whose code is shared by the routes with the same
+/// types of parameters.
+#[allow(clippy::doc_overindented_list_items)]
 #[proc_macro_attribute]
 pub fn route(attr: TokenStream, mut item: TokenStream) -> TokenStream {
-    match _route(attr, item.clone(), false) {
+    match _route(attr.into(), item.clone().into(), false, Vec::new()) {
         Ok(tokens) => tokens.into(),
         Err(err) => {
             let err: TokenStream = err.to_compile_error().into();
@@ -78,26 +572,210 @@ pub fn route(attr: TokenStream, mut item: TokenStream) -> TokenStream {
 ///
 /// # Syntax
 /// ```ignore
-/// #[api_route(<METHOD> "<PATH>" [with <STATE>] [{
+/// #[api_route(<METHOD> "<PATH>" [with <STATE> [where <BOUNDS>]] [multipart(<FIELD>: <TYPE>, ..)] [inject(<ARG> = <VALUE>, ..)] [{
+///     path_extractor: <EXTRACTOR>,
+///     path_struct: <bool> | <TYPE>,
+///     rejection: <REJECTION>,
+///     deny_unknown_query: <bool>,
+///     lenient_query: <bool>,
+///     infer_extensions: <bool>,
+///     status: <CODE>,
+///     head: auto,
+///     version: <N>,
+///     version_format: "<PREFIX>",
+///     alias: "<PATH>" | ["<PATH>", ..],
+///     deprecate_alias: <bool>,
+///     locale: "<LOCALE>",
+///     localized: { "<LOCALE>": "<PATH>", .. },
+///     deprecated: <bool>,
+///     sunset: "<HTTP-DATE>",
+///     name: <IDENT>,
+///     keep_fn: <bool>,
+///     vis: <VISIBILITY>,
+///     module: <bool>,
+///     in_module: <bool>,
+///     in_impl: <bool>,
+///     base: <BASE>,
+///     enabled_if: <EXPR>,
+///     moved_to: <ROUTE>,
+///     cache: { max_age: <SECONDS>, public: <bool>, immutable: <bool>, no_store: <bool> },
+///     cached: { ttl: <DURATION>, key: [<PARAM>, ..] },
+///     idempotent: <bool> | { ttl: <DURATION> },
+///     compression: off,
+///     cors: { origins: ["<ORIGIN>", ..], headers: ["<HEADER>", ..], max_age: <SECONDS>, credentials: <bool> },
+///     deadline: { header: "<HEADER>", default: <DURATION>, max: <DURATION> },
+///     generate_test: <bool>,
+///     guards: [<EXTRACTOR>, ..],
+///     before: [<HOOK>[(<PARAM>, ..)], ..],
+///     after: [<HOOK>[(<PARAM>, ..)], ..],
+///     permissions: ["<PERMISSION>", ..],
+///     sitemap: false | { priority: <0.0-1.0>, changefreq: <CHANGEFREQ> },
+///     include: <MIXIN> | [<MIXIN>, ..],
+///     paginated: <bool>,
+///     accepts: [json | msgpack, ..],
+///     host: "<HOST>",
+///     feature_flag: "<FLAG>",
+///     debug_handler: <bool>,
 ///     summary: "<SUMMARY>",
 ///     description: "<DESCRIPTION>",
 ///     id: "<ID>",
+///     id_strategy: raw | snake_case | camelCase | PascalCase,
+///     id_module_prefix: <bool>,
+///     doc_format: raw | trimmed | unwrapped,
 ///     tags: ["<TAG>", ..],
 ///     hidden: <bool>,
-///     security: { <SCHEME>: ["<SCOPE>", ..], .. },
-///     responses: { <CODE>: <TYPE>, .. },
-///     transform: |op| { .. },
+///     api_doc: "<NAME>",
+///     security: { <SCHEME>: [<SCOPE>, ..], .. },
+///     responses: { <CODE>: <TYPE> [|r| ..], .. },
+///     infer_responses: <bool>,
+///     transform: |op| { .. } | <FUNCTION>,
+///     request_body: multipart { <FIELD>: <TYPE | binary>, .. } | binary("<MEDIA-TYPE>", ..),
+///     servers: ["<URL>", ..],
+///     extensions: { "x-<NAME>": <VALUE>, .. },
+///     params: { <PARAM>: { example: <VALUE>, description: "<DESCRIPTION>", required: <bool>, deprecated: <bool> }, .. },
+///     parameters: [{ name: "<NAME>", in: path | query | header | cookie, schema: <TYPE>, required: <bool>, description: "<DESCRIPTION>", example: <VALUE>, deprecated: <bool> }, ..],
+///     examples: { "<NAME>": { request: <VALUE>, response: <VALUE> }, .. },
 /// }])]
 /// ```
+/// - `path_extractor`, `path_struct`, `rejection`, `deny_unknown_query`, `lenient_query` and
+///   `infer_extensions`: see [`macro@route`]. With `path_struct`, the path parameters are
+///   documented from the schema of the struct, and with `lenient_query`, the query doesn't have
+///   a `400` response.
+/// - `status`: see [`macro@route`]. The return type of the handler is documented as the response
+///   for this status code, instead of `200`.
+/// - `version` and `version_format`: see [`macro@route`]. The operation is tagged with the version,
+///   e.g. `v2`.
+/// - `alias` and `deprecate_alias`: see [`macro@route`]. The aliases are not documented.
+/// - `locale` and `localized`: see [`macro@route`]. Only the path of the route is documented, and
+///   the `Locale` argument isn't part of the operation input.
+/// - `deprecated` and `sunset`: see [`macro@route`]. The operation is documented as deprecated,
+///   so the documentation matches the headers of the responses.
+/// - `name`, `keep_fn`, `vis`, `in_module`, `in_impl`, `base` and `enabled_if`: see
+///   [`macro@route`]. A disabled route is not
+///   documented.
+/// - `moved_to`: see [`macro@route`]. The only documented response is the
+///   `308 Permanent Redirect`, with the path of the other route in its `Location` header.
+/// - `cache`, `cached`, `idempotent`, `compression`, `cors`, `deadline`, `generate_test`,
+///   `guards`, `before`, `after`, `permissions`, `sitemap`, `include`, `paginated`, `host`,
+///   `feature_flag` and `debug_handler`: see [`macro@route`]. The guards, the host and the feature
+///   flag aren't documented, since they aren't part of the operation input, and neither is the
+///   `OPTIONS` route of `cors`. The header of `deadline` is documented as an optional header
+///   parameter, with a `504 Gateway Timeout` response, the `Idempotency-Key` of `idempotent` as a
+///   required header parameter, and the `permissions` as the `x-permissions` extension of the
+///   operation. The query parameters of `paginated` are documented, and the `Paginated<T>`
+///   envelope as the JSON response.
+/// - `head`: see [`macro@route`]. The `HEAD` operation is documented like the `GET` operation,
+///   without response bodies, and with `_head` appended to its operationId.
 /// - `summary` is the OpenApi summary. If not specified, the first line of the function's doc-comments
 /// - `description` is the OpenApi description. If not specified, the rest of the function's doc-comments
-/// - `id` is the OpenApi operationId. If not specified, the function's name is used.
-/// - `tags` are the OpenApi tags.
+///
+///   Instead of a string literal, the `summary` and `description` can be any `&str` expression,
+///   such as a constant or `include_str!("docs/get_item.md")` to keep long documentation in a
+///   markdown file.
+/// - `doc_format` is how the `summary` and `description` are taken from the doc-comments:
+///   `trimmed` (the default) removes the indentation shared by all lines, such as the space after
+///   `///`, and the blank lines around the description. `unwrapped` also joins the lines of each
+///   paragraph with spaces, leaving headings, lists, tables and code blocks on their own lines,
+///   and uses the whole first paragraph as the summary. `raw` uses the lines as written.
+/// - `id` is the OpenApi operationId. If not specified, the function's name is used. Instead of
+///   a string literal, it can be any `&'static str` expression, such as
+///   `concat!(module_path!(), "::get_item")`.
+/// - `id_strategy` converts the function's name into the operationId when there's no `id`:
+///   `raw` (the default) uses it as written, and `snake_case`, `camelCase` or `PascalCase`
+///   convert it, e.g. `get_item` into `getItem`. Spec linters and client generators often
+///   require one of these styles; use [`macro@api_defaults`] to set it for a whole module.
+/// - `id_module_prefix` prefixes the operationId with the name of the module of the handler,
+///   e.g. `items.getItem`.
+///
+///   With the `unique-operation-ids` feature, two routes of a module with the same operationId
+///   fail to compile, with an error pointing at both handlers. Ids of routes in different
+///   modules, with a module prefix or given as an expression are checked by
+///   `assert_no_conflicts!` and `TypedRouterBuilder` instead.
+/// - `tags` are the OpenApi tags: string literals, or any `&'static str` expressions, such as
+///   constants or function calls, e.g. `tags: [TAG_ITEMS, admin_tag()]` to name the tags in one
+///   place. The expressions are evaluated when the route is added.
 /// - `hidden` sets whether docs should be hidden for this route.
-/// - `security` is the OpenApi security requirements.
-/// - `responses` are the OpenApi responses.
+/// - `api_doc` assigns the route to a named document, e.g. `api_doc: "internal"` for a binary
+///   serving both a public and an internal API. `finish_named_api` of `TypedApiRouter` moves the
+///   routes of a document out of the main documentation, with the schemas they reference. Until
+///   then, the name is the `x-api-doc` extension of the operation.
+/// - `security` is the OpenApi security requirements. The schemes and scopes are string literals,
+///   or any expression implementing `AsRef<str>`, such as a constant or an enum variant, so their
+///   names can be defined in one place:
+///   `security: { SCHEME_BEARER: [Scope::ReadItems] }`.
+/// - `responses` are the OpenApi responses. A response can be followed by a closure that takes
+///   and returns a `TransformResponse`, e.g. `200: Item |r| r.description("the item")`, to
+///   document it without a `transform` of the whole operation. A status code can be repeated
+///   for a response with several media types or schemas, e.g. `200: Json<Item>` and
+///   `200: Html<String>`, which are merged into one response. The schemas of a media type that's
+///   repeated, such as two `Json` responses, are documented as `oneOf` them.
+///
+///   Instead of an integer, a status code can be a `StatusCode` constant, e.g.
+///   `StatusCode::IM_A_TEAPOT: Teapot`, or a constant of your own of type `StatusCode`, so the
+///   codes can be shared instead of repeated as numbers. Constants with the same code are only
+///   merged if they're written the same way.
+/// - `infer_responses: false` removes the responses `aide` infers from the return type of the
+///   handler, so only the `status` and `responses` options are documented, e.g. for strict
+///   public specs where the inferred error responses are unwanted.
 /// - `transform` is a closure that takes an `TransformOperation` and returns an `TransformOperation`.
 /// This may override the other options. (see the crate `aide` for more information).
+///   Instead of a closure, this can be the path of a function
+///   `fn(TransformOperation) -> TransformOperation`, to share the same transform between routes.
+/// - `request_body` documents a request body that can't be inferred from the handler, such as
+///   `axum::extract::Multipart`. The fields of a `multipart` body are documented with their
+///   `JsonSchema`, or as a file if the type is `binary`. Fields of type `Option<T>` are optional.
+///   A `binary` body documents a raw body, such as a `Bytes` or `Body` argument of an upload
+///   endpoint, with the given media types, e.g. `binary("image/png", "image/*")`, or
+///   `application/octet-stream` without them.
+///   A `Form<T>` argument is documented as an `application/x-www-form-urlencoded` body with the
+///   `JsonSchema` of `T`, unless the route has a `request_body`.
+/// - `servers` are the OpenApi servers of this operation, for operations served from a different
+///   host than the rest of the API.
+/// - `extensions` are OpenApi specification extensions of this operation. The names must start
+///   with `x-`, and the values can be any expression implementing `serde::Serialize`.
+/// - `params` documents path- and query-parameters with an `example` (any expression
+///   implementing `serde::Serialize`) and a `description`, e.g.
+///   `params: { id: { example: 42, description: "item id" } }`. Path parameters are added to the
+///   documentation if `aide` didn't document them. With `required`, a query parameter is
+///   documented as required or optional regardless of its type, e.g. an `Option<T>` that the
+///   handler defaults itself, but the API requires. With `deprecated: true`, the parameter is
+///   documented as deprecated while the handler still accepts it, e.g. during a migration.
+/// - `parameters` documents parameter objects explicitly, replacing a parameter `aide` documented
+///   with the same name and location, e.g. a header a gateway injects that the handler doesn't
+///   extract: `parameters: [{ name: "x-request-id", in: header, schema: Uuid, required: true }]`.
+///   The `schema` is the `JsonSchema` of a type, `String` by default, and parameters are optional
+///   unless they're in the path. `params` still applies on top of these.
+/// - `examples` are named pairs of examples of the request body and of the successful response
+///   (with the `status` of the route, or `200`), so documentation UIs show them side by side, e.g.
+///   `examples: { "happy path": { request: json!({ "id": 1 }), response: json!({ "name": "x" }) } }`.
+///   The values can be any expression implementing `serde::Serialize`, and an example may have
+///   only a `request` or only a `response`. They're added to every media type of the body.
+///
+/// # Error responses
+/// A handler returning `Result<T, E>` documents the responses of `T` and the error responses
+/// of `E`, which are declared once per error type by implementing `ErrorResponses`, instead of
+/// with the `responses` of every route. Error types implementing `aide`'s `OperationOutput`
+/// are documented with their inferred responses.
+/// ```ignore
+/// impl ErrorResponses for ApiError {
+///     fn error_responses(docs: &mut ErrorResponseDocs<'_>) {
+///         docs.response::<Json<ErrorBody>>(404, "The item doesn't exist")
+///             .response::<()>(401, "The request isn't authenticated");
+///     }
+/// }
+///
+/// #[api_route(GET "/item/:id")]
+/// async fn get_item(id: u32) -> Result<impl IntoApiResponse, ApiError> {
+///     todo!("handle request")
+/// }
+/// ```
+/// The `Result` is detected by its name, so a type alias such as `ApiResult<T>` isn't.
+///
+/// # Dependencies
+/// The generated parameter structs derive `JsonSchema` with `axum_typed_routing::schemars`,
+/// which is the version `aide` depends on. A crate using `api_route` therefore only needs the
+/// `aide` feature, not a `schemars` dependency of its own; without the feature, `api_route` is a
+/// compile error naming it, instead of a failing expansion.
 ///
 /// # Example
 /// ```
@@ -123,9 +801,37 @@ pub fn route(attr: TokenStream, mut item: TokenStream) -> TokenStream {
 ///     todo!("handle request")
 /// }
 /// ```
+///
+/// # Crate-wide configuration
+/// Defaults for every `api_route` can be set with environment variables, which are read when the
+/// macro is expanded. To use the same configuration for a whole workspace, set them in the
+/// `[env]` section of `.cargo/config.toml`:
+/// ```toml
+/// [env]
+/// AXUM_TYPED_ROUTING_TAGS = "api"
+/// AXUM_TYPED_ROUTING_ID_STRATEGY = "camelCase"
+/// AXUM_TYPED_ROUTING_ID_MODULE_PREFIX = "true"
+/// AXUM_TYPED_ROUTING_DOC_FORMAT = "unwrapped"
+/// AXUM_TYPED_ROUTING_IDE_HELPERS = "false"
+/// ```
+/// - `AXUM_TYPED_ROUTING_TAGS` are comma-separated default `tags`.
+/// - `AXUM_TYPED_ROUTING_ID_STRATEGY`, `AXUM_TYPED_ROUTING_ID_MODULE_PREFIX` and
+///   `AXUM_TYPED_ROUTING_DOC_FORMAT` are the default `id_strategy`, `id_module_prefix` and
+///   `doc_format`.
+/// - `AXUM_TYPED_ROUTING_IDE_HELPERS` disables the IDE helpers for the options when `false`,
+///   which are otherwise generated in debug builds.
+///
+/// The defaults are merged like those of [`macro@api_defaults`], which in turn take precedence
+/// over the crate-wide defaults. The expansion reads the variables with `option_env!`, so Cargo
+/// rebuilds the crate when they change.
+///
+/// The path syntax and the handling of trailing slashes aren't configured here: paths are always
+/// written like `/item/:id`, and trailing slashes can be normalized for the whole router with
+/// `tower-http`'s `NormalizePath`.
+#[allow(clippy::doc_lazy_continuation)]
 #[proc_macro_attribute]
 pub fn api_route(attr: TokenStream, mut item: TokenStream) -> TokenStream {
-    match _route(attr, item.clone(), true) {
+    match _route(attr.into(), item.clone().into(), true, Vec::new()) {
         Ok(tokens) => tokens.into(),
         Err(err) => {
             let err: TokenStream = err.to_compile_error().into();
@@ -135,37 +841,992 @@ pub fn api_route(attr: TokenStream, mut item: TokenStream) -> TokenStream {
     }
 }
 
-fn _route(attr: TokenStream, item: TokenStream, with_aide: bool) -> syn::Result<TokenStream2> {
-    // Parse the route and function
-    let route = syn::parse::<Route>(attr)?;
+/// Same as [`macro@api_route`], but documents the handler as an OpenApi webhook: a request the
+/// API sends to its consumers, such as a notification that an order was placed. A webhook has a
+/// name instead of a path, and is documented under `webhooks` instead of `paths`.
+///
+/// The generated function returns the name and an `ApiMethodRouter`, like an `api_route`, but
+/// isn't routed. Instead, it's added to the documentation with `TypedWebhooks::typed_webhook`,
+/// in the transform of `finish_api_with`, so the schemas of the webhook are added to the
+/// components of the documentation like those of the routes.
+///
+/// # Syntax
+/// ```ignore
+/// #[api_webhook([<METHOD>] "<NAME>" [with <STATE> [where <BOUNDS>]] [{ <OPTIONS> }])]
+/// ```
+/// The method defaults to `POST`. The OpenApi options are those of [`macro@api_route`]; options
+/// that only affect serving a route, such as `cache` or `guards`, have no effect.
+///
+/// # Example
+/// ```
+/// use axum::Json;
+/// use axum_typed_routing_macros::api_webhook;
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct Order {
+///     id: u32,
+/// }
+///
+/// /// An order was placed.
+/// #[api_webhook("order.created")]
+/// async fn order_created(Json(order): Json<Order>) {}
+/// ```
+#[proc_macro_attribute]
+pub fn api_webhook(attr: TokenStream, mut item: TokenStream) -> TokenStream {
+    match _api_webhook(attr.into(), item.clone().into()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => {
+            let err: TokenStream = err.to_compile_error().into();
+            item.extend(err);
+            item
+        }
+    }
+}
+
+/// An `api_webhook` is an `api_route` of `/`, with the name of the webhook as an option.
+fn _api_webhook(attr: TokenStream2, item: TokenStream2) -> syn::Result<TokenStream2> {
+    require_aide("api_webhook")?;
+    let WebhookAttr { method, name, rest } = syn::parse2(attr)?;
+    let attr = insert_options(quote!(#method "/" #rest), &quote!(webhook: #name,));
+    _route(attr, item, true, Vec::new())
+}
+
+/// The OpenApi macros generate code using the `aide` and `schemars` re-exported by
+/// `axum-typed-routing`, so they can't be used without its `aide` feature.
+fn require_aide(macro_name: &str) -> syn::Result<()> {
+    match cfg!(feature = "aide") {
+        true => Ok(()),
+        false => Err(syn::Error::new(
+            Span::call_site(),
+            format!("`{macro_name}` requires the `aide` feature of `axum-typed-routing`"),
+        )),
+    }
+}
+
+/// Declares reusable sets of query parameters, which are added to a route with its `include`
+/// option, so standard parameters such as pagination aren't repeated across routes.
+///
+/// # Syntax
+/// ```ignore
+/// route_params! {
+///     [<VISIBILITY>] <NAME>: "?<PARAM>[:<TYPE>]&.." [{ <PARAM>: { example: <EXPR>, description: <EXPR> }, .. }];
+///     ..
+/// }
+/// ```
+/// - The query parameters are written like those of a route, and are added after its own query
+///   parameters. The handler only needs arguments for the parameters it uses, if they have a
+///   type.
+/// - The documentation of the parameters is the same as the `params` option of
+///   [`macro@api_route`], and is ignored by [`macro@route`]. The `params` of the route take
+///   precedence.
+///
+/// Each mixin is a `macro_rules!` macro, so it must be declared before the routes using it, and
+/// can be imported into other modules with `use` if it's `pub(crate)`. Mixins can't be `pub`.
+///
+/// # Example
+/// ```ignore
+/// route_params! {
+///     /// Pagination of list endpoints.
+///     pub(crate) pagination: "?page:u32&per_page:Option<u32>" {
+///         page: { description: "The page, starting at 0", example: 0 },
+///     };
+/// }
+///
+/// #[route(GET "/items?q" { include: pagination })]
+/// async fn list_items(q: String, page: u32) -> String {
+///     format!("{q} {page}")
+/// }
+/// ```
+#[proc_macro]
+pub fn route_params(input: TokenStream) -> TokenStream {
+    match _route_params(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn _route_params(input: TokenStream2) -> syn::Result<TokenStream2> {
+    let RouteParamsMixins(mixins) = syn::parse2(input)?;
+    let mixins = mixins
+        .into_iter()
+        .map(|mixin| {
+            let (attrs, vis, name, tokens) = (mixin.attrs, mixin.vis, mixin.name, mixin.params.tokens);
+            let export = match &vis {
+                syn::Visibility::Inherited => None,
+                syn::Visibility::Public(_) => Some(
+                    syn::Error::new_spanned(
+                        &vis,
+                        "mixins can't be `pub`, since `macro_rules!` macros can't be re-exported from a crate, use `pub(crate)` instead",
+                    )
+                    .to_compile_error(),
+                ),
+                syn::Visibility::Restricted(_) => Some(quote! {
+                    #[allow(unused_imports)]
+                    #vis use #name;
+                }),
+            };
+            quote! {
+                #(#attrs)*
+                #[allow(unused_macros)]
+                macro_rules! #name {
+                    ($($args:tt)*) => {
+                        ::axum_typed_routing::__private::include_route_params! {
+                            { #tokens } $($args)*
+                        }
+                    };
+                }
+                #export
+            }
+        });
+    Ok(quote!(#(#mixins)*))
+}
+
+/// Expands a route after the mixins of its `include` option passed their params back to it.
+/// Used by the macros of [`route_params!`]. Not public API.
+#[doc(hidden)]
+#[proc_macro]
+pub fn include_route_params(input: TokenStream) -> TokenStream {
+    let IncludeRouteParams {
+        included,
+        with_aide,
+        attr,
+        item,
+    } = parse_macro_input!(input);
+    match _route(attr, item.clone(), with_aide.value(), included) {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            let err = err.to_compile_error();
+            quote!(#item #err)
+        }
+    }
+    .into()
+}
+
+/// Generates a module `routes` with a constant for each of the given routes, so code that isn't
+/// a handler (e.g. email templates and background jobs) can reference routes symbolically.
+///
+/// # Syntax
+/// ```ignore
+/// route_index! {
+///     <HANDLER>,
+///     <NAME> = <HANDLER>,
+///     ..
+/// }
+/// ```
+/// - `HANDLER` is the path of a handler of [`macro@route`] or [`macro@api_route`], relative to
+///   the module of the macro, e.g. `items::get_item`.
+/// - `NAME` is the name of the constant, which is the name of the handler in uppercase if not
+///   specified, e.g. `GET_ITEM`. Handlers with the same name in different modules need one.
+///
+/// Each constant is the `axum_typed_routing::RouteInfo` of the route, with its method, path and
+/// handler, which is the constant `ROUTE` in the [module](macro@route#route-name) of the
+/// handler, so the handlers need the `module` option unless a feature generates the module.
+/// `routes::ALL` lists all of them. Routes with a `base` don't have a constant path, and routes
+/// `in_impl` have no module, so they can't be listed. The routes have to be listed here, since a macro can't see
+/// the routes of a crate.
+///
+/// The module also has an enum `routes::OperationId` with a variant for each route, in
+/// PascalCase, e.g. `GetItem`, so that authorization layers and permission maps can match on
+/// the routes exhaustively: a route added to the index can't be forgotten. It has the methods
+/// `as_str()` (the operation id of an [`macro@api_route`], or the name of the handler for a
+/// route without one), `path()`, `method()` and `route()`, the constant `OperationId::ALL`,
+/// and `OperationId::find(method, path)` to look up the route of a request by its method and
+/// `MatchedPath`.
+///
+/// # Example
+/// ```ignore
+/// route_index! {
+///     get_item,
+///     ITEM_PAGE = pages::get_item,
+/// }
+///
+/// let link = format!("https://example.com{}", routes::ITEM_PAGE.path);
+///
+/// fn required_role(route: routes::OperationId) -> Role {
+///     match route {
+///         routes::OperationId::GetItem => Role::Reader,
+///         routes::OperationId::ItemPage => Role::Guest,
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn route_index(input: TokenStream) -> TokenStream {
+    let RouteIndex(routes) = parse_macro_input!(input);
+    let (names, handlers): (Vec<_>, Vec<_>) = routes
+        .into_iter()
+        .map(|(name, mut handler)| {
+            // The handlers are relative to the module of the macro, which is the parent of
+            // `routes`.
+            let first = &mut handler.segments[0].ident;
+            if handler.leading_colon.is_none() && first == "self" {
+                *first = Ident::new("super", first.span());
+            } else if handler.leading_colon.is_none() && first == "super" {
+                handler.segments.insert(0, parse_quote!(super));
+            }
+            (name, handler)
+        })
+        .unzip();
+    let variants = names
+        .iter()
+        .map(|name| {
+            let variant = IdStrategy::PascalCase.apply(&name.to_string());
+            Ident::new(&variant, name.span())
+        })
+        .collect::<Vec<_>>();
+    quote! {
+        /// The routes of `route_index!`.
+        pub mod routes {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #(
+                pub const #names: ::axum_typed_routing::RouteInfo = #handlers::ROUTE;
+            )*
+
+            /// All routes of this module.
+            pub const ALL: &[::axum_typed_routing::RouteInfo] = &[#(#names),*];
+
+            /// The routes of this module, e.g. to match on them exhaustively.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub enum OperationId {
+                #(
+                    #[doc = ::core::concat!("The route `", ::core::stringify!(#names), "`.")]
+                    #variants,
+                )*
+            }
+
+            impl OperationId {
+                /// All variants, in the order of `route_index!`.
+                pub const ALL: &[Self] = &[#(Self::#variants),*];
+
+                /// The operation id of the route, or the name of its handler if it has none.
+                pub fn as_str(self) -> &'static str {
+                    match self {
+                        #(Self::#variants => #handlers::operation_id().unwrap_or(#handlers::NAME),)*
+                    }
+                }
+
+                /// The method, path and handler of the route.
+                pub const fn route(self) -> ::axum_typed_routing::RouteInfo {
+                    match self {
+                        #(Self::#variants => #names,)*
+                    }
+                }
+
+                /// The path of the route, e.g. `/items/{id}`.
+                pub const fn path(self) -> &'static str {
+                    self.route().path
+                }
+
+                /// The method of the route, e.g. `GET`.
+                pub const fn method(self) -> &'static str {
+                    self.route().method
+                }
+
+                /// The route with the given method and path, e.g. the `MatchedPath` of a request.
+                pub fn find(method: &str, path: &str) -> ::core::option::Option<Self> {
+                    Self::ALL
+                        .iter()
+                        .copied()
+                        .find(|id| id.method() == method && id.path() == path)
+                }
+            }
+
+            impl ::core::fmt::Display for OperationId {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// Same as [`macro@route`], but for a function returning a `tower::Service`, such as `ServeDir`,
+/// instead of a handler.
+///
+/// # Syntax
+/// ```ignore
+/// #[route_service(<METHOD> "<PATH>" [with <STATE>])]
+/// ```
+/// The path may contain path parameters to match the request, but these aren't extracted. Query
+/// parameters and options are not supported. Without `with <STATE>`, the route can be added to
+/// routers with any state.
+///
+/// # Example
+/// ```ignore
+/// #[route_service(GET "/assets/*path")]
+/// fn assets() -> ServeDir {
+///     ServeDir::new("assets")
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn route_service(attr: TokenStream, mut item: TokenStream) -> TokenStream {
+    match _route_service(attr, item.clone()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => {
+            let err: TokenStream = err.to_compile_error().into();
+            item.extend(err);
+            item
+        }
+    }
+}
+
+fn _route_service(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream2> {
+    let route = parse_unextracted_route(attr.into(), "route_service")?;
     let function = syn::parse::<ItemFn>(item)?;
 
+    if !function.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &function.sig.generics,
+            "`route_service` functions can't be generic",
+        ));
+    }
+
+    let axum_path = compilation::axum_path_string(&route.path_params);
+    let method_name = route.method.name();
+    let fn_name = &function.sig.ident;
+    let method_service = route.method.method_service(quote!(#fn_name()));
+    let fn_name_str = fn_name.to_string();
+    let vis = &function.vis;
+    let fn_docs = function
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"));
+    let (generics, state_type) = unextracted_state(&route);
+
+    Ok(quote! {
+        #(#fn_docs)*
+        #vis fn #fn_name #generics() -> (
+            &'static str,
+            ::axum::routing::MethodRouter<#state_type>,
+            ::std::boxed::Box<dyn ::core::any::Any>,
+        ) {
+            #function
+
+            let description = (
+                #method_name,
+                ::core::concat!(::core::module_path!(), "::", #fn_name_str),
+            );
+            (#axum_path, #method_service, ::std::boxed::Box::new(description))
+        }
+    })
+}
+
+/// Routes a GraphQL endpoint for a schema, which is taken from the state of the router with
+/// `FromRef`, so GraphQL and REST routes can be served by the same typed router.
+///
+/// # Syntax
+/// ```ignore
+/// #[graphql_route("<PATH>" [with <STATE>])]
+/// [<VISIBILITY>] type <SCHEMA> = <TYPE>;
+/// ```
+/// The macro generates a function named after the type alias in snake_case, e.g. `items_schema`
+/// for `ItemsSchema`, which returns the path and the method router of the endpoint, like a
+/// [`macro@route`]:
+/// - `POST` executes the `GraphQLRequest` in its JSON body, and responds with the
+///   `GraphQLResponse` as JSON.
+/// - `GET` serves the GraphiQL IDE, which sends its requests to the same path.
+///
+/// Without `with <STATE>`, the state of the route is the schema itself. The schema must
+/// implement `axum_typed_routing::GraphQLSchema`, which is implemented for the `Schema` of
+/// `async-graphql` with the `async-graphql` feature, and for an `Arc<RootNode>` of `juniper`
+/// with the `juniper` feature, creating its context from the state with `FromRef`. Like with
+/// [`macro@route_service`], query parameters and options are not supported.
+///
+/// # Example
+/// ```ignore
+/// #[graphql_route("/graphql" with AppState)]
+/// type ItemsSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+///
+/// let router = Router::new()
+///     .typed_route(items_schema)
+///     .with_state(AppState { schema, db });
+/// ```
+#[proc_macro_attribute]
+pub fn graphql_route(attr: TokenStream, mut item: TokenStream) -> TokenStream {
+    match _graphql_route(attr, item.clone(), false) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => {
+            let err: TokenStream = err.to_compile_error().into();
+            item.extend(err);
+            item
+        }
+    }
+}
+
+/// Same as [`macro@graphql_route`], but returns an `ApiMethodRouter` to be added with
+/// `typed_api_route`, like an [`macro@api_route`].
+///
+/// The `POST` is documented with the request and response schemas, the name of the generated
+/// function as its operation id, and the doc-comments of the type alias as its summary and
+/// description. The `GET` is documented as `<ID>_graphiql`, responding with HTML.
+///
+/// # Example
+/// ```ignore
+/// /// The items and their stock.
+/// #[api_graphql_route("/graphql" with AppState)]
+/// type ItemsSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+/// ```
+#[proc_macro_attribute]
+pub fn api_graphql_route(attr: TokenStream, mut item: TokenStream) -> TokenStream {
+    match _graphql_route(attr, item.clone(), true) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => {
+            let err: TokenStream = err.to_compile_error().into();
+            item.extend(err);
+            item
+        }
+    }
+}
+
+fn _graphql_route(
+    attr: TokenStream,
+    item: TokenStream,
+    with_aide: bool,
+) -> syn::Result<TokenStream2> {
+    let macro_name = match with_aide {
+        true => "api_graphql_route",
+        false => "graphql_route",
+    };
+    if !cfg!(feature = "graphql") {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!("`{macro_name}` requires the `async-graphql`, `juniper` or `graphql` feature of `axum-typed-routing`"),
+        ));
+    }
+    if with_aide {
+        require_aide(macro_name)?;
+    }
+    let attr = TokenStream2::from(attr);
+    let route = parse_unextracted_route(quote!(POST #attr), macro_name)?;
+    let alias = syn::parse::<ItemType>(item)?;
+
+    if !alias.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &alias.generics,
+            format!("`{macro_name}` schemas can't be generic"),
+        ));
+    }
+
+    let axum_path = compilation::axum_path_string(&route.path_params);
+    let schema = &alias.ident;
+    let fn_name = format_ident!(
+        "{}",
+        IdStrategy::SnakeCase.apply(&schema.to_string()),
+        span = schema.span()
+    );
+    let fn_name_str = fn_name.to_string();
+    let vis = &alias.vis;
+    let state_type = match &route.state {
+        Some(state) => quote!(#state),
+        None => quote!(#schema),
+    };
+    let (method_router_type, method_router) = match with_aide {
+        true => {
+            let docs = alias
+                .attrs
+                .iter()
+                .filter_map(|attr| match &attr.meta {
+                    Meta::NameValue(meta) if meta.path.is_ident("doc") => Some(&meta.value),
+                    _ => None,
+                })
+                .filter_map(|value| match value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit), ..
+                    }) => Some(lit.value()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let (summary, description) = DocFormat::default().apply(&docs);
+            let summary = match summary {
+                Some(summary) => quote!(::core::option::Option::Some(#summary)),
+                None => quote!(::core::option::Option::None),
+            };
+            let description = match description {
+                Some(description) => quote!(::core::option::Option::Some(#description)),
+                None => quote!(::core::option::Option::None),
+            };
+            (
+                quote!(::axum_typed_routing::aide::axum::routing::ApiMethodRouter),
+                quote! {
+                    ::axum_typed_routing::__private::api_graphql_method_router::<#schema, #state_type>(
+                        #fn_name_str,
+                        #summary,
+                        #description,
+                    )
+                },
+            )
+        }
+        false => (
+            quote!(::axum::routing::MethodRouter),
+            quote!(::axum_typed_routing::__private::graphql_method_router::<#schema, #state_type>()),
+        ),
+    };
+    let fn_doc = format!("The GraphQL endpoint of [`{schema}`].");
+
+    Ok(quote! {
+        #alias
+
+        #[doc = #fn_doc]
+        #vis fn #fn_name() -> (
+            &'static str,
+            #method_router_type<#state_type>,
+            ::std::boxed::Box<dyn ::core::any::Any>,
+        ) {
+            let description = ("POST", ::core::concat!(::core::module_path!(), "::", #fn_name_str));
+            (#axum_path, #method_router, ::std::boxed::Box::new(description))
+        }
+    })
+}
+
+/// A function-like version of [`macro@route`] for small handlers, such as health checks, that
+/// don't need a named function.
+///
+/// # Syntax
+/// ```ignore
+/// inline_route!(<METHOD> "<PATH>" [with <STATE>] => <HANDLER>)
+/// ```
+/// This evaluates to a typed handler, which can be added to a router with `typed_route`. The
+/// `HANDLER` can be any axum handler, usually a closure, but it can't capture local variables.
+/// Like with [`macro@route_service`], path parameters aren't extracted, and query parameters and
+/// options are not supported.
+///
+/// # Example
+/// ```ignore
+/// let router = Router::new()
+///     .typed_route(inline_route!(GET "/ping" => || async { "pong" }));
+/// ```
+#[proc_macro]
+pub fn inline_route(input: TokenStream) -> TokenStream {
+    match _inline_route(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn _inline_route(input: TokenStream) -> syn::Result<TokenStream2> {
+    let trees = TokenStream2::from(input).into_iter().collect::<Vec<_>>();
+    let arrow = trees.windows(2).position(|trees| match trees {
+        [TokenTree::Punct(eq), TokenTree::Punct(gt)] => {
+            eq.as_char() == '=' && eq.spacing() == Spacing::Joint && gt.as_char() == '>'
+        }
+        _ => false,
+    });
+    let Some(arrow) = arrow else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "expected `<METHOD> \"<PATH>\" => <HANDLER>`",
+        ));
+    };
+    let route = trees[..arrow].iter().cloned().collect::<TokenStream2>();
+    let route = parse_unextracted_route(route, "inline_route")?;
+    let handler = syn::parse2::<Expr>(trees[arrow + 2..].iter().cloned().collect())?;
+
+    let axum_path = compilation::axum_path_string(&route.path_params);
+    let method_name = route.method.name();
+    let method_router = route.method.method_router(quote!(#handler));
+    let (generics, state_type) = unextracted_state(&route);
+
+    Ok(quote! {{
+        fn __inline_route__ #generics() -> (
+            &'static str,
+            ::axum::routing::MethodRouter<#state_type>,
+            ::std::boxed::Box<dyn ::core::any::Any>,
+        ) {
+            let description = (#method_name, ::core::concat!(::core::module_path!(), "::inline_route!"));
+            (#axum_path, #method_router, ::std::boxed::Box::new(description))
+        }
+        __inline_route__
+    }})
+}
+
+/// Parses the route of [`macro@route_service`] and [`macro@inline_route`], which don't extract
+/// any parameters, and therefore don't support query parameters or options.
+fn parse_unextracted_route(tokens: TokenStream2, macro_name: &str) -> syn::Result<Route> {
+    let has_options = tokens.clone().into_iter().any(
+        |tree| matches!(tree, TokenTree::Group(group) if group.delimiter() == Delimiter::Brace),
+    );
+    let route = syn::parse2::<Route>(tokens)?;
+
+    if has_options {
+        return Err(syn::Error::new(
+            route.route_lit.span(),
+            format!("`{macro_name}` doesn't support options"),
+        ));
+    }
+    if let Some((ident, _ty)) = route.query_params.first() {
+        return Err(syn::Error::new(
+            ident.span(),
+            format!("`{macro_name}` doesn't support query parameters"),
+        ));
+    }
+    if let Some((multipart, _fields)) = &route.multipart {
+        return Err(syn::Error::new(
+            multipart.span,
+            format!("`{macro_name}` doesn't support `multipart`"),
+        ));
+    }
+    if let Some((inject, _args)) = &route.inject {
+        return Err(syn::Error::new(
+            inject.span,
+            format!("`{macro_name}` doesn't support `inject`"),
+        ));
+    }
+    if let Some(pattern) = route.path_patterns.first() {
+        return Err(syn::Error::new(
+            pattern.pattern.span(),
+            format!("`{macro_name}` doesn't support the patterns of path parameters"),
+        ));
+    }
+    if let Some(bounds) = &route.state_bounds {
+        return Err(syn::Error::new_spanned(
+            bounds,
+            format!("`{macro_name}` doesn't support a `where` clause, without `with <STATE>` it is generic over the state"),
+        ));
+    }
+    Ok(route)
+}
+
+/// The generics and state type of a route without a state extractor. Without `with <STATE>`,
+/// the route is generic over the state.
+fn unextracted_state(route: &Route) -> (TokenStream2, TokenStream2) {
+    match &route.state {
+        Some(state) => (quote!(), quote!(#state)),
+        None => (
+            quote!(<__S__: ::core::clone::Clone + ::core::marker::Send + ::core::marker::Sync + 'static>),
+            quote!(__S__),
+        ),
+    }
+}
+
+/// Shared OpenApi options for every [`macro@api_route`] in a module.
+///
+/// # Syntax
+/// ```ignore
+/// #[api_defaults(
+///     tags: ["<TAG>", ..],
+///     security: { <SCHEME>: [<SCOPE>, ..], .. },
+///     id_strategy: raw | snake_case | camelCase | PascalCase,
+///     id_module_prefix: <bool>,
+///     doc_format: raw | trimmed | unwrapped,
+/// )]
+/// mod <MODULE> { .. }
+/// ```
+/// The options are merged into the options of every `api_route` inside the (inline) module,
+/// including nested modules: the tags and security requirements of the route are added to the
+/// defaults, and the `id_strategy`, `id_module_prefix` and `doc_format` of the route override
+/// the defaults.
+///
+/// # Example
+/// ```ignore
+/// #[api_defaults(tags: ["items"], security: { "bearer": [] })]
+/// mod items {
+///     #[api_route(GET "/item/:id" { tags: ["public"] })]
+///     async fn item_handler(id: u32) -> String {
+///         todo!("handle request")
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn api_defaults(attr: TokenStream, mut item: TokenStream) -> TokenStream {
+    match _api_defaults(attr, item.clone()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => {
+            let err: TokenStream = err.to_compile_error().into();
+            item.extend(err);
+            item
+        }
+    }
+}
+
+fn _api_defaults(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream2> {
+    require_aide("api_defaults")?;
+    let ApiDefaults(mut defaults) = syn::parse::<ApiDefaults>(attr)?;
+    let mut module = syn::parse::<ItemMod>(item)?;
+    if module.content.is_none() {
+        return Err(syn::Error::new(
+            module.ident.span(),
+            "`api_defaults` can only be used on inline modules",
+        ));
+    }
+    if !defaults.is_empty() && !defaults.to_string().ends_with(',') {
+        defaults.extend(quote!(,));
+    }
+
+    insert_api_defaults(&mut module, &defaults);
+    Ok(quote!(#module))
+}
+
+/// Inserts `defaults` in the options block of every `api_route` attribute in the module.
+fn insert_api_defaults(module: &mut ItemMod, defaults: &TokenStream2) {
+    let Some((_brace, items)) = &mut module.content else {
+        return;
+    };
+    for item in items {
+        match item {
+            Item::Fn(function) => {
+                for attr in &mut function.attrs {
+                    let Meta::List(list) = &mut attr.meta else {
+                        continue;
+                    };
+                    let is_api_route = list
+                        .path
+                        .segments
+                        .last()
+                        .is_some_and(|segment| segment.ident == "api_route");
+                    if is_api_route {
+                        list.tokens = insert_options(list.tokens.clone(), defaults);
+                    }
+                }
+            }
+            Item::Mod(module) => insert_api_defaults(module, defaults),
+            _ => {}
+        }
+    }
+}
+
+/// Inserts `options` at the start of the options block of the route, creating it if needed.
+fn insert_options(route: TokenStream2, options: &TokenStream2) -> TokenStream2 {
+    let mut trees = route.into_iter().collect::<Vec<_>>();
+    match trees.last_mut() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            let stream = group.stream();
+            let mut new_group = Group::new(Delimiter::Brace, quote!(#options #stream));
+            new_group.set_span(group.span());
+            *group = new_group;
+        }
+        _ => trees.push(TokenTree::Group(Group::new(
+            Delimiter::Brace,
+            options.clone(),
+        ))),
+    }
+    trees.into_iter().collect()
+}
+
+/// Expands a route, with the params of the `route_params!` mixins that were `included` so far,
+/// in reverse order.
+fn _route(
+    attr: TokenStream2,
+    item: TokenStream2,
+    with_aide: bool,
+    mut included: Vec<IncludedParams>,
+) -> syn::Result<TokenStream2> {
+    if with_aide {
+        require_aide("api_route")?;
+    }
+
+    // Parse the route and function, with the crate-wide defaults of `api_route`
+    let config = config::Config::from_env()?;
+    let mut route_attr = attr.clone();
+    if with_aide && !config.api_defaults.is_empty() {
+        route_attr = insert_options(route_attr, &config.api_defaults);
+    }
+    let mut route = syn::parse2::<Route>(route_attr)?;
+
+    // The mixins are `macro_rules!` macros, which pass their params back to
+    // `include_route_params!`, one after another.
+    if let Some((_ident, Includes(includes))) = &route.options.include {
+        if let Some(mixin) = includes.get(included.len()) {
+            let included = included.iter().map(|params| &params.tokens);
+            return Ok(quote! {
+                #mixin! {
+                    #({ #included })* #with_aide (#attr) #item
+                }
+            });
+        }
+        included.reverse();
+        route.include_params(included, with_aide)?;
+    }
+    route.paginate(with_aide)?;
+    let mut function = syn::parse2::<ItemFn>(item)?;
+
     // Now we can compile the route
-    let route = CompiledRoute::from_route(route, &function, with_aide)?;
+    let route = CompiledRoute::from_route(route, &mut function, with_aide)?;
     let path_extractor = route.path_extractor();
     let query_extractor = route.query_extractor();
+    let accept_extractor = route.accept_extractor();
+    let cache_extractor = route.cache_extractor();
+    let idempotency_extractor = route.idempotency_extractor();
+    let feature_extractor = route.feature_extractor();
+    let feature_flag_struct = route.feature_flag_struct();
+    let multipart_extractor = route.multipart_extractor();
+    let multipart_struct = route.multipart_struct(with_aide);
+    let debug_handler = route.debug_handler();
     let query_params_struct = route.query_params_struct(with_aide);
+    let path_params_structs = route.path_params_structs(with_aide);
     let state_type = &route.state;
-    let axum_path = route.to_axum_path_string();
+    // A webhook isn't routed, so it isn't registered or given a module like a route.
+    let webhook = route.webhook().cloned();
+    let path = match &webhook {
+        Some(name) => quote!(#name),
+        None => route.path_expr(),
+    };
     let http_method = route.method.to_axum_method_name();
-    let remaining_numbered_pats = route.remaining_pattypes_numbered(&function.sig.inputs);
+    // A webhook returns only its name and method router.
+    let (description_ty, description) = match webhook {
+        Some(_) => (quote!(), quote!()),
+        None => (
+            quote!(, ::std::boxed::Box<dyn ::core::any::Any>),
+            route.route_description(&function.sig.ident)?,
+        ),
+    };
+    let param_assertions = route.param_assertions();
+    let mut remaining_pats = route.remaining_pattypes(&function.sig.ident, &function.sig.inputs);
+    // The multipart extractor follows the other arguments.
+    if multipart_extractor.is_some() && !remaining_pats.is_empty() {
+        remaining_pats.push_punct(Comma::default());
+    }
     let extracted_idents = route.extracted_idents();
-    let remaining_numbered_idents = remaining_numbered_pats.iter().map(|pat_type| &pat_type.pat);
+    let remaining_args = route.remaining_args(&function.sig.inputs, &remaining_pats);
     let route_docs = route.to_doc_comments();
+    // Modules, `inventory` registrations and exported macros can't be put in an `impl` block.
+    let in_impl = route.in_impl();
+    let routed = !in_impl && webhook.is_none();
+    let ts_route = route.ts_route(&function).filter(|_| routed);
+    let unique_operation_ids = route
+        .unique_operation_ids(&function.sig.ident)
+        .filter(|_| routed);
 
     // Get the variables we need for code generation
     let fn_name = &function.sig.ident;
-    let fn_output = &function.sig.output;
-    let vis = &function.vis;
+    let route_fn_name = route.route_fn_name(fn_name);
+    let (kept_function, nested_function) = match route.keep_fn() {
+        true => (Some(&function), None),
+        false => (None, Some(&function)),
+    };
+    let (vis, module_vis) = route.route_fn_vis(&function.vis);
     let asyncness = &function.sig.asyncness;
     let (impl_generics, ty_generics, where_clause) = &function.sig.generics.split_for_impl();
     let ty_generics = ty_generics.as_turbofish();
+    // In an `impl` block, the inner function is a closure, since a nested function can't use
+    // the generics of the `impl` or call `Self`.
+    let (inner_fn, handler_path) = match in_impl {
+        true => (quote!(__inner__function__), quote!(Self::#fn_name)),
+        false => (quote!(__inner__function__ #ty_generics), quote!(#fn_name)),
+    };
+    let extractor_assertions = route.extractor_assertions(&remaining_pats, inner_fn.clone());
+    let handler_call = quote! {
+        #handler_path #ty_generics(#(#extracted_idents,)* #(#remaining_args,)* )
+    };
+    let (inner_fn_output, inner_fn_value) = route.inner_output(
+        fn_name,
+        &function.sig.output,
+        route.hooked_call(quote!(#handler_call.await)),
+    );
+    // Without anything to do after the handler, the inner function returns its future, so a
+    // request isn't handled by a future that only awaits another one.
+    let forwards_future = asyncness.is_some() && route.forwards_future(&function.sig.output);
+    let handler_output = match &function.sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_arrow, ty) => quote!(#ty),
+    };
+    // With `erased`, the future of the handler is boxed, so it isn't nested in the futures of
+    // axum for every instantiation of a generic handler.
+    let erased = route.erased();
+    let erased_future = quote! {
+        ::std::boxed::Box::pin(async move {
+            ::axum::response::IntoResponse::into_response(#inner_fn_value)
+        })
+    };
+    let inner_function = match (in_impl, erased) {
+        (true, false) => quote! {
+            let __inner__function__ = |
+                #feature_extractor
+                #path_extractor
+                #query_extractor
+                #accept_extractor
+                #cache_extractor
+                #idempotency_extractor
+                #remaining_pats
+                #multipart_extractor
+            | async move { #inner_fn_value };
+        },
+        (true, true) => quote! {
+            let __inner__function__ = |
+                #feature_extractor
+                #path_extractor
+                #query_extractor
+                #accept_extractor
+                #cache_extractor
+                #idempotency_extractor
+                #remaining_pats
+                #multipart_extractor
+            | -> ::axum_typed_routing::__private::ErasedFuture { #erased_future };
+        },
+        (false, true) => quote! {
+            fn __inner__function__ #impl_generics(
+                #feature_extractor
+                #path_extractor
+                #query_extractor
+                #accept_extractor
+                #cache_extractor
+                #idempotency_extractor
+                #remaining_pats
+                #multipart_extractor
+            ) -> ::axum_typed_routing::__private::ErasedFuture #where_clause {
+                #nested_function
+
+                #erased_future
+            }
+        },
+        (false, false) if forwards_future => quote! {
+            fn __inner__function__ #impl_generics(
+                #feature_extractor
+                #path_extractor
+                #query_extractor
+                #accept_extractor
+                #cache_extractor
+                #idempotency_extractor
+                #remaining_pats
+                #multipart_extractor
+            ) -> impl ::core::future::Future<Output = #handler_output> #where_clause {
+                #nested_function
+
+                #handler_call
+            }
+        },
+        (false, false) => quote! {
+            #debug_handler
+            #asyncness fn __inner__function__ #impl_generics(
+                #feature_extractor
+                #path_extractor
+                #query_extractor
+                #accept_extractor
+                #cache_extractor
+                #idempotency_extractor
+                #remaining_pats
+                #multipart_extractor
+            ) #inner_fn_output #where_clause {
+                #nested_function
+
+                #inner_fn_value
+            }
+        },
+    };
     let fn_docs = function
         .attrs
         .iter()
         .filter(|attr| attr.path().is_ident("doc"));
 
-    let (aide_ident_docs, inner_fn_call, method_router_ty) = if with_aide {
+    let guard_layers = route.guard_layers();
+    let deprecation_layer = route.deprecation_layer();
+    let cache_layer = route.cache_layer();
+    let compression_layer = route.compression_layer();
+    let cors_layer = route.cors_layer();
+    let cors_policy = route.webhook().is_none().then(|| route.cors_policy());
+    let deadline_layer = route.deadline_layer();
+    let moved_to_path = route.moved_to_path();
+    let moved_to_layer = route.moved_to_layer();
+    let deadline_policy = route.webhook().is_none().then(|| route.deadline_policy());
+    let path_patterns = route.path_patterns();
+    let path_pattern_layer = route.path_pattern_layer();
+    let locale_layer = route.locale_layer();
+    let matched_route_layer = route.matched_route_layer(&function.sig.ident);
+
+    let (route_fn_items, inner_fn_call, method_router_ty) = if with_aide {
         let http_method = format_ident!("{}_with", http_method);
         let summary = route
             .get_oapi_summary()
@@ -177,67 +1838,185 @@ fn _route(attr: TokenStream, item: TokenStream, with_aide: bool) -> syn::Result<
             .get_oapi_hidden()
             .map(|hidden| quote! { .hidden(#hidden) });
         let tags = route.get_oapi_tags();
-        let id = route
-            .get_oapi_id(&function.sig)
-            .map(|id| quote! { .id(#id) });
+        let id = route.get_oapi_id().map(|id| quote! { .id(#id) });
         let transform = route.get_oapi_transform()?;
+        let request_body = route.get_oapi_request_body();
+        let flattened_query = route.get_oapi_flattened_query();
+        let servers = route.get_oapi_servers();
+        let extensions = route.get_oapi_extensions();
+        let examples = route.get_oapi_examples();
+        let deprecated = route.get_oapi_deprecated();
+        let permissions = route.get_oapi_permissions();
+        let api_doc = route.get_oapi_api_doc();
+        let deadline = route.get_oapi_deadline();
+        let moved_to = route.get_oapi_moved_to();
+        let path_pattern_docs = route.get_oapi_path_patterns();
+        let params = route.get_oapi_params()?;
+        let parameters = route.get_oapi_parameters()?;
+        let query_styles = route.get_oapi_query_styles();
+        let media_types = route.get_oapi_media_types();
+        let status_response = route.get_oapi_status_response(&function.sig.output);
         let responses = route.get_oapi_responses();
-        let response_code = responses.iter().map(|response| &response.0);
-        let response_type = responses.iter().map(|response| &response.1);
+        let clear_responses = route.get_oapi_clear_responses();
         let security = route.get_oapi_security();
         let schemes = security.iter().map(|sec| &sec.0);
         let scopes = security.iter().map(|sec| &sec.1);
 
+        let operation_docs = quote! {
+            #clear_responses
+            let __op__ = __op__
+                #summary
+                #description
+                #hidden
+                #id
+                #(.tag(#tags))*
+                #(.security_requirement_scopes::<::std::vec::Vec<&str>, &str>(
+                    ::core::convert::AsRef::<str>::as_ref(&#schemes),
+                    ::std::vec![#(::core::convert::AsRef::<str>::as_ref(&#scopes)),*],
+                ))*
+                #status_response
+                #(#responses)*
+                ;
+            #media_types
+            #flattened_query
+            #parameters
+            #params
+            #path_pattern_docs
+            #query_styles
+            #request_body
+            #examples
+            #servers
+            #permissions
+            #api_doc
+            #extensions
+            #deprecated
+            #deadline
+            #moved_to
+            #transform
+        };
+        let head = route.auto_head().then(|| {
+            quote! {
+                .head_with(
+                    #inner_fn,
+                    |__op__| {
+                        #operation_docs
+                        ::axum_typed_routing::__private::head_operation(__op__)
+                    }
+                )
+            }
+        });
+
+        let layers = webhook.is_none().then(|| {
+            quote! {
+                #head
+                #guard_layers
+                #path_pattern_layer
+                #moved_to_layer
+                #deadline_layer
+                #deprecation_layer
+                #cache_layer
+                #compression_layer
+                #cors_layer
+                #locale_layer
+                #matched_route_layer
+            }
+        });
+
+        let ide_helpers = match config.ide_helpers {
+            true => route.ide_documentation_for_aide_methods(),
+            false => quote!(),
+        };
+
         (
-            route.ide_documentation_for_aide_methods(),
+            ide_helpers,
             quote! {
-                ::aide::axum::routing::#http_method(
-                    __inner__function__ #ty_generics,
+                ::axum_typed_routing::aide::axum::routing::#http_method(
+                    #inner_fn,
                     |__op__| {
-                        let __op__ = __op__
-                            #summary
-                            #description
-                            #hidden
-                            #id
-                            #(.tag(#tags))*
-                            #(.security_requirement_scopes::<Vec<&'static str>, _>(#schemes, vec![#(#scopes),*]))*
-                            #(.response::<#response_code, #response_type>())*
-                            ;
-                        #transform
+                        #operation_docs
                         __op__
                     }
                 )
+                #layers
+            },
+            quote! { ::axum_typed_routing::aide::axum::routing::ApiMethodRouter },
+        )
+    } else if erased {
+        let head = route.auto_head().then(|| quote! { .head(#inner_fn) });
+        let method_router = route.method.method_router(inner_fn.clone());
+        let layers = route.erased_layers();
+        (
+            layers,
+            quote! {
+                ::axum_typed_routing::__private::erased_layers(
+                    #method_router #head #guard_layers #path_pattern_layer #moved_to_layer,
+                    &__LAYERS__,
+                )
+                #matched_route_layer
             },
-            quote! { ::aide::axum::routing::ApiMethodRouter },
+            quote! { ::axum::routing::MethodRouter },
         )
     } else {
+        let head = route.auto_head().then(|| quote! { .head(#inner_fn) });
+        let method_router = route.method.method_router(inner_fn.clone());
         (
             quote!(),
-            quote! { ::axum::routing::#http_method(__inner__function__ #ty_generics) },
+            quote! {
+                #method_router
+                #head
+                #guard_layers
+                #path_pattern_layer
+                #moved_to_layer
+                #deadline_layer
+                #deprecation_layer
+                #cache_layer
+                #compression_layer
+                #cors_layer
+                #locale_layer
+                #matched_route_layer
+            },
             quote! { ::axum::routing::MethodRouter },
         )
     };
 
     // Generate the code
-    Ok(quote! {
+    let env_dependencies = with_aide.then(config::env_dependencies);
+    let route_fn = quote! {
         #(#fn_docs)*
         #route_docs
-        #vis fn #fn_name #impl_generics() -> (&'static str, #method_router_ty<#state_type>) #where_clause {
+        #vis fn #route_fn_name #impl_generics() -> (&'static str, #method_router_ty<#state_type> #description_ty) #where_clause {
 
             #query_params_struct
+            #path_params_structs
+            #feature_flag_struct
+            #multipart_struct
+            #cors_policy
+            #deadline_policy
+            #path_patterns
+            #env_dependencies
 
-            #aide_ident_docs
-            #asyncness fn __inner__function__ #impl_generics(
-                #path_extractor
-                #query_extractor
-                #remaining_numbered_pats
-            ) #fn_output #where_clause {
-                #function
-
-                #fn_name #ty_generics(#(#extracted_idents,)* #(#remaining_numbered_idents,)* ).await
-            }
+            #route_fn_items
+            #inner_function
 
-            (#axum_path, #inner_fn_call)
+            #param_assertions
+            #extractor_assertions
+            #moved_to_path
+            (#path, #inner_fn_call, #description)
         }
+    };
+    let (route_fn, module_route_fn) = match route.in_module() {
+        true => (None, Some(route_fn)),
+        false => (Some(route_fn), None),
+    };
+    let route_module = (routed && route.has_module())
+        .then(|| route.route_module(&module_vis, fn_name, module_route_fn));
+    Ok(quote! {
+        #kept_function
+
+        #route_fn
+
+        #route_module
+        #ts_route
+        #unique_operation_ids
     })
 }
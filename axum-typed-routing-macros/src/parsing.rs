@@ -1,83 +1,475 @@
+#![allow(clippy::to_string_trait_impl)]
+
 use core::panic;
 
-use quote::ToTokens;
+use quote::{quote, ToTokens};
 use syn::{
-    token::{Brace, Star},
-    Attribute, Expr, ExprClosure, Lit, LitBool, LitInt,
+    spanned::Spanned,
+    token::{Brace, Paren, Star},
+    Attribute, Expr, ExprClosure, ExprLit, ExprMacro, Lit, LitBool, LitFloat, LitInt, Visibility,
+    WhereClause,
 };
 
 use super::*;
 
 struct RouteParser {
     path_params: Vec<(Slash, PathParam)>,
-    query_params: Vec<Ident>,
+    query_params: Vec<(Ident, Option<Box<Type>>)>,
+    csv_params: Vec<Ident>,
+    path_patterns: Vec<PathPattern>,
 }
 
 impl RouteParser {
     fn new(lit: LitStr) -> syn::Result<Self> {
         let val = lit.value();
-        let span = lit.span();
-        let split_route = val.split('?').collect::<Vec<_>>();
+        let split_route = split_outside_patterns(&val, '?');
         if split_route.len() > 2 {
-            return Err(syn::Error::new(span, "expected at most one '?'"));
+            let second = split_route[0].len() + split_route[1].len() + 1;
+            return Err(syn::Error::new(
+                subspan(&lit, second..second + 1),
+                "expected at most one '?'",
+            ));
         }
 
         let path = split_route[0];
         if !path.starts_with('/') {
-            return Err(syn::Error::new(span, "expected path to start with '/'"));
+            return Err(syn::Error::new(
+                subspan(&lit, 0..path.len()),
+                "expected path to start with '/'",
+            ));
         }
         let path = path.strip_prefix('/').unwrap();
 
+        // The offset of each segment in the literal, for error spans.
+        let mut offset = 1;
         let mut path_params = Vec::new();
-        #[allow(clippy::never_loop)]
-        for path_param in path.split('/') {
-            path_params.push((
-                Slash(span),
-                PathParam::new(path_param, span, Box::new(parse_quote!(()))),
-            ));
+        let mut path_patterns = Vec::new();
+        let segments = split_outside_patterns(path, '/');
+        for path_param in &segments {
+            let span = subspan(&lit, offset..offset + path_param.len());
+            let (param, pattern) = PathParam::new(path_param, span, Box::new(parse_quote!(())))?;
+            if let PathParam::Static(_) = param {
+                validate_static_segment(&lit, offset, path_param)?;
+            }
+            offset += path_param.len() + 1;
+            path_params.push((Slash(span), param));
+            path_patterns.extend(pattern);
         }
 
         let path_param_len = path_params.len();
-        for (i, (_slash, path_param)) in path_params.iter().enumerate() {
-            match path_param {
-                PathParam::WildCard(_, _, _, _, _, _) => {
-                    if i != path_param_len - 1 {
-                        return Err(syn::Error::new(
-                            span,
-                            "wildcard path param must be the last path param",
-                        ));
-                    }
-                }
-                PathParam::Capture(_, _, _, _, _) => (),
-                PathParam::Static(lit) => {
-                    if lit.value() == "*" && i != path_param_len - 1 {
-                        return Err(syn::Error::new(
-                            span,
-                            "wildcard path param must be the last path param",
-                        ));
-                    }
+        for (i, (slash, path_param)) in path_params.iter().enumerate() {
+            let is_wildcard = match path_param {
+                PathParam::WildCard(..) => true,
+                PathParam::Capture(..) => false,
+                PathParam::Static(lit) => lit.value() == "*",
+            };
+            if is_wildcard && i != path_param_len - 1 {
+                let segment = segments[i];
+                return Err(syn::Error::new(
+                    slash.span,
+                    format!("wildcard path param `{segment}` must be the last path param"),
+                ));
+            }
+        }
+
+        let (query_params, csv_params) = match split_route.get(1) {
+            Some(query) => parse_query(&lit, query, split_route[0].len() + 1)?,
+            None => (Vec::new(), Vec::new()),
+        };
+
+        Ok(Self {
+            path_params,
+            query_params,
+            csv_params,
+            path_patterns,
+        })
+    }
+}
+
+/// Splits `str` at `separator`, except inside the pattern of a path parameter, as in
+/// `:slug(regex = "[a-z]+/?")`, which may contain the separators of the path.
+fn split_outside_patterns(str: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut depth, mut quoted, mut escaped) = (0, 0, false, false);
+    for (i, c) in str.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            c if c == separator && !quoted && depth == 0 => {
+                parts.push(&str[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&str[start..]);
+    parts
+}
+
+/// The pattern of a path parameter, as in `/:slug(regex = "[a-z0-9-]+")`, which its value must
+/// match as a whole.
+pub struct PathPattern {
+    /// The name of the parameter in the path.
+    pub name: LitStr,
+    pub pattern: LitStr,
+}
+
+/// The `regex = "<PATTERN>"` inside the parentheses of a path parameter.
+struct PatternConstraint(LitStr);
+
+impl Parse for PatternConstraint {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        if ident != "regex" {
+            return Err(syn::Error::new(ident.span(), "expected `regex`"));
+        }
+        input.parse::<Token![=]>()?;
+        Ok(Self(input.parse()?))
+    }
+}
+
+/// Splits the pattern off a path parameter, e.g. `slug(regex = "[a-z]+"):String` into
+/// `slug:String` and the pattern. Only parentheses before the type annotation are a pattern.
+fn split_pattern(str: &str, span: Span) -> syn::Result<(String, Option<LitStr>)> {
+    let Some(start) = str.find(['(', ':']).filter(|i| str[*i..].starts_with('(')) else {
+        return Ok((str.to_string(), None));
+    };
+    let (mut depth, mut quoted, mut escaped) = (0, false, false);
+    let mut end = None;
+    for (i, c) in str[start..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(start + i);
+                    break;
                 }
             }
+            _ => {}
+        }
+    }
+    let Some(end) = end else {
+        return Err(syn::Error::new(span, format!("unclosed `(` in `{str}`")));
+    };
+    let constraint = &str[start + 1..end];
+    let PatternConstraint(pattern) = syn::parse_str(constraint).map_err(|_| {
+        syn::Error::new(
+            span,
+            format!("expected `regex = \"<PATTERN>\"`, found `{constraint}`"),
+        )
+    })?;
+    let rest = format!("{}{}", &str[..start], &str[end + 1..]);
+    Ok((rest, Some(LitStr::new(&pattern.value(), span))))
+}
+
+/// Parses the query parameters `query`, starting at byte `offset` of `lit`, e.g.
+/// `page:u32&per_page&ids(csv):Vec<u32>`. Also returns the parameters annotated with `(csv)`.
+#[allow(clippy::type_complexity)]
+fn parse_query(
+    lit: &LitStr,
+    query: &str,
+    mut offset: usize,
+) -> syn::Result<(Vec<(Ident, Option<Box<Type>>)>, Vec<Ident>)> {
+    let mut query_params = Vec::new();
+    let mut csv_params = Vec::new();
+    for query_param in query.split('&') {
+        let span = subspan(lit, offset..offset + query_param.len());
+        offset += query_param.len() + 1;
+        let (name, annotation) = split_annotation(query_param, span)?;
+        let (name, csv) = match name.split_once('(') {
+            Some((name, "csv)")) => (name, true),
+            Some((name, _)) => {
+                return Err(syn::Error::new(
+                    span,
+                    format!("expected `{name}(csv)`, found `{query_param}`"),
+                ))
+            }
+            None => (name, false),
+        };
+        let mut ident = syn::parse_str::<Ident>(name).map_err(|_| {
+            syn::Error::new(
+                span,
+                format!("`{name}` is not a valid query parameter name"),
+            )
+        })?;
+        ident.set_span(span);
+        if csv {
+            csv_params.push(ident.clone());
+        }
+        query_params.push((ident, annotation));
+    }
+    Ok((query_params, csv_params))
+}
+
+/// The `include` option of a route, the paths of the `route_params!` mixins whose parameters
+/// are added to the route, e.g. `pagination` or `[pagination, sorting]`.
+pub struct Includes(pub Vec<syn::Path>);
+impl Parse for Includes {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if !input.peek(syn::token::Bracket) {
+            return Ok(Self(vec![input.parse()?]));
+        }
+        let inner;
+        bracketed!(inner in input);
+        let paths = Punctuated::<syn::Path, Token![,]>::parse_terminated(&inner)?;
+        Ok(Self(paths.into_iter().collect()))
+    }
+}
+
+/// The `accepts` option of a route, the encodings of its response, e.g. `[json, msgpack]`.
+pub struct Accepts(pub Vec<Ident>);
+impl Parse for Accepts {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let bracket = bracketed!(inner in input);
+        let encodings = Punctuated::<Ident, Token![,]>::parse_terminated(&inner)?;
+        for encoding in &encodings {
+            if encoding != "json" && encoding != "msgpack" {
+                return Err(syn::Error::new(
+                    encoding.span(),
+                    "expected one of (json, msgpack)",
+                ));
+            }
+        }
+        if encodings.is_empty() {
+            return Err(syn::Error::new(
+                bracket.span.join(),
+                "expected at least one encoding",
+            ));
+        }
+        Ok(Self(encodings.into_iter().collect()))
+    }
+}
+
+/// The mixins of `route_params!`, e.g.
+/// `pub(crate) pagination: "?page:u32&per_page:Option<u32>" { page: { example: 1 } }`.
+pub struct RouteParamsMixins(pub Vec<RouteParamsMixin>);
+impl Parse for RouteParamsMixins {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut mixins = Vec::new();
+        while !input.is_empty() {
+            let attrs = input.call(Attribute::parse_outer)?;
+            let vis = input.parse::<Visibility>()?;
+            let name = input.parse::<Ident>()?;
+            let _ = input.parse::<Token![:]>()?;
+            let params = input.parse::<IncludedParams>()?;
+            let _ = input.parse::<Token![;]>().ok();
+            mixins.push(RouteParamsMixin {
+                attrs,
+                vis,
+                name,
+                params,
+            });
         }
+        Ok(Self(mixins))
+    }
+}
 
-        let mut query_params = Vec::new();
-        if split_route.len() == 2 {
-            let query = split_route[1];
-            for query_param in query.split('&') {
-                query_params.push(Ident::new(query_param, span));
+/// The handlers of `route_index!`, each with an optional name for its constant, e.g.
+/// `get_item, ITEM_PAGE = pages::get_item`.
+pub struct RouteIndex(pub Vec<(Ident, syn::Path)>);
+impl Parse for RouteIndex {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut routes = Vec::<(Ident, syn::Path)>::new();
+        while !input.is_empty() {
+            let (name, handler) = match input.peek2(Token![=]) {
+                true => {
+                    let name = input.parse::<Ident>()?;
+                    let _ = input.parse::<Token![=]>()?;
+                    (Some(name), input.parse::<syn::Path>()?)
+                }
+                false => (None, input.parse::<syn::Path>()?),
+            };
+            let name = match name {
+                Some(name) => name,
+                None => {
+                    let last = &handler.segments.last().unwrap().ident;
+                    let name = IdStrategy::SnakeCase.apply(&last.to_string());
+                    Ident::new(&name.to_uppercase(), last.span())
+                }
+            };
+            if routes.iter().any(|(other, _handler)| *other == name) {
+                return Err(syn::Error::new(
+                    handler.span(),
+                    format!(
+                        "duplicate route constant `{name}`, rename one with `<NAME> = <HANDLER>`"
+                    ),
+                ));
             }
+            routes.push((name, handler));
+            let _ = input.parse::<Token![,]>().ok();
         }
+        Ok(Self(routes))
+    }
+}
 
+/// The attribute of `api_webhook`: an optional method, the name of the webhook and the rest of
+/// a route, e.g. `"order.created" with AppState { summary: "An order was placed" }`.
+pub struct WebhookAttr {
+    pub method: Ident,
+    pub name: LitStr,
+    pub rest: TokenStream2,
+}
+impl Parse for WebhookAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let method = match input.peek(syn::Ident) {
+            true => input.parse()?,
+            false => Ident::new("POST", input.span()),
+        };
+        let name = input.parse::<LitStr>()?;
+        if name.value().is_empty() {
+            return Err(syn::Error::new(
+                name.span(),
+                "the name of a webhook can't be empty",
+            ));
+        }
         Ok(Self {
-            path_params,
+            method,
+            name,
+            rest: input.parse()?,
+        })
+    }
+}
+
+pub struct RouteParamsMixin {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    pub params: IncludedParams,
+}
+
+/// The query parameters of a mixin, with their documentation, e.g.
+/// `"?page:u32&per_page:Option<u32>" { page: { example: 1 } }`.
+pub struct IncludedParams {
+    pub query_lit: LitStr,
+    pub query_params: Vec<(Ident, Option<Box<Type>>)>,
+    /// The query parameters annotated with `(csv)`, as in `?ids(csv):Vec<u32>`.
+    pub csv_params: Vec<Ident>,
+    pub docs: Option<ParamDocs>,
+    /// The tokens of the literal and documentation, which the generated `macro_rules!` passes
+    /// back to `include_route_params!`.
+    pub tokens: TokenStream2,
+}
+impl Parse for IncludedParams {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let query_lit = input.parse::<LitStr>()?;
+        let query = query_lit.value();
+        let Some(query) = query.strip_prefix('?').filter(|query| !query.is_empty()) else {
+            return Err(syn::Error::new(
+                query_lit.span(),
+                "expected query parameters, e.g. \"?page:u32&per_page:Option<u32>\"",
+            ));
+        };
+        let (query_params, csv_params) = parse_query(&query_lit, query, 1)?;
+        let docs = input
+            .peek(Brace)
+            .then(|| input.parse::<Group>())
+            .transpose()?;
+        Ok(Self {
+            tokens: quote!(#query_lit #docs),
+            docs: docs
+                .map(|docs| syn::parse2::<ParamDocs>(TokenTree::Group(docs).into()))
+                .transpose()?,
+            query_lit,
             query_params,
+            csv_params,
+        })
+    }
+}
+
+/// The input of `include_route_params!`: the params of the mixins that were included so far,
+/// in reverse order, whether the route is an `api_route`, the route, and the handler.
+pub struct IncludeRouteParams {
+    pub included: Vec<IncludedParams>,
+    pub with_aide: LitBool,
+    pub attr: TokenStream2,
+    pub item: TokenStream2,
+}
+impl Parse for IncludeRouteParams {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut included = Vec::new();
+        while input.peek(Brace) {
+            let inner;
+            braced!(inner in input);
+            included.push(inner.parse()?);
+        }
+        let with_aide = input.parse()?;
+        let attr;
+        parenthesized!(attr in input);
+        Ok(Self {
+            included,
+            with_aide,
+            attr: attr.parse()?,
+            item: input.parse()?,
         })
     }
 }
 
+/// Checks that the static `segment`, starting at byte `offset` of `lit`, doesn't contain
+/// characters that must be percent-encoded in a URL, since such a route could never match and
+/// its links would be broken.
+fn validate_static_segment(lit: &LitStr, offset: usize, segment: &str) -> syn::Result<()> {
+    for (i, c) in segment.char_indices() {
+        let invalid = match c {
+            '%' => {
+                let escape = segment.get(i + 1..i + 3).unwrap_or_default();
+                escape.len() != 2 || !escape.chars().all(|c| c.is_ascii_hexdigit())
+            }
+            ' ' | '"' | '#' | '<' | '>' | '`' | '{' | '}' | '\\' | '^' | '|' => true,
+            c => c.is_control() || !c.is_ascii(),
+        };
+        if invalid {
+            let start = offset + i;
+            return Err(syn::Error::new(
+                subspan(lit, start..start + c.len_utf8()),
+                format!(
+                    "static path segment `{segment}` contains `{}`, which must be percent-encoded",
+                    c.escape_debug()
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The span of the bytes `range` of the value of `lit`, for errors about a part of the route.
+///
+/// Falls back to the span of the whole literal if the compiler doesn't support this (on stable),
+/// or if the literal contains escapes, which would shift the offsets.
+fn subspan(lit: &LitStr, range: std::ops::Range<usize>) -> Span {
+    let token = lit.token();
+    let repr = token.to_string();
+    let Some(quote) = repr.find('"') else {
+        return lit.span();
+    };
+    if !repr.starts_with('r') && repr.contains('\\') {
+        return lit.span();
+    }
+    let start = quote + 1 + range.start;
+    let end = quote + 1 + range.end;
+    token.subspan(start..end).unwrap_or_else(|| lit.span())
+}
+
+/// A segment of the path. The last field of captures is the type annotated in the
+/// route, as in `/:id:u32`.
 pub enum PathParam {
-    WildCard(LitStr, Brace, Star, Ident, Box<Type>, Brace),
-    Capture(LitStr, Brace, Ident, Box<Type>, Brace),
+    WildCard(
+        LitStr,
+        Brace,
+        Star,
+        Ident,
+        Box<Type>,
+        Brace,
+        Option<Box<Type>>,
+    ),
+    Capture(LitStr, Brace, Ident, Box<Type>, Brace, Option<Box<Type>>),
     Static(LitStr),
 }
 
@@ -96,50 +488,295 @@ impl PathParam {
 
     pub fn capture(&self) -> Option<(&Ident, &Type)> {
         match self {
-            Self::Capture(_, _, ident, ty, _) => Some((ident, ty)),
-            Self::WildCard(_, _, _, ident, ty, _) => Some((ident, ty)),
+            Self::Capture(_, _, ident, ty, _, _) => Some((ident, ty)),
+            Self::WildCard(_, _, _, ident, ty, _, _) => Some((ident, ty)),
             _ => None,
         }
     }
 
-    fn new(str: &str, span: Span, ty: Box<Type>) -> Self {
-        if str.starts_with(':') {
-            let str = str.strip_prefix(':').unwrap();
-            Self::Capture(
-                LitStr::new(str, span),
-                Brace(span),
-                Ident::new(str, span),
-                ty,
-                Brace(span),
-            )
-        } else if str.starts_with('*') && str.len() > 1 {
-            let str = str.strip_prefix('*').unwrap();
-            Self::WildCard(
-                LitStr::new(str, span),
+    /// Parses a segment of the path, and the pattern of its parameter.
+    fn new(str: &str, span: Span, ty: Box<Type>) -> syn::Result<(Self, Option<PathPattern>)> {
+        let (str, wildcard) = if let Some(str) = str.strip_prefix(':') {
+            (str, false)
+        } else if let Some(str) = str.strip_prefix('*').filter(|str| !str.is_empty()) {
+            (str, true)
+        } else {
+            return Ok((Self::Static(LitStr::new(str, span)), None));
+        };
+        let (str, pattern) = split_pattern(str, span)?;
+        let (str, annotation) = split_annotation(&str, span)?;
+        let (name, ident) = split_rename(str, span)?;
+        let name = LitStr::new(name, span);
+        let pattern = pattern.map(|pattern| PathPattern {
+            name: name.clone(),
+            pattern,
+        });
+        let param = match wildcard {
+            false => Self::Capture(name, Brace(span), ident, ty, Brace(span), annotation),
+            true => Self::WildCard(
+                name,
                 Brace(span),
                 Star(span),
-                Ident::new(str, span),
+                ident,
                 ty,
                 Brace(span),
-            )
-        } else {
-            Self::Static(LitStr::new(str, span))
-        }
+                annotation,
+            ),
+        };
+        Ok((param, pattern))
     }
 }
 
+/// Splits `name as ident` into the name used in the path and the identifier of the argument.
+fn split_rename(str: &str, span: Span) -> syn::Result<(&str, Ident)> {
+    let (name, ident) = str.split_once(" as ").unwrap_or((str, str));
+    let mut ident = syn::parse_str::<Ident>(ident.trim()).map_err(|_| {
+        let message = if name == ident {
+            format!("`{name}` is not a valid identifier, rename it with `{name} as <IDENT>`")
+        } else {
+            format!("expected an identifier after `{name} as`, found `{ident}`")
+        };
+        syn::Error::new(span, message)
+    })?;
+    ident.set_span(span);
+    Ok((name, ident))
+}
+
+/// Splits `name:Type` into the name and the parsed type.
+fn split_annotation(str: &str, span: Span) -> syn::Result<(&str, Option<Box<Type>>)> {
+    let Some((name, ty)) = str.split_once(':') else {
+        return Ok((str, None));
+    };
+    let ty = syn::parse_str::<Type>(ty).map_err(|_| {
+        syn::Error::new(
+            span,
+            format!("expected a type after `{name}:`, found `{ty}`"),
+        )
+    })?;
+    Ok((name, Some(Box::new(ty))))
+}
+
+#[derive(Default)]
 pub struct OapiOptions {
-    pub summary: Option<(Ident, LitStr)>,
-    pub description: Option<(Ident, LitStr)>,
-    pub id: Option<(Ident, LitStr)>,
+    /// Any expression of type `&str`, e.g. `include_str!("docs/get_item.md")`.
+    pub summary: Option<(Ident, Expr)>,
+    pub description: Option<(Ident, Expr)>,
+    /// A string literal, or any expression of type `&'static str`.
+    pub id: Option<(Ident, Expr)>,
+    pub id_strategy: Option<(Ident, IdStrategy)>,
+    pub id_module_prefix: Option<(Ident, LitBool)>,
+    pub doc_format: Option<(Ident, DocFormat)>,
+    /// Whether the `id` was derived from the name of the handler, instead of given explicitly.
+    pub derived_id: bool,
     pub hidden: Option<(Ident, LitBool)>,
-    pub tags: Option<(Ident, StrArray)>,
+    /// The named document of the route, as in `api_doc: "internal"`, which is split off by
+    /// `finish_named_api`.
+    pub api_doc: Option<(Ident, LitStr)>,
+    pub tags: Option<(Ident, ExprArray)>,
     pub security: Option<(Ident, Security)>,
     pub responses: Option<(Ident, Responses)>,
-    pub transform: Option<(Ident, ExprClosure)>,
+    pub infer_responses: Option<(Ident, LitBool)>,
+    pub transform: Option<(Ident, Transform)>,
+    pub request_body: Option<(Ident, RequestBody)>,
+    pub servers: Option<(Ident, StrArray)>,
+    pub extensions: Option<(Ident, Extensions)>,
+    pub params: Option<(Ident, ParamDocs)>,
+    pub parameters: Option<(Ident, Parameters)>,
+    pub examples: Option<(Ident, Examples)>,
+    /// The name of the webhook documented by `api_webhook`, instead of a path.
+    pub webhook: Option<(Ident, LitStr)>,
+}
+
+/// How the summary and description of a route are taken from the doc-comments of its handler.
+#[derive(Clone, Copy, Default)]
+pub enum DocFormat {
+    /// The lines as written, including the space after `///`.
+    Raw,
+    /// Without the indentation shared by all lines, and without surrounding blank lines.
+    #[default]
+    Trimmed,
+    /// Trimmed, and with the lines of a paragraph joined, for specs with unwrapped markdown.
+    Unwrapped,
+}
+
+impl Parse for DocFormat {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        match ident.to_string().as_str() {
+            "raw" => Ok(Self::Raw),
+            "trimmed" => Ok(Self::Trimmed),
+            "unwrapped" => Ok(Self::Unwrapped),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected one of (raw, trimmed, unwrapped)",
+            )),
+        }
+    }
+}
+
+impl DocFormat {
+    /// The summary and description in the doc-comments `docs`: the first line (or paragraph, if
+    /// unwrapped), and the lines after it.
+    pub fn apply(self, docs: &[String]) -> (Option<String>, Option<String>) {
+        if let Self::Raw = self {
+            let description = docs.get(2..).filter(|lines| !lines.is_empty());
+            return (
+                docs.first().cloned(),
+                description.map(|lines| lines.join("\n")),
+            );
+        }
+
+        // A doc attribute can have several lines, e.g. with `/** .. */`.
+        let lines = docs
+            .iter()
+            .flat_map(|doc| doc.split('\n'))
+            .map(str::trim_end)
+            .collect::<Vec<_>>();
+        let indent = lines
+            .iter()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or_default();
+        let lines = lines
+            .iter()
+            .map(|line| line.get(indent..).unwrap_or_default())
+            .collect::<Vec<_>>();
+        let lines = match self {
+            Self::Unwrapped => unwrap_lines(&lines),
+            _ => lines.into_iter().map(str::to_string).collect(),
+        };
+
+        let mut lines = lines.into_iter().skip_while(|line| line.is_empty());
+        let summary = lines.next();
+        let mut description = lines.skip_while(|line| line.is_empty()).collect::<Vec<_>>();
+        while description.last().is_some_and(|line| line.is_empty()) {
+            description.pop();
+        }
+        let description = (!description.is_empty()).then(|| description.join("\n"));
+        (summary, description)
+    }
+}
+
+/// Joins the lines of markdown paragraphs with spaces. Headings, list items, block quotes,
+/// tables and code blocks start new lines, and code blocks are kept as they are.
+fn unwrap_lines(lines: &[&str]) -> Vec<String> {
+    let mut unwrapped = Vec::<String>::new();
+    let mut in_fence = false;
+    // Whether the last line is text that the next line continues.
+    let mut continued = false;
+    for line in lines {
+        let text = line.trim_start();
+        if text.starts_with("```") || text.starts_with("~~~") {
+            in_fence = !in_fence;
+            unwrapped.push(line.to_string());
+            continued = false;
+            continue;
+        }
+        // An indented code block can't interrupt a paragraph.
+        if in_fence || text.is_empty() || (!continued && line.starts_with("    ")) {
+            unwrapped.push(line.to_string());
+            continued = false;
+            continue;
+        }
+        let starts_block = text.starts_with(['#', '|', '>']) || is_list_item(text);
+        match unwrapped.last_mut() {
+            Some(last) if continued && !starts_block => {
+                last.push(' ');
+                last.push_str(text);
+            }
+            _ => unwrapped.push(line.to_string()),
+        }
+        continued = !text.starts_with(['#', '|']);
+    }
+    unwrapped
+}
+
+/// Whether `text` starts a markdown list item, e.g. `- item` or `1. item`.
+fn is_list_item(text: &str) -> bool {
+    let ordered = text.trim_start_matches(|c: char| c.is_ascii_digit());
+    let marker = match ordered.len() < text.len() {
+        true => ordered.strip_prefix(['.', ')']),
+        false => text.strip_prefix(['-', '*', '+']),
+    };
+    marker.is_some_and(|rest| rest.starts_with(' '))
+}
+
+/// How the operation id of a route without an `id` is derived from the name of its handler.
+#[derive(Clone, Copy)]
+pub enum IdStrategy {
+    Raw,
+    SnakeCase,
+    CamelCase,
+    PascalCase,
+}
+
+impl Parse for IdStrategy {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        match ident.to_string().as_str() {
+            "raw" => Ok(Self::Raw),
+            "snake_case" => Ok(Self::SnakeCase),
+            "camelCase" => Ok(Self::CamelCase),
+            "PascalCase" => Ok(Self::PascalCase),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected one of (raw, snake_case, camelCase, PascalCase)",
+            )),
+        }
+    }
+}
+
+impl IdStrategy {
+    /// Converts the name of a handler into an operation id.
+    pub fn apply(self, name: &str) -> String {
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        if let Self::Raw = self {
+            return name.to_string();
+        }
+
+        // Split into lowercase words, at underscores and at the start of uppercase letters.
+        let mut words = Vec::<String>::new();
+        let mut prev_lowercase = false;
+        for c in name.chars() {
+            if c == '_' {
+                words.push(String::new());
+            } else {
+                if words.is_empty() || (c.is_uppercase() && prev_lowercase) {
+                    words.push(String::new());
+                }
+                words.last_mut().unwrap().extend(c.to_lowercase());
+            }
+            prev_lowercase = c.is_lowercase() || c.is_ascii_digit();
+        }
+        words.retain(|word| !word.is_empty());
+
+        let capitalize = |word: &String| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        };
+        match self {
+            Self::Raw => unreachable!(),
+            Self::SnakeCase => words.join("_"),
+            Self::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| match i {
+                    0 => word.clone(),
+                    _ => capitalize(word),
+                })
+                .collect(),
+            Self::PascalCase => words.iter().map(capitalize).collect(),
+        }
+    }
 }
 
-pub struct Security(pub Vec<(LitStr, StrArray)>);
+/// The security requirements of an operation. The schemes and scopes are string literals, or
+/// any expression implementing `AsRef<str>`, such as constants or enum variants.
+pub struct Security(pub Vec<(Expr, Vec<Expr>)>);
 impl Parse for Security {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let inner;
@@ -147,17 +784,29 @@ impl Parse for Security {
 
         let mut arr = Vec::new();
         while !inner.is_empty() {
-            let scheme = inner.parse::<LitStr>()?;
+            let scheme = inner.parse::<Expr>()?;
             let _ = inner.parse::<Token![:]>()?;
-            let scopes = inner.parse::<StrArray>()?;
+            let scopes;
+            bracketed!(scopes in inner);
+            let scopes = Punctuated::<Expr, Token![,]>::parse_terminated(&scopes)?;
             let _ = inner.parse::<Token![,]>().ok();
-            arr.push((scheme, scopes));
+            arr.push((scheme, scopes.into_iter().collect()));
         }
 
         Ok(Self(arr))
     }
 }
 
+/// Literals are shown as their value, and other expressions as written.
+fn display_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => format!("\"{}\"", lit.value()),
+        expr => expr.to_token_stream().to_string(),
+    }
+}
+
 impl ToString for Security {
     fn to_string(&self) -> String {
         let mut s = String::new();
@@ -166,52 +815,999 @@ impl ToString for Security {
             if i > 0 {
                 s.push_str(", ");
             }
-            s.push_str(&scheme.value());
-            s.push_str(": ");
-            s.push_str(&scopes.to_string());
+            let scopes = scopes.iter().map(display_expr).collect::<Vec<_>>();
+            s.push_str(&display_expr(scheme));
+            s.push_str(": [");
+            s.push_str(&scopes.join(", "));
+            s.push(']');
+        }
+        s.push('}');
+        s
+    }
+}
+
+/// The documented responses, e.g. `{ 404: String, 200: Item |r| r.description("ok") }`, with an
+/// optional closure transforming the response.
+pub struct Responses(pub Vec<(ResponseStatus, Type, Option<ExprClosure>)>);
+impl Parse for Responses {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        braced!(inner in input);
+
+        let mut arr = Vec::new();
+        while !inner.is_empty() {
+            let status = inner.parse::<ResponseStatus>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            let ty = inner.parse::<Type>()?;
+            let transform = match inner.peek(Token![|]) {
+                true => Some(inner.parse::<ExprClosure>()?),
+                false => None,
+            };
+            let _ = inner.parse::<Token![,]>().ok();
+            arr.push((status, ty, transform));
+        }
+
+        Ok(Self(arr))
+    }
+}
+
+/// The status code of a documented response: an integer, or a `StatusCode` constant such as
+/// `StatusCode::IM_A_TEAPOT` or `TEAPOT`. The associated constants of `StatusCode` are resolved
+/// to their code when the macro is expanded, other constants when the code is compiled.
+pub enum ResponseStatus {
+    Code(LitInt),
+    Const(syn::Path),
+}
+
+impl Parse for ResponseStatus {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitInt) {
+            return Ok(Self::Code(input.parse()?));
+        }
+        let path = input.parse::<syn::Path>()?;
+        let mut segments = path.segments.iter().rev();
+        let (Some(name), Some(ty)) = (segments.next(), segments.next()) else {
+            return Ok(Self::Const(path));
+        };
+        if ty.ident != "StatusCode" {
+            return Ok(Self::Const(path));
+        }
+        match status_code(&name.ident.to_string()) {
+            Some(code) => Ok(Self::Code(LitInt::new(&code.to_string(), path.span()))),
+            None => Err(syn::Error::new(
+                name.ident.span(),
+                format!("`StatusCode` has no constant `{}`", name.ident),
+            )),
+        }
+    }
+}
+
+impl ResponseStatus {
+    /// Identifies the status, to find repeated ones. Two constants with the same code can't be
+    /// told apart here.
+    pub fn key(&self) -> String {
+        match self {
+            Self::Code(lit) => lit.base10_digits().to_string(),
+            Self::Const(path) => path.to_token_stream().to_string(),
+        }
+    }
+}
+
+impl ToTokens for ResponseStatus {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            Self::Code(lit) => lit.to_tokens(tokens),
+            // A const generic argument that isn't a literal must be a block.
+            Self::Const(path) => tokens.extend(quote! {
+                { ::axum_typed_routing::__private::status_code(#path) }
+            }),
+        }
+    }
+}
+
+/// The code of the associated constant `name` of `http::StatusCode`.
+fn status_code(name: &str) -> Option<u16> {
+    let code = match name {
+        "CONTINUE" => 100,
+        "SWITCHING_PROTOCOLS" => 101,
+        "PROCESSING" => 102,
+        "EARLY_HINTS" => 103,
+        "OK" => 200,
+        "CREATED" => 201,
+        "ACCEPTED" => 202,
+        "NON_AUTHORITATIVE_INFORMATION" => 203,
+        "NO_CONTENT" => 204,
+        "RESET_CONTENT" => 205,
+        "PARTIAL_CONTENT" => 206,
+        "MULTI_STATUS" => 207,
+        "ALREADY_REPORTED" => 208,
+        "IM_USED" => 226,
+        "MULTIPLE_CHOICES" => 300,
+        "MOVED_PERMANENTLY" => 301,
+        "FOUND" => 302,
+        "SEE_OTHER" => 303,
+        "NOT_MODIFIED" => 304,
+        "USE_PROXY" => 305,
+        "TEMPORARY_REDIRECT" => 307,
+        "PERMANENT_REDIRECT" => 308,
+        "BAD_REQUEST" => 400,
+        "UNAUTHORIZED" => 401,
+        "PAYMENT_REQUIRED" => 402,
+        "FORBIDDEN" => 403,
+        "NOT_FOUND" => 404,
+        "METHOD_NOT_ALLOWED" => 405,
+        "NOT_ACCEPTABLE" => 406,
+        "PROXY_AUTHENTICATION_REQUIRED" => 407,
+        "REQUEST_TIMEOUT" => 408,
+        "CONFLICT" => 409,
+        "GONE" => 410,
+        "LENGTH_REQUIRED" => 411,
+        "PRECONDITION_FAILED" => 412,
+        "PAYLOAD_TOO_LARGE" => 413,
+        "URI_TOO_LONG" => 414,
+        "UNSUPPORTED_MEDIA_TYPE" => 415,
+        "RANGE_NOT_SATISFIABLE" => 416,
+        "EXPECTATION_FAILED" => 417,
+        "IM_A_TEAPOT" => 418,
+        "MISDIRECTED_REQUEST" => 421,
+        "UNPROCESSABLE_ENTITY" => 422,
+        "LOCKED" => 423,
+        "FAILED_DEPENDENCY" => 424,
+        "TOO_EARLY" => 425,
+        "UPGRADE_REQUIRED" => 426,
+        "PRECONDITION_REQUIRED" => 428,
+        "TOO_MANY_REQUESTS" => 429,
+        "REQUEST_HEADER_FIELDS_TOO_LARGE" => 431,
+        "UNAVAILABLE_FOR_LEGAL_REASONS" => 451,
+        "INTERNAL_SERVER_ERROR" => 500,
+        "NOT_IMPLEMENTED" => 501,
+        "BAD_GATEWAY" => 502,
+        "SERVICE_UNAVAILABLE" => 503,
+        "GATEWAY_TIMEOUT" => 504,
+        "HTTP_VERSION_NOT_SUPPORTED" => 505,
+        "VARIANT_ALSO_NEGOTIATES" => 506,
+        "INSUFFICIENT_STORAGE" => 507,
+        "LOOP_DETECTED" => 508,
+        "NOT_EXTENDED" => 510,
+        "NETWORK_AUTHENTICATION_REQUIRED" => 511,
+        _ => return None,
+    };
+    Some(code)
+}
+
+impl ToString for Responses {
+    fn to_string(&self) -> String {
+        let mut s = String::new();
+        s.push('{');
+        for (i, (status, ty, transform)) in self.0.iter().enumerate() {
+            if i > 0 {
+                s.push_str(", ");
+            }
+            s.push_str(&status.key());
+            s.push_str(": ");
+            s.push_str(&ty.to_token_stream().to_string());
+            if let Some(transform) = transform {
+                s.push(' ');
+                s.push_str(&transform.to_token_stream().to_string());
+            }
+        }
+        s.push('}');
+        s
+    }
+}
+
+/// Specification extensions, e.g. `{ "x-internal": true }`. The values can be any expression
+/// implementing `Serialize`.
+pub struct Extensions(pub Vec<(LitStr, Expr)>);
+impl Parse for Extensions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        braced!(inner in input);
+
+        let mut arr = Vec::new();
+        while !inner.is_empty() {
+            let name = inner.parse::<LitStr>()?;
+            if !name.value().starts_with("x-") {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "extension names must start with `x-`",
+                ));
+            }
+            let _ = inner.parse::<Token![:]>()?;
+            let value = inner.parse::<Expr>()?;
+            let _ = inner.parse::<Token![,]>().ok();
+            arr.push((name, value));
+        }
+
+        Ok(Self(arr))
+    }
+}
+
+/// The `transform` of an operation: an inline closure, or the path of a function
+/// `fn(TransformOperation) -> TransformOperation`.
+pub enum Transform {
+    Closure(ExprClosure),
+    Path(syn::Path),
+}
+impl Parse for Transform {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![|]) || input.peek(Token![||]) || input.peek(Token![move]) {
+            Ok(Self::Closure(input.parse()?))
+        } else {
+            Ok(Self::Path(input.parse()?))
+        }
+    }
+}
+
+/// The documentation of path- and query-parameters, e.g.
+/// `{ id: { example: 42, description: "item id" } }`.
+pub struct ParamDocs(pub Vec<(Ident, ParamDoc)>);
+impl Parse for ParamDocs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        braced!(inner in input);
+
+        let mut arr = Vec::new();
+        while !inner.is_empty() {
+            let name = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            let doc = inner.parse::<ParamDoc>()?;
+            let _ = inner.parse::<Token![,]>().ok();
+            arr.push((name, doc));
+        }
+
+        Ok(Self(arr))
+    }
+}
+
+/// The documentation of a single parameter in [`ParamDocs`].
+pub struct ParamDoc {
+    /// Any expression implementing `serde::Serialize`.
+    pub example: Option<Expr>,
+    /// Any expression of type `&str`.
+    pub description: Option<Expr>,
+    /// Overrides whether the parameter is documented as required, which is otherwise inferred
+    /// from its type.
+    pub required: Option<LitBool>,
+    /// Marks the parameter as deprecated, while the handler still accepts it.
+    pub deprecated: Option<LitBool>,
+}
+impl Parse for ParamDoc {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        braced!(inner in input);
+
+        let mut doc = Self {
+            example: None,
+            description: None,
+            required: None,
+            deprecated: None,
+        };
+        while !inner.is_empty() {
+            let ident = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "example" => doc.example = Some(inner.parse()?),
+                "description" => doc.description = Some(inner.parse()?),
+                "required" => doc.required = Some(inner.parse()?),
+                "deprecated" => doc.deprecated = Some(inner.parse()?),
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (example, description, required, deprecated)",
+                    ))
+                }
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        Ok(doc)
+    }
+}
+
+/// Parameter objects documented explicitly, replacing the parameters `aide` inferred with the
+/// same name and location, e.g. `[{ name: "x-request-id", in: header, schema: Uuid }]`.
+pub struct Parameters(pub Vec<ParameterDoc>);
+impl Parse for Parameters {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        bracketed!(inner in input);
+
+        let mut parameters = Vec::<ParameterDoc>::new();
+        while !inner.is_empty() {
+            let parameter = inner.parse::<ParameterDoc>()?;
+            let duplicate = parameters.iter().any(|other| {
+                other.name.value() == parameter.name.value() && other.location == parameter.location
+            });
+            if duplicate {
+                return Err(syn::Error::new(
+                    parameter.name.span(),
+                    format!(
+                        "duplicate {} parameter `{}`",
+                        parameter.location,
+                        parameter.name.value()
+                    ),
+                ));
+            }
+            parameters.push(parameter);
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        Ok(Self(parameters))
+    }
+}
+
+/// A single parameter object in [`Parameters`].
+pub struct ParameterDoc {
+    pub name: LitStr,
+    /// The location: `path`, `query`, `header` or `cookie`.
+    pub location: Ident,
+    /// The type whose `JsonSchema` is the schema of the parameter, `String` by default.
+    pub schema: Option<Type>,
+    /// Whether the parameter is required, which path parameters always are. Other parameters
+    /// are optional by default.
+    pub required: Option<LitBool>,
+    /// Any expression of type `&str`.
+    pub description: Option<Expr>,
+    /// Any expression implementing `serde::Serialize`.
+    pub example: Option<Expr>,
+    pub deprecated: Option<LitBool>,
+}
+impl Parse for ParameterDoc {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let brace = braced!(inner in input);
+
+        let mut name = None;
+        let mut location = None;
+        let mut parameter = Self {
+            name: LitStr::new("", brace.span.join()),
+            location: Ident::new("query", brace.span.join()),
+            schema: None,
+            required: None,
+            description: None,
+            example: None,
+            deprecated: None,
+        };
+        while !inner.is_empty() {
+            let ident = match inner.peek(Token![in]) {
+                true => {
+                    let token = inner.parse::<Token![in]>()?;
+                    Ident::new("in", token.span)
+                }
+                false => inner.parse::<Ident>()?,
+            };
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "name" => name = Some(inner.parse::<LitStr>()?),
+                "in" => {
+                    let ident = inner.parse::<Ident>()?;
+                    if !matches!(
+                        ident.to_string().as_str(),
+                        "path" | "query" | "header" | "cookie"
+                    ) {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "expected one of (path, query, header, cookie)",
+                        ));
+                    }
+                    location = Some(ident);
+                }
+                "schema" => parameter.schema = Some(inner.parse()?),
+                "required" => parameter.required = Some(inner.parse()?),
+                "description" => parameter.description = Some(inner.parse()?),
+                "example" => parameter.example = Some(inner.parse()?),
+                "deprecated" => parameter.deprecated = Some(inner.parse()?),
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (name, in, schema, required, description, example, deprecated)",
+                    ))
+                }
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        parameter.name = name
+            .filter(|name| !name.value().is_empty())
+            .ok_or_else(|| {
+                syn::Error::new(
+                    brace.span.join(),
+                    "a parameter must have a non-empty `name`",
+                )
+            })?;
+        parameter.location = location.ok_or_else(|| {
+            syn::Error::new(
+                brace.span.join(),
+                "a parameter must have a location, `in: path | query | header | cookie`",
+            )
+        })?;
+        if let (true, Some(required)) = (parameter.location == "path", &parameter.required) {
+            if !required.value() {
+                return Err(syn::Error::new(
+                    required.span(),
+                    "path parameters are always required",
+                ));
+            }
+        }
+        Ok(parameter)
+    }
+}
+
+/// Named pairs of request and response examples, e.g.
+/// `{ "happy path": { request: json!({ "id": 1 }), response: json!({ "name": "x" }) } }`.
+pub struct Examples(pub Vec<(LitStr, ExampleDoc)>);
+impl Parse for Examples {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        braced!(inner in input);
+
+        let mut arr = Vec::new();
+        while !inner.is_empty() {
+            let name = inner.parse::<LitStr>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            let example = inner.parse::<ExampleDoc>()?;
+            let _ = inner.parse::<Token![,]>().ok();
+            arr.push((name, example));
+        }
+
+        Ok(Self(arr))
+    }
+}
+
+/// A single example in [`Examples`]. The values can be any expression implementing `Serialize`.
+pub struct ExampleDoc {
+    pub request: Option<Expr>,
+    pub response: Option<Expr>,
+}
+impl Parse for ExampleDoc {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let brace = braced!(inner in input);
+
+        let mut example = Self {
+            request: None,
+            response: None,
+        };
+        while !inner.is_empty() {
+            let ident = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "request" => example.request = Some(inner.parse()?),
+                "response" => example.response = Some(inner.parse()?),
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (request, response)",
+                    ))
+                }
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+        if example.request.is_none() && example.response.is_none() {
+            return Err(syn::Error::new(
+                brace.span.join(),
+                "an example needs a `request`, a `response` or both",
+            ));
+        }
+
+        Ok(example)
+    }
+}
+
+/// The `cache` option of a route, e.g. `{ max_age: 300, public: true }`.
+pub struct CacheControl {
+    pub max_age: Option<LitInt>,
+    pub public: Option<LitBool>,
+    pub immutable: Option<LitBool>,
+    pub no_store: Option<LitBool>,
+}
+impl Parse for CacheControl {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let brace = braced!(inner in input);
+
+        let mut cache = Self {
+            max_age: None,
+            public: None,
+            immutable: None,
+            no_store: None,
+        };
+        while !inner.is_empty() {
+            let ident = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "max_age" => {
+                    let max_age = inner.parse::<LitInt>()?;
+                    max_age.base10_parse::<u32>()?;
+                    cache.max_age = Some(max_age)
+                }
+                "public" => cache.public = Some(inner.parse()?),
+                "immutable" => cache.immutable = Some(inner.parse()?),
+                "no_store" => cache.no_store = Some(inner.parse()?),
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (max_age, public, immutable, no_store)",
+                    ))
+                }
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        if cache.header_value().is_empty() {
+            return Err(syn::Error::new(
+                brace.span.join(),
+                "expected at least one of (max_age, public, immutable, no_store)",
+            ));
+        }
+        if let Some(no_store) = cache.no_store.as_ref().filter(|lit| lit.value()) {
+            if cache.max_age.is_some() || cache.public.is_some() || cache.immutable.is_some() {
+                return Err(syn::Error::new(
+                    no_store.span(),
+                    "`no_store` can't be combined with other cache directives",
+                ));
+            }
+        }
+        Ok(cache)
+    }
+}
+
+/// The `cors` option of a route: the origins allowed to call it from a browser, and the request
+/// headers they may send, e.g.
+/// `{ origins: ["https://app.example.com"], headers: ["content-type"], max_age: 600 }`.
+pub struct Cors {
+    pub origins: Vec<LitStr>,
+    pub headers: Vec<LitStr>,
+    pub max_age: Option<LitInt>,
+    pub credentials: Option<LitBool>,
+}
+impl Parse for Cors {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let brace = braced!(inner in input);
+
+        let mut cors = Self {
+            origins: Vec::new(),
+            headers: Vec::new(),
+            max_age: None,
+            credentials: None,
+        };
+        while !inner.is_empty() {
+            let ident = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "origins" => cors.origins = inner.parse::<StrArray>()?.0,
+                "headers" => cors.headers = inner.parse::<StrArray>()?.0,
+                "max_age" => {
+                    let max_age = inner.parse::<LitInt>()?;
+                    max_age.base10_parse::<u32>()?;
+                    cors.max_age = Some(max_age)
+                }
+                "credentials" => cors.credentials = Some(inner.parse()?),
+                _ => return Err(syn::Error::new(
+                    ident.span(),
+                    "unexpected field, expected one of (origins, headers, max_age, credentials)",
+                )),
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        if cors.origins.is_empty() {
+            return Err(syn::Error::new(
+                brace.span.join(),
+                "expected the allowed `origins`, e.g. `origins: [\"https://app.example.com\"]`",
+            ));
+        }
+        for origin in &cors.origins {
+            let value = origin.value();
+            let is_origin = (value.starts_with("https://") || value.starts_with("http://"))
+                && !value.ends_with('/');
+            if value != "*" && !is_origin {
+                return Err(syn::Error::new(
+                    origin.span(),
+                    "expected `*` or an origin without a path, e.g. `https://app.example.com`",
+                ));
+            }
+            if value == "*" && cors.credentials.as_ref().is_some_and(LitBool::value) {
+                return Err(syn::Error::new(
+                    origin.span(),
+                    "credentials can't be allowed for any origin, list the origins instead",
+                ));
+            }
+        }
+        for header in &cors.headers {
+            let value = header.value();
+            if value.is_empty()
+                || !value
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+            {
+                return Err(syn::Error::new(header.span(), "invalid header name"));
+            }
+        }
+        Ok(cors)
+    }
+}
+
+/// The `cached` option: the time to live of the cached responses, and the parameters they are
+/// keyed by, e.g. `{ ttl: 60s, key: [id, amount] }`.
+pub struct ResponseCaching {
+    pub ttl_millis: u64,
+    pub key: Option<Vec<Ident>>,
+}
+impl Parse for ResponseCaching {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let brace = braced!(inner in input);
+
+        let mut ttl_millis = None;
+        let mut key = None;
+        while !inner.is_empty() {
+            let ident = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "ttl" => ttl_millis = Some(parse_duration_millis(&inner, &ident)?),
+                "key" => {
+                    let params;
+                    bracketed!(params in inner);
+                    let params = Punctuated::<Ident, Token![,]>::parse_terminated(&params)?;
+                    key = Some(params.into_iter().collect())
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (ttl, key)",
+                    ))
+                }
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        let Some(ttl_millis) = ttl_millis else {
+            return Err(syn::Error::new(
+                brace.span.join(),
+                "expected a `ttl`, e.g. `cached: { ttl: 60s }`",
+            ));
+        };
+        Ok(Self { ttl_millis, key })
+    }
+}
+
+/// The `idempotent` option: `true` to require an `Idempotency-Key` header, or the time to live
+/// of the responses replayed to retries with the same key, e.g. `{ ttl: 24h }`. `false` leaves
+/// the header out, e.g. to override a default.
+pub enum Idempotency {
+    Disabled,
+    Required,
+    Replayed { ttl_millis: u64 },
+}
+impl Parse for Idempotency {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitBool) {
+            return match input.parse::<LitBool>()?.value() {
+                true => Ok(Self::Required),
+                false => Ok(Self::Disabled),
+            };
+        }
+
+        let inner;
+        let brace = braced!(inner in input);
+        let mut ttl_millis = None;
+        while !inner.is_empty() {
+            let ident = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "ttl" => ttl_millis = Some(parse_duration_millis(&inner, &ident)?),
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (ttl)",
+                    ))
+                }
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        let Some(ttl_millis) = ttl_millis else {
+            return Err(syn::Error::new(
+                brace.span.join(),
+                "expected `true` or a `ttl`, e.g. `idempotent: { ttl: 24h }`",
+            ));
+        };
+        Ok(Self::Replayed { ttl_millis })
+    }
+}
+
+/// Parses a duration with a unit, e.g. `500ms`, `60s`, `5m` or `1h`, in milliseconds. The
+/// duration of the option `ident` can't be zero.
+fn parse_duration_millis(input: ParseStream, ident: &Ident) -> syn::Result<u64> {
+    let duration = input.parse::<LitInt>()?;
+    let unit = match duration.suffix() {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => {
+            return Err(syn::Error::new(
+                duration.span(),
+                "expected a duration with a unit of (ms, s, m, h), e.g. `60s`",
+            ))
+        }
+    };
+    let value = duration.base10_parse::<u64>()?;
+    if value == 0 {
+        return Err(syn::Error::new(
+            duration.span(),
+            format!("the {ident} can't be zero"),
+        ));
+    }
+    Ok(value * unit)
+}
+
+/// The `deadline` option: the header with the time left for a request, and the timeout of the
+/// requests without it, e.g. `{ header: "grpc-timeout", default: 5s, max: 30s }`.
+pub struct RequestDeadline {
+    pub header: LitStr,
+    pub default_millis: Option<u64>,
+    pub max_millis: Option<u64>,
+}
+impl Parse for RequestDeadline {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let brace = braced!(inner in input);
+
+        let mut header = None::<LitStr>;
+        let mut default_millis = None;
+        let mut max_millis = None;
+        while !inner.is_empty() {
+            let ident = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "header" => {
+                    let lit = inner.parse::<LitStr>()?;
+                    let value = lit.value();
+                    if value.is_empty()
+                        || !value
+                            .bytes()
+                            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                    {
+                        return Err(syn::Error::new(lit.span(), "invalid header name"));
+                    }
+                    header = Some(lit)
+                }
+                "default" => default_millis = Some(parse_duration_millis(&inner, &ident)?),
+                "max" => max_millis = Some(parse_duration_millis(&inner, &ident)?),
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (header, default, max)",
+                    ))
+                }
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        let Some(header) = header else {
+            return Err(syn::Error::new(
+                brace.span.join(),
+                "expected a `header`, e.g. `deadline: { header: \"grpc-timeout\" }`",
+            ));
+        };
+        if let (Some(default), Some(max)) = (default_millis, max_millis) {
+            if default > max {
+                return Err(syn::Error::new(
+                    brace.span.join(),
+                    "the `default` deadline can't be longer than the `max` deadline",
+                ));
+            }
+        }
+        Ok(Self {
+            header,
+            default_millis,
+            max_millis,
+        })
+    }
+}
+
+impl CacheControl {
+    /// The value of the `Cache-Control` header, e.g. `public, max-age=300`.
+    pub fn header_value(&self) -> String {
+        let mut directives = Vec::new();
+        if self.no_store.as_ref().is_some_and(|lit| lit.value()) {
+            directives.push("no-store".to_string());
+        }
+        if let Some(public) = &self.public {
+            directives.push(if public.value() { "public" } else { "private" }.to_string());
+        }
+        if let Some(max_age) = &self.max_age {
+            directives.push(format!("max-age={}", max_age.base10_digits()));
+        }
+        if self.immutable.as_ref().is_some_and(|lit| lit.value()) {
+            directives.push("immutable".to_string());
+        }
+        directives.join(", ")
+    }
+}
+
+/// The `sitemap` option of a route, `false` to leave it out of the sitemap, or the entry of the
+/// route, e.g. `{ priority: 0.8, changefreq: daily }`.
+pub enum Sitemap {
+    Exclude(LitBool),
+    Entry {
+        priority: Option<LitFloat>,
+        changefreq: Option<Ident>,
+    },
+}
+impl Parse for Sitemap {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitBool) {
+            let lit = input.parse::<LitBool>()?;
+            return match lit.value() {
+                true => Ok(Self::Entry {
+                    priority: None,
+                    changefreq: None,
+                }),
+                false => Ok(Self::Exclude(lit)),
+            };
+        }
+
+        let inner;
+        let brace = braced!(inner in input);
+        let mut priority = None;
+        let mut changefreq = None;
+        while !inner.is_empty() {
+            let ident = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "priority" => {
+                    let lit = inner.parse::<LitFloat>()?;
+                    if !(0.0..=1.0).contains(&lit.base10_parse::<f32>()?) {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "the priority must be between 0.0 and 1.0",
+                        ));
+                    }
+                    priority = Some(lit);
+                }
+                "changefreq" => {
+                    let ident = inner.parse::<Ident>()?;
+                    if !SITEMAP_CHANGEFREQS.contains(&ident.to_string().as_str()) {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            format!("expected one of ({})", SITEMAP_CHANGEFREQS.join(", ")),
+                        ));
+                    }
+                    changefreq = Some(ident);
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (priority, changefreq)",
+                    ))
+                }
+            }
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+
+        if priority.is_none() && changefreq.is_none() {
+            return Err(syn::Error::new(
+                brace.span.join(),
+                "expected at least one of (priority, changefreq)",
+            ));
+        }
+        Ok(Self::Entry {
+            priority,
+            changefreq,
+        })
+    }
+}
+
+/// The values of the `changefreq` element of a sitemap.
+const SITEMAP_CHANGEFREQS: [&str; 7] = [
+    "always", "hourly", "daily", "weekly", "monthly", "yearly", "never",
+];
+
+/// A documented request body, e.g. `multipart { file: binary, description: String }`.
+pub enum RequestBody {
+    /// Fields of a `multipart/form-data` body. The type `binary` documents a file.
+    Multipart(Vec<(Ident, Type)>),
+    /// A raw body with the given media types, e.g. `binary("image/png", "image/jpeg")`, or
+    /// `application/octet-stream` if there are none.
+    Binary(Vec<LitStr>),
+}
+impl Parse for RequestBody {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind = input.parse::<Ident>()?;
+        match kind.to_string().as_str() {
+            "multipart" => {
+                let inner;
+                braced!(inner in input);
+
+                let mut fields = Vec::new();
+                while !inner.is_empty() {
+                    let name = inner.parse::<Ident>()?;
+                    let _ = inner.parse::<Token![:]>()?;
+                    let ty = inner.parse::<Type>()?;
+                    let _ = inner.parse::<Token![,]>().ok();
+                    fields.push((name, ty));
+                }
+                Ok(Self::Multipart(fields))
+            }
+            "binary" => {
+                if !input.peek(syn::token::Paren) {
+                    return Ok(Self::Binary(Vec::new()));
+                }
+                let inner;
+                parenthesized!(inner in input);
+                let media_types = Punctuated::<LitStr, Token![,]>::parse_terminated(&inner)?;
+                if media_types.is_empty() {
+                    return Err(syn::Error::new(
+                        kind.span(),
+                        "expected a media type, e.g. `binary(\"image/png\")`",
+                    ));
+                }
+                Ok(Self::Binary(media_types.into_iter().collect()))
+            }
+            _ => Err(syn::Error::new(
+                kind.span(),
+                "expected one of (multipart, binary)",
+            )),
         }
-        s.push('}');
-        s
     }
 }
 
-pub struct Responses(pub Vec<(LitInt, Type)>);
-impl Parse for Responses {
+#[derive(Clone)]
+/// An array of types, e.g. the `guards` option `[RequireAuth, RequireJson]`.
+pub struct TypeArray(pub Vec<Type>);
+impl Parse for TypeArray {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let inner;
-        braced!(inner in input);
-
+        bracketed!(inner in input);
         let mut arr = Vec::new();
         while !inner.is_empty() {
-            let status = inner.parse::<LitInt>()?;
-            let _ = inner.parse::<Token![:]>()?;
-            let ty = inner.parse::<Type>()?;
-            let _ = inner.parse::<Token![,]>().ok();
-            arr.push((status, ty));
+            arr.push(inner.parse::<Type>()?);
+            inner.parse::<Token![,]>().ok();
         }
-
         Ok(Self(arr))
     }
 }
 
-impl ToString for Responses {
-    fn to_string(&self) -> String {
-        let mut s = String::new();
-        s.push('{');
-        for (i, (status, ty)) in self.0.iter().enumerate() {
-            if i > 0 {
-                s.push_str(", ");
-            }
-            s.push_str(&status.to_string());
-            s.push_str(": ");
-            s.push_str(&ty.to_token_stream().to_string());
+/// An async function called around the handler by the `before` and `after` options, with
+/// references to the parameters in parentheses, e.g. `audit(id)`, or to all parameters passed to
+/// the handler without them.
+#[derive(Clone)]
+pub struct Hook {
+    pub function: syn::Path,
+    pub params: Option<Vec<Ident>>,
+}
+
+/// The hooks of the `before` or `after` option, e.g. `[log_entry, audit(id)]`.
+#[derive(Clone)]
+pub struct Hooks(pub Vec<Hook>);
+impl Parse for Hooks {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        bracketed!(inner in input);
+        let mut hooks = Vec::new();
+        while !inner.is_empty() {
+            let function = inner.parse::<syn::Path>()?;
+            let params = match inner.peek(Paren) {
+                true => {
+                    let params;
+                    parenthesized!(params in inner);
+                    let params = Punctuated::<Ident, Token![,]>::parse_terminated(&params)?;
+                    Some(params.into_iter().collect())
+                }
+                false => None,
+            };
+            hooks.push(Hook { function, params });
+            inner.parse::<Token![,]>().ok();
         }
-        s.push('}');
-        s
+        Ok(Self(hooks))
     }
 }
 
-#[derive(Clone)]
 pub struct StrArray(pub Vec<LitStr>);
 impl Parse for StrArray {
     fn parse(input: ParseStream) -> syn::Result<Self> {
@@ -226,6 +1822,98 @@ impl Parse for StrArray {
     }
 }
 
+/// An array of string literals, or any expressions of type `&'static str`, such as constants or
+/// function calls, e.g. the `tags` of an operation.
+pub struct ExprArray(pub Vec<Expr>);
+impl Parse for ExprArray {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        bracketed!(inner in input);
+        let exprs = Punctuated::<Expr, Token![,]>::parse_terminated(&inner)?;
+        Ok(Self(exprs.into_iter().collect()))
+    }
+}
+
+impl ToString for ExprArray {
+    fn to_string(&self) -> String {
+        let exprs = self.0.iter().map(display_expr).collect::<Vec<_>>();
+        format!("[{}]", exprs.join(", "))
+    }
+}
+
+/// The paths of the `alias` option: a single path, or an array of paths.
+pub struct Aliases(pub Vec<(LitStr, Vec<(Slash, PathParam)>)>);
+
+impl Parse for Aliases {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lits = match input.peek(LitStr) {
+            true => vec![input.parse::<LitStr>()?],
+            false => input.parse::<StrArray>()?.0,
+        };
+        let mut aliases = Vec::new();
+        for lit in lits {
+            let route_parser = RouteParser::new(lit.clone())?;
+            if !route_parser.query_params.is_empty() {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "aliases can't have query parameters",
+                ));
+            }
+            if let Some(pattern) = route_parser.path_patterns.first() {
+                return Err(syn::Error::new(
+                    pattern.pattern.span(),
+                    "the patterns of path parameters are declared in the path of the route, not in its aliases",
+                ));
+            }
+            aliases.push((lit, route_parser.path_params));
+        }
+        Ok(Self(aliases))
+    }
+}
+
+/// The paths of the `localized` option by their locale, e.g. `{ "de": "/de/artikel/:id" }`.
+#[allow(clippy::type_complexity)]
+pub struct Localized(pub Vec<(LitStr, LitStr, Vec<(Slash, PathParam)>)>);
+
+impl Parse for Localized {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        braced!(inner in input);
+
+        let mut localized = Vec::<(LitStr, LitStr, _)>::new();
+        while !inner.is_empty() {
+            let locale = inner.parse::<LitStr>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            let lit = inner.parse::<LitStr>()?;
+            if localized
+                .iter()
+                .any(|(other, _, _)| other.value() == locale.value())
+            {
+                return Err(syn::Error::new(
+                    locale.span(),
+                    format!("duplicate locale `{}`", locale.value()),
+                ));
+            }
+            let route_parser = RouteParser::new(lit.clone())?;
+            if !route_parser.query_params.is_empty() {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "localized paths can't have query parameters",
+                ));
+            }
+            if let Some(pattern) = route_parser.path_patterns.first() {
+                return Err(syn::Error::new(
+                    pattern.pattern.span(),
+                    "the patterns of path parameters are declared in the path of the route, not in its localized paths",
+                ));
+            }
+            localized.push((locale, lit, route_parser.path_params));
+            let _ = inner.parse::<Token![,]>().ok();
+        }
+        Ok(Self(localized))
+    }
+}
+
 impl ToString for StrArray {
     fn to_string(&self) -> String {
         let mut s = String::new();
@@ -243,66 +1931,319 @@ impl ToString for StrArray {
     }
 }
 
-impl Parse for OapiOptions {
+/// The `path_struct` option: `true` to extract the captures into a generated struct with named
+/// fields, or the type of a struct that is passed to the handler as a single argument.
+pub enum PathStruct {
+    Generated(LitBool),
+    Type(Box<Type>),
+}
+impl Parse for PathStruct {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let mut this = Self {
-            summary: None,
-            description: None,
-            id: None,
-            hidden: None,
-            tags: None,
-            security: None,
-            responses: None,
-            transform: None,
-        };
+        if input.peek(LitBool) {
+            Ok(Self::Generated(input.parse()?))
+        } else {
+            Ok(Self::Type(input.parse()?))
+        }
+    }
+}
+
+/// Options that apply to both `route` and `api_route`.
+#[derive(Default)]
+pub struct RouteOptions {
+    pub path_extractor: Option<(Ident, syn::Path)>,
+    pub path_struct: Option<(Ident, PathStruct)>,
+    pub rejection: Option<(Ident, Type)>,
+    pub deny_unknown_query: Option<(Ident, LitBool)>,
+    pub lenient_query: Option<(Ident, LitBool)>,
+    pub infer_extensions: Option<(Ident, LitBool)>,
+    pub status: Option<(Ident, LitInt)>,
+    pub head: Option<(Ident, Ident)>,
+    pub version: Option<(Ident, LitInt)>,
+    pub version_format: Option<(Ident, LitStr)>,
+    pub alias: Option<(Ident, Aliases)>,
+    pub locale: Option<(Ident, LitStr)>,
+    pub localized: Option<(Ident, Localized)>,
+    pub deprecate_alias: Option<(Ident, LitBool)>,
+    pub deprecated: Option<(Ident, LitBool)>,
+    pub sunset: Option<(Ident, LitStr)>,
+    pub name: Option<(Ident, Ident)>,
+    pub keep_fn: Option<(Ident, LitBool)>,
+    /// The visibility of the generated function and module, e.g. `pub(crate)`, instead of the
+    /// visibility of the handler.
+    pub vis: Option<(Ident, Visibility)>,
+    /// Whether the module of the route is generated, without the features generating it.
+    pub module: Option<(Ident, LitBool)>,
+    /// Whether the generated function is put into the module of the route, as `route`.
+    pub in_module: Option<(Ident, LitBool)>,
+    pub in_impl: Option<(Ident, LitBool)>,
+    pub base: Option<(Ident, Expr)>,
+    pub enabled_if: Option<(Ident, Expr)>,
+    /// The generated function of the route the route moved to, which is redirected to.
+    pub moved_to: Option<(Ident, syn::Path)>,
+    pub cache: Option<(Ident, CacheControl)>,
+    pub cached: Option<(Ident, ResponseCaching)>,
+    pub idempotent: Option<(Ident, Idempotency)>,
+    pub compression: Option<(Ident, Ident)>,
+    pub cors: Option<(Ident, Cors)>,
+    pub deadline: Option<(Ident, RequestDeadline)>,
+    pub generate_test: Option<(Ident, LitBool)>,
+    pub erased: Option<(Ident, LitBool)>,
+    pub guards: Option<(Ident, TypeArray)>,
+    /// The hooks called before and after the handler, with its typed parameters.
+    pub before: Option<(Ident, Hooks)>,
+    pub after: Option<(Ident, Hooks)>,
+    /// The permissions required by the route, for an authorization middleware, e.g.
+    /// `["items:read"]`.
+    pub permissions: Option<(Ident, StrArray)>,
+    pub sitemap: Option<(Ident, Sitemap)>,
+    pub include: Option<(Ident, Includes)>,
+    pub paginated: Option<(Ident, LitBool)>,
+    pub accepts: Option<(Ident, Accepts)>,
+    pub host: Option<(Ident, LitStr)>,
+    pub feature_flag: Option<(Ident, LitStr)>,
+    pub debug_handler: Option<(Ident, LitBool)>,
+}
+
+/// The options block of a route, containing both [`RouteOptions`] and [`OapiOptions`].
+/// The `OapiOptions` are only set if at least one of its fields is given.
+struct Options {
+    route: RouteOptions,
+    oapi: Option<OapiOptions>,
+}
+
+impl Parse for Options {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut route = RouteOptions::default();
+        let mut oapi = None;
 
         while !input.is_empty() {
             let ident = input.parse::<Ident>()?;
             let _ = input.parse::<Token![:]>()?;
             match ident.to_string().as_str() {
-                "summary" => this.summary = Some((ident, input.parse()?)),
-                "description" => this.description = Some((ident, input.parse()?)),
-                "id" => this.id = Some((ident, input.parse()?)),
-                "hidden" => this.hidden = Some((ident, input.parse()?)),
-                "tags" => this.tags = Some((ident, input.parse()?)),
-                "security" => this.security = Some((ident, input.parse()?)),
-                "responses" => this.responses = Some((ident, input.parse()?)),
-                "transform" => this.transform = Some((ident, input.parse()?)),
-                _ => {
-                    return Err(syn::Error::new(
-                        ident.span(),
-                        "unexpected field, expected one of (summary, description, id, hidden, tags, security, responses, transform)",
-                    ))
+                "path_extractor" => route.path_extractor = Some((ident, input.parse()?)),
+                "path_struct" => route.path_struct = Some((ident, input.parse()?)),
+                "rejection" => route.rejection = Some((ident, input.parse()?)),
+                "deny_unknown_query" => route.deny_unknown_query = Some((ident, input.parse()?)),
+                "lenient_query" => route.lenient_query = Some((ident, input.parse()?)),
+                "infer_extensions" => route.infer_extensions = Some((ident, input.parse()?)),
+                "status" => route.status = Some((ident, parse_status(input)?)),
+                "base" => route.base = Some((ident, input.parse()?)),
+                "enabled_if" => route.enabled_if = Some((ident, input.parse()?)),
+                "moved_to" => route.moved_to = Some((ident, input.parse()?)),
+                "cache" => route.cache = Some((ident, input.parse()?)),
+                "cached" => route.cached = Some((ident, input.parse()?)),
+                "idempotent" => route.idempotent = Some((ident, input.parse()?)),
+                "name" => route.name = Some((ident, input.parse()?)),
+                "keep_fn" => route.keep_fn = Some((ident, input.parse()?)),
+                "vis" => route.vis = Some((ident, input.parse()?)),
+                "module" => route.module = Some((ident, input.parse()?)),
+                "in_module" => route.in_module = Some((ident, input.parse()?)),
+                "in_impl" => route.in_impl = Some((ident, input.parse()?)),
+                "generate_test" => route.generate_test = Some((ident, input.parse()?)),
+                "erased" => route.erased = Some((ident, input.parse()?)),
+                "debug_handler" => route.debug_handler = Some((ident, input.parse()?)),
+                "guards" => route.guards = Some((ident, input.parse()?)),
+                // Hooks are merged when given multiple times, like the tags of `api_defaults`.
+                "before" | "after" => {
+                    let Hooks(hooks) = input.parse()?;
+                    let existing = match ident == "before" {
+                        true => &mut route.before,
+                        false => &mut route.after,
+                    };
+                    match existing {
+                        Some((_ident, existing)) => existing.0.extend(hooks),
+                        None => *existing = Some((ident, Hooks(hooks))),
+                    }
+                }
+                "permissions" => route.permissions = Some((ident, input.parse()?)),
+                "sitemap" => route.sitemap = Some((ident, input.parse()?)),
+                "include" => route.include = Some((ident, input.parse()?)),
+                "paginated" => route.paginated = Some((ident, input.parse()?)),
+                "host" => {
+                    let host = input.parse::<LitStr>()?;
+                    let value = host.value();
+                    if value.is_empty()
+                        || !value
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':'))
+                    {
+                        return Err(syn::Error::new(
+                            host.span(),
+                            "expected a host without a scheme or path, e.g. \"internal.example.com\"",
+                        ));
+                    }
+                    route.host = Some((ident, host))
+                }
+                "accepts" => route.accepts = Some((ident, input.parse()?)),
+                "feature_flag" => {
+                    let flag = input.parse::<LitStr>()?;
+                    if flag.value().is_empty() {
+                        return Err(syn::Error::new(
+                            flag.span(),
+                            "the name of a feature flag can't be empty",
+                        ));
+                    }
+                    route.feature_flag = Some((ident, flag))
+                }
+                "alias" => route.alias = Some((ident, input.parse()?)),
+                "locale" => route.locale = Some((ident, input.parse()?)),
+                "localized" => route.localized = Some((ident, input.parse()?)),
+                "deprecate_alias" => route.deprecate_alias = Some((ident, input.parse()?)),
+                "deprecated" => route.deprecated = Some((ident, input.parse()?)),
+                "sunset" => {
+                    let sunset = input.parse::<LitStr>()?;
+                    if !sunset
+                        .value()
+                        .chars()
+                        .all(|c| c.is_ascii() && !c.is_ascii_control())
+                    {
+                        return Err(syn::Error::new(
+                            sunset.span(),
+                            "expected an HTTP-date, e.g. \"Wed, 31 Dec 2025 23:59:59 GMT\"",
+                        ));
+                    }
+                    route.sunset = Some((ident, sunset))
+                }
+                "version" => {
+                    let version = input.parse::<LitInt>()?;
+                    version.base10_parse::<u32>()?;
+                    route.version = Some((ident, version))
+                }
+                "version_format" => {
+                    let format = input.parse::<LitStr>()?;
+                    if !format.value().starts_with('/') || !format.value().contains("{n}") {
+                        return Err(syn::Error::new(
+                            format.span(),
+                            "expected a path starting with `/` and containing `{n}`, e.g. \"/v{n}\"",
+                        ));
+                    }
+                    route.version_format = Some((ident, format))
+                }
+                "head" => {
+                    let value = input.parse::<Ident>()?;
+                    if value != "auto" {
+                        return Err(syn::Error::new(value.span(), "expected `auto`"));
+                    }
+                    route.head = Some((ident, value))
+                }
+                "compression" => {
+                    let value = input.parse::<Ident>()?;
+                    if value != "off" {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "expected `off`, routes can only opt out of the compression layer of the application",
+                        ));
+                    }
+                    route.compression = Some((ident, value))
+                }
+                "cors" => route.cors = Some((ident, input.parse()?)),
+                "deadline" => route.deadline = Some((ident, input.parse()?)),
+                field => {
+                    let oapi = oapi.get_or_insert_with(OapiOptions::default);
+                    match field {
+                        "summary" => oapi.summary = Some((ident, input.parse()?)),
+                        "description" => oapi.description = Some((ident, input.parse()?)),
+                        "id" => oapi.id = Some((ident, input.parse()?)),
+                        "id_strategy" => oapi.id_strategy = Some((ident, input.parse()?)),
+                        "doc_format" => oapi.doc_format = Some((ident, input.parse()?)),
+                        "id_module_prefix" => {
+                            oapi.id_module_prefix = Some((ident, input.parse()?))
+                        }
+                        "hidden" => oapi.hidden = Some((ident, input.parse()?)),
+                        "api_doc" => {
+                            let name = input.parse::<LitStr>()?;
+                            if name.value().is_empty() {
+                                return Err(syn::Error::new(
+                                    name.span(),
+                                    "the name of an API document can't be empty",
+                                ));
+                            }
+                            oapi.api_doc = Some((ident, name))
+                        }
+                        // Tags and security requirements are merged when given multiple
+                        // times, which is used by `api_defaults`.
+                        "tags" => {
+                            let ExprArray(tags) = input.parse()?;
+                            match &mut oapi.tags {
+                                Some((_ident, existing)) => existing.0.extend(tags),
+                                None => oapi.tags = Some((ident, ExprArray(tags))),
+                            }
+                        }
+                        "security" => {
+                            let Security(security) = input.parse()?;
+                            match &mut oapi.security {
+                                Some((_ident, existing)) => existing.0.extend(security),
+                                None => oapi.security = Some((ident, Security(security))),
+                            }
+                        }
+                        "responses" => oapi.responses = Some((ident, input.parse()?)),
+                        "infer_responses" => {
+                            oapi.infer_responses = Some((ident, input.parse()?))
+                        }
+                        "transform" => oapi.transform = Some((ident, input.parse()?)),
+                        "request_body" => oapi.request_body = Some((ident, input.parse()?)),
+                        "servers" => oapi.servers = Some((ident, input.parse()?)),
+                        "extensions" => oapi.extensions = Some((ident, input.parse()?)),
+                        "params" => oapi.params = Some((ident, input.parse()?)),
+                        "parameters" => oapi.parameters = Some((ident, input.parse()?)),
+                        "examples" => oapi.examples = Some((ident, input.parse()?)),
+                        // Set by `api_webhook`, which documents the route under `webhooks`.
+                        "webhook" => oapi.webhook = Some((ident, input.parse()?)),
+                        _ => {
+                            return Err(syn::Error::new(
+                                ident.span(),
+                                "unexpected field, expected one of (path_extractor, path_struct, rejection, deny_unknown_query, lenient_query, infer_extensions, status, head, version, version_format, alias, deprecate_alias, locale, localized, deprecated, sunset, name, keep_fn, vis, module, in_module, in_impl, base, enabled_if, moved_to, cache, cached, idempotent, compression, cors, deadline, generate_test, erased, guards, before, after, permissions, sitemap, include, paginated, accepts, host, feature_flag, debug_handler, summary, description, id, id_strategy, id_module_prefix, doc_format, hidden, api_doc, tags, security, responses, infer_responses, transform, request_body, servers, extensions, params, parameters, examples)",
+                            ))
+                        }
+                    }
                 }
             }
             let _ = input.parse::<Token![,]>().ok();
         }
 
-        Ok(this)
+        Ok(Self { route, oapi })
+    }
+}
+
+/// Parses a status code, which must be in the range `100..=999`.
+fn parse_status(input: ParseStream) -> syn::Result<LitInt> {
+    let status = input.parse::<LitInt>()?;
+    match status.base10_parse::<u16>() {
+        Ok(100..=999) => Ok(status),
+        _ => Err(syn::Error::new(
+            status.span(),
+            "expected a status code between 100 and 999",
+        )),
     }
 }
 
 impl OapiOptions {
     pub fn merge_with_fn(&mut self, function: &ItemFn) {
+        let docs = doc_iter(&function.attrs)
+            .map(|item| item.value())
+            .collect::<Vec<_>>();
+        let format = match &self.doc_format {
+            Some((_ident, format)) => *format,
+            None => DocFormat::default(),
+        };
+        let (summary, description) = format.apply(&docs);
         if self.description.is_none() {
-            self.description = doc_iter(&function.attrs)
-                .skip(2)
-                .map(|item| item.value())
-                .reduce(|mut acc, item| {
-                    acc.push('\n');
-                    acc.push_str(&item);
-                    acc
-                })
-                .map(|item| (parse_quote!(description), parse_quote!(#item)))
+            self.description =
+                description.map(|item| (parse_quote!(description), parse_quote!(#item)))
         }
         if self.summary.is_none() {
-            self.summary = doc_iter(&function.attrs)
-                .next()
-                .map(|item| (parse_quote!(summary), item.clone()))
+            self.summary = summary.map(|item| (parse_quote!(summary), parse_quote!(#item)))
         }
         if self.id.is_none() {
-            let id = &function.sig.ident;
-            self.id = Some((parse_quote!(id), LitStr::new(&id.to_string(), id.span())));
+            let ident = &function.sig.ident;
+            let strategy = match &self.id_strategy {
+                Some((_ident, strategy)) => *strategy,
+                None => IdStrategy::Raw,
+            };
+            let id = strategy.apply(&ident.to_string());
+            let id = LitStr::new(&id, ident.span());
+            self.id = Some((parse_quote!(id), parse_quote!(#id)));
+            self.derived_id = true;
         }
     }
 }
@@ -325,44 +2266,413 @@ fn doc_iter(attrs: &[Attribute]) -> impl Iterator<Item = &LitStr> + '_ {
         })
 }
 
+/// The options of a path- or query-parameter, given with `#[route_param(..)]` on its argument.
+#[derive(Default)]
+pub struct ParamOptions {
+    pub flatten: Option<Ident>,
+    pub deserialize_with: Option<(Ident, LitStr)>,
+    /// The raw type of `try_from = <TYPE>`, which is deserialized and converted with `TryFrom`.
+    pub try_from: Option<(Ident, Type)>,
+    /// Parses the parameter from a string with `FromStr`.
+    pub from_str: Option<Ident>,
+    /// The OpenAPI `style` of a query parameter: `form` or `deepObject`.
+    pub style: Option<(Ident, Ident)>,
+    pub explode: Option<(Ident, LitBool)>,
+    /// The function generating the schema of a query parameter whose type doesn't implement
+    /// `JsonSchema`, as with `#[schemars(schema_with = "..")]`.
+    pub schema_with: Option<(Ident, LitStr)>,
+    /// Leaves a query parameter out of the documentation, so its type needn't implement
+    /// `JsonSchema`.
+    pub skip_schema: Option<Ident>,
+}
+
+impl ParamOptions {
+    /// Whether the parameter has `style = deepObject`, e.g. `filter[name]=x&filter[age]=3`.
+    pub fn deep_object(&self) -> bool {
+        matches!(&self.style, Some((_ident, style)) if style == "deepObject")
+    }
+
+    /// The `schema_with` or `skip_schema` option, which replace the `JsonSchema` of the type.
+    pub fn schema_option(&self) -> Option<&Ident> {
+        let schema_with = self.schema_with.as_ref().map(|(ident, _path)| ident);
+        schema_with.or(self.skip_schema.as_ref())
+    }
+
+    /// Whether the parameter is converted with `deserialize_with`, `try_from` or `from_str`.
+    pub fn converts(&self) -> bool {
+        self.deserialize_with.is_some() || self.try_from.is_some() || self.from_str.is_some()
+    }
+
+    /// Whether the parameter is a comma-separated list, because of `explode = false`.
+    pub fn comma_separated(&self) -> bool {
+        !self.deep_object() && matches!(&self.explode, Some((_ident, lit)) if !lit.value())
+    }
+}
+
+impl Parse for ParamOptions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = ParamOptions::default();
+        while !input.is_empty() {
+            let ident = input.parse::<Ident>()?;
+            match ident.to_string().as_str() {
+                "flatten" => options.flatten = Some(ident),
+                "from_str" => options.from_str = Some(ident),
+                "skip_schema" => options.skip_schema = Some(ident),
+                "deserialize_with" => {
+                    let _ = input.parse::<Token![=]>()?;
+                    options.deserialize_with = Some((ident, input.parse()?));
+                }
+                "try_from" => {
+                    let _ = input.parse::<Token![=]>()?;
+                    options.try_from = Some((ident, input.parse()?));
+                }
+                "schema_with" => {
+                    let _ = input.parse::<Token![=]>()?;
+                    options.schema_with = Some((ident, input.parse()?));
+                }
+                "style" => {
+                    let _ = input.parse::<Token![=]>()?;
+                    let style = input.parse::<Ident>()?;
+                    if style != "form" && style != "deepObject" {
+                        return Err(syn::Error::new(
+                            style.span(),
+                            "expected one of (form, deepObject)",
+                        ));
+                    }
+                    options.style = Some((ident, style));
+                }
+                "explode" => {
+                    let _ = input.parse::<Token![=]>()?;
+                    options.explode = Some((ident, input.parse()?));
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected option, expected one of (flatten, deserialize_with, try_from, from_str, style, explode, schema_with, skip_schema)",
+                    ))
+                }
+            }
+            let _ = input.parse::<Token![,]>().ok();
+        }
+        Ok(options)
+    }
+}
+
+/// The options of `api_defaults`, which are inserted in the options of every `api_route` of
+/// a module.
+pub struct ApiDefaults(pub TokenStream2);
+
+impl Parse for ApiDefaults {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let tokens = input.fork().parse::<TokenStream2>()?;
+        while !input.is_empty() {
+            let ident = input.parse::<Ident>()?;
+            let _ = input.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "tags" => {
+                    input.parse::<StrArray>()?;
+                }
+                "security" => {
+                    input.parse::<Security>()?;
+                }
+                "id_strategy" => {
+                    input.parse::<IdStrategy>()?;
+                }
+                "id_module_prefix" => {
+                    input.parse::<LitBool>()?;
+                }
+                "doc_format" => {
+                    input.parse::<DocFormat>()?;
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "unexpected field, expected one of (tags, security, id_strategy, id_module_prefix, doc_format)",
+                    ))
+                }
+            }
+            let _ = input.parse::<Token![,]>().ok();
+        }
+        Ok(Self(tokens))
+    }
+}
+
+/// The fields of a `multipart` clause with their types, e.g. `(file: Bytes, name: String)`.
+pub struct MultipartFields(pub Vec<(Ident, Type)>);
+impl Parse for MultipartFields {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let paren = parenthesized!(inner in input);
+        let mut fields = Vec::<(Ident, Type)>::new();
+        while !inner.is_empty() {
+            let name = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![:]>()?;
+            let ty = inner.parse::<Type>()?;
+            if fields.iter().any(|(other, _ty)| *other == name) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("duplicate multipart field `{name}`"),
+                ));
+            }
+            fields.push((name, ty));
+            if !inner.is_empty() {
+                inner.parse::<Token![,]>()?;
+            }
+        }
+        if fields.is_empty() {
+            return Err(syn::Error::new(
+                paren.span.join(),
+                "expected at least one multipart field, as in `multipart(file: Bytes)`",
+            ));
+        }
+        Ok(Self(fields))
+    }
+}
+
+/// The values of an `inject` clause, passed to the arguments with the same names, e.g.
+/// `(version = 2u8, realm = "admin")`.
+pub struct InjectedArgs(pub Vec<(Ident, Expr)>);
+impl Parse for InjectedArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let inner;
+        let paren = parenthesized!(inner in input);
+        let mut args = Vec::<(Ident, Expr)>::new();
+        while !inner.is_empty() {
+            let name = inner.parse::<Ident>()?;
+            let _ = inner.parse::<Token![=]>()?;
+            let value = inner.parse::<Expr>()?;
+            if args.iter().any(|(other, _value)| *other == name) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("duplicate injected argument `{name}`"),
+                ));
+            }
+            args.push((name, value));
+            if !inner.is_empty() {
+                inner.parse::<Token![,]>()?;
+            }
+        }
+        if args.is_empty() {
+            return Err(syn::Error::new(
+                paren.span.join(),
+                "expected at least one injected argument, as in `inject(realm = \"admin\")`",
+            ));
+        }
+        Ok(Self(args))
+    }
+}
+
 pub struct Route {
     pub method: Method,
     pub path_params: Vec<(Slash, PathParam)>,
-    pub query_params: Vec<Ident>,
+    pub query_params: Vec<(Ident, Option<Box<Type>>)>,
+    /// The query parameters annotated with `(csv)`, as in `?ids(csv):Vec<u32>`, which are
+    /// comma-separated lists like with `#[route_param(explode = false)]`.
+    pub csv_params: Vec<Ident>,
+    /// The patterns of the path parameters, as in `/:slug(regex = "[a-z0-9-]+")`.
+    pub path_patterns: Vec<PathPattern>,
     pub state: Option<Type>,
+    /// The bounds of a generic state, as in `with S where S: AuthProvider`.
+    pub state_bounds: Option<WhereClause>,
+    /// The fields of a `multipart(file: Bytes, name: String)` clause, extracted from a
+    /// `multipart/form-data` body into the arguments with the same names.
+    pub multipart: Option<(kw::multipart, MultipartFields)>,
+    /// The values of an `inject(version = 2u8, realm = "admin")` clause, passed to the arguments
+    /// with the same names instead of extracting them.
+    pub inject: Option<(kw::inject, InjectedArgs)>,
     pub route_lit: LitStr,
+    pub options: RouteOptions,
     pub oapi_options: Option<OapiOptions>,
 }
 
 impl Parse for Route {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let method = input.parse::<Method>()?;
-        let route_lit = input.parse::<LitStr>()?;
+        let route_lit = parse_route_lit(input)?;
         let route_parser = RouteParser::new(route_lit.clone())?;
         let state = match input.parse::<kw::with>() {
             Ok(_) => Some(input.parse::<Type>()?),
             Err(_) => None,
         };
-        let oapi_options = input
+        let state_bounds = match state.is_some() && input.peek(Token![where]) {
+            true => Some(input.parse::<WhereClause>()?),
+            false => None,
+        };
+        let multipart = match input.peek(kw::multipart) {
+            true => Some((
+                input.parse::<kw::multipart>()?,
+                input.parse::<MultipartFields>()?,
+            )),
+            false => None,
+        };
+        let inject = match input.peek(kw::inject) {
+            true => Some((input.parse::<kw::inject>()?, input.parse::<InjectedArgs>()?)),
+            false => None,
+        };
+        let options = input
             .peek(Brace)
             .then(|| {
                 let inner;
                 braced!(inner in input);
-                inner.parse::<OapiOptions>()
+                inner.parse::<Options>()
             })
             .transpose()?;
+        let (options, oapi_options) = match options {
+            Some(options) => (options.route, options.oapi),
+            None => (RouteOptions::default(), None),
+        };
 
         Ok(Route {
             method,
             path_params: route_parser.path_params,
             query_params: route_parser.query_params,
+            csv_params: route_parser.csv_params,
+            path_patterns: route_parser.path_patterns,
             state,
+            state_bounds,
+            multipart,
+            inject,
             route_lit,
+            options,
             oapi_options,
         })
     }
 }
 
+impl Route {
+    /// Adds the query parameters of the `route_params!` mixins of the `include` option, and
+    /// their documentation if the route is an `api_route`. The documentation of the route itself
+    /// takes precedence.
+    pub fn include_params(
+        &mut self,
+        included: Vec<IncludedParams>,
+        with_aide: bool,
+    ) -> syn::Result<()> {
+        for params in included {
+            for (ident, annotation) in params.query_params {
+                if let Some((existing, _)) =
+                    self.query_params.iter().find(|(name, _)| *name == ident)
+                {
+                    return Err(syn::Error::new(
+                        existing.span(),
+                        format!("query parameter `{ident}` is also included with `include`"),
+                    ));
+                }
+                self.query_params.push((ident, annotation));
+            }
+            self.csv_params.extend(params.csv_params);
+
+            let route = self.route_lit.value();
+            let separator = match split_outside_patterns(&route, '?').len() {
+                1 => '?',
+                _ => '&',
+            };
+            let query = &params.query_lit.value()[1..];
+            self.route_lit =
+                LitStr::new(&format!("{route}{separator}{query}"), self.route_lit.span());
+
+            if let (true, Some(ParamDocs(docs))) = (with_aide, params.docs) {
+                let oapi = self.oapi_options.get_or_insert_with(OapiOptions::default);
+                match &mut oapi.params {
+                    Some((_ident, ParamDocs(existing))) => {
+                        existing.splice(0..0, docs);
+                    }
+                    None => {
+                        let ident = Ident::new("params", params.query_lit.span());
+                        oapi.params = Some((ident, ParamDocs(docs)));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Route {
+    /// Adds the `page` and `per_page` query parameters of the `paginated` option, which are read
+    /// by the `Pagination` argument of the handler, and their documentation if the route is an
+    /// `api_route`.
+    pub fn paginate(&mut self, with_aide: bool) -> syn::Result<()> {
+        let Some((ident, paginated)) = &self.options.paginated else {
+            return Ok(());
+        };
+        if !paginated.value() {
+            return Ok(());
+        }
+        if !cfg!(feature = "pagination") {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`paginated` requires the `pagination` feature",
+            ));
+        }
+        for name in ["page", "per_page"] {
+            if let Some((existing, _)) = self.query_params.iter().find(|(ident, _)| *ident == name)
+            {
+                return Err(syn::Error::new(
+                    existing.span(),
+                    format!("query parameter `{name}` is also added by `paginated`"),
+                ));
+            }
+        }
+        let params = syn::parse2::<IncludedParams>(quote! {
+            "?page:Option<u32>&per_page:Option<u32>" {
+                page: { description: "The page, starting at 1", example: 1 },
+                per_page: {
+                    description: "The number of items per page, 20 by default and at most 100",
+                    example: 20,
+                },
+            }
+        })?;
+        self.include_params(vec![params], with_aide)
+    }
+}
+
+/// Parses the path of a route: a string literal, or a `concat!` of string literals.
+fn parse_route_lit(input: ParseStream) -> syn::Result<LitStr> {
+    if input.peek(LitStr) {
+        return input.parse();
+    }
+    let mac = input.parse::<syn::Macro>().map_err(|err| {
+        syn::Error::new(
+            err.span(),
+            "expected a string literal or `concat!` of string literals",
+        )
+    })?;
+    concat_lit(&mac)
+}
+
+/// Resolves `concat!("..", ..)`, which may contain nested `concat!`s.
+fn concat_lit(mac: &syn::Macro) -> syn::Result<LitStr> {
+    let is_concat = mac
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "concat");
+    if !is_concat {
+        return Err(syn::Error::new_spanned(
+            &mac.path,
+            "expected a string literal or `concat!` of string literals",
+        ));
+    }
+    let parts = mac.parse_body_with(Punctuated::<Expr, Comma>::parse_terminated)?;
+    let mut value = String::new();
+    for part in &parts {
+        match part {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) => value.push_str(&lit.value()),
+            Expr::Macro(ExprMacro { mac, .. }) => value.push_str(&concat_lit(mac)?.value()),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    part,
+                    "only string literals can be concatenated, use the `base` option for constants",
+                ))
+            }
+        }
+    }
+    Ok(LitStr::new(&value, mac.path.span()))
+}
+
 pub enum Method {
     Get(Span),
     Post(Span),
@@ -372,6 +2682,8 @@ pub enum Method {
     Connect(Span),
     Options(Span),
     Trace(Span),
+    /// A method without a `MethodFilter`, such as the WebDAV method `PROPFIND`.
+    Custom(Ident),
 }
 
 impl Parse for Method {
@@ -386,8 +2698,13 @@ impl Parse for Method {
             "CONNECT" => Ok(Self::Connect(ident.span())),
             "OPTIONS" => Ok(Self::Options(ident.span())),
             "TRACE" => Ok(Self::Trace(ident.span())),
-            _ => Err(input
-                .error("expected one of (GET, POST, PUT, DELETE, HEAD, CONNECT, OPTIONS, TRACE)")),
+            _ if ident.to_string().chars().all(|c| c.is_ascii_uppercase()) => {
+                Ok(Self::Custom(ident))
+            }
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected one of (GET, POST, PUT, DELETE, HEAD, CONNECT, OPTIONS, TRACE), or an uppercase custom method such as PROPFIND",
+            )),
         }
     }
 }
@@ -403,10 +2720,54 @@ impl Method {
             Self::Connect(span) => Ident::new("connect", *span),
             Self::Options(span) => Ident::new("options", *span),
             Self::Trace(span) => Ident::new("trace", *span),
+            Self::Custom(ident) => Ident::new(&ident.to_string().to_lowercase(), ident.span()),
+        }
+    }
+
+    /// The name of the method, in uppercase.
+    pub fn name(&self) -> String {
+        self.to_axum_method_name().to_string().to_uppercase()
+    }
+
+    /// The `MethodRouter` for `handler`. Custom methods are routed by a fallback that only
+    /// accepts requests with that method.
+    pub fn method_router(&self, handler: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Custom(ident) => {
+                let name = ident.to_string();
+                quote! {
+                    ::axum_typed_routing::__private::custom_method(#name, ::axum::routing::any(#handler))
+                }
+            }
+            _ => {
+                let method = self.to_axum_method_name();
+                quote! { ::axum::routing::#method(#handler) }
+            }
+        }
+    }
+
+    /// Same as [`Self::method_router`], but for a `Service`.
+    pub fn method_service(&self, service: TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Custom(ident) => {
+                let name = ident.to_string();
+                quote! {
+                    ::axum_typed_routing::__private::custom_method(
+                        #name,
+                        ::axum::routing::any_service(#service),
+                    )
+                }
+            }
+            _ => {
+                let method = format_ident!("{}_service", self.to_axum_method_name());
+                quote! { ::axum::routing::#method(#service) }
+            }
         }
     }
 }
 
 mod kw {
     syn::custom_keyword!(with);
+    syn::custom_keyword!(multipart);
+    syn::custom_keyword!(inject);
 }
@@ -0,0 +1,77 @@
+//! Compares the typed routes to the same handlers written for axum by hand, which should take
+//! the same time, since the generated code only adds the extractors of the parameters.
+//!
+//! Run with `cargo bench -p axum-typed-routing`.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, Request},
+    routing::get,
+    Router,
+};
+use axum_typed_routing::{route, TypedRouter};
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde::Deserialize;
+use std::hint::black_box;
+use tokio::runtime::Runtime;
+use tower_service::Service;
+
+#[route(GET "/health")]
+async fn typed_health() -> &'static str {
+    "ok"
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+#[route(GET "/items/:id?page&per_page")]
+async fn typed_item(id: u32, page: Option<u32>, per_page: Option<u32>) -> String {
+    format!("{id} {page:?} {per_page:?}")
+}
+
+#[derive(Deserialize)]
+struct ItemQuery {
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+async fn item(Path(id): Path<u32>, Query(query): Query<ItemQuery>) -> String {
+    format!("{id} {:?} {:?}", query.page, query.per_page)
+}
+
+/// Benchmarks a request to `uri` on the typed and the hand-written router.
+fn compare(c: &mut Criterion, name: &str, uri: &str, typed: Router, by_hand: Router) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group(name);
+    for (router_name, router) in [("typed", typed), ("by_hand", by_hand)] {
+        group.bench_function(router_name, |b| {
+            b.iter(|| {
+                let request = Request::get(uri).body(Body::empty()).unwrap();
+                let response = runtime.block_on(router.clone().call(request)).unwrap();
+                black_box(response)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn routing(c: &mut Criterion) {
+    compare(
+        c,
+        "static_path",
+        "/health",
+        Router::new().typed_route(typed_health),
+        Router::new().route("/health", get(health)),
+    );
+    compare(
+        c,
+        "path_and_query",
+        "/items/42?page=2&per_page=20",
+        Router::new().typed_route(typed_item),
+        Router::new().route("/items/{id}", get(item)),
+    );
+}
+
+criterion_group!(benches, routing);
+criterion_main!(benches);
@@ -0,0 +1,1598 @@
+//! A router builder that remembers the typed routes registered on it.
+
+use crate::{cors, cors::CorsPolicy, BuildError, Error, IntoTypedRoute, TypedRouter};
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{
+        header::{ALLOW, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE, HOST},
+        request::Parts,
+        HeaderName, HeaderValue, Method, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Write,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+};
+
+/// What the macros know about a typed handler, besides its path and method router. It's returned
+/// by the handlers of routes with options the [`TypedRouter`] needs to know about, after their
+/// path and method router. The handlers of other routes return their method and name instead, so
+/// they don't depend on this crate.
+#[derive(Clone, Copy)]
+pub struct RouteDescription {
+    pub method: &'static str,
+    pub handler: &'static str,
+    pub aliases: &'static [&'static str],
+    pub deprecate_alias: bool,
+    /// The `(locale, path)` of the localized paths of the route.
+    pub localized: &'static [(&'static str, &'static str)],
+    /// Whether the route is added to the router, `false` if its `enabled_if` option is `false`.
+    pub enabled: bool,
+    /// The OpenAPI operation id of an `api_route`.
+    pub operation_id: Option<&'static str>,
+    /// The OpenAPI tags of an `api_route`.
+    pub tags: &'static [&'static str],
+    /// The `(priority, changefreq)` of the `sitemap` option of the route, or `None` if it's left
+    /// out of the sitemap with `sitemap: false`.
+    pub sitemap: Option<(Option<&'static str>, Option<&'static str>)>,
+    /// The policy of the `cors` option of the route.
+    pub cors: Option<&'static CorsPolicy>,
+    /// The `permissions` option of the route.
+    pub permissions: &'static [&'static str],
+}
+
+impl RouteDescription {
+    /// The description of a route without options.
+    pub const fn new(method: &'static str, handler: &'static str) -> Self {
+        Self {
+            method,
+            handler,
+            aliases: &[],
+            deprecate_alias: false,
+            localized: &[],
+            enabled: true,
+            operation_id: None,
+            tags: &[],
+            sitemap: Some((None, None)),
+            cors: None,
+            permissions: &[],
+        }
+    }
+
+    /// The description returned by a typed handler after its path and method router: a
+    /// `RouteDescription`, or the `(method, name)` of a route without options.
+    fn of(description: Box<dyn Any>) -> Option<Self> {
+        match description.downcast::<Self>() {
+            Ok(description) => Some(*description),
+            Err(description) => description
+                .downcast::<(&'static str, &'static str)>()
+                .ok()
+                .map(|description| Self::new(description.0, description.1)),
+        }
+    }
+}
+
+/// The value returned by a typed handler: the path and the method router of the route, followed
+/// by its description if the handler was created by the macros. Implemented for the
+/// `(path, method_router)` returned by closures, and the `(path, method_router, description)`
+/// returned by the macros.
+pub trait TypedRouteValue {
+    /// The method router of the route, e.g. axum's `MethodRouter<S>`.
+    type MethodRouter;
+
+    /// The path, the method router and the description of the route.
+    fn into_parts(self) -> (&'static str, Self::MethodRouter, Option<Box<dyn Any>>);
+}
+
+impl<M> TypedRouteValue for (&'static str, M) {
+    type MethodRouter = M;
+
+    fn into_parts(self) -> (&'static str, M, Option<Box<dyn Any>>) {
+        (self.0, self.1, None)
+    }
+}
+
+impl<M> TypedRouteValue for (&'static str, M, Box<dyn Any>) {
+    type MethodRouter = M;
+
+    fn into_parts(self) -> (&'static str, M, Option<Box<dyn Any>>) {
+        (self.0, self.1, Some(self.2))
+    }
+}
+
+/// Adds the `Deprecation` and `Sunset` headers of a deprecated route to its `response`.
+pub async fn deprecation_headers(mut response: Response, sunset: Option<&'static str>) -> Response {
+    let headers = response.headers_mut();
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static("true"),
+    );
+    if let Some(sunset) = sunset {
+        headers.insert(
+            HeaderName::from_static("sunset"),
+            HeaderValue::from_static(sunset),
+        );
+    }
+    response
+}
+
+/// Sets the `Cache-Control` header of a successful `response` to `value`, unless the handler set
+/// it already.
+pub async fn cache_control(mut response: Response, value: &'static str) -> Response {
+    if response.status().is_success() && !response.headers().contains_key(CACHE_CONTROL) {
+        response
+            .headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static(value));
+    }
+    response
+}
+
+/// Marks `response` with `Content-Encoding: identity`, so compression layers leave it untouched,
+/// unless it's encoded already.
+pub async fn disable_compression(mut response: Response) -> Response {
+    if !response.headers().contains_key(CONTENT_ENCODING) {
+        response
+            .headers_mut()
+            .insert(CONTENT_ENCODING, HeaderValue::from_static("identity"));
+    }
+    response
+}
+
+/// Requests `uri` from `router`, which routes it to a handler with the path- and query-extractors
+/// of a typed route, and panics unless they succeed. This is the test of the `generate_test`
+/// option.
+pub async fn assert_extracts(mut router: axum::Router, uri: &str) {
+    let uri = match uri.starts_with('/') {
+        true => uri.to_string(),
+        false => format!("/{uri}"),
+    };
+    let request = Request::builder()
+        .uri(&uri)
+        .body(axum::body::Body::empty())
+        .unwrap_or_else(|err| panic!("invalid sample request `{uri}`: {err}"));
+    std::future::poll_fn(|cx| tower_service::Service::<Request>::poll_ready(&mut router, cx))
+        .await
+        .unwrap_or_else(|err| match err {});
+    let response = tower_service::Service::call(&mut router, request)
+        .await
+        .unwrap_or_else(|err| match err {});
+    let status = response.status();
+    if !status.is_success() {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await;
+        let body = body.unwrap_or_default();
+        panic!(
+            "the parameters of `{uri}` weren't extracted: {status} {}",
+            String::from_utf8_lossy(&body)
+        );
+    }
+}
+
+/// The future of the handler of an `erased` route, which is boxed so it isn't nested in the
+/// futures of axum for every instantiation of a generic handler.
+pub type ErasedFuture = Pin<Box<dyn Future<Output = Response> + Send>>;
+
+/// The layers of an `erased` route, applied by [`erased_layers`].
+pub struct RouteLayers {
+    /// The `Sunset` header of a deprecated route, if it has one.
+    pub deprecation: Option<Option<&'static str>>,
+    pub cache_control: Option<&'static str>,
+    pub disable_compression: bool,
+    pub cors: Option<&'static CorsPolicy>,
+    #[cfg(feature = "deadline")]
+    pub deadline: Option<&'static crate::deadline::DeadlinePolicy>,
+    pub locale: Option<&'static str>,
+}
+
+/// Applies the `layers` of an `erased` route to its `router`. Unlike the `layer` calls generated
+/// for other routes, this is only instantiated once for every state type.
+pub fn erased_layers<S>(
+    mut router: MethodRouter<S>,
+    layers: &'static RouteLayers,
+) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    #[cfg(feature = "deadline")]
+    if let Some(policy) = layers.deadline {
+        router = router.layer(axum::middleware::from_fn(move |request, next| {
+            crate::deadline::deadline(policy, request, next)
+        }));
+    }
+    if let Some(sunset) = layers.deprecation {
+        router = router.layer(axum::middleware::map_response(move |response| {
+            deprecation_headers(response, sunset)
+        }));
+    }
+    if let Some(value) = layers.cache_control {
+        router = router.layer(axum::middleware::map_response(move |response| {
+            cache_control(response, value)
+        }));
+    }
+    if layers.disable_compression {
+        router = router.layer(axum::middleware::map_response(disable_compression));
+    }
+    if let Some(policy) = layers.cors {
+        router = router.layer(axum::middleware::from_fn(move |request, next| {
+            cors::cors_headers(policy, request, next)
+        }));
+    }
+    if let Some(locale) = layers.locale {
+        router = router.layer(axum::middleware::map_request(move |request| {
+            default_locale(request, locale)
+        }));
+    }
+    router
+}
+
+/// Restricts `router`, which handles any method, to the custom `method`, such as `PROPFIND`.
+/// axum's `MethodFilter` only has the standard methods, so custom methods are routed by a
+/// fallback, and other methods are rejected with `405 Method Not Allowed`.
+pub fn custom_method<S>(method: &'static str, router: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let method = Method::from_bytes(method.as_bytes()).expect("invalid custom method");
+    router.layer(axum::middleware::from_fn(
+        move |request: Request, next: Next| {
+            let method = method.clone();
+            async move {
+                match request.method() == method {
+                    true => next.run(request).await,
+                    false => {
+                        (StatusCode::METHOD_NOT_ALLOWED, [(ALLOW, method.as_str())]).into_response()
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// Calls the typed `handler`, returning its path and method router, and its [`RouteDescription`]
+/// if it was created by the macros.
+fn typed_route_parts<V: TypedRouteValue>(
+    handler: impl FnOnce() -> V,
+) -> (&'static str, V::MethodRouter, Option<RouteDescription>) {
+    let (path, method_router, description) = handler().into_parts();
+    (
+        path,
+        method_router,
+        description.and_then(RouteDescription::of),
+    )
+}
+
+/// The path and method of the typed `handler`. The method is `None` for handlers that weren't
+/// created by the macros.
+#[cfg(feature = "axum-test")]
+pub(crate) fn typed_handler_route<V: TypedRouteValue>(
+    handler: fn() -> V,
+) -> (&'static str, Option<&'static str>) {
+    let (path, _method_router, description) = typed_route_parts(handler);
+    (path, description.map(|description| description.method))
+}
+
+/// Calls the typed `handler`, adds it to `router` with `route`, and adds the aliases of the
+/// route. Returns the router and the routes that were added.
+pub(crate) fn route_typed_handler<R, V>(
+    router: R,
+    handler: impl FnOnce() -> V,
+    route: impl FnOnce(R, &'static str, V::MethodRouter) -> R,
+) -> (R, Vec<RouteInfo>)
+where
+    R: sealed::BuildRouter,
+    V: TypedRouteValue,
+    V::MethodRouter: Clone + Into<MethodRouter<R::State>>,
+{
+    let (path, method_router, description) = typed_route_parts(handler);
+    add_typed_handler(router, path, method_router, description, route)
+}
+
+/// Adds the route of a typed handler with the given path, method router and
+/// [`RouteDescription`] to `router` like [`route_typed_handler`].
+fn add_typed_handler<R, M>(
+    router: R,
+    path: &'static str,
+    method_router: M,
+    description: Option<RouteDescription>,
+    route: impl FnOnce(R, &'static str, M) -> R,
+) -> (R, Vec<RouteInfo>)
+where
+    R: sealed::BuildRouter,
+    M: Clone + Into<MethodRouter<R::State>>,
+{
+    let routes = route_infos(path, description);
+    let mut router = add_described_route(router, path, method_router, description, route);
+    for (path, method, policy) in cors_routes(&routes, description) {
+        router = router.add_route(path, cors::preflight_fallback(method, policy));
+    }
+    (router, routes)
+}
+
+/// Calls the typed `handler` and adds it to `router` like [`route_typed_handler`], with its
+/// method router replaced by the one `wrap` returns for its canonical route. Its routes are
+/// added to `route_map` and returned, and are empty if the route is disabled.
+#[cfg(feature = "dynamic")]
+pub(crate) fn route_wrapped_handler<S, H: IntoTypedRoute<S>>(
+    router: axum::Router<S>,
+    route_map: &mut RouteMap,
+    handler: H,
+    wrap: impl FnOnce(RouteInfo, MethodRouter<S>) -> MethodRouter<S>,
+) -> (axum::Router<S>, Vec<RouteInfo>)
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let key = HandlerKey::of::<H, H::Value>(&handler);
+    let (path, method_router, description) = typed_route_parts(|| handler.call());
+    let Some(route) = route_infos(path, description).first().copied() else {
+        return (router, Vec::new());
+    };
+    let method_router = wrap(route, method_router);
+    let (router, routes) = add_typed_handler(
+        router,
+        path,
+        method_router,
+        description,
+        axum::Router::route,
+    );
+    route_map.insert(key, &routes);
+    (router, routes)
+}
+
+/// The method router and the routes of the typed `handler`, starting with its canonical route.
+#[cfg(feature = "dynamic")]
+pub(crate) fn typed_handler_routes<V: TypedRouteValue>(
+    handler: impl FnOnce() -> V,
+) -> (V::MethodRouter, Vec<RouteInfo>) {
+    let (path, method_router, description) = typed_route_parts(handler);
+    (method_router, route_infos(path, description))
+}
+
+/// The `(path, method, policy)` of the `routes` of a typed handler with the `cors` option.
+fn cors_routes(
+    routes: &[RouteInfo],
+    description: Option<RouteDescription>,
+) -> Vec<(&'static str, &'static str, &'static CorsPolicy)> {
+    let Some(policy) = description.and_then(|description| description.cors) else {
+        return Vec::new();
+    };
+    routes
+        .iter()
+        .map(|route| (route.path, route.method, policy))
+        .collect()
+}
+
+/// The routes of a typed handler with the given path and [`RouteDescription`].
+fn route_infos(path: &'static str, description: Option<RouteDescription>) -> Vec<RouteInfo> {
+    let Some(description) = description else {
+        return vec![RouteInfo {
+            method: "*",
+            path,
+            handler: "<unknown>",
+            permissions: &[],
+        }];
+    };
+    if !description.enabled {
+        return Vec::new();
+    }
+    let localized = description.localized.iter().map(|(_locale, path)| *path);
+    std::iter::once(path)
+        .chain(description.aliases.iter().copied())
+        .chain(localized)
+        .map(|path| RouteInfo {
+            method: description.method,
+            path,
+            handler: description.handler,
+            permissions: description.permissions,
+        })
+        .collect()
+}
+
+/// The sitemap entries of a typed handler with the given path and [`RouteDescription`]: its path and
+/// localized paths, if it's an enabled `GET` route without path parameters. Aliases aren't
+/// listed, since they are duplicates of the canonical path.
+fn sitemap_entries(path: &'static str, description: Option<RouteDescription>) -> Vec<SitemapEntry> {
+    let Some(description) = description.filter(|d| d.enabled && d.method == "GET") else {
+        return Vec::new();
+    };
+    let Some((priority, changefreq)) = description.sitemap else {
+        return Vec::new();
+    };
+    let localized = description.localized.iter().map(|(_locale, path)| *path);
+    std::iter::once(path)
+        .chain(localized)
+        .filter(|path| !path.contains('{'))
+        .map(|path| SitemapEntry {
+            path,
+            priority,
+            changefreq,
+        })
+        .collect()
+}
+
+/// The [`RouteDetails`] of the `routes` of a typed handler with the given [`RouteDescription`].
+fn route_details(routes: &[RouteInfo], description: Option<RouteDescription>) -> Vec<RouteDetails> {
+    routes
+        .iter()
+        .map(|route| RouteDetails {
+            route: *route,
+            operation_id: description.and_then(|description| description.operation_id),
+            tags: description.map_or(&[], |description| description.tags),
+        })
+        .collect()
+}
+
+/// Formats `routes` as a JSON array of objects with their method, path, handler, operation id
+/// and tags.
+fn routes_json(routes: &[RouteDetails]) -> String {
+    let mut json = String::from("[");
+    for (i, details) in routes.iter().enumerate() {
+        let RouteInfo {
+            method,
+            path,
+            handler,
+            ..
+        } = details.route;
+        let operation_id = match details.operation_id {
+            Some(operation_id) => json_string(operation_id),
+            None => "null".to_string(),
+        };
+        let tags = details.tags.iter().map(|tag| json_string(tag));
+        let _ = write!(
+            json,
+            "{}{{\"method\":{},\"path\":{},\"handler\":{},\"operation_id\":{operation_id},\"tags\":[{}]}}",
+            if i == 0 { "" } else { "," },
+            json_string(method),
+            json_string(path),
+            json_string(handler),
+            tags.collect::<Vec<_>>().join(","),
+        );
+    }
+    json.push(']');
+    json
+}
+
+/// Formats `text` as a JSON string literal.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Formats `entries` as a `sitemap.xml` document, with their paths appended to `base_url`.
+fn sitemap_xml(entries: &[SitemapEntry], base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    let mut xml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    ));
+    for entry in entries {
+        let loc = xml_escape(&format!("{base_url}{}", entry.path));
+        let _ = writeln!(xml, "  <url>\n    <loc>{loc}</loc>");
+        if let Some(changefreq) = entry.changefreq {
+            let _ = writeln!(xml, "    <changefreq>{changefreq}</changefreq>");
+        }
+        if let Some(priority) = entry.priority {
+            let _ = writeln!(xml, "    <priority>{priority}</priority>");
+        }
+        xml.push_str("  </url>\n");
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Adds the method router of a typed handler to `router` with `route`, and adds its aliases.
+fn add_described_route<R, M>(
+    router: R,
+    path: &'static str,
+    method_router: M,
+    description: Option<RouteDescription>,
+    route: impl FnOnce(R, &'static str, M) -> R,
+) -> R
+where
+    R: sealed::BuildRouter,
+    M: Clone + Into<MethodRouter<R::State>>,
+{
+    let Some(description) = description else {
+        return route(router, path, method_router);
+    };
+    if !description.enabled {
+        return router;
+    }
+
+    let mut router = route(router, path, method_router.clone());
+    for alias in description.aliases {
+        let mut alias_router = method_router.clone().into();
+        if description.deprecate_alias {
+            alias_router = alias_router.layer(axum::middleware::map_response(
+                |mut response: Response| async move {
+                    response.headers_mut().insert(
+                        HeaderName::from_static("deprecation"),
+                        HeaderValue::from_static("true"),
+                    );
+                    response
+                },
+            ));
+        }
+        router = router.add_route(alias, alias_router);
+    }
+    for &(locale, path) in description.localized {
+        let localized_router = method_router
+            .clone()
+            .into()
+            .layer(axum::middleware::map_request(
+                move |mut request: Request| async move {
+                    request.extensions_mut().insert(Locale(locale));
+                    request
+                },
+            ));
+        router = router.add_route(path, localized_router);
+    }
+    router
+}
+
+/// The locale of the requested path of a route with the `locale` and `localized` options, e.g.
+/// `"de"` for `/de/artikel/1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Locale(pub &'static str);
+
+impl<S: Send + Sync> FromRequestParts<S> for Locale {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Locale>().copied().ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "the route has no `locale`",
+        ))
+    }
+}
+
+/// The typed route that handled a request, with the `matched-route` feature: its handler,
+/// path and method, e.g. for auditing or logging, without parsing axum's `MatchedPath`.
+///
+/// Every typed route inserts it into the extensions of its requests, where it can be extracted
+/// by handlers, guards and `route_layer` middleware, and of its responses, where it can be read
+/// by middleware added with `Router::layer`, which runs before the route:
+/// ```ignore
+/// async fn audit(request: Request, next: Next) -> Response {
+///     let response = next.run(request).await;
+///     if let Some(route) = response.extensions().get::<MatchedTypedRoute>() {
+///         tracing::info!(handler = route.name, status = %response.status());
+///     }
+///     response
+/// }
+/// ```
+#[cfg(feature = "matched-route")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MatchedTypedRoute {
+    /// The name of the handler, including its module path, e.g. `my_crate::items::get_item`.
+    pub name: &'static str,
+    /// The axum path of the route, e.g. `/item/{id}`. Aliases and localized paths of the route
+    /// have the template of its canonical path.
+    pub template: &'static str,
+    /// The HTTP method, in uppercase.
+    pub method: &'static str,
+    /// The `permissions` option of the route, e.g. `["items:read"]`.
+    pub permissions: &'static [&'static str],
+}
+
+#[cfg(feature = "matched-route")]
+impl<S: Send + Sync> FromRequestParts<S> for MatchedTypedRoute {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<MatchedTypedRoute>().copied().ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "the request wasn't handled by a typed route",
+        ))
+    }
+}
+
+/// Inserts the [`MatchedTypedRoute`] of a typed route into the extensions of the `request` and
+/// its response.
+#[cfg(feature = "matched-route")]
+pub async fn matched_typed_route(
+    axum::extract::State(route): axum::extract::State<MatchedTypedRoute>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    request.extensions_mut().insert(route);
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(route);
+    response
+}
+
+/// Creates the spans of the [`trace_layer`](TypedRouterBuilder::trace_layer) of a
+/// [`TypedRouterBuilder`], with the `trace` feature.
+///
+/// The route of a request is looked up by its method and axum's `MatchedPath`, so the layer must
+/// be added with `Router::layer`, which runs after the request is routed. The spans are named
+/// `request`, with the fields:
+/// - `method`: the HTTP method, e.g. `GET`.
+/// - `route`: the path of the route, e.g. `/item/{id}`, or the path of the URI if the request
+///   wasn't routed.
+/// - `handler`: the name of the handler, e.g. `my_crate::items::get_item`.
+/// - `operation_id`: the operation id of an `api_route`.
+/// - `otel.name`: the method and route, e.g. `GET /item/{id}`, the name of the span for
+///   OpenTelemetry.
+#[cfg(feature = "trace")]
+#[derive(Debug, Clone)]
+pub struct TypedMakeSpan {
+    /// The routes by path.
+    routes: std::sync::Arc<HashMap<&'static str, Vec<RouteDetails>>>,
+}
+
+#[cfg(feature = "trace")]
+impl TypedMakeSpan {
+    fn new(details: &[RouteDetails]) -> Self {
+        let mut routes = HashMap::<_, Vec<_>>::new();
+        for details in details {
+            routes.entry(details.route.path).or_default().push(*details);
+        }
+        Self {
+            routes: std::sync::Arc::new(routes),
+        }
+    }
+}
+
+#[cfg(feature = "trace")]
+impl<B> tower_http::trace::MakeSpan<B> for TypedMakeSpan {
+    fn make_span(&mut self, request: &axum::http::Request<B>) -> tracing::Span {
+        let method = request.method().as_str();
+        let matched_path = request.extensions().get::<axum::extract::MatchedPath>();
+        let route = matched_path.map_or(request.uri().path(), |path| path.as_str());
+        let details = self
+            .routes
+            .get(route)
+            .and_then(|routes| routes.iter().find(|details| details.route.method == method));
+        tracing::info_span!(
+            "request",
+            method,
+            route,
+            handler = details.map(|details| details.route.handler),
+            operation_id = details.and_then(|details| details.operation_id),
+            otel.name = format!("{method} {route}"),
+        )
+    }
+}
+
+/// The `permissions` options of the typed routes of a [`TypedRouterBuilder`], returned by
+/// [`route_permissions`](TypedRouterBuilder::route_permissions), so a single authorization
+/// middleware can enforce them:
+/// ```ignore
+/// async fn authorize(
+///     State(permissions): State<RoutePermissions>,
+///     session: Session,
+///     request: Request,
+///     next: Next,
+/// ) -> Response {
+///     let required = permissions.required(&request);
+///     if !required.iter().all(|permission| session.has_permission(permission)) {
+///         return StatusCode::FORBIDDEN.into_response();
+///     }
+///     next.run(request).await
+/// }
+///
+/// let permissions = builder.route_permissions();
+/// let router = builder
+///     .build()?
+///     .layer(axum::middleware::from_fn_with_state(permissions, authorize));
+/// ```
+/// The route of a request is looked up by its method and axum's `MatchedPath`, so the middleware
+/// must be added with `Router::layer`, which runs after the request is routed.
+#[derive(Debug, Clone, Default)]
+pub struct RoutePermissions {
+    /// The `(method, permissions)` of the routes with a `permissions` option, by path.
+    #[allow(clippy::type_complexity)]
+    routes: std::sync::Arc<HashMap<&'static str, Vec<(&'static str, &'static [&'static str])>>>,
+}
+
+impl RoutePermissions {
+    fn new(routes: &[RouteInfo]) -> Self {
+        let mut permissions = HashMap::<_, Vec<_>>::new();
+        for route in routes.iter().filter(|route| !route.permissions.is_empty()) {
+            permissions
+                .entry(route.path)
+                .or_default()
+                .push((route.method, route.permissions));
+        }
+        Self {
+            routes: std::sync::Arc::new(permissions),
+        }
+    }
+
+    /// The permissions required by the route of `request`, which are empty for a route without
+    /// a `permissions` option or a request that wasn't routed. `HEAD` requests require the
+    /// permissions of the `GET` route.
+    pub fn required<B>(&self, request: &axum::http::Request<B>) -> &'static [&'static str] {
+        let Some(path) = request.extensions().get::<axum::extract::MatchedPath>() else {
+            return &[];
+        };
+        self.get(request.method().as_str(), path.as_str())
+    }
+
+    /// The permissions required by the route with the `method` and axum `path`, e.g.
+    /// `("GET", "/items/{id}")`.
+    pub fn get(&self, method: &str, path: &str) -> &'static [&'static str] {
+        let method = match method {
+            "HEAD" => "GET",
+            method => method,
+        };
+        let routes = self.routes.get(path).map_or(&[][..], Vec::as_slice);
+        routes
+            .iter()
+            .find(|(route_method, _permissions)| *route_method == method)
+            .map_or(&[], |(_method, permissions)| *permissions)
+    }
+}
+
+/// Provides the `locale` of a route to [`Locale`], unless a localized path provided its own.
+pub async fn default_locale(mut request: Request, locale: &'static str) -> Request {
+    if request.extensions().get::<Locale>().is_none() {
+        request.extensions_mut().insert(Locale(locale));
+    }
+    request
+}
+
+/// Passes on requests to the `host` of a route, and rejects the others with `404 Not Found`. The
+/// host is taken from the `Host` header, or from the URI of HTTP/2 requests, which don't have one.
+pub async fn require_host(request: Request, host: &'static str) -> Result<Request, StatusCode> {
+    let authority = match request.headers().get(HOST) {
+        Some(value) => value.to_str().ok(),
+        None => request
+            .uri()
+            .authority()
+            .map(|authority| authority.as_str()),
+    };
+    let authority = authority.unwrap_or_default();
+    let requested = match host.contains(':') {
+        true => authority,
+        false => authority.split(':').next().unwrap_or_default(),
+    };
+    match requested.eq_ignore_ascii_case(host) {
+        true => Ok(request),
+        false => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Decides whether the routes with a `feature_flag` are enabled, for every request. It's
+/// implemented by the state of the router, e.g. with a client of a feature flag service, so
+/// endpoints can be rolled out progressively:
+/// ```ignore
+/// impl FeatureGate for AppState {
+///     fn is_enabled(&self, flag: &str, _parts: &Parts) -> bool {
+///         self.flags.is_enabled(flag)
+///     }
+/// }
+/// ```
+pub trait FeatureGate {
+    /// Whether the feature `flag` is enabled for the request with the given `parts`, e.g. for
+    /// the user of its session cookie.
+    fn is_enabled(&self, flag: &str, parts: &Parts) -> bool;
+
+    /// The status of the requests to a route whose `flag` is disabled: `404 Not Found` by
+    /// default, as if the route didn't exist, or e.g. `503 Service Unavailable`.
+    fn disabled_status(&self, flag: &str) -> StatusCode {
+        let _ = flag;
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// The name of the feature flag checked by [`RequireFeature`].
+pub trait FeatureFlagName {
+    const NAME: &'static str;
+}
+
+/// Extracted by the routes with a `feature_flag` before their other arguments, and rejects the
+/// request with the [`FeatureGate::disabled_status`] if the flag `F::NAME` is disabled.
+pub struct RequireFeature<F>(PhantomData<F>);
+
+impl<F, S> FromRequestParts<S> for RequireFeature<F>
+where
+    F: FeatureFlagName,
+    S: FeatureGate + Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match state.is_enabled(F::NAME, parts) {
+            true => Ok(Self(PhantomData)),
+            false => Err(state.disabled_status(F::NAME)),
+        }
+    }
+}
+
+/// Information about a typed route registered on a [`TypedRouterBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouteInfo {
+    /// The HTTP method, in uppercase. This is `*` for handlers that weren't created by the
+    /// macros.
+    pub method: &'static str,
+    /// The axum path of the route, e.g. `/item/{id}`.
+    pub path: &'static str,
+    /// The name of the handler, including its module path, e.g. `my_crate::items::get_item`.
+    /// This is `<unknown>` for handlers that weren't created by the macros.
+    pub handler: &'static str,
+    /// The `permissions` option of the route, e.g. `["items:read"]`, for an authorization
+    /// middleware, see [`RoutePermissions`].
+    pub permissions: &'static [&'static str],
+}
+
+/// A typed route with its OpenAPI operation id and tags, returned by
+/// [`route_details`](TypedRouterBuilder::route_details).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouteDetails {
+    /// The method, path and handler of the route.
+    pub route: RouteInfo,
+    /// The operation id of an `api_route`.
+    pub operation_id: Option<&'static str>,
+    /// The tags of an `api_route`.
+    pub tags: &'static [&'static str],
+}
+
+/// A page of the sitemap of a [`TypedRouterBuilder`], returned by
+/// [`sitemap_entries`](TypedRouterBuilder::sitemap_entries).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SitemapEntry {
+    /// The path of the page, e.g. `/about`.
+    pub path: &'static str,
+    /// The `priority` of the `sitemap` option of the route, e.g. `0.8`.
+    pub priority: Option<&'static str>,
+    /// The `changefreq` of the `sitemap` option of the route, e.g. `daily`.
+    pub changefreq: Option<&'static str>,
+}
+
+/// Wraps a router ([`axum::Router`] or `aide`'s `ApiRouter`), and remembers all typed routes
+/// added to it, so they can be listed when the service starts:
+/// ```ignore
+/// let builder = TypedRouterBuilder::new(Router::new())
+///     .typed_route(item_handler)
+///     .typed_route(create_item);
+/// builder.print_routes();
+/// let router = builder.into_router();
+/// ```
+///
+/// Routes conflicting with a route that was added before, with an invalid path, or with the
+/// operation id of another handler aren't added to the router, but collected as an [`Error`],
+/// so [`build`](Self::build) can report all of them at once.
+#[derive(Debug, Clone, Default)]
+pub struct TypedRouterBuilder<R> {
+    router: R,
+    routes: Vec<RouteInfo>,
+    details: Vec<RouteDetails>,
+    route_map: RouteMap,
+    errors: Vec<Error>,
+    auto_options: bool,
+    /// The `(path, method, policy)` of the routes with the `cors` option, whose preflight routes
+    /// are added by [`build`](Self::build).
+    cors: Vec<(&'static str, &'static str, &'static CorsPolicy)>,
+    sitemap: Vec<SitemapEntry>,
+    /// The base URL of the `/sitemap.xml` route, if it's added by [`build`](Self::build).
+    sitemap_url: Option<String>,
+}
+
+/// Two typed routes that can't be added to the same router: they have the same method and
+/// path, or the same path with differently named path parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouteConflict {
+    /// The route that was added first.
+    pub existing: RouteInfo,
+    /// The route that conflicts with it.
+    pub conflicting: RouteInfo,
+}
+
+/// The routes of the typed handlers added to a [`TypedRouterBuilder`], by handler, returned by
+/// [`route_map`](TypedRouterBuilder::route_map).
+///
+/// Handlers are identified by their function, so the map can answer what the path and method of
+/// a handler are at runtime:
+/// ```ignore
+/// let route_map = builder.route_map();
+/// assert_eq!(route_map.get(get_item).unwrap().path, "/item/{id}");
+/// ```
+/// A handler added as a [`TypedHandler`](crate::TypedHandler) function pointer, e.g. from a
+/// `Vec<TypedHandler<S>>`, is identified by the pointer, so it must be looked up with a pointer
+/// too. Closures are identified by their type, so the routes of all closures created by the same
+/// expression are merged.
+#[derive(Debug, Clone, Default)]
+pub struct RouteMap {
+    routes: HashMap<HandlerKey, Vec<RouteInfo>>,
+}
+
+impl RouteMap {
+    /// The canonical route of `handler`, which is its path without aliases or localized paths,
+    /// or `None` if it wasn't added to the builder.
+    pub fn get<H, V>(&self, handler: H) -> Option<RouteInfo>
+    where
+        H: FnOnce() -> V + 'static,
+        V: 'static,
+    {
+        self.routes(handler).first().copied()
+    }
+
+    /// All routes of `handler`, starting with its canonical route, followed by its aliases and
+    /// localized paths.
+    pub fn routes<H, V>(&self, handler: H) -> &[RouteInfo]
+    where
+        H: FnOnce() -> V + 'static,
+        V: 'static,
+    {
+        self.routes
+            .get(&HandlerKey::of::<H, V>(&handler))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The number of handlers in the map.
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// Whether the map contains no handlers.
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    fn insert(&mut self, handler: HandlerKey, routes: &[RouteInfo]) {
+        if !routes.is_empty() {
+            self.routes
+                .entry(handler)
+                .or_default()
+                .extend_from_slice(routes);
+        }
+    }
+}
+
+/// Identifies a typed handler in a [`RouteMap`]: a function pointer by its address, and any other
+/// function or closure by its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum HandlerKey {
+    Pointer(usize),
+    Type(TypeId),
+}
+
+impl HandlerKey {
+    /// The key of `handler`, which returns a `V`.
+    fn of<H: 'static, V: 'static>(handler: &H) -> Self {
+        match (handler as &dyn Any).downcast_ref::<fn() -> V>() {
+            Some(pointer) => Self::Pointer(*pointer as *const () as usize),
+            None => Self::Type(TypeId::of::<H>()),
+        }
+    }
+}
+
+/// The routes of `new` conflicting with routes of `existing`.
+fn find_conflicts(existing: &[RouteInfo], new: &[RouteInfo]) -> Vec<RouteConflict> {
+    new.iter()
+        .flat_map(|conflicting| {
+            existing
+                .iter()
+                .filter(|existing| conflicts(existing, conflicting))
+                .map(|existing| RouteConflict {
+                    existing: *existing,
+                    conflicting: *conflicting,
+                })
+        })
+        .collect()
+}
+
+/// The routes and operation id of the typed `handler`, for [`assert_no_conflicts`].
+pub fn describe_handler<V: TypedRouteValue>(
+    handler: fn() -> V,
+) -> (Vec<RouteInfo>, Option<&'static str>) {
+    let (path, _method_router, description) = typed_route_parts(handler);
+    let routes = route_infos(path, description);
+    let operation_id = description
+        .and_then(|description| description.operation_id)
+        .filter(|_| !routes.is_empty());
+    (routes, operation_id)
+}
+
+/// Panics if any of the `handlers` described by [`describe_handler`] have an invalid path,
+/// conflict, or share an operation id. This is the test of
+/// [`assert_no_conflicts!`](crate::assert_no_conflicts).
+pub fn assert_no_conflicts(handlers: Vec<(Vec<RouteInfo>, Option<&'static str>)>) {
+    let mut details = Vec::new();
+    let mut errors = Vec::new();
+    for (routes, operation_id) in handlers {
+        let handler_details = routes
+            .iter()
+            .map(|route| RouteDetails {
+                route: *route,
+                operation_id,
+                tags: &[],
+            })
+            .collect::<Vec<_>>();
+        errors.extend(route_errors(&details, &handler_details));
+        details.extend(handler_details);
+    }
+    if !errors.is_empty() {
+        panic!("{}", BuildError { errors });
+    }
+}
+
+/// The errors of adding the `new` routes to a router with the `existing` routes: the invalid
+/// paths of the new routes, or their conflicts and duplicate operation ids.
+fn route_errors(existing: &[RouteDetails], new: &[RouteDetails]) -> Vec<Error> {
+    let invalid_paths = new
+        .iter()
+        .filter_map(|details| {
+            let reason = validate_path(details.route.path).err()?;
+            Some(Error::InvalidPath {
+                route: details.route,
+                reason,
+            })
+        })
+        .collect::<Vec<_>>();
+    if !invalid_paths.is_empty() {
+        return invalid_paths;
+    }
+
+    let existing_routes = existing
+        .iter()
+        .map(|details| details.route)
+        .collect::<Vec<_>>();
+    let new_routes = new.iter().map(|details| details.route).collect::<Vec<_>>();
+    let mut errors = find_conflicts(&existing_routes, &new_routes)
+        .into_iter()
+        .map(Error::Conflict)
+        .collect::<Vec<_>>();
+    // The aliases and localized paths of a handler share its operation id, so only the first
+    // route of every handler is reported.
+    let mut reported = Vec::new();
+    for details in new {
+        let Some(operation_id) = details.operation_id else {
+            continue;
+        };
+        let existing = existing.iter().find(|existing| {
+            existing.operation_id == Some(operation_id)
+                && existing.route.handler != details.route.handler
+        });
+        if let Some(existing) = existing {
+            if !reported.contains(&details.route.handler) {
+                reported.push(details.route.handler);
+                errors.push(Error::DuplicateOperationId {
+                    operation_id,
+                    existing: existing.route,
+                    duplicate: details.route,
+                });
+            }
+        }
+    }
+    errors
+}
+
+/// Checks that none of the typed handlers conflict with each other, in a generated `#[test]`
+/// named `no_route_conflicts`.
+///
+/// Two handlers conflict if axum would refuse to add both to the same router, like with
+/// [`TypedRouterBuilder::build`], or if they are `api_route`s with the same operation id. The
+/// paths of the routes are only known when the handlers are called, so this can't be checked
+/// at compile time. Unlike the builder, this doesn't need the handlers to share a state type:
+/// ```ignore
+/// assert_no_conflicts!(get_item, create_item, admin::delete_item);
+/// ```
+#[macro_export]
+macro_rules! assert_no_conflicts {
+    ($($handler:expr),* $(,)?) => {
+        #[test]
+        fn no_route_conflicts() {
+            $crate::__private::assert_no_conflicts(::std::vec![
+                $($crate::__private::describe_handler($handler)),*
+            ]);
+        }
+    };
+}
+
+/// Merges the method routers of typed handlers on the same path into a single typed handler
+/// value `(path, method_router)`, e.g. to pass it to `nest_service` or add a `route_layer`
+/// yourself:
+/// ```ignore
+/// let (path, method_router) = merge_method_routers!(get_item, update_item, delete_item);
+/// let router = Router::new().route(path, method_router.route_layer(auth_layer));
+/// ```
+///
+/// Panics if the paths of the handlers differ, or if two handlers use the same method. Disabled
+/// routes are left out, and aliases and localized paths aren't added, since a method router has
+/// a single path.
+#[macro_export]
+macro_rules! merge_method_routers {
+    ($($handler:expr),+ $(,)?) => {
+        $crate::__private::merge_method_routers(&[
+            $($handler as fn() -> _),+
+        ])
+    };
+}
+
+/// Merges the method routers of the `handlers`, for
+/// [`merge_method_routers!`](crate::merge_method_routers).
+pub fn merge_method_routers<S, V>(handlers: &[fn() -> V]) -> (&'static str, MethodRouter<S>)
+where
+    S: Clone + Send + Sync + 'static,
+    V: TypedRouteValue,
+    V::MethodRouter: Into<MethodRouter<S>>,
+{
+    let mut merged: Option<(&'static str, &'static str)> = None;
+    let mut merged_router = MethodRouter::new();
+    let mut cors_routes = Vec::new();
+    for handler in handlers {
+        let (path, method_router, description) = typed_route_parts(*handler);
+        if description.is_some_and(|description| !description.enabled) {
+            continue;
+        }
+        if let Some(description) = description {
+            cors_routes.extend(description.cors.map(|policy| (description.method, policy)));
+        }
+        let name = description.map_or("<unknown>", |description| description.handler);
+        match merged {
+            Some((merged_path, merged_name)) => assert_eq!(
+                merged_path, path,
+                "can't merge the method routers of `{merged_name}` and `{name}` with different paths",
+            ),
+            None => merged = Some((path, name)),
+        }
+        merged_router = merged_router.merge(method_router.into());
+    }
+    if !cors_routes.is_empty() {
+        merged_router = merged_router.merge(cors::preflight(cors_routes));
+    }
+    let path = match merged {
+        Some((path, _name)) => path,
+        // All routes are disabled, so the path of the first one is as good as any.
+        None => typed_route_parts(handlers[0]).0,
+    };
+    (path, merged_router)
+}
+
+/// Whether axum would refuse to add both routes to a router.
+fn conflicts(a: &RouteInfo, b: &RouteInfo) -> bool {
+    if normalize_path(a.path) != normalize_path(b.path) {
+        return false;
+    }
+    // The method routers of routes on the same path are merged, which fails for the same method.
+    // The methods of handlers that weren't created by the macros are unknown.
+    a.path != b.path || (a.method == b.method && a.method != "*")
+}
+
+/// Checks that axum accepts the `path`, which may not be a literal of a macro, e.g. the path of
+/// a closure created from the configuration, or the reason it would panic.
+fn validate_path(path: &str) -> Result<(), &'static str> {
+    if !path.starts_with('/') {
+        return Err("paths must start with a `/`");
+    }
+    let segments = path.split('/').skip(1).collect::<Vec<_>>();
+    for (i, segment) in segments.iter().enumerate() {
+        if segment.starts_with(':') || segment.starts_with('*') {
+            return Err(
+                "path parameters are written as `{name}` or `{*name}`, not `:name` or `*name`",
+            );
+        }
+        // `{{` and `}}` are escaped braces.
+        let unescaped = segment.replace("{{", "").replace("}}", "");
+        let mut rest = unescaped.as_str();
+        while let Some(start) = rest.find(['{', '}']) {
+            if rest[start..].starts_with('}') {
+                return Err("unmatched `}`, escape it as `}}`");
+            }
+            let Some(end) = rest[start..].find('}') else {
+                return Err("unclosed `{`, escape it as `{{`");
+            };
+            let name = &rest[start + 1..start + end];
+            let wildcard = name.strip_prefix('*');
+            match wildcard.unwrap_or(name) {
+                "" => return Err("path parameters must have a name"),
+                name if name.contains(['{', '*']) => return Err("invalid path parameter name"),
+                _ => {}
+            }
+            if wildcard.is_some() && (i + 1 != segments.len() || unescaped != format!("{{{name}}}"))
+            {
+                return Err("a wildcard must be the whole last segment of the path");
+            }
+            rest = &rest[start + end + 1..];
+        }
+    }
+    Ok(())
+}
+
+/// The path without the names of its path parameters.
+fn normalize_path(path: &str) -> String {
+    path.split('/')
+        .map(
+            |segment| match segment.starts_with('{') && segment.ends_with('}') {
+                true if segment.starts_with("{*") => "{*}",
+                true => "{}",
+                false => segment,
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The routers typed routes can be added to.
+pub(crate) mod sealed {
+    use axum::routing::MethodRouter;
+
+    pub trait BuildRouter: Sized {
+        type State: Clone + Send + Sync + 'static;
+
+        /// Add an undocumented route, used for aliases and the `OPTIONS` routes.
+        fn add_route(self, path: &str, method_router: MethodRouter<Self::State>) -> Self;
+
+        /// Merge the routes of `other` into this router.
+        fn merge_router(self, other: Self) -> Self;
+    }
+}
+
+impl<R> TypedRouterBuilder<R> {
+    /// Create a builder adding its routes to `router`.
+    pub fn new(router: R) -> Self {
+        Self {
+            router,
+            routes: Vec::new(),
+            details: Vec::new(),
+            route_map: RouteMap::default(),
+            errors: Vec::new(),
+            auto_options: false,
+            cors: Vec::new(),
+            sitemap: Vec::new(),
+            sitemap_url: None,
+        }
+    }
+
+    /// Answer `OPTIONS` requests to every path with typed routes with `204 No Content`, and an
+    /// `Allow` header listing the methods of the routes on that path.
+    ///
+    /// Paths with an `OPTIONS` route, with a handler that wasn't created by the macros, or with
+    /// routes with the `cors` option, whose preflight requests are answered instead, are skipped.
+    pub fn auto_options(mut self) -> Self {
+        self.auto_options = true;
+        self
+    }
+
+    /// Serve the [`sitemap_xml`](Self::sitemap_xml) of the typed routes at `/sitemap.xml`, with
+    /// the absolute URLs of the pages starting with `base_url`, e.g. `https://example.com`.
+    ///
+    /// The route is added by [`build`](Self::build), so it includes routes merged later.
+    pub fn sitemap(mut self, base_url: impl Into<String>) -> Self {
+        self.sitemap_url = Some(base_url.into());
+        self
+    }
+
+    /// The pages of the sitemap: the paths and localized paths of all `GET` routes without path
+    /// parameters, in the order they were added, except routes with `sitemap: false`.
+    pub fn sitemap_entries(&self) -> Vec<SitemapEntry> {
+        self.sitemap.clone()
+    }
+
+    /// Format the [`sitemap_entries`](Self::sitemap_entries) as a `sitemap.xml` document, with
+    /// the absolute URLs of the pages starting with `base_url`.
+    pub fn sitemap_xml(&self, base_url: &str) -> String {
+        sitemap_xml(&self.sitemap, base_url)
+    }
+
+    /// All typed routes, in the order they were added.
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        self.routes.clone()
+    }
+
+    /// All typed routes with their OpenAPI operation ids and tags, in the order they were added.
+    pub fn route_details(&self) -> Vec<RouteDetails> {
+        self.details.clone()
+    }
+
+    /// Format the [`route_details`](Self::route_details) as a JSON array of objects with a
+    /// `method`, `path`, `handler`, `operation_id` and `tags`.
+    pub fn routes_json(&self) -> String {
+        routes_json(&self.details)
+    }
+
+    /// A `GET` handler serving the [`routes_json`](Self::routes_json), e.g. to diagnose a `404`
+    /// in a large deployment. It lists the routes added so far, so it should be created after
+    /// all routes are added, and mounted behind an admin guard:
+    /// ```ignore
+    /// let endpoint = builder.routes_debug_endpoint();
+    /// let router = builder
+    ///     .into_router()
+    ///     .route("/admin/routes", endpoint.route_layer(admin_layer));
+    /// ```
+    pub fn routes_debug_endpoint<S>(&self) -> MethodRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let json = self.routes_json();
+        axum::routing::get(move || {
+            std::future::ready(([(CONTENT_TYPE, "application/json")], json.clone()))
+        })
+    }
+
+    /// A map from the typed handlers to their routes, e.g. to look up the path of a handler in
+    /// middleware or background jobs, without calling the handler.
+    pub fn route_map(&self) -> RouteMap {
+        self.route_map.clone()
+    }
+
+    /// A `tower-http` `TraceLayer` for the typed routes, with the `trace` feature, whose spans
+    /// are described by [`TypedMakeSpan`]. It knows the routes added so far, so it should be
+    /// created after all routes are added:
+    /// ```ignore
+    /// let trace_layer = builder.trace_layer();
+    /// let router = builder.build()?.layer(trace_layer);
+    /// ```
+    #[cfg(feature = "trace")]
+    pub fn trace_layer(
+        &self,
+    ) -> tower_http::trace::TraceLayer<
+        tower_http::classify::SharedClassifier<tower_http::classify::ServerErrorsAsFailures>,
+        TypedMakeSpan,
+    > {
+        tower_http::trace::TraceLayer::new_for_http()
+            .make_span_with(TypedMakeSpan::new(&self.details))
+    }
+
+    /// The [`RoutePermissions`] of the typed routes, for an authorization middleware. It knows the
+    /// routes added so far, so it should be created after all routes are added.
+    pub fn route_permissions(&self) -> RoutePermissions {
+        RoutePermissions::new(&self.routes)
+    }
+
+    /// Format the typed routes as a table with a method, path and handler column.
+    pub fn route_table(&self) -> String {
+        let method_width = column_width("METHOD", self.routes.iter().map(|r| r.method));
+        let path_width = column_width("PATH", self.routes.iter().map(|r| r.path));
+
+        let mut table = String::new();
+        let rows = std::iter::once(("METHOD", "PATH", "HANDLER")).chain(
+            self.routes
+                .iter()
+                .map(|route| (route.method, route.path, route.handler)),
+        );
+        for (method, path, handler) in rows {
+            let _ = writeln!(
+                table,
+                "{method:<method_width$}  {path:<path_width$}  {handler}"
+            );
+        }
+        table
+    }
+
+    /// Print the [`route_table`](Self::route_table) to stdout.
+    pub fn print_routes(&self) {
+        print!("{}", self.route_table());
+    }
+}
+
+impl<R: sealed::BuildRouter> TypedRouterBuilder<R> {
+    /// Call the typed `handler`, remember its routes, and add it to the router with `route`,
+    /// unless it can't be added, e.g. because it conflicts with a route that was added before.
+    pub(crate) fn add_typed_route<H: IntoTypedRoute<R::State>>(
+        self,
+        handler: H,
+        route: impl FnOnce(R, &'static str, MethodRouter<R::State>) -> R,
+    ) -> Self {
+        let key = HandlerKey::of::<H, H::Value>(&handler);
+        self.add_keyed_route(key, || handler.call(), route)
+    }
+
+    /// Same as [`add_typed_route`](Self::add_typed_route), but for the handlers of `api_route`.
+    #[cfg(feature = "aide")]
+    pub(crate) fn add_typed_api_route<V>(
+        self,
+        handler: fn() -> V,
+        route: impl FnOnce(R, &'static str, V::MethodRouter) -> R,
+    ) -> Self
+    where
+        V: TypedRouteValue + 'static,
+        V::MethodRouter: Clone + Into<MethodRouter<R::State>>,
+    {
+        let key = HandlerKey::of::<_, V>(&handler);
+        self.add_keyed_route(key, handler, route)
+    }
+
+    /// Adds the typed `handler`, which is remembered in the route map by `key`.
+    fn add_keyed_route<V>(
+        mut self,
+        key: HandlerKey,
+        handler: impl FnOnce() -> V,
+        route: impl FnOnce(R, &'static str, V::MethodRouter) -> R,
+    ) -> Self
+    where
+        V: TypedRouteValue,
+        V::MethodRouter: Clone + Into<MethodRouter<R::State>>,
+    {
+        let (path, method_router, description) = typed_route_parts(handler);
+        let routes = route_infos(path, description);
+        let details = route_details(&routes, description);
+
+        let errors = route_errors(&self.details, &details);
+        if !errors.is_empty() {
+            self.errors.extend(errors);
+            return self;
+        }
+
+        self.sitemap.extend(sitemap_entries(path, description));
+        self.cors.extend(cors_routes(&routes, description));
+        self.details.extend(details);
+        self.router = add_described_route(self.router, path, method_router, description, route);
+        self.route_map.insert(key, &routes);
+        self.routes.extend(routes);
+        self
+    }
+
+    /// Merge the routes of `other` into this builder, e.g. to compose the routers exported by
+    /// different crates.
+    ///
+    /// If routes of `other` conflict with routes of this builder, or share their operation ids,
+    /// nothing is merged, and the errors are reported by [`build`](Self::build).
+    pub fn merge_typed(mut self, other: Self) -> Self {
+        let errors = route_errors(&self.details, &other.details);
+
+        self.errors.extend(other.errors);
+        self.auto_options |= other.auto_options;
+        self.sitemap_url = self.sitemap_url.or(other.sitemap_url);
+        if !errors.is_empty() {
+            self.errors.extend(errors);
+            return self;
+        }
+        self.router = self.router.merge_router(other.router);
+        self.routes.extend(other.routes);
+        self.details.extend(other.details);
+        self.cors.extend(other.cors);
+        self.sitemap.extend(other.sitemap);
+        for (handler, routes) in other.route_map.routes {
+            self.route_map.insert(handler, &routes);
+        }
+        self
+    }
+
+    /// The router with all routes added to it.
+    ///
+    /// # Panics
+    /// If any routes couldn't be added, listing all of them. Use [`build`](Self::build) to
+    /// handle the errors instead.
+    pub fn into_router(self) -> R {
+        self.build().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// The router with all routes added to it, or the errors of all routes that couldn't be
+    /// added, e.g. because they conflict with a route that was added before:
+    /// ```ignore
+    /// let router = builder.build().unwrap_or_else(|err| {
+    ///     tracing::error!("{err}");
+    ///     std::process::exit(1);
+    /// });
+    /// ```
+    pub fn build(self) -> Result<R, BuildError> {
+        if !self.errors.is_empty() {
+            return Err(BuildError {
+                errors: self.errors,
+            });
+        }
+
+        let mut router = self.router;
+        if let Some(base_url) = &self.sitemap_url {
+            let xml = sitemap_xml(&self.sitemap, base_url);
+            let sitemap = axum::routing::get(move || {
+                std::future::ready(([(CONTENT_TYPE, "application/xml")], xml.clone()))
+            });
+            router = router.add_route("/sitemap.xml", sitemap);
+        }
+        let preflights = preflight_routes(&self.routes, &self.cors);
+        for (path, routes) in &preflights {
+            router = router.add_route(path, cors::preflight(routes.clone()));
+        }
+        if !self.auto_options {
+            return Ok(router);
+        }
+
+        let mut paths = Vec::<(&str, Vec<&str>)>::new();
+        for route in &self.routes {
+            match paths.iter_mut().find(|(path, _)| *path == route.path) {
+                Some((_path, methods)) => methods.push(route.method),
+                None => paths.push((route.path, vec![route.method])),
+            }
+        }
+
+        for (path, mut methods) in paths {
+            if methods
+                .iter()
+                .any(|method| matches!(*method, "OPTIONS" | "*"))
+                || preflights.iter().any(|(preflight, _)| *preflight == path)
+            {
+                continue;
+            }
+            if methods.contains(&"GET") && !methods.contains(&"HEAD") {
+                methods.push("HEAD");
+            }
+            methods.push("OPTIONS");
+            let allow = methods.join(",");
+            let options = axum::routing::options(move || {
+                std::future::ready((StatusCode::NO_CONTENT, [(ALLOW, allow.clone())]))
+            });
+            router = router.add_route(path, options);
+        }
+        Ok(router)
+    }
+}
+
+impl<S> sealed::BuildRouter for axum::Router<S>
+where
+    S: Send + Sync + Clone + 'static,
+{
+    type State = S;
+
+    fn add_route(self, path: &str, method_router: MethodRouter<S>) -> Self {
+        self.route(path, method_router)
+    }
+
+    fn merge_router(self, other: Self) -> Self {
+        self.merge(other)
+    }
+}
+
+/// The `(method, policy)` of the routes with the `cors` option of every path, except paths with
+/// an `OPTIONS` route or a handler that wasn't created by the macros, which may answer the
+/// preflight requests themselves.
+fn preflight_routes(
+    routes: &[RouteInfo],
+    cors: &[(&'static str, &'static str, &'static CorsPolicy)],
+) -> Vec<(&'static str, Vec<(&'static str, &'static CorsPolicy)>)> {
+    let mut paths = Vec::<(&str, Vec<_>)>::new();
+    for &(path, method, policy) in cors {
+        let answered = routes
+            .iter()
+            .any(|route| route.path == path && matches!(route.method, "OPTIONS" | "*"));
+        if answered {
+            continue;
+        }
+        match paths.iter_mut().find(|(preflight, _)| *preflight == path) {
+            Some((_path, methods)) => methods.push((method, policy)),
+            None => paths.push((path, vec![(method, policy)])),
+        }
+    }
+    paths
+}
+
+fn column_width<'a>(header: &str, values: impl Iterator<Item = &'a str>) -> usize {
+    values.map(str::len).fold(header.len(), usize::max)
+}
+
+impl<S> TypedRouter for TypedRouterBuilder<axum::Router<S>>
+where
+    S: Send + Sync + Clone + 'static,
+{
+    type State = S;
+
+    fn typed_route(self, handler: impl IntoTypedRoute<Self::State>) -> Self {
+        self.add_typed_route(handler, axum::Router::route)
+    }
+}
@@ -0,0 +1,146 @@
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::FromRequestParts,
+    http::{header::SET_COOKIE, request::Parts, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::{convert::Infallible, fmt::Debug, future::Future, marker::PhantomData, time::Duration};
+
+/// Stores the responses of the routes with the `cached` option. It's implemented by the state
+/// of the router, e.g. with an in-memory cache or a client of a shared cache:
+/// ```ignore
+/// impl ResponseCache for AppState {
+///     async fn get(&self, key: &str) -> Option<CachedResponse> {
+///         self.responses.get(key).await
+///     }
+///
+///     async fn insert(&self, key: String, response: CachedResponse, ttl: Duration) {
+///         self.responses.insert_with_ttl(key, response, ttl).await
+///     }
+/// }
+/// ```
+pub trait ResponseCache {
+    /// The response cached under `key`, if it hasn't expired.
+    fn get(&self, key: &str) -> impl Future<Output = Option<CachedResponse>> + Send;
+
+    /// Caches the `response` under `key` for the `ttl` of the route.
+    fn insert(
+        &self,
+        key: String,
+        response: CachedResponse,
+        ttl: Duration,
+    ) -> impl Future<Output = ()> + Send;
+}
+
+/// A response of a route with the `cached` option, with its body read into memory.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl IntoResponse for CachedResponse {
+    fn into_response(self) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        response
+    }
+}
+
+/// Extracted by the routes with the `cached` option, to look up and store their responses in
+/// the [`ResponseCache`] of the state.
+pub struct CacheState<S>(S);
+
+impl<S> FromRequestParts<S> for CacheState<S>
+where
+    S: ResponseCache + Clone + Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(state.clone()))
+    }
+}
+
+impl<S: ResponseCache + Sync> CacheState<S> {
+    pub async fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.0.get(key).await
+    }
+
+    /// Caches a successful response, unless it sets a cookie. Its body is read into memory, and
+    /// a body that can't be read is a `500 Internal Server Error`.
+    pub async fn insert(&self, key: String, response: Response, ttl: Duration) -> Response {
+        if !response.status().is_success() || response.headers().contains_key(SET_COOKIE) {
+            return response;
+        }
+        let (parts, body) = response.into_parts();
+        let Ok(body) = to_bytes(body, usize::MAX).await else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let cached = CachedResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        };
+        self.0.insert(key, cached.clone(), ttl).await;
+        cached.into_response()
+    }
+}
+
+/// The key of a cached response: the name of the handler with the parameters of the `key`, e.g.
+/// `my_crate::get_item?id=1&amount=Some(2)`.
+pub fn cache_key(handler: &str, params: &[(&str, &dyn Debug)]) -> String {
+    let params = params
+        .iter()
+        .map(|(name, value)| format!("{name}={value:?}"))
+        .collect::<Vec<_>>();
+    match params.is_empty() {
+        true => handler.to_string(),
+        false => format!("{handler}?{}", params.join("&")),
+    }
+}
+
+/// The response of a route with the `cached` or `idempotent` option, which is either stored or
+/// the response of the handler returning `T`. With `api_route`, it's documented like `T`.
+pub struct Cached<T>(Response, PhantomData<fn() -> T>);
+
+impl<T> Cached<T> {
+    pub fn new(response: Response) -> Self {
+        Self(response, PhantomData)
+    }
+}
+
+impl<T> IntoResponse for Cached<T> {
+    fn into_response(self) -> Response {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use super::*;
+    use aide::{
+        generate::GenContext,
+        openapi::{Operation, Response},
+        operation::{OperationInput, OperationOutput},
+    };
+
+    impl<S> OperationInput for CacheState<S> {}
+
+    impl<T: OperationOutput> OperationOutput for Cached<T> {
+        type Inner = T::Inner;
+
+        fn operation_response(ctx: &mut GenContext, operation: &mut Operation) -> Option<Response> {
+            T::operation_response(ctx, operation)
+        }
+
+        fn inferred_responses(
+            ctx: &mut GenContext,
+            operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            T::inferred_responses(ctx, operation)
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Verification of the documented routes against a checked-in OpenApi contract.
+
+use aide::openapi::{OpenApi, Operation, ReferenceOr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// An operation of the OpenApi documentation, e.g. `GET /items/{id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContractOperation {
+    /// The uppercase method, e.g. `GET`.
+    pub method: String,
+    pub path: String,
+    pub operation_id: Option<String>,
+}
+
+impl fmt::Display for ContractOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+        match &self.operation_id {
+            Some(id) => write!(f, " ({id})"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The differences between the documentation of a router and a checked-in OpenApi contract,
+/// returned by [`contract_drift`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractDrift {
+    /// Operations of the contract that the router doesn't serve.
+    pub missing: Vec<ContractOperation>,
+    /// Operations served by the router that aren't part of the contract.
+    pub undocumented: Vec<ContractOperation>,
+    /// Operations documented differently than in the contract, e.g. with other parameters or
+    /// responses.
+    pub changed: Vec<ContractOperation>,
+    /// The names of the component schemas that were added, removed or changed.
+    pub changed_schemas: Vec<String>,
+}
+
+impl ContractDrift {
+    /// Whether the documentation matches the contract.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.undocumented.is_empty()
+            && self.changed.is_empty()
+            && self.changed_schemas.is_empty()
+    }
+}
+
+impl fmt::Display for ContractDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the routes don't match the OpenApi contract:")?;
+        for operation in &self.missing {
+            write!(f, "\n  missing:      {operation}")?;
+        }
+        for operation in &self.undocumented {
+            write!(f, "\n  undocumented: {operation}")?;
+        }
+        for operation in &self.changed {
+            write!(f, "\n  changed:      {operation}")?;
+        }
+        for schema in &self.changed_schemas {
+            write!(f, "\n  schema:       {schema}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ContractDrift {}
+
+/// Compares the operations and schemas of `api`, the finished documentation of the router, with
+/// the checked-in `contract`, e.g. at startup, to fail fast when the routes drift from it:
+/// ```ignore
+/// let contract = serde_json::from_str(include_str!("../openapi.json"))?;
+/// let mut api = OpenApi::default();
+/// let router = ApiRouter::new().typed_api_route(item_handler).finish_api(&mut api);
+/// contract_drift(&api, &contract).unwrap_or_else(|drift| panic!("{drift}"));
+/// ```
+/// Or to only log the drift, e.g. with `tracing::warn!("{drift}")`. The info, servers and tags
+/// of the documentation aren't compared, only what clients depend on.
+pub fn contract_drift(api: &OpenApi, contract: &OpenApi) -> Result<(), ContractDrift> {
+    let documented = operations(api);
+    let contract_operations = operations(contract);
+    let mut drift = ContractDrift::default();
+    for (key, operation) in &contract_operations {
+        match documented.get(key) {
+            None => drift.missing.push(contract_operation(key, operation)),
+            Some(documented) if to_json(documented) != to_json(operation) => {
+                drift.changed.push(contract_operation(key, documented))
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, operation) in &documented {
+        if !contract_operations.contains_key(key) {
+            drift.undocumented.push(contract_operation(key, operation));
+        }
+    }
+
+    let documented = schemas(api);
+    let contract_schemas = schemas(contract);
+    let names = documented.keys().chain(contract_schemas.keys());
+    for name in names.collect::<BTreeSet<_>>() {
+        if documented.get(name) != contract_schemas.get(name) {
+            drift.changed_schemas.push(name.to_string());
+        }
+    }
+
+    match drift.is_empty() {
+        true => Ok(()),
+        false => Err(drift),
+    }
+}
+
+/// The operations of `api` by `(path, method)`, sorted by path.
+fn operations(api: &OpenApi) -> BTreeMap<(&str, &str), &Operation> {
+    let paths = api.paths.iter().flat_map(|paths| paths.iter());
+    paths
+        .filter_map(|(path, item)| match item {
+            ReferenceOr::Item(item) => Some((path, item)),
+            ReferenceOr::Reference { .. } => None,
+        })
+        .flat_map(|(path, item)| {
+            item.iter()
+                .map(move |(method, operation)| ((path.as_str(), method), operation))
+        })
+        .collect()
+}
+
+fn contract_operation((path, method): &(&str, &str), operation: &Operation) -> ContractOperation {
+    ContractOperation {
+        method: method.to_uppercase(),
+        path: path.to_string(),
+        operation_id: operation.operation_id.clone(),
+    }
+}
+
+/// The component schemas of `api` as JSON, by name.
+fn schemas(api: &OpenApi) -> BTreeMap<&str, serde_json::Value> {
+    let schemas = api
+        .components
+        .iter()
+        .flat_map(|components| &components.schemas);
+    schemas
+        .map(|(name, schema)| (name.as_str(), to_json(schema)))
+        .collect()
+}
+
+fn to_json(value: &impl serde::Serialize) -> serde_json::Value {
+    serde_json::to_value(value).expect("failed to serialize the OpenApi documentation")
+}
@@ -0,0 +1,149 @@
+use axum::{
+    extract::Request,
+    http::{
+        header::{
+            ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+            ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE,
+            ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY,
+        },
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+};
+/// The `cors` option of a route: the origins allowed to call it from a browser, and the request
+/// headers they may send.
+#[derive(Debug)]
+pub struct CorsPolicy {
+    /// The allowed origins, e.g. `https://app.example.com`, or `*` for any origin.
+    pub origins: &'static [&'static str],
+    /// The lowercase names of the request headers allowed besides the CORS-safelisted ones.
+    pub headers: &'static [&'static str],
+    /// How long browsers may cache the answer to a preflight request, in seconds.
+    pub max_age: Option<u32>,
+    pub credentials: bool,
+}
+
+impl CorsPolicy {
+    /// The `Access-Control-Allow-Origin` of a request from `origin`, if it's allowed.
+    fn allow_origin(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        if self.origins.contains(&"*") {
+            return Some(HeaderValue::from_static("*"));
+        }
+        self.origins
+            .iter()
+            .any(|allowed| allowed.as_bytes() == origin.as_bytes())
+            .then(|| origin.clone())
+    }
+
+    fn insert_origin_headers(&self, headers: &mut HeaderMap, allow_origin: HeaderValue) {
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if self.credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+}
+
+/// Adds the CORS headers of `policy` to the response of a request from an allowed origin.
+pub async fn cors_headers(policy: &'static CorsPolicy, request: Request, next: Next) -> Response {
+    let origin = request.headers().get(ORIGIN).cloned();
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    if let Some(allow_origin) = origin.and_then(|origin| policy.allow_origin(&origin)) {
+        policy.insert_origin_headers(headers, allow_origin);
+    }
+    headers.append(VARY, HeaderValue::from_static("origin"));
+    response
+}
+
+/// The `OPTIONS` route of a path answering the preflight requests of its routes with the `cors`
+/// option, given as `(method, policy)`.
+pub(crate) fn preflight<S>(routes: Vec<(&'static str, &'static CorsPolicy)>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    axum::routing::options(move |headers: HeaderMap| {
+        std::future::ready(preflight_response(&routes, &headers))
+    })
+}
+
+/// The method router answering the preflight requests of a route with the `cors` option added
+/// on its own, with its fallback. Unlike an `OPTIONS` route, it can be merged with the method
+/// routers of the other routes of the path, but only the fallback of the last one is kept.
+pub(crate) fn preflight_fallback<S>(
+    method: &'static str,
+    policy: &'static CorsPolicy,
+) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // A layer on the default fallback, which a fallback of its own would replace.
+    MethodRouter::new().layer(axum::middleware::from_fn(
+        move |request: Request, next: Next| async move {
+            match request.method() == Method::OPTIONS {
+                true => preflight_response(&[(method, policy)], request.headers()),
+                false => next.run(request).await,
+            }
+        },
+    ))
+}
+
+/// Answers a preflight request with the methods whose policy allows its origin, and the headers
+/// of the route of the requested method. A request that isn't allowed is answered without CORS
+/// headers, so the browser refuses to send the actual request.
+fn preflight_response(
+    routes: &[(&'static str, &'static CorsPolicy)],
+    headers: &HeaderMap,
+) -> Response {
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    response
+        .headers_mut()
+        .append(VARY, HeaderValue::from_static("origin"));
+    let Some(origin) = headers.get(ORIGIN) else {
+        return response;
+    };
+    let allowed = routes
+        .iter()
+        .filter_map(|(method, policy)| Some((*method, *policy, policy.allow_origin(origin)?)))
+        .collect::<Vec<_>>();
+    // `HEAD` requests are answered by the `GET` route.
+    let requested = headers
+        .get(ACCESS_CONTROL_REQUEST_METHOD)
+        .and_then(|method| method.to_str().ok())
+        .map(|method| match method {
+            "HEAD" => "GET",
+            method => method,
+        });
+    let route = allowed
+        .iter()
+        .find(|(method, _policy, _allow_origin)| Some(*method) == requested);
+    let Some((_method, policy, allow_origin)) = route else {
+        return response;
+    };
+
+    let mut methods = allowed
+        .iter()
+        .map(|(method, _policy, _allow_origin)| *method)
+        .collect::<Vec<_>>();
+    if methods.contains(&"GET") {
+        methods.push("HEAD");
+    }
+    let headers = response.headers_mut();
+    policy.insert_origin_headers(headers, allow_origin.clone());
+    if let Ok(methods) = HeaderValue::from_str(&methods.join(",")) {
+        headers.insert(ACCESS_CONTROL_ALLOW_METHODS, methods);
+    }
+    if !policy.headers.is_empty() {
+        if let Ok(allow_headers) = HeaderValue::from_str(&policy.headers.join(",")) {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+        }
+    }
+    if let Some(max_age) = policy.max_age {
+        headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age));
+    }
+    response
+}
@@ -0,0 +1,201 @@
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{convert::Infallible, time::Duration};
+use tokio::time::Instant;
+
+/// The `deadline` option of a route: the header with the time left for a request, and the
+/// timeouts of the requests without it, in milliseconds.
+#[derive(Debug)]
+pub struct DeadlinePolicy {
+    /// The lowercase name of the header, e.g. `grpc-timeout`.
+    pub header: &'static str,
+    /// The timeout of a request without the header.
+    pub default_millis: Option<u64>,
+    /// The longest timeout, even if the header allows more time.
+    pub max_millis: Option<u64>,
+}
+
+impl DeadlinePolicy {
+    /// The timeout of a request with the `header`, or `Err` if it can't be parsed.
+    fn timeout(&self, header: Option<&HeaderValue>) -> Result<Option<Duration>, ()> {
+        let timeout = match header {
+            Some(value) => {
+                let value = value.to_str().map_err(|_| ())?;
+                Some(parse_timeout(value.trim()).ok_or(())?)
+            }
+            None => self.default_millis.map(Duration::from_millis),
+        };
+        let max = self.max_millis.map(Duration::from_millis);
+        Ok(match (timeout, max) {
+            (Some(timeout), Some(max)) => Some(timeout.min(max)),
+            (timeout, max) => timeout.or(max),
+        })
+    }
+}
+
+/// Parses the time left for a request: a number of milliseconds, or a `grpc-timeout` with a
+/// unit of (H, M, S, m, u, n), e.g. `500m` or `5S`.
+fn parse_timeout(value: &str) -> Option<Duration> {
+    if let Ok(millis) = value.parse::<u64>() {
+        return Some(Duration::from_millis(millis));
+    }
+    let unit = value.chars().last()?;
+    let amount = value[..value.len() - unit.len_utf8()].parse::<u64>().ok()?;
+    match unit {
+        'H' => Some(Duration::from_secs(amount.checked_mul(60 * 60)?)),
+        'M' => Some(Duration::from_secs(amount.checked_mul(60)?)),
+        'S' => Some(Duration::from_secs(amount)),
+        'm' => Some(Duration::from_millis(amount)),
+        'u' => Some(Duration::from_micros(amount)),
+        'n' => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// The deadline of a request to a route with the `deadline` option, extracted as an argument of
+/// its handler, e.g. to pass the time left on to the services it calls:
+/// ```ignore
+/// #[route(GET "/items/:id" { deadline: { header: "grpc-timeout", max: 10s } })]
+/// async fn get_item(id: u32, deadline: Deadline) -> String {
+///     let mut request = client.get(format!("{ITEMS}/{id}"));
+///     if let Some(timeout) = deadline.grpc_timeout() {
+///         request = request.header("grpc-timeout", timeout);
+///     }
+///     request.send().await.unwrap().text().await.unwrap()
+/// }
+/// ```
+/// A request without a deadline, e.g. to another route, has no [`Deadline::instant`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Deadline(Option<Instant>);
+
+impl Deadline {
+    /// The instant the request times out with `504 Gateway Timeout`, if it has a deadline.
+    pub fn instant(&self) -> Option<Instant> {
+        self.0
+    }
+
+    /// The time left before the request times out, if it has a deadline.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.0
+            .map(|instant| instant.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether the deadline has passed.
+    pub fn is_expired(&self) -> bool {
+        self.remaining()
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// The time left as the value of a `grpc-timeout` header, e.g. `1500m`, to propagate the
+    /// deadline to the next service.
+    pub fn grpc_timeout(&self) -> Option<HeaderValue> {
+        let millis = self.remaining()?.as_millis();
+        // The value of a `grpc-timeout` has at most 8 digits.
+        let value = match millis < 100_000_000 {
+            true => format!("{millis}m"),
+            false => format!("{}S", millis / 1000),
+        };
+        HeaderValue::from_str(&value).ok()
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Deadline {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<Deadline>()
+            .copied()
+            .unwrap_or_default())
+    }
+}
+
+/// Reads the [`Deadline`] of a request with the header of `policy`, and answers it with
+/// `504 Gateway Timeout` when the deadline passes before the response is ready. A header that
+/// can't be parsed is rejected with `400 Bad Request`.
+pub async fn deadline(
+    policy: &'static DeadlinePolicy,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Ok(timeout) = policy.timeout(request.headers().get(policy.header)) else {
+        let message = format!("Invalid `{}` header", policy.header);
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    };
+    let instant = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+    request.extensions_mut().insert(Deadline(instant));
+    let Some(instant) = instant else {
+        return next.run(request).await;
+    };
+    match tokio::time::timeout_at(instant, next.run(request)).await {
+        Ok(response) => response,
+        Err(_elapsed) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            "The deadline of the request expired",
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(feature = "aide")]
+pub use openapi::document_deadline;
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use super::*;
+    use aide::{
+        openapi::{
+            HeaderStyle, Operation, Parameter, ParameterData, ParameterSchemaOrContent,
+            ReferenceOr, Response, SchemaObject, StatusCode,
+        },
+        operation::OperationInput,
+    };
+
+    impl OperationInput for Deadline {}
+
+    /// Documents the optional header of `policy`, and the `504 Gateway Timeout` response to a
+    /// request whose deadline passed.
+    pub fn document_deadline(operation: &mut Operation, policy: &DeadlinePolicy) {
+        let json_schema = aide::generate::in_context(|ctx| ctx.schema.subschema_for::<String>());
+        let parameter_data = ParameterData {
+            name: policy.header.to_string(),
+            description: Some(
+                "The time left for the request, in milliseconds or with a unit of (H, M, S, m, u, n), e.g. `500m`."
+                    .to_string(),
+            ),
+            required: false,
+            format: ParameterSchemaOrContent::Schema(SchemaObject {
+                json_schema,
+                example: None,
+                external_docs: None,
+            }),
+            extensions: Default::default(),
+            deprecated: None,
+            example: None,
+            examples: Default::default(),
+            explode: None,
+        };
+        operation
+            .parameters
+            .push(ReferenceOr::Item(Parameter::Header {
+                parameter_data,
+                style: HeaderStyle::Simple,
+            }));
+
+        let responses = operation.responses.get_or_insert_with(Default::default);
+        responses
+            .responses
+            .entry(StatusCode::Code(504))
+            .or_insert_with(|| {
+                ReferenceOr::Item(Response {
+                    description: "The deadline of the request expired".to_string(),
+                    ..Default::default()
+                })
+            });
+    }
+}
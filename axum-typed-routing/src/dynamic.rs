@@ -0,0 +1,284 @@
+//! A router whose typed routes can be disabled and re-pointed at other handlers at runtime.
+
+use crate::{builder, Error, IntoTypedRoute, RouteInfo, RouteMap, TypedRouteValue, TypedRouter};
+use arc_swap::ArcSwap;
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{any, on, MethodFilter, MethodRouter},
+};
+use std::{collections::HashMap, sync::Arc};
+use tower_service::Service;
+
+/// A router whose typed routes are served from a table that can be changed while it serves
+/// requests, e.g. to put single endpoints into maintenance mode, or to roll a handler back
+/// without a restart:
+/// ```ignore
+/// let router = DynamicTypedRouter::new()
+///     .typed_route(list_items)
+///     .typed_route(create_item);
+/// let routes = router.routes();
+/// let app = router.into_router().with_state(state);
+///
+/// // Later, e.g. from an admin endpoint:
+/// let route = routes.route_map().get(create_item).unwrap();
+/// routes.disable(&route);
+/// routes.repoint(&route, create_item_v2)?;
+/// routes.enable(&route);
+/// ```
+///
+/// The routes are keyed by the canonical [`RouteInfo`] of their handler, and their aliases and
+/// localized paths follow it. A disabled route answers `503 Service Unavailable`.
+pub struct DynamicTypedRouter<S = ()> {
+    router: axum::Router<S>,
+    routes: DynamicRoutes<S>,
+}
+
+impl<S> DynamicTypedRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// Create a router without routes.
+    pub fn new() -> Self {
+        Self {
+            router: axum::Router::new(),
+            routes: DynamicRoutes {
+                table: Arc::new(ArcSwap::from_pointee(RouteTable {
+                    slots: HashMap::new(),
+                    route_map: RouteMap::default(),
+                })),
+            },
+        }
+    }
+
+    /// A handle to the table of the routes, to change them after the router is built.
+    pub fn routes(&self) -> DynamicRoutes<S> {
+        self.routes.clone()
+    }
+
+    /// The axum router serving the routes from the table.
+    pub fn into_router(self) -> axum::Router<S> {
+        self.router
+    }
+}
+
+impl<S> Default for DynamicTypedRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> TypedRouter for DynamicTypedRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    type State = S;
+
+    fn typed_route(mut self, handler: impl IntoTypedRoute<Self::State>) -> Self {
+        let mut route_map = self.routes.table.load().route_map.clone();
+        let routes = self.routes.clone();
+        let (router, _routes) = builder::route_wrapped_handler(
+            self.router,
+            &mut route_map,
+            handler,
+            |route, method_router| {
+                routes.insert(route, method_router);
+                routes.dispatcher(route)
+            },
+        );
+        self.router = router;
+        self.routes
+            .update(|table| table.route_map = route_map.clone());
+        self
+    }
+}
+
+/// The table of the routes of a [`DynamicTypedRouter`], which can be cloned and changed while
+/// the router serves requests. Changes apply to the requests that start after them.
+pub struct DynamicRoutes<S = ()> {
+    table: Arc<ArcSwap<RouteTable<S>>>,
+}
+
+impl<S> Clone for DynamicRoutes<S> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+        }
+    }
+}
+
+struct RouteTable<S> {
+    slots: HashMap<RouteInfo, Slot<S>>,
+    route_map: RouteMap,
+}
+
+impl<S> Clone for RouteTable<S> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            route_map: self.route_map.clone(),
+        }
+    }
+}
+
+/// The handler a route currently points at.
+struct Slot<S> {
+    /// The method router the route was added with, restored by [`DynamicRoutes::reset`].
+    original: MethodRouter<S>,
+    method_router: MethodRouter<S>,
+    enabled: bool,
+}
+
+impl<S> Clone for Slot<S> {
+    fn clone(&self) -> Self {
+        Self {
+            original: self.original.clone(),
+            method_router: self.method_router.clone(),
+            enabled: self.enabled,
+        }
+    }
+}
+
+impl<S> DynamicRoutes<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// All routes in the table, ordered by path and method.
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        let mut routes = self.table.load().slots.keys().copied().collect::<Vec<_>>();
+        routes.sort_by_key(|route| (route.path, route.method));
+        routes
+    }
+
+    /// A map from the typed handlers to their routes, to find the route of a handler in the
+    /// table.
+    pub fn route_map(&self) -> RouteMap {
+        self.table.load().route_map.clone()
+    }
+
+    /// Whether the `route` is in the table and enabled.
+    pub fn is_enabled(&self, route: &RouteInfo) -> bool {
+        self.table
+            .load()
+            .slots
+            .get(route)
+            .is_some_and(|slot| slot.enabled)
+    }
+
+    /// Serve the `route` again after it was disabled. Returns `false` if it isn't in the table.
+    pub fn enable(&self, route: &RouteInfo) -> bool {
+        self.update_slot(route, |slot| slot.enabled = true)
+    }
+
+    /// Answer the requests of the `route` with `503 Service Unavailable`. Returns `false` if it
+    /// isn't in the table.
+    pub fn disable(&self, route: &RouteInfo) -> bool {
+        self.update_slot(route, |slot| slot.enabled = false)
+    }
+
+    /// Serve the `route` with another typed handler with the same method and path, e.g. a new
+    /// version of its handler. If the `enabled_if` option of `handler` is `false`, the route is
+    /// disabled instead.
+    #[allow(clippy::result_large_err)]
+    pub fn repoint<V>(&self, route: &RouteInfo, handler: impl FnOnce() -> V) -> Result<(), Error>
+    where
+        V: TypedRouteValue,
+        V::MethodRouter: Into<MethodRouter<S>>,
+    {
+        if !self.table.load().slots.contains_key(route) {
+            return Err(Error::UnknownRoute(*route));
+        }
+        let (method_router, routes) = builder::typed_handler_routes(handler);
+        let Some(replacement) = routes.first().copied() else {
+            self.disable(route);
+            return Ok(());
+        };
+        if replacement.method != route.method || replacement.path != route.path {
+            return Err(Error::MismatchedRoute {
+                route: *route,
+                replacement,
+            });
+        }
+        let method_router = method_router.into();
+        self.update_slot(route, |slot| slot.method_router = method_router.clone());
+        Ok(())
+    }
+
+    /// Point the `route` back at the handler it was added with, and enable it. Returns `false` if
+    /// it isn't in the table.
+    pub fn reset(&self, route: &RouteInfo) -> bool {
+        self.update_slot(route, |slot| {
+            slot.method_router = slot.original.clone();
+            slot.enabled = true;
+        })
+    }
+
+    fn insert(&self, route: RouteInfo, method_router: MethodRouter<S>) {
+        self.update(|table| {
+            let slot = Slot {
+                original: method_router.clone(),
+                method_router: method_router.clone(),
+                enabled: true,
+            };
+            table.slots.insert(route, slot);
+        });
+    }
+
+    /// Applies `update` to the slot of `route`, if it's in the table.
+    fn update_slot(&self, route: &RouteInfo, update: impl Fn(&mut Slot<S>)) -> bool {
+        let mut found = false;
+        self.update(|table| {
+            found = table.slots.get_mut(route).map(&update).is_some();
+        });
+        found
+    }
+
+    /// Replaces the table with a copy changed by `update`, which may be called again if another
+    /// thread changed the table at the same time.
+    fn update(&self, mut update: impl FnMut(&mut RouteTable<S>)) {
+        self.table.rcu(|table| {
+            let mut table = RouteTable::clone(table);
+            update(&mut table);
+            table
+        });
+    }
+
+    /// The method router added to the axum router for `route`, which calls the handler the
+    /// route points at when the request arrives.
+    fn dispatcher(&self, route: RouteInfo) -> MethodRouter<S> {
+        let routes = self.clone();
+        let handler = move |State(state): State<S>, request: Request| {
+            let routes = routes.clone();
+            async move { routes.call(&route, state, request).await }
+        };
+        let filter = Method::from_bytes(route.method.as_bytes())
+            .ok()
+            .and_then(|method| MethodFilter::try_from(method).ok());
+        match filter {
+            Some(filter) => on(filter, handler),
+            // Handlers that weren't created by the macros, or with a custom method.
+            None => any(handler),
+        }
+    }
+
+    async fn call(&self, route: &RouteInfo, state: S, request: Request) -> Response {
+        let method_router = self
+            .table
+            .load()
+            .slots
+            .get(route)
+            .filter(|slot| slot.enabled)
+            .map(|slot| slot.method_router.clone());
+        let Some(method_router) = method_router else {
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        };
+        match method_router.with_state::<()>(state).call(request).await {
+            Ok(response) => response,
+            Err(infallible) => match infallible {},
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! The errors of registering typed routes, reported by
+//! [`TypedRouterBuilder::build`](crate::TypedRouterBuilder::build), and of re-pointing the routes
+//! of a `DynamicTypedRouter`.
+
+use crate::{RouteConflict, RouteInfo};
+use std::fmt;
+
+/// A typed route that can't be added to a router, with the method, path and handler of the
+/// routes involved, so a service can report its configuration problems at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The path of a route would make axum panic, e.g. a path from the configuration that
+    /// doesn't start with a `/`, or has an unclosed `{`.
+    InvalidPath {
+        /// The route with the invalid path.
+        route: RouteInfo,
+        /// Why axum would reject the path.
+        reason: &'static str,
+    },
+    /// Two routes with the same method and path, or the same path with differently named path
+    /// parameters.
+    Conflict(RouteConflict),
+    /// Two `api_route`s of different handlers with the same OpenAPI operation id, which breaks
+    /// most client generators.
+    DuplicateOperationId {
+        operation_id: &'static str,
+        /// The route that was added first.
+        existing: RouteInfo,
+        /// The route with the same operation id.
+        duplicate: RouteInfo,
+    },
+    /// A route that isn't in the table of a `DynamicTypedRouter`, e.g. an alias instead of the
+    /// canonical route of a handler.
+    UnknownRoute(RouteInfo),
+    /// A route of a `DynamicTypedRouter` re-pointed at a handler with a different method or path.
+    MismatchedRoute {
+        /// The route that was re-pointed.
+        route: RouteInfo,
+        /// The route of the handler it was re-pointed at.
+        replacement: RouteInfo,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPath { route, reason } => write!(
+                f,
+                "{} {} ({}) has an invalid path: {reason}",
+                route.method, route.path, route.handler,
+            ),
+            Self::Conflict(RouteConflict {
+                existing,
+                conflicting,
+            }) => write!(
+                f,
+                "{} {} ({}) conflicts with {} {} ({})",
+                conflicting.method,
+                conflicting.path,
+                conflicting.handler,
+                existing.method,
+                existing.path,
+                existing.handler,
+            ),
+            Self::DuplicateOperationId {
+                operation_id,
+                existing,
+                duplicate,
+            } => write!(
+                f,
+                "operation id `{operation_id}` of {} {} ({}) is already used by {} {} ({})",
+                duplicate.method,
+                duplicate.path,
+                duplicate.handler,
+                existing.method,
+                existing.path,
+                existing.handler,
+            ),
+            Self::UnknownRoute(route) => write!(
+                f,
+                "{} {} ({}) isn't a dynamic route",
+                route.method, route.path, route.handler,
+            ),
+            Self::MismatchedRoute { route, replacement } => write!(
+                f,
+                "{} {} ({}) can't be re-pointed at {} {} ({})",
+                route.method,
+                route.path,
+                route.handler,
+                replacement.method,
+                replacement.path,
+                replacement.handler,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The typed routes of a [`TypedRouterBuilder`](crate::TypedRouterBuilder) that couldn't be
+/// added, returned by [`build`](crate::TypedRouterBuilder::build).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildError {
+    /// All errors, in the order the routes were added.
+    pub errors: Vec<Error>,
+}
+
+impl BuildError {
+    /// The conflicting routes among the [`errors`](Self::errors).
+    pub fn conflicts(&self) -> impl Iterator<Item = &RouteConflict> {
+        self.errors.iter().filter_map(|error| match error {
+            Error::Conflict(conflict) => Some(conflict),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid typed routes:")?;
+        for error in &self.errors {
+            write!(f, "\n  {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BuildError {}
@@ -0,0 +1,209 @@
+//! GraphQL endpoints, routed with the [`graphql_route`](crate::graphql_route) macro.
+
+use axum::{
+    extract::{FromRef, State},
+    response::Html,
+    routing::{post, MethodRouter},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+/// A GraphQL request, as sent in the JSON body of a `POST` to a GraphQL endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "aide", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest {
+    /// The GraphQL document.
+    pub query: String,
+    /// The name of the operation to execute, if the document contains more than one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+    /// The values of the variables of the operation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variables: Option<serde_json::Value>,
+}
+
+/// The response to a [`GraphQLRequest`], served as JSON with `200 OK`, even if it contains
+/// errors.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "aide", derive(schemars::JsonSchema))]
+pub struct GraphQLResponse {
+    /// The result of the operation, if it was executed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    /// The errors of the request or the operation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<serde_json::Value>,
+    /// The extensions of the response, such as tracing data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<serde_json::Value>,
+}
+
+impl GraphQLResponse {
+    /// Converts the serialized response of a GraphQL library, which has the same fields.
+    #[cfg(any(feature = "async-graphql", feature = "juniper"))]
+    fn from_serialize(response: impl Serialize) -> Self {
+        serde_json::to_value(response)
+            .and_then(serde_json::from_value)
+            .unwrap_or_else(|err| Self::error(format!("Invalid GraphQL response: {err}")))
+    }
+
+    /// A response with a single error, e.g. for a request that can't be executed.
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            data: None,
+            errors: vec![serde_json::json!({ "message": message.into() })],
+            extensions: None,
+        }
+    }
+}
+
+/// A GraphQL schema served by a [`graphql_route`](crate::graphql_route), which takes it from the
+/// state of the router with `FromRef`.
+///
+/// This is implemented for the `Schema` of `async-graphql` with the `async-graphql` feature, and
+/// for an `Arc<RootNode>` of `juniper` whose context implements `FromRef` of the state with the
+/// `juniper` feature. Implement it to serve the schemas of other libraries.
+pub trait GraphQLSchema<S>: Send + Sync + 'static {
+    /// Executes the `request` on the schema, with the `state` of the router to create a context.
+    fn execute(
+        &self,
+        request: GraphQLRequest,
+        state: &S,
+    ) -> impl Future<Output = GraphQLResponse> + Send;
+}
+
+#[cfg(feature = "async-graphql")]
+impl<S, Query, Mutation, Subscription> GraphQLSchema<S>
+    for async_graphql::Schema<Query, Mutation, Subscription>
+where
+    S: Sync,
+    Query: async_graphql::ObjectType + 'static,
+    Mutation: async_graphql::ObjectType + 'static,
+    Subscription: async_graphql::SubscriptionType + 'static,
+{
+    async fn execute(&self, request: GraphQLRequest, _state: &S) -> GraphQLResponse {
+        let mut graphql_request = async_graphql::Request::new(request.query);
+        if let Some(operation_name) = request.operation_name {
+            graphql_request = graphql_request.operation_name(operation_name);
+        }
+        if let Some(variables) = request.variables {
+            graphql_request =
+                graphql_request.variables(async_graphql::Variables::from_json(variables));
+        }
+        let response = async_graphql::Schema::execute(self, graphql_request).await;
+        GraphQLResponse::from_serialize(response)
+    }
+}
+
+#[cfg(feature = "juniper")]
+impl<S, Query, Mutation, Subscription> GraphQLSchema<S>
+    for std::sync::Arc<juniper::RootNode<'static, Query, Mutation, Subscription>>
+where
+    S: Sync,
+    Query: juniper::GraphQLTypeAsync + Send + 'static,
+    Query::TypeInfo: Send + Sync,
+    Query::Context: FromRef<S> + Send + Sync,
+    Mutation: juniper::GraphQLTypeAsync<Context = Query::Context> + Send + 'static,
+    Mutation::TypeInfo: Send + Sync,
+    Subscription: juniper::GraphQLType<Context = Query::Context> + Send + Sync + 'static,
+    Subscription::TypeInfo: Send + Sync,
+{
+    async fn execute(&self, request: GraphQLRequest, state: &S) -> GraphQLResponse {
+        let variables = match request.variables.map(serde_json::from_value).transpose() {
+            Ok(variables) => variables,
+            Err(err) => return GraphQLResponse::error(format!("Invalid variables: {err}")),
+        };
+        let graphql_request =
+            juniper::http::GraphQLRequest::new(request.query, request.operation_name, variables);
+        let context = Query::Context::from_ref(state);
+        let response = graphql_request.execute(self, &context).await;
+        GraphQLResponse::from_serialize(response)
+    }
+}
+
+/// Executes the GraphQL request in the body of a `POST` on the schema in the state.
+async fn execute<G, S>(
+    State(state): State<S>,
+    Json(request): Json<GraphQLRequest>,
+) -> Json<GraphQLResponse>
+where
+    G: GraphQLSchema<S> + FromRef<S>,
+    S: Send + Sync,
+{
+    Json(G::from_ref(&state).execute(request, &state).await)
+}
+
+/// Serves the GraphiQL IDE for a `GET`, which sends its requests to the same path.
+async fn graphiql() -> Html<&'static str> {
+    Html(GRAPHIQL)
+}
+
+const GRAPHIQL: &str = r#"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8" />
+    <title>GraphiQL</title>
+    <link rel="stylesheet" href="https://unpkg.com/graphiql@3/graphiql.min.css" />
+  </head>
+  <body style="margin: 0">
+    <div id="graphiql" style="height: 100vh"></div>
+    <script crossorigin src="https://unpkg.com/react@18/umd/react.production.min.js"></script>
+    <script crossorigin src="https://unpkg.com/react-dom@18/umd/react-dom.production.min.js"></script>
+    <script crossorigin src="https://unpkg.com/graphiql@3/graphiql.min.js"></script>
+    <script>
+      const fetcher = GraphiQL.createFetcher({ url: window.location.pathname });
+      ReactDOM.createRoot(document.getElementById("graphiql")).render(
+        React.createElement(GraphiQL, { fetcher })
+      );
+    </script>
+  </body>
+</html>
+"#;
+
+/// The method router of a `graphql_route`: `POST` executes requests on the schema `G`, and `GET`
+/// serves GraphiQL.
+pub fn graphql_method_router<G, S>() -> MethodRouter<S>
+where
+    G: GraphQLSchema<S> + FromRef<S>,
+    S: Clone + Send + Sync + 'static,
+{
+    post(execute::<G, S>).get(graphiql)
+}
+
+#[cfg(feature = "aide")]
+pub use openapi::api_graphql_method_router;
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use super::*;
+    use aide::axum::routing::{post_with, ApiMethodRouter};
+
+    /// Same as [`graphql_method_router`], documenting the `POST` with the `operation_id`,
+    /// `summary` and `description` of the `api_graphql_route`, and the `GET` as
+    /// `{operation_id}_graphiql`.
+    pub fn api_graphql_method_router<G, S>(
+        operation_id: &'static str,
+        summary: Option<&'static str>,
+        description: Option<&'static str>,
+    ) -> ApiMethodRouter<S>
+    where
+        G: GraphQLSchema<S> + FromRef<S>,
+        S: Clone + Send + Sync + 'static,
+    {
+        post_with(execute::<G, S>, |op| {
+            let op = op
+                .id(operation_id)
+                .summary(summary.unwrap_or("Execute a GraphQL request"));
+            match description {
+                Some(description) => op.description(description),
+                None => op,
+            }
+        })
+        .get_with(graphiql, |op| {
+            op.id(&format!("{operation_id}_graphiql"))
+                .summary("The GraphiQL IDE")
+        })
+    }
+}
@@ -0,0 +1,192 @@
+use crate::CachedResponse;
+use axum::{
+    body::to_bytes,
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::{convert::Infallible, future::Future, time::Duration};
+
+/// The header of the idempotency key of a request.
+const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// The header marking a response that was replayed from the [`IdempotencyStore`].
+const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// The `Idempotency-Key` header of a request to a route with the `idempotent` option, which
+/// clients send to retry a request without repeating its side effects. Requests without it, or
+/// with a key that isn't 1 to 255 visible ASCII characters, are rejected with
+/// `400 Bad Request`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for IdempotencyKey {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(IDEMPOTENCY_KEY) else {
+            let message = "Missing `Idempotency-Key` header";
+            return Err((StatusCode::BAD_REQUEST, message).into_response());
+        };
+        match value.to_str() {
+            Ok(key)
+                if (1..=255).contains(&key.len()) && key.bytes().all(|b| b.is_ascii_graphic()) =>
+            {
+                Ok(Self(key.to_string()))
+            }
+            _ => {
+                let message =
+                    "Invalid `Idempotency-Key` header: expected 1 to 255 visible ASCII characters";
+                Err((StatusCode::BAD_REQUEST, message).into_response())
+            }
+        }
+    }
+}
+
+/// Stores the responses of the routes with e.g. `idempotent: { ttl: 24h }`, to replay them to
+/// retries with the same [`IdempotencyKey`]. It's implemented by the state of the router, like
+/// the [`ResponseCache`](crate::ResponseCache):
+/// ```ignore
+/// impl IdempotencyStore for AppState {
+///     async fn get(&self, key: &str) -> Option<CachedResponse> {
+///         self.idempotency.get(key).await
+///     }
+///
+///     async fn insert(&self, key: String, response: CachedResponse, ttl: Duration) {
+///         self.idempotency.insert_with_ttl(key, response, ttl).await
+///     }
+/// }
+/// ```
+/// Retries that arrive while the first request is still handled aren't replayed, so a store
+/// shared by several instances should be paired with a lock on the key where that matters.
+pub trait IdempotencyStore {
+    /// The response stored under `key`, if it hasn't expired.
+    fn get(&self, key: &str) -> impl Future<Output = Option<CachedResponse>> + Send;
+
+    /// Stores the `response` under `key` for the `ttl` of the route.
+    fn insert(
+        &self,
+        key: String,
+        response: CachedResponse,
+        ttl: Duration,
+    ) -> impl Future<Output = ()> + Send;
+}
+
+/// Extracted by the routes with `idempotent: { ttl: .. }`, to replay and store their responses
+/// in the [`IdempotencyStore`] of the state.
+pub struct IdempotencyState<S>(S);
+
+impl<S> FromRequestParts<S> for IdempotencyState<S>
+where
+    S: IdempotencyStore + Clone + Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(state.clone()))
+    }
+}
+
+impl<S: IdempotencyStore + Sync> IdempotencyState<S> {
+    /// The stored response to `key`, marked with the `Idempotent-Replayed` header.
+    pub async fn get(&self, key: &str) -> Option<Response> {
+        let mut response = self.0.get(key).await?.into_response();
+        response
+            .headers_mut()
+            .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+        Some(response)
+    }
+
+    /// Stores a response that isn't a server error, so a retry after a failure is handled
+    /// again. Its body is read into memory, and a body that can't be read is a
+    /// `500 Internal Server Error`.
+    pub async fn insert(&self, key: String, response: Response, ttl: Duration) -> Response {
+        if response.status().is_server_error() {
+            return response;
+        }
+        let (parts, body) = response.into_parts();
+        let Ok(body) = to_bytes(body, usize::MAX).await else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let stored = CachedResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        };
+        self.0.insert(key, stored.clone(), ttl).await;
+        stored.into_response()
+    }
+}
+
+/// The key of a stored response: the name of the handler with the idempotency key, e.g.
+/// `my_crate::create_item#8e03978e`.
+pub fn idempotency_key(handler: &str, key: &IdempotencyKey) -> String {
+    format!("{handler}#{}", key.0)
+}
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use super::*;
+    use aide::{
+        generate::GenContext,
+        openapi::{
+            HeaderStyle, Operation, Parameter, ParameterData, ParameterSchemaOrContent,
+            ReferenceOr, Response, SchemaObject, StatusCode,
+        },
+        operation::OperationInput,
+    };
+
+    impl<S> OperationInput for IdempotencyState<S> {}
+
+    /// Documents the required header, once if the handler takes the key too, and the
+    /// `400 Bad Request` response to a request without it.
+    impl OperationInput for IdempotencyKey {
+        fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+            let documented = operation.parameters.iter().any(|parameter| {
+                matches!(
+                    parameter,
+                    ReferenceOr::Item(Parameter::Header { parameter_data, .. })
+                        if parameter_data.name.eq_ignore_ascii_case(IDEMPOTENCY_KEY.as_str())
+                )
+            });
+            if documented {
+                return;
+            }
+            let parameter_data = ParameterData {
+                name: "Idempotency-Key".to_string(),
+                description: Some(
+                    "A unique key of the request, sent again with its retries.".to_string(),
+                ),
+                required: true,
+                format: ParameterSchemaOrContent::Schema(SchemaObject {
+                    json_schema: ctx.schema.subschema_for::<String>(),
+                    example: None,
+                    external_docs: None,
+                }),
+                extensions: Default::default(),
+                deprecated: None,
+                example: None,
+                examples: Default::default(),
+                explode: None,
+            };
+            operation
+                .parameters
+                .push(ReferenceOr::Item(Parameter::Header {
+                    parameter_data,
+                    style: HeaderStyle::Simple,
+                }));
+
+            let responses = operation.responses.get_or_insert_with(Default::default);
+            responses
+                .responses
+                .entry(StatusCode::Code(400))
+                .or_insert_with(|| {
+                    ReferenceOr::Item(Response {
+                        description: "The `Idempotency-Key` header is missing or invalid"
+                            .to_string(),
+                        ..Default::default()
+                    })
+                });
+        }
+    }
+}
@@ -18,39 +18,453 @@
 //! - `/item/1?amount=2`
 //! - `/item/1?offset=3`
 //! - `/item/500`
-//! 
+//!
 //! By marking the `amount` and `offset` parameters as `Option<T>`, they become optional.
 //!
+//! The route only adds the extractors of the parameters to the handler, whose future is returned
+//! as it is unless an option wraps its output, so a typed route handles requests as fast as the
+//! same handler written for axum by hand. The `routing` benchmark compares the two.
+//!
+//! The macros and router traits can be imported at once with `use axum_typed_routing::prelude::*;`,
+//! see the [`prelude`].
+//!
+//! Services, such as `ServeDir`, can be added to typed paths with the [`route_service`] macro,
+//! and small handlers without a named function with the [`inline_route!`] macro.
+//!
+//! Query parameters shared by many routes, such as pagination, can be declared once with
+//! [`route_params!`], and added to a route with its `include` option.
+//!
+//! ## Listing routes
+//! A [`TypedRouterBuilder`] wraps a router and remembers the method, path and handler of every
+//! typed route added to it. These can be retrieved with [`TypedRouterBuilder::routes`], or
+//! logged as a table at startup with [`TypedRouterBuilder::print_routes`]:
+//! ```ignore
+//! let builder = TypedRouterBuilder::new(Router::new()).typed_route(item_handler);
+//! builder.print_routes();
+//! // METHOD  PATH        HANDLER
+//! // GET     /item/{id}  my_crate::item_handler
+//! let router = builder.into_router();
+//! ```
+//!
+//! [`TypedRouterBuilder::routes_debug_endpoint`] serves the routes with their OpenAPI operation
+//! ids and tags as JSON, e.g. behind an admin guard to diagnose `404`s in production.
+//!
+//! With [`TypedRouterBuilder::auto_options`], the builder also answers `OPTIONS` requests with
+//! the `Allow` header of every path.
+//!
+//! For server-rendered sites, [`TypedRouterBuilder::sitemap`] serves a `/sitemap.xml` listing
+//! every `GET` route without path parameters. Routes are left out with the `sitemap: false`
+//! option, or listed with a priority and change frequency with e.g.
+//! `sitemap: { priority: 0.8, changefreq: daily }`.
+//!
+//! With the `matched-route` feature, every typed route inserts a `MatchedTypedRoute` with its
+//! handler, path and method into the extensions of its requests and responses, so middleware
+//! can identify the route without parsing axum's `MatchedPath`.
+//!
+//! With the `trace` feature, [`TypedRouterBuilder::trace_layer`] creates a `tower-http`
+//! `TraceLayer` whose spans are named after the typed route of the request, with its handler and
+//! operation id, see [`TypedMakeSpan`].
+//!
+//! Routes can declare the permissions they require with e.g. `permissions: ["items:read"]`,
+//! which a single authorization middleware enforces with the [`RoutePermissions`] of the builder.
+//!
+//! Expensive `GET` routes can cache their responses with e.g.
+//! `cached: { ttl: 60s, key: [id] }`, in the [`ResponseCache`] implemented by the state of the
+//! router.
+//!
+//! With the `pagination` feature, list routes with the `paginated: true` option take the
+//! `page` and `per_page` query parameters as a [`Pagination`] argument, and return a
+//! [`Paginated`] envelope with the total number of items and links to the next and previous
+//! pages, which `api_route`s document.
+//!
+//! Routes with side effects can require an `Idempotency-Key` header with `idempotent: true`,
+//! and replay their responses to retries with e.g. `idempotent: { ttl: 24h }`, from the
+//! [`IdempotencyStore`] implemented by the state.
+//!
+//! When a route moves, e.g. `#[route(GET "/user/:id" { moved_to: get_account })]` keeps its old
+//! path working by redirecting it with `308 Permanent Redirect` to the path of the typed
+//! `get_account` route, with the values of the path parameters they share.
+//!
+//! Routes called by browsers from other origins can allow them with e.g.
+//! `cors: { origins: ["https://app.example.com"] }`, which also answers their preflight requests.
+//!
+//! With the `deadline` feature, routes of services that propagate deadlines can read them from
+//! a header with e.g. `deadline: { header: "grpc-timeout", max: 30s }`, time out when they
+//! pass, and hand the time left to the next service with a [`Deadline`] argument.
+//!
+//! With the `regex` feature, path parameters can be constrained with e.g.
+//! `/posts/:slug(regex = "[a-z0-9-]+")`, rejecting requests that don't match with
+//! `400 Bad Request`.
+//!
+//! With the `async-graphql` or `juniper` feature, a GraphQL schema taken from the state is
+//! served next to the typed routes with [`graphql_route`], which executes `POST` requests and
+//! serves GraphiQL for `GET`, see [`GraphQLSchema`]. With `aide`, [`api_graphql_route`]
+//! documents the endpoint as well.
+//!
+//! With the `dynamic` feature, a [`DynamicTypedRouter`] serves its typed routes from a table
+//! that can be changed at runtime, so single routes can be disabled (e.g. for maintenance) or
+//! re-pointed at another handler with the same method and path, see [`DynamicRoutes`].
+//!
+//! Typed routes can still be added after wrapping the router, e.g. with `tower-http`'s
+//! `NormalizePath` and the `normalize-path` feature, see [`RouterWrapper`].
+//!
+//! With the `multipart` feature, a `multipart(file: Bytes, name: String)` clause of a route
+//! reads the fields of a `multipart/form-data` body into the arguments of the handler, with
+//! `FromMultipartField`.
+//!
+//! Unlike axum, which panics on the first conflicting route, [`TypedRouterBuilder::build`]
+//! returns a [`BuildError`] listing every route that couldn't be added with its handler: an
+//! [`Error`] for a conflict, an invalid path (e.g. from the configuration), or a duplicate
+//! OpenAPI operation id. This includes the errors of builders combined with
+//! [`TypedRouterBuilder::merge_typed`].
+//! Without a builder, [`assert_no_conflicts!`] checks a list of handlers in a generated test,
+//! including the uniqueness of their OpenAPI operation ids.
+//!
+//! The handlers of one path can be combined into a single `(path, MethodRouter)` with
+//! [`merge_method_routers!`], for plain axum APIs like `nest_service` or `route_layer`.
+//!
+//! Code that isn't a handler, such as email templates and background jobs, can reference routes
+//! with the `module` option through the constants generated by [`route_index!`]:
+//! ```ignore
+//! #[route(GET "/item/:id" { module: true })]
+//! async fn item_handler(id: u32) {}
+//!
+//! route_index! { item_handler }
+//!
+//! assert_eq!(routes::ITEM_HANDLER.path, "/item/{id}");
+//! ```
+//! It also generates an enum `routes::OperationId` with a variant per route, so authorization
+//! layers can match on the routes exhaustively.
+//!
+//! ## Template links
+//! When the `templates` feature is enabled, every route gets a module with the same name as its
+//! handler, containing an `href` function that formats the path of the route, and an `attrs`
+//! function that returns the `action` and `method` of a form submitting to it. This allows
+//! templates (e.g. `maud` or `askama`) to reference routes without string literals:
+//! ```ignore
+//! let link = item_handler::href(&1); // "/item/1"
+//! let (action, method) = item_handler::attrs(&1); // ("/item/1", "GET")
+//! ```
+//!
+//! Path parameters are percent-encoded, and can be of any type implementing
+//! [`PathSegmentEncode`], which includes all types implementing `Display`.
+//!
+//! ## TypeScript route definitions
+//! When the `ts-client` feature is enabled, every route is registered for TypeScript generation.
+//! The [`ts_client::TsClient`] renders all of them into a `.ts` file with path builders and
+//! method constants, see the [`ts_client`] module for more information.
+//!
+//! ## Testing with `axum-test`
+//! When the `axum-test` feature is enabled, [`testing::TypedTestServer`] creates requests to
+//! typed routes on an `axum_test::TestServer`, deriving the method and path from the route:
+//! ```ignore
+//! let response = server.typed_request(item_handler, (1,)).await; // GET /item/1
+//! ```
+//!
+//! ## Client requests
+//! When the `client` feature is enabled, the module of every route contains a `request`
+//! builder, which takes the path parameters as arguments and the query parameters as builder
+//! methods, and builds an `http::Request<Body>` for `reqwest`, `hyper` or `tower`'s `oneshot`:
+//! ```ignore
+//! let request = item_handler::request(&1).amount(3).build(); // GET /item/1?amount=3
+//! let response = router.oneshot(request).await?;
+//! ```
+//!
+//! ## Fuzzing with `proptest`
+//! When the `fuzz` feature is enabled, the module of every route contains a `proptest` strategy
+//! for its paths with arbitrary path- and query-parameters, to fuzz the real router with
+//! structurally valid requests:
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn item_handler_accepts_any_id(path in item_handler::param_strategy()) {
+//!         // request `path` from the router
+//!     }
+//! }
+//! ```
+//!
 //! ## Example with `aide`
 //! When the `aide` feature is enabled, it's possible to automatically generate OpenAPI
 //! documentation for the routes. The [`api_route`] macro is used in place of the [`route`] macro.
 //! 
+//! Tags and security requirements shared by all routes of a module can be declared once with
+//! [`api_defaults`].
+//!
+//! The security schemes referenced by the routes can be defined with
+//! [`define_security_schemes!`], see the [`security`] module for more information.
+//!
+//! With the `scalar` or `swagger` feature enabled, [`TypedApiRouter::serve_docs`] serves the
+//! generated documentation together with a browsable UI. [`openapi_snapshot`] and
+//! [`openapi_yaml`] render it as stable JSON or YAML, to catch changes of the documentation
+//! with snapshot tests, and
+//! [`TypedApiRouter::write_openapi`] writes it to a JSON or YAML file, e.g. for client
+//! generators. [`contract_drift`] compares the documentation with a checked-in contract, e.g. at
+//! startup, to fail fast or log when the routes drift from it.
+//!
+//! A binary serving both a public and an internal API can assign routes to named documents with
+//! e.g. `api_doc: "internal"`, and document them separately with
+//! [`TypedApiRouter::finish_named_api`].
+//!
+//! Duplicate operation ids break most client generators. With the `unique-operation-ids`
+//! feature, two `api_route`s of a module with the same operation id are a compile error naming
+//! both handlers, and [`TypedRouterBuilder`] rejects the ones of different modules.
+//!
+//! Requests the API sends to its consumers, such as notifications, are documented as webhooks
+//! with the [`api_webhook`] macro and [`TypedWebhooks::typed_webhook`].
+//!
+//! Handlers returning `Result<T, E>` document the error responses of `E`, which are declared once
+//! per error type by implementing [`ErrorResponses`].
+//!
+//! The generated code uses the [`aide`] and [`schemars`] re-exported by this crate, so they
+//! don't have to be dependencies of the same version. Types deriving `JsonSchema` with the
+//! re-export name it with `#[schemars(crate = "axum_typed_routing::schemars")]`.
+//!
 //! Please read the [`aide`] documentation for more information on usage.
-//! ```
+#![cfg_attr(feature = "aide", doc = "```")]
+#![cfg_attr(not(feature = "aide"), doc = "```ignore")]
 #![doc = include_str!("../examples/aide.rs")]
 //! ```
 
 use axum::routing::MethodRouter;
+use std::any::Any;
+
+#[cfg(feature = "aide")]
+pub use aide;
+#[cfg(feature = "aide")]
+pub use schemars;
 
-type TypedHandler<S = ()> = fn() -> (&'static str, MethodRouter<S>);
-pub use axum_typed_routing_macros::route;
+mod builder;
+mod cache;
+#[cfg(feature = "aide")]
+mod contract;
+mod cors;
+#[cfg(feature = "deadline")]
+mod deadline;
+#[cfg(feature = "dynamic")]
+mod dynamic;
+mod error;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod idempotency;
+mod moved;
+#[cfg(feature = "multipart")]
+mod multipart;
+#[cfg(feature = "aide")]
+mod named_api;
+mod negotiation;
+#[cfg(feature = "pagination")]
+mod pagination;
+#[cfg(feature = "regex")]
+mod path_pattern;
+mod query;
+#[cfg(feature = "aide")]
+pub mod security;
+mod segments;
+#[cfg(feature = "axum-test")]
+pub mod testing;
+#[cfg(feature = "ts-client")]
+pub mod ts_client;
+
+/// A typed handler, as generated by the [`route`] macro: a function returning the path, the
+/// method router and the description of the route. Plugins can expose their routes as a
+/// `Vec<TypedHandler<S>>`.
+pub type TypedHandler<S = ()> = fn() -> (&'static str, MethodRouter<S>, Box<dyn Any>);
+
+/// A typed route that can be added with [`TypedRouter::typed_route`]: a [`TypedHandler`], or a
+/// closure returning the path and the method router of a route, e.g. to create routes from the
+/// configuration at startup:
+/// ```ignore
+/// let prefix: &'static str = config.admin_prefix.leak();
+/// router.typed_route(move || (prefix, axum::routing::get(admin_page)))
+/// ```
+pub trait IntoTypedRoute<S>: 'static {
+    /// The value returned by the handler.
+    type Value: TypedRouteValue<MethodRouter = MethodRouter<S>> + 'static;
+
+    /// Call the handler.
+    fn call(self) -> Self::Value;
+}
+
+impl<S, F, V> IntoTypedRoute<S> for F
+where
+    F: FnOnce() -> V + 'static,
+    V: TypedRouteValue<MethodRouter = MethodRouter<S>> + 'static,
+{
+    type Value = V;
+
+    fn call(self) -> V {
+        self()
+    }
+}
+pub use axum_typed_routing_macros::{
+    graphql_route, inline_route, route, route_index, route_params, route_service,
+};
+#[cfg(feature = "matched-route")]
+pub use builder::MatchedTypedRoute;
+#[cfg(feature = "trace")]
+pub use builder::TypedMakeSpan;
+pub use builder::{
+    FeatureGate, Locale, RouteConflict, RouteDetails, RouteInfo, RouteMap, RoutePermissions,
+    SitemapEntry, TypedRouteValue, TypedRouterBuilder,
+};
+pub use cache::{CachedResponse, ResponseCache};
+#[cfg(feature = "aide")]
+pub use contract::{contract_drift, ContractDrift, ContractOperation};
+#[cfg(feature = "deadline")]
+pub use deadline::Deadline;
+#[cfg(feature = "dynamic")]
+pub use dynamic::{DynamicRoutes, DynamicTypedRouter};
+pub use error::{BuildError, Error};
+#[cfg(feature = "graphql")]
+pub use graphql::{GraphQLRequest, GraphQLResponse, GraphQLSchema};
+pub use idempotency::{IdempotencyKey, IdempotencyStore};
+#[cfg(feature = "multipart")]
+pub use multipart::{FromMultipartField, MultipartFile};
+#[cfg(feature = "aide")]
+pub use named_api::NamedApiRouter;
+#[cfg(feature = "pagination")]
+pub use pagination::{Paginated, Pagination};
+#[cfg(feature = "deep-object")]
+pub use query::{DeepObjectQuery, DeepObjectQueryRejection};
+pub use segments::Segments;
+
+/// The macros and traits used by most routers, imported with
+/// `use axum_typed_routing::prelude::*;`.
+///
+/// [`api_route`](macro@crate::prelude::api_route) and
+/// [`api_webhook`](macro@crate::prelude::api_webhook) are exported without the `aide` feature
+/// too, so using them is a compile error naming the feature instead of an unresolved import.
+pub mod prelude {
+    #[cfg(feature = "aide")]
+    pub use crate::{TypedApiRouter, TypedWebhooks};
+    pub use crate::{TypedRouter, TypedRouterBuilder};
+    pub use axum_typed_routing_macros::{
+        api_graphql_route, api_route, api_webhook, graphql_route, inline_route, route,
+        route_params, route_service,
+    };
+}
+
+/// A value that can be formatted as a path parameter by the template links, the fuzzing
+/// strategies, the client requests and [`testing::TypedTestServer`].
+///
+/// The returned segment is percent-encoded before it's inserted into the path, so values
+/// containing `/`, `?` or spaces can't change the structure of the URL. This is implemented for
+/// all types implementing `Display`; implement it for types that should be formatted
+/// differently in paths, and don't implement `Display`:
+/// ```ignore
+/// struct Slug(Vec<String>);
+///
+/// impl PathSegmentEncode for Slug {
+///     fn to_path_segment(&self) -> Cow<'_, str> {
+///         Cow::Owned(self.0.join("-"))
+///     }
+/// }
+/// ```
+#[cfg(any(
+    feature = "templates",
+    feature = "axum-test",
+    feature = "fuzz",
+    feature = "client"
+))]
+pub trait PathSegmentEncode {
+    /// The value of the path segment, before percent-encoding.
+    fn to_path_segment(&self) -> std::borrow::Cow<'_, str>;
+}
+
+#[cfg(any(
+    feature = "templates",
+    feature = "axum-test",
+    feature = "fuzz",
+    feature = "client"
+))]
+impl<T: std::fmt::Display + ?Sized> PathSegmentEncode for T {
+    fn to_path_segment(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Owned(self.to_string())
+    }
+}
+
+/// A sample value of a path- or query-parameter, used by the test of the `generate_test` option
+/// of the [`route`] macro to request the route.
+///
+/// This is implemented for the primitive types, `String`, `Option<T>`, `Vec<T>` and
+/// [`Segments`]. Implement it for the other types of parameters, such as enums:
+/// ```ignore
+/// impl SampleParam for Color {
+///     fn sample() -> String {
+///         "red".to_string()
+///     }
+/// }
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "the parameter type `{Self}` has no sample value for `generate_test`",
+    label = "implement `axum_typed_routing::SampleParam` for this type"
+)]
+pub trait SampleParam {
+    /// The sample value, as it appears in the URL. It isn't percent-encoded.
+    fn sample() -> String;
+}
+
+macro_rules! impl_sample_param {
+    ($sample:literal: $($ty:ty),*) => {
+        $(impl SampleParam for $ty {
+            fn sample() -> String {
+                $sample.to_string()
+            }
+        })*
+    };
+}
+
+impl_sample_param!("1": u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_sample_param!("1.5": f32, f64);
+impl_sample_param!("true": bool);
+impl_sample_param!("a": char);
+impl_sample_param!("sample": String);
+
+impl<T: SampleParam> SampleParam for Option<T> {
+    fn sample() -> String {
+        T::sample()
+    }
+}
+
+/// A single element, e.g. a single segment of a wildcard.
+impl<T: SampleParam> SampleParam for Vec<T> {
+    fn sample() -> String {
+        T::sample()
+    }
+}
+
+impl SampleParam for Segments {
+    fn sample() -> String {
+        "sample".to_string()
+    }
+}
 
 /// A trait that allows typed routes, created with the [`route`] macro to
 /// be added to an axum router.
 ///
-/// Typed handlers are of the form `fn() -> (&'static str, MethodRouter<S>)`, where
-/// `S` is the state type. The first element of the tuple is the path, and the second
-/// is the method router.
+/// Typed handlers are of the form `fn() -> (&'static str, MethodRouter<S>, Box<dyn Any>)`,
+/// where `S` is the state type. The first element of the tuple is the path, the second is the
+/// method router, and the third describes the route, e.g. its method and aliases. Closures
+/// returning only the path and the method router are accepted too, see [`IntoTypedRoute`].
 pub trait TypedRouter: Sized {
     /// The state type of the router.
     type State: Clone + Send + Sync + 'static;
 
     /// Add a typed route to the router, usually created with the [`route`] macro.
     ///
-    /// Typed handlers are of the form `fn() -> (&'static str, MethodRouter<S>)`, where
-    /// `S` is the state type. The first element of the tuple is the path, and the second
-    /// is the method router.
-    fn typed_route(self, handler: TypedHandler<Self::State>) -> Self;
+    /// Typed handlers are of the form `fn() -> (&'static str, MethodRouter<S>, Box<dyn Any>)`,
+    /// where `S` is the state type. The first element of the tuple is the path, the second is
+    /// the method router, and the third describes the route.
+    fn typed_route(self, handler: impl IntoTypedRoute<Self::State>) -> Self;
+
+    /// Add a typed route to the router if `enabled` is `true`, e.g. for feature-flagged or
+    /// environment-gated endpoints.
+    fn typed_route_if(self, enabled: bool, handler: impl IntoTypedRoute<Self::State>) -> Self {
+        match enabled {
+            true => self.typed_route(handler),
+            false => self,
+        }
+    }
 }
 
 impl<S> TypedRouter for axum::Router<S>
@@ -59,9 +473,45 @@ where
 {
     type State = S;
 
-    fn typed_route(self, handler: TypedHandler<Self::State>) -> Self {
-        let (path, method_router) = handler();
-        self.route(path, method_router)
+    fn typed_route(self, handler: impl IntoTypedRoute<Self::State>) -> Self {
+        builder::route_typed_handler(self, || handler.call(), axum::Router::route).0
+    }
+}
+
+/// A wrapper of a router, such as `tower-http`'s `NormalizePath`, whose typed routes are added
+/// to the router it wraps. Every `RouterWrapper` is a [`TypedRouter`], so `typed_route` calls can
+/// still be chained after wrapping the router:
+/// ```ignore
+/// let app = NormalizePath::trim_trailing_slash(Router::new())
+///     .typed_route(item_handler)
+///     .typed_route(list_handler);
+/// ```
+///
+/// With the `normalize-path` feature, this is implemented for `NormalizePath`. Implement it for
+/// other wrappers exposing their router.
+pub trait RouterWrapper {
+    /// The wrapped router.
+    type Router: TypedRouter + Default;
+
+    fn router_mut(&mut self) -> &mut Self::Router;
+}
+
+impl<W: RouterWrapper> TypedRouter for W {
+    type State = <W::Router as TypedRouter>::State;
+
+    fn typed_route(mut self, handler: impl IntoTypedRoute<Self::State>) -> Self {
+        let router = std::mem::take(self.router_mut());
+        *self.router_mut() = router.typed_route(handler);
+        self
+    }
+}
+
+#[cfg(feature = "normalize-path")]
+impl<R: TypedRouter + Default> RouterWrapper for tower_http::normalize_path::NormalizePath<R> {
+    type Router = R;
+
+    fn router_mut(&mut self) -> &mut Self::Router {
+        self.get_mut()
     }
 }
 
@@ -69,15 +519,32 @@ where
 pub use aide_support::*;
 #[cfg(feature = "aide")]
 mod aide_support {
-    use crate::{TypedHandler, TypedRouter};
+    use crate::{
+        builder::route_typed_handler, IntoTypedRoute, NamedApiRouter, TypedRouter,
+        TypedRouterBuilder,
+    };
+    use aide::openapi::{OpenApi, Operation, PathItem, ReferenceOr, Response};
     use aide::{
         axum::{routing::ApiMethodRouter, ApiRouter},
-        transform::TransformPathItem,
+        generate::GenContext,
+        operation::OperationOutput,
+        transform::{TransformOpenApi, TransformPathItem},
     };
+    use std::{any::Any, io, path::Path};
+
+    type TypedApiHandler<S = ()> = fn() -> (&'static str, ApiMethodRouter<S>, Box<dyn Any>);
+
+    /// A webhook, as generated by the [`api_webhook`] macro: a function returning the name and
+    /// the method router of the webhook.
+    type TypedApiWebhook<S = ()> = fn() -> (&'static str, ApiMethodRouter<S>);
+
+    pub use axum_typed_routing_macros::{api_defaults, api_graphql_route, api_route, api_webhook};
 
-    type TypedApiHandler<S = ()> = fn() -> (&'static str, ApiMethodRouter<S>);
+    /// The locale is provided by the router, so it isn't part of the operation input.
+    impl aide::operation::OperationInput for crate::Locale {}
 
-    pub use axum_typed_routing_macros::api_route;
+    /// The feature flag is checked with the state, so it isn't part of the operation input.
+    impl<F> aide::operation::OperationInput for crate::__private::RequireFeature<F> {}
 
     impl<S> TypedRouter for ApiRouter<S>
     where
@@ -85,9 +552,8 @@ mod aide_support {
     {
         type State = S;
 
-        fn typed_route(self, handler: TypedHandler<Self::State>) -> Self {
-            let (path, method_router) = handler();
-            self.route(path, method_router)
+        fn typed_route(self, handler: impl IntoTypedRoute<Self::State>) -> Self {
+            route_typed_handler(self, || handler.call(), ApiRouter::route).0
         }
     }
 
@@ -103,6 +569,148 @@ mod aide_support {
             handler: TypedApiHandler<Self::State>,
             transform: impl FnOnce(TransformPathItem) -> TransformPathItem,
         ) -> Self;
+
+        /// Finish the documentation of the router into `api`, and serve it as JSON at
+        /// `/openapi.json`, with the documentation UI `ui` at `path`.
+        ///
+        /// The UI is not part of the documentation itself. Since the UI loads the documentation
+        /// from `/openapi.json`, the returned router should not be nested.
+        #[cfg(any(feature = "scalar", feature = "swagger"))]
+        fn serve_docs(self, api: OpenApi, path: &str, ui: DocsUi) -> axum::Router<Self::State>;
+
+        /// Finish the documentation of the router into `api`, and render it as JSON with
+        /// [`openapi_snapshot`]. An empty title and version of `api` default to `"API"` and
+        /// `"0.1.0"`, since both are required by OpenApi.
+        fn finish_api_to_json(self, api: OpenApi) -> (axum::Router<Self::State>, String);
+
+        /// Same as [`TypedApiRouter::finish_api_to_json`], but rendered as YAML with
+        /// [`openapi_yaml`].
+        fn finish_api_to_yaml(self, api: OpenApi) -> (axum::Router<Self::State>, String);
+
+        /// Finish the documentation of the router into `api`, and write it to `path`, as YAML if
+        /// the extension is `.yaml` or `.yml`, and as JSON otherwise. See
+        /// [`TypedApiRouter::finish_api_to_json`] for the defaults.
+        fn write_openapi(
+            self,
+            api: OpenApi,
+            path: impl AsRef<Path>,
+        ) -> io::Result<axum::Router<Self::State>> {
+            let path = path.as_ref();
+            let is_yaml = matches!(
+                path.extension().and_then(|extension| extension.to_str()),
+                Some("yaml" | "yml")
+            );
+            let (router, spec) = match is_yaml {
+                true => self.finish_api_to_yaml(api),
+                false => self.finish_api_to_json(api),
+            };
+            std::fs::write(path, spec)?;
+            Ok(router)
+        }
+
+        /// Document the routes with `api_doc: "<name>"` into `api`, instead of the main
+        /// documentation, which is finished with [`NamedApiRouter::finish_api`]. This can be
+        /// chained for every named document, e.g.
+        /// `router.finish_named_api("internal", &mut internal).finish_api(&mut public)`.
+        fn finish_named_api<'a>(
+            self,
+            name: &'a str,
+            api: &'a mut OpenApi,
+        ) -> NamedApiRouter<'a, Self::State>;
+    }
+
+    /// An extension of [`TransformOpenApi`] documenting the webhooks created with the
+    /// [`api_webhook`] macro.
+    pub trait TypedWebhooks: Sized {
+        /// Document `webhook` under the `webhooks` of the documentation. This should be called
+        /// in the transform of `finish_api_with`, so the schemas of the webhook are added to the
+        /// components, e.g.
+        /// `router.finish_api_with(&mut api, |api| api.typed_webhook(order_created))`.
+        fn typed_webhook<S>(self, webhook: TypedApiWebhook<S>) -> Self
+        where
+            S: Send + Sync + Clone + 'static;
+    }
+
+    impl TypedWebhooks for TransformOpenApi<'_> {
+        fn typed_webhook<S>(mut self, webhook: TypedApiWebhook<S>) -> Self
+        where
+            S: Send + Sync + Clone + 'static,
+        {
+            let (name, method_router) = webhook();
+            // The operations of an `ApiMethodRouter` can only be taken as a path item by routing
+            // it, so it's routed by a router which is thrown away.
+            let mut path_item = PathItem::default();
+            let _ = ApiRouter::new()
+                .api_route("/", method_router)
+                .with_path_items(|mut item| {
+                    path_item = std::mem::take(item.inner_mut());
+                    item
+                });
+            self.inner_mut()
+                .webhooks
+                .insert(name.to_owned(), ReferenceOr::Item(path_item));
+            self
+        }
+    }
+
+    /// The error responses documented for the error type `E` of the handlers of `api_route`
+    /// returning `Result<T, E>`, next to the responses of `T`.
+    ///
+    /// This is implemented for the types implementing [`OperationOutput`], and can be
+    /// implemented for an error type to document its statuses, e.g.
+    /// ```ignore
+    /// impl ErrorResponses for ApiError {
+    ///     fn error_responses(docs: &mut ErrorResponseDocs<'_>) {
+    ///         docs.response::<Json<ErrorBody>>(404, "The item doesn't exist")
+    ///             .response::<Json<ErrorBody>>(422, "The item is invalid");
+    ///     }
+    /// }
+    /// ```
+    pub trait ErrorResponses {
+        fn error_responses(docs: &mut ErrorResponseDocs<'_>);
+    }
+
+    impl<E: OperationOutput> ErrorResponses for E {
+        fn error_responses(docs: &mut ErrorResponseDocs<'_>) {
+            let responses = E::inferred_responses(docs.ctx, docs.operation);
+            docs.responses.extend(responses);
+        }
+    }
+
+    /// The error responses documented by [`ErrorResponses::error_responses`].
+    pub struct ErrorResponseDocs<'a> {
+        pub(crate) ctx: &'a mut GenContext,
+        pub(crate) operation: &'a mut Operation,
+        pub(crate) responses: Vec<(Option<u16>, Response)>,
+    }
+
+    impl ErrorResponseDocs<'_> {
+        /// Documents the response with `status`, with the body of `R`, e.g. `Json<ErrorBody>`, or
+        /// `()` for a response without a body.
+        pub fn response<R: OperationOutput>(
+            &mut self,
+            status: u16,
+            description: &str,
+        ) -> &mut Self {
+            let mut response = R::operation_response(self.ctx, self.operation).unwrap_or_default();
+            response.description = description.to_owned();
+            self.responses.push((Some(status), response));
+            self
+        }
+    }
+
+    /// The documentation UI mounted by [`TypedApiRouter::serve_docs`].
+    #[cfg(any(feature = "scalar", feature = "swagger"))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum DocsUi {
+        /// [Scalar](https://github.com/scalar/scalar), enabled with the `scalar` feature.
+        #[cfg(feature = "scalar")]
+        Scalar,
+        /// [Swagger UI](https://github.com/swagger-api/swagger-ui), enabled with the `swagger`
+        /// feature.
+        #[cfg(feature = "swagger")]
+        Swagger,
     }
 
     impl<S> TypedApiRouter for ApiRouter<S>
@@ -110,8 +718,7 @@ mod aide_support {
         S: Send + Sync + Clone + 'static,
     {
         fn typed_api_route(self, handler: TypedApiHandler<Self::State>) -> Self {
-            let (path, method_router) = handler();
-            self.api_route(path, method_router)
+            route_typed_handler(self, handler, ApiRouter::api_route).0
         }
 
         fn typed_api_route_with(
@@ -119,8 +726,1139 @@ mod aide_support {
             handler: TypedApiHandler<Self::State>,
             transform: impl FnOnce(TransformPathItem) -> TransformPathItem,
         ) -> Self {
-            let (path, method_router) = handler();
-            self.api_route_with(path, method_router, transform)
+            route_typed_handler(self, handler, |router, path, method_router| {
+                router.api_route_with(path, method_router, transform)
+            })
+            .0
+        }
+
+        #[cfg(any(feature = "scalar", feature = "swagger"))]
+        fn serve_docs(self, mut api: OpenApi, path: &str, ui: DocsUi) -> axum::Router<S> {
+            const SPEC_PATH: &str = "/openapi.json";
+
+            let ui_route = match ui {
+                #[cfg(feature = "scalar")]
+                DocsUi::Scalar => aide::scalar::Scalar::new(SPEC_PATH).axum_route(),
+                #[cfg(feature = "swagger")]
+                DocsUi::Swagger => aide::swagger::Swagger::new(SPEC_PATH).axum_route(),
+            };
+            let router = self.route(path, ui_route).finish_api(&mut api);
+
+            let spec = axum::body::Bytes::from(
+                serde_json::to_vec(&api).expect("failed to serialize the OpenApi documentation"),
+            );
+            router.route(
+                SPEC_PATH,
+                axum::routing::get(move || async move {
+                    (
+                        [(axum::http::header::CONTENT_TYPE, "application/json")],
+                        spec,
+                    )
+                }),
+            )
+        }
+
+        fn finish_api_to_json(self, api: OpenApi) -> (axum::Router<S>, String) {
+            let (router, api) = finish_with_defaults(self, api);
+            (router, openapi_snapshot(&api))
+        }
+
+        fn finish_api_to_yaml(self, api: OpenApi) -> (axum::Router<S>, String) {
+            let (router, api) = finish_with_defaults(self, api);
+            (router, openapi_yaml(&api))
+        }
+
+        fn finish_named_api<'a>(
+            self,
+            name: &'a str,
+            api: &'a mut OpenApi,
+        ) -> NamedApiRouter<'a, S> {
+            NamedApiRouter::new(self).finish_named_api(name, api)
+        }
+    }
+
+    /// Finish the documentation of `router` into `api`, with a default title and version.
+    fn finish_with_defaults<S>(router: ApiRouter<S>, mut api: OpenApi) -> (axum::Router<S>, OpenApi)
+    where
+        S: Send + Sync + Clone + 'static,
+    {
+        let router = router.finish_api(&mut api);
+        if api.info.title.is_empty() {
+            api.info.title = "API".to_string();
+        }
+        if api.info.version.is_empty() {
+            api.info.version = "0.1.0".to_string();
+        }
+        (router, api)
+    }
+
+    /// Render `api` as pretty-printed JSON for snapshot tests, e.g. with `insta`:
+    /// ```ignore
+    /// let mut api = OpenApi::default();
+    /// ApiRouter::new().typed_api_route(item_handler).finish_api(&mut api);
+    /// insta::assert_snapshot!(openapi_snapshot(&api));
+    /// ```
+    ///
+    /// The keys of all objects, including the paths and schemas, are sorted, so the output
+    /// doesn't depend on the order in which the routes were added. [`openapi_yaml`] renders the
+    /// same snapshot as YAML.
+    pub fn openapi_snapshot(api: &OpenApi) -> String {
+        let value =
+            serde_json::to_value(api).expect("failed to serialize the OpenApi documentation");
+        let mut snapshot = serde_json::to_string_pretty(&sort_keys(value))
+            .expect("failed to serialize the OpenApi documentation");
+        snapshot.push('\n');
+        snapshot
+    }
+
+    /// Render `api` as YAML, with the keys sorted like [`openapi_snapshot`], e.g. for snapshot
+    /// tests of a YAML contract:
+    /// ```ignore
+    /// insta::assert_snapshot!(openapi_yaml(&api));
+    /// ```
+    pub fn openapi_yaml(api: &OpenApi) -> String {
+        let value =
+            serde_json::to_value(api).expect("failed to serialize the OpenApi documentation");
+        let mut yaml = String::new();
+        write_yaml_block(&mut yaml, &sort_keys(value), 0);
+        yaml
+    }
+
+    /// Writes a non-empty object or array as a YAML block, indented by `indent` spaces. Keys and
+    /// strings are written as JSON strings, which are valid double-quoted YAML scalars.
+    fn write_yaml_block(out: &mut String, value: &serde_json::Value, indent: usize) {
+        let entries: Vec<(String, &serde_json::Value)> = match value {
+            serde_json::Value::Object(object) => object
+                .iter()
+                .map(|(key, value)| (format!("{}:", serde_json::Value::from(key.as_str())), value))
+                .collect(),
+            serde_json::Value::Array(array) => {
+                array.iter().map(|value| ("-".to_string(), value)).collect()
+            }
+            _ => unreachable!("only objects and arrays are written as blocks"),
+        };
+        if entries.is_empty() {
+            out.push_str(&" ".repeat(indent));
+            out.push_str(&yaml_scalar(value));
+            out.push('\n');
+        }
+        for (prefix, value) in entries {
+            out.push_str(&" ".repeat(indent));
+            out.push_str(&prefix);
+            match value {
+                serde_json::Value::Object(object) if !object.is_empty() => {
+                    out.push('\n');
+                    write_yaml_block(out, value, indent + 2);
+                }
+                serde_json::Value::Array(array) if !array.is_empty() => {
+                    out.push('\n');
+                    write_yaml_block(out, value, indent + 2);
+                }
+                value => {
+                    out.push(' ');
+                    out.push_str(&yaml_scalar(value));
+                    out.push('\n');
+                }
+            }
+        }
+    }
+
+    /// A value that is written on a single line: a scalar, or an empty object or array.
+    fn yaml_scalar(value: &serde_json::Value) -> String {
+        match value {
+            serde_json::Value::Object(_) => "{}".to_string(),
+            serde_json::Value::Array(_) => "[]".to_string(),
+            value => value.to_string(),
+        }
+    }
+
+    fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(object) => {
+                let mut entries = object.into_iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect()
+            }
+            serde_json::Value::Array(array) => array.into_iter().map(sort_keys).collect(),
+            value => value,
+        }
+    }
+
+    impl<S> crate::builder::sealed::BuildRouter for ApiRouter<S>
+    where
+        S: Send + Sync + Clone + 'static,
+    {
+        type State = S;
+
+        fn add_route(self, path: &str, method_router: axum::routing::MethodRouter<S>) -> Self {
+            self.route(path, method_router)
+        }
+
+        fn merge_router(self, other: Self) -> Self {
+            self.merge(other)
+        }
+    }
+
+    impl<S> TypedRouter for TypedRouterBuilder<ApiRouter<S>>
+    where
+        S: Send + Sync + Clone + 'static,
+    {
+        type State = S;
+
+        fn typed_route(self, handler: impl IntoTypedRoute<Self::State>) -> Self {
+            self.add_typed_route(handler, ApiRouter::route)
+        }
+    }
+
+    impl<S> TypedApiRouter for TypedRouterBuilder<ApiRouter<S>>
+    where
+        S: Send + Sync + Clone + 'static,
+    {
+        fn typed_api_route(self, handler: TypedApiHandler<Self::State>) -> Self {
+            self.add_typed_api_route(handler, ApiRouter::api_route)
+        }
+
+        fn typed_api_route_with(
+            self,
+            handler: TypedApiHandler<Self::State>,
+            transform: impl FnOnce(TransformPathItem) -> TransformPathItem,
+        ) -> Self {
+            self.add_typed_api_route(handler, |router, path, method_router| {
+                router.api_route_with(path, method_router, transform)
+            })
+        }
+
+        #[cfg(any(feature = "scalar", feature = "swagger"))]
+        fn serve_docs(self, api: OpenApi, path: &str, ui: DocsUi) -> axum::Router<S> {
+            self.into_router().serve_docs(api, path, ui)
+        }
+
+        fn finish_api_to_json(self, api: OpenApi) -> (axum::Router<S>, String) {
+            self.into_router().finish_api_to_json(api)
+        }
+
+        fn finish_api_to_yaml(self, api: OpenApi) -> (axum::Router<S>, String) {
+            self.into_router().finish_api_to_yaml(api)
+        }
+
+        fn finish_named_api<'a>(
+            self,
+            name: &'a str,
+            api: &'a mut OpenApi,
+        ) -> NamedApiRouter<'a, S> {
+            self.into_router().finish_named_api(name, api)
+        }
+    }
+}
+
+/// Items used by the code generated by the macros. Not public API.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "matched-route")]
+    pub use crate::builder::matched_typed_route;
+    pub use crate::builder::{
+        assert_extracts, assert_no_conflicts, cache_control, custom_method, default_locale,
+        deprecation_headers, describe_handler, disable_compression, erased_layers,
+        merge_method_routers, require_host, ErasedFuture, FeatureFlagName, RequireFeature,
+        RouteDescription, RouteLayers,
+    };
+    pub use crate::cache::{cache_key, CacheState, Cached};
+    pub use crate::cors::{cors_headers, CorsPolicy};
+    #[cfg(all(feature = "deadline", feature = "aide"))]
+    pub use crate::deadline::document_deadline;
+    #[cfg(feature = "deadline")]
+    pub use crate::deadline::{deadline, DeadlinePolicy};
+    #[cfg(all(feature = "graphql", feature = "aide"))]
+    pub use crate::graphql::api_graphql_method_router;
+    #[cfg(feature = "graphql")]
+    pub use crate::graphql::graphql_method_router;
+    pub use crate::idempotency::{idempotency_key, IdempotencyState};
+    #[cfg(feature = "aide")]
+    pub use crate::moved::document_moved_to;
+    pub use crate::moved::{moved_to, moved_to_path};
+    #[cfg(feature = "multipart")]
+    pub use crate::multipart::{
+        missing_multipart_field, multipart, next_multipart_field, read_multipart_field,
+    };
+    pub use crate::negotiation::{AcceptHeader, Encoding, Negotiated};
+    #[cfg(all(feature = "regex", feature = "aide"))]
+    pub use crate::path_pattern::document_path_patterns;
+    #[cfg(feature = "regex")]
+    pub use crate::path_pattern::{path_patterns, PathPattern};
+    #[cfg(feature = "deep-object")]
+    pub use crate::query::DeepObjectQuery;
+    pub use crate::query::{
+        LenientQuery, QueryParamName, QueryParamNames, QueryParamTypes, QueryParams,
+        SingleQueryParam,
+    };
+    pub use crate::segments::path_segments;
+    pub use axum_macros::debug_handler;
+    pub use axum_typed_routing_macros::include_route_params;
+    #[cfg(feature = "fuzz")]
+    pub use proptest;
+    use serde::de::{Deserialize, DeserializeOwned, Deserializer, Error};
+    use std::{fmt::Display, str::FromStr};
+
+    /// Implemented by the types of path parameters, for a clear error message when a type can't
+    /// be extracted, instead of an error about the whole handler.
+    #[diagnostic::on_unimplemented(
+        message = "the path parameter type `{Self}` doesn't implement `serde::Deserialize`",
+        label = "path parameters must implement `serde::de::DeserializeOwned`"
+    )]
+    pub trait PathParam: DeserializeOwned {}
+    impl<T: DeserializeOwned> PathParam for T {}
+
+    /// Same as [`PathParam`], but for query parameters.
+    #[diagnostic::on_unimplemented(
+        message = "the query parameter type `{Self}` doesn't implement `serde::Deserialize`",
+        label = "query parameters must implement `serde::de::DeserializeOwned`"
+    )]
+    pub trait QueryParam: DeserializeOwned {}
+    impl<T: DeserializeOwned> QueryParam for T {}
+
+    pub fn assert_path_param<T: PathParam>() {}
+
+    /// Deserializes a path- or query-parameter with `try_from = <RAW>` as `RAW`, and converts it
+    /// with `TryFrom`. A failed conversion is a deserialization error, so it's rejected with
+    /// `400 Bad Request`.
+    pub fn try_from_param<'de, R, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        R: Deserialize<'de>,
+        T: TryFrom<R>,
+        T::Error: Display,
+        D: Deserializer<'de>,
+    {
+        T::try_from(R::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    /// Same as [`try_from_param`], but for optional query parameters.
+    pub fn try_from_param_option<'de, R, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        R: Deserialize<'de>,
+        T: TryFrom<R>,
+        T::Error: Display,
+        D: Deserializer<'de>,
+    {
+        try_from_param::<R, T, D>(deserializer).map(Some)
+    }
+
+    /// Deserializes a path- or query-parameter with `from_str` as a string, and parses it with
+    /// `FromStr`. A failed parse is a deserialization error, so it's rejected with
+    /// `400 Bad Request`.
+    pub fn from_str_param<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+
+    /// Same as [`from_str_param`], but for optional query parameters.
+    pub fn from_str_param_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        from_str_param(deserializer).map(Some)
+    }
+
+    /// Deserializes a query parameter with `explode = false` from a comma-separated list, e.g.
+    /// `ids=1,2,3`.
+    pub fn comma_separated<'de, D, C, T>(deserializer: D) -> Result<C, D::Error>
+    where
+        D: Deserializer<'de>,
+        C: FromIterator<T>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        let list = String::deserialize(deserializer)?;
+        list.split(',')
+            .filter(|item| !item.is_empty())
+            .map(|item| item.parse().map_err(D::Error::custom))
+            .collect()
+    }
+
+    /// Same as [`comma_separated`], but for optional parameters.
+    pub fn comma_separated_option<'de, D, C, T>(deserializer: D) -> Result<Option<C>, D::Error>
+    where
+        D: Deserializer<'de>,
+        C: FromIterator<T>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        comma_separated(deserializer).map(Some)
+    }
+
+    pub fn assert_query_param<T: QueryParam>() {}
+
+    /// Implemented by the types of all handler arguments except the last one.
+    #[diagnostic::on_unimplemented(
+        message = "the argument type `{Self}` can't be extracted from the request parts",
+        label = "not the last argument, so it must implement `FromRequestParts<{S}>`",
+        note = "only the last argument of a handler can consume the request body"
+    )]
+    pub trait PartsArgument<S> {}
+    impl<T: axum::extract::FromRequestParts<S>, S> PartsArgument<S> for T {}
+
+    /// Implemented by the type of the last handler argument.
+    #[diagnostic::on_unimplemented(
+        message = "the argument type `{Self}` can't be extracted from the request",
+        label = "the last argument must implement `FromRequest<{S}>` or `FromRequestParts<{S}>`"
+    )]
+    pub trait LastArgument<S, M> {}
+    impl<T: axum::extract::FromRequest<S, M>, S, M> LastArgument<S, M> for T {}
+
+    /// Implemented by the functions generated for the routes, if they are valid handlers.
+    #[diagnostic::on_unimplemented(
+        message = "the function of this route is not a valid axum handler",
+        label = "not a handler with state `{S}`",
+        note = "handlers must be `async`, take at most 16 extractors, and return a type implementing `IntoResponse`",
+        note = "arguments that aren't extractors are reported separately"
+    )]
+    pub trait RouteHandler<T, S> {}
+    impl<H: axum::handler::Handler<T, S>, T, S> RouteHandler<T, S> for H {}
+
+    pub fn assert_handler<H: RouteHandler<T, S>, T, S>(_handler: &H) {}
+
+    pub fn assert_parts_argument<T: PartsArgument<S>, S>() {}
+
+    pub fn assert_last_argument<T: LastArgument<S, M>, S, M>() {}
+
+    #[cfg(feature = "ts-client")]
+    pub use inventory;
+
+    #[cfg(feature = "aide")]
+    pub use openapi::*;
+    #[cfg(feature = "aide")]
+    mod openapi {
+        use crate::{ErrorResponseDocs, ErrorResponses};
+        use aide::{
+            generate::GenContext,
+            openapi::{
+                CookieStyle, Example, HeaderStyle, MediaType, Operation, Parameter, ParameterData,
+                ParameterSchemaOrContent, PathStyle, QueryStyle, ReferenceOr, RequestBody,
+                Response, SchemaObject, StatusCode,
+            },
+            operation::{OperationOutput, ParamLocation},
+            transform::TransformOperation,
+        };
+        use axum::response::IntoResponse;
+        use schemars::{
+            schema::{
+                InstanceType, ObjectValidation, Schema, SchemaObject as JsonSchemaObject,
+                SubschemaValidation,
+            },
+            JsonSchema,
+        };
+
+        /// The output of the `api_route` handlers returning `Result<T, E>`, which documents the
+        /// [`ErrorResponses`](crate::ErrorResponses) of `E` next to the responses of `T`.
+        pub struct ApiResult<T, E>(pub Result<T, E>);
+
+        impl<T: IntoResponse, E: IntoResponse> IntoResponse for ApiResult<T, E> {
+            fn into_response(self) -> axum::response::Response {
+                match self.0 {
+                    Ok(value) => value.into_response(),
+                    Err(err) => err.into_response(),
+                }
+            }
+        }
+
+        impl<T: OperationOutput, E: ErrorResponses> OperationOutput for ApiResult<T, E> {
+            type Inner = T;
+
+            fn operation_response(
+                ctx: &mut GenContext,
+                operation: &mut Operation,
+            ) -> Option<Response> {
+                T::operation_response(ctx, operation)
+            }
+
+            fn inferred_responses(
+                ctx: &mut GenContext,
+                operation: &mut Operation,
+            ) -> Vec<(Option<u16>, Response)> {
+                let mut responses = T::inferred_responses(ctx, operation);
+                let mut docs = ErrorResponseDocs {
+                    ctx,
+                    operation,
+                    responses: Vec::new(),
+                };
+                E::error_responses(&mut docs);
+                responses.extend(docs.responses);
+                responses
+            }
+        }
+
+        /// A field of a documented `multipart/form-data` body: `(name, schema, required)`.
+        pub type MultipartField = (&'static str, Schema, bool);
+
+        pub fn multipart_field<T: JsonSchema>(
+            name: &'static str,
+            required: bool,
+        ) -> MultipartField {
+            let schema = aide::generate::in_context(|ctx| ctx.schema.subschema_for::<T>());
+            (name, schema, required)
+        }
+
+        /// Same as [`multipart_field`], for the `OperationInput` of a `multipart(..)` clause,
+        /// which is given the context.
+        pub fn multipart_input_field<T: JsonSchema>(
+            ctx: &mut aide::generate::GenContext,
+            name: &'static str,
+            required: bool,
+        ) -> MultipartField {
+            (name, ctx.schema.subschema_for::<T>(), required)
+        }
+
+        pub fn multipart_binary_field(name: &'static str, required: bool) -> MultipartField {
+            let schema = Schema::Object(JsonSchemaObject {
+                instance_type: Some(InstanceType::String.into()),
+                format: Some("binary".to_string()),
+                ..Default::default()
+            });
+            (name, schema, required)
+        }
+
+        /// The operation id `<MODULE>.<ID>` of `id_module_prefix`, where `<MODULE>` is the last
+        /// segment of the module path of the handler.
+        pub fn module_operation_id(module_path: &str, id: &str) -> String {
+            let module = module_path.rsplit("::").next().unwrap_or(module_path);
+            format!("{module}.{id}")
+        }
+
+        /// The code of a `StatusCode` constant of the `responses` option, e.g. `TEAPOT`.
+        pub const fn status_code(status: axum::http::StatusCode) -> u16 {
+            status.as_u16()
+        }
+
+        /// The documented parameter `name` of the operation. If `aide` didn't document it, it is
+        /// added as a required parameter at `location` with the schema of `T`.
+        pub fn param_data<'a, T: JsonSchema>(
+            operation: &'a mut Operation,
+            name: &str,
+            location: ParamLocation,
+        ) -> &'a mut ParameterData {
+            let index = operation.parameters.iter().position(|param| {
+                param
+                    .as_item()
+                    .is_some_and(|param| param.parameter_data_ref().name == name)
+            });
+            let index = index.unwrap_or_else(|| {
+                let json_schema = aide::generate::in_context(|ctx| ctx.schema.subschema_for::<T>());
+                let parameter_data = ParameterData {
+                    name: name.to_string(),
+                    description: None,
+                    required: true,
+                    format: ParameterSchemaOrContent::Schema(SchemaObject {
+                        json_schema,
+                        example: None,
+                        external_docs: None,
+                    }),
+                    extensions: Default::default(),
+                    deprecated: None,
+                    example: None,
+                    examples: Default::default(),
+                    explode: None,
+                };
+                let parameter = match location {
+                    ParamLocation::Path => Parameter::Path {
+                        parameter_data,
+                        style: PathStyle::Simple,
+                    },
+                    _ => Parameter::Query {
+                        parameter_data,
+                        allow_reserved: false,
+                        style: QueryStyle::Form,
+                        allow_empty_value: None,
+                    },
+                };
+                operation.parameters.push(ReferenceOr::Item(parameter));
+                operation.parameters.len() - 1
+            });
+
+            match &mut operation.parameters[index] {
+                ReferenceOr::Item(param) => param.parameter_data_mut(),
+                ReferenceOr::Reference { .. } => unreachable!("only items are matched by name"),
+            }
+        }
+
+        /// Documents the parameter `name` at `location` with the schema of `T`, for the
+        /// `parameters` option. A parameter `aide` documented with the same name and location is
+        /// replaced.
+        pub fn set_parameter<'a, T: JsonSchema>(
+            operation: &'a mut Operation,
+            name: &str,
+            location: ParamLocation,
+            required: bool,
+        ) -> &'a mut ParameterData {
+            operation.parameters.retain(|param| {
+                param.as_item().is_none_or(|param| {
+                    param.parameter_data_ref().name != name || param_location(param) != location
+                })
+            });
+            let json_schema = aide::generate::in_context(|ctx| ctx.schema.subschema_for::<T>());
+            let parameter_data = ParameterData {
+                name: name.to_string(),
+                description: None,
+                required,
+                format: ParameterSchemaOrContent::Schema(SchemaObject {
+                    json_schema,
+                    example: None,
+                    external_docs: None,
+                }),
+                extensions: Default::default(),
+                deprecated: None,
+                example: None,
+                examples: Default::default(),
+                explode: None,
+            };
+            let parameter = match location {
+                ParamLocation::Path => Parameter::Path {
+                    parameter_data,
+                    style: PathStyle::Simple,
+                },
+                ParamLocation::Query => Parameter::Query {
+                    parameter_data,
+                    allow_reserved: false,
+                    style: QueryStyle::Form,
+                    allow_empty_value: None,
+                },
+                ParamLocation::Header => Parameter::Header {
+                    parameter_data,
+                    style: HeaderStyle::Simple,
+                },
+                ParamLocation::Cookie => Parameter::Cookie {
+                    parameter_data,
+                    style: CookieStyle::Form,
+                },
+            };
+            operation.parameters.push(ReferenceOr::Item(parameter));
+            match operation.parameters.last_mut() {
+                Some(ReferenceOr::Item(param)) => param.parameter_data_mut(),
+                _ => unreachable!("the parameter was just added"),
+            }
+        }
+
+        fn param_location(param: &Parameter) -> ParamLocation {
+            match param {
+                Parameter::Query { .. } => ParamLocation::Query,
+                Parameter::Header { .. } => ParamLocation::Header,
+                Parameter::Path { .. } => ParamLocation::Path,
+                Parameter::Cookie { .. } => ParamLocation::Cookie,
+            }
+        }
+
+        /// Serializes the example of the parameter `name`.
+        pub fn json_value(name: &str, value: impl serde::Serialize) -> serde_json::Value {
+            serde_json::to_value(value)
+                .unwrap_or_else(|err| panic!("failed to serialize example of `{name}`: {err}"))
+        }
+
+        /// Adds a specification extension, such as `x-internal`, to the operation.
+        pub fn set_extension(operation: &mut Operation, name: &str, value: impl serde::Serialize) {
+            let value = serde_json::to_value(value)
+                .unwrap_or_else(|err| panic!("failed to serialize extension `{name}`: {err}"));
+            operation.extensions.insert(name.to_string(), value);
+        }
+
+        /// Adds the request of the example `name` to every media type of the request body.
+        pub fn set_request_example(
+            operation: &mut Operation,
+            name: &str,
+            value: impl serde::Serialize,
+        ) {
+            let media_types = match &mut operation.request_body {
+                Some(ReferenceOr::Item(body)) => body.content.values_mut(),
+                _ => {
+                    panic!("the example `{name}` has a request, but the route has no request body")
+                }
+            };
+            add_example(media_types, name, value);
+        }
+
+        /// Adds the response of the example `name` to every media type of the response with
+        /// `status`.
+        pub fn set_response_example(
+            operation: &mut Operation,
+            status: u16,
+            name: &str,
+            value: impl serde::Serialize,
+        ) {
+            let response = operation
+                .responses
+                .as_mut()
+                .and_then(|responses| responses.responses.get_mut(&StatusCode::Code(status)));
+            let media_types = match response {
+                Some(ReferenceOr::Item(response)) if !response.content.is_empty() => {
+                    response.content.values_mut()
+                }
+                _ => panic!(
+                    "the example `{name}` has a response, but the route has no `{status}` response with a body"
+                ),
+            };
+            add_example(media_types, name, value);
+        }
+
+        fn add_example<'a>(
+            media_types: impl Iterator<Item = &'a mut MediaType>,
+            name: &str,
+            value: impl serde::Serialize,
+        ) {
+            let value = json_value(name, value);
+            for media_type in media_types {
+                let example = Example {
+                    value: Some(value.clone()),
+                    ..Default::default()
+                };
+                media_type
+                    .examples
+                    .insert(name.to_string(), ReferenceOr::Item(example));
+            }
+        }
+
+        /// Turns the documentation of a `GET` operation into that of the `HEAD` operation of
+        /// `head: auto`, by removing the response bodies and suffixing the operationId.
+        pub fn head_operation(mut op: TransformOperation) -> TransformOperation {
+            let operation = op.inner_mut();
+            if let Some(id) = &mut operation.operation_id {
+                id.push_str("_head");
+            }
+            if let Some(responses) = &mut operation.responses {
+                let responses = responses
+                    .responses
+                    .values_mut()
+                    .chain(responses.default.as_mut());
+                for response in responses {
+                    if let ReferenceOr::Item(response) = response {
+                        response.content.clear();
+                    }
+                }
+            }
+            op
+        }
+
+        /// Documents the fields of a flattened query parameter of type `T` as query parameters.
+        /// `aide` only documents the properties of the query struct itself, which a flattened
+        /// enum doesn't have. Fields of the variants are documented as optional.
+        pub fn flattened_query_params<T: JsonSchema>(
+            mut op: TransformOperation,
+        ) -> TransformOperation {
+            let properties = aide::generate::in_context(|ctx| {
+                let schema = ctx.schema.subschema_for::<T>().into_object();
+                let mut properties = Vec::<(String, JsonSchemaObject, bool)>::new();
+                collect_properties(ctx, &schema, true, &mut properties);
+                properties
+            });
+
+            let operation = op.inner_mut();
+            for (name, schema, required) in properties {
+                let documented = operation.parameters.iter().any(|param| {
+                    param
+                        .as_item()
+                        .is_some_and(|param| param.parameter_data_ref().name == name)
+                });
+                if documented {
+                    continue;
+                }
+                operation
+                    .parameters
+                    .push(ReferenceOr::Item(Parameter::Query {
+                        parameter_data: ParameterData {
+                            name,
+                            description: schema
+                                .metadata
+                                .as_ref()
+                                .and_then(|metadata| metadata.description.clone()),
+                            required,
+                            format: ParameterSchemaOrContent::Schema(SchemaObject {
+                                json_schema: schema.into(),
+                                example: None,
+                                external_docs: None,
+                            }),
+                            extensions: Default::default(),
+                            deprecated: None,
+                            example: None,
+                            examples: Default::default(),
+                            explode: None,
+                        },
+                        allow_reserved: false,
+                        style: QueryStyle::Form,
+                        allow_empty_value: None,
+                    }));
+            }
+            op
+        }
+
+        /// Collects the properties of `schema` and its subschemas as `(name, schema, required)`.
+        /// Properties of `oneOf` and `anyOf` subschemas are never required, and the enum values
+        /// of a property in multiple subschemas (such as the tag of an enum) are merged.
+        fn collect_properties(
+            ctx: &GenContext,
+            schema: &JsonSchemaObject,
+            required: bool,
+            properties: &mut Vec<(String, JsonSchemaObject, bool)>,
+        ) {
+            let schema = ctx.resolve_schema(schema);
+            if let Some(object) = &schema.object {
+                for (name, property) in &object.properties {
+                    let property = ctx.resolve_schema(&property.clone().into_object()).clone();
+                    let property_required = required && object.required.contains(name);
+                    match properties
+                        .iter_mut()
+                        .find(|(existing, ..)| existing == name)
+                    {
+                        Some((_name, existing, _required)) => {
+                            if let (Some(values), Some(new_values)) =
+                                (&mut existing.enum_values, property.enum_values)
+                            {
+                                values.extend(new_values);
+                            }
+                        }
+                        None => properties.push((name.clone(), property, property_required)),
+                    }
+                }
+            }
+            if let Some(subschemas) = &schema.subschemas {
+                for subschema in subschemas.all_of.iter().flatten() {
+                    collect_properties(ctx, &subschema.clone().into_object(), required, properties);
+                }
+                let alternatives = subschemas.one_of.iter().chain(&subschemas.any_of);
+                for subschema in alternatives.flatten() {
+                    collect_properties(ctx, &subschema.clone().into_object(), false, properties);
+                }
+            }
+        }
+
+        /// Replaces the request body of the operation with an `application/x-www-form-urlencoded`
+        /// body with the schema of `T`, like aide does with its `axum-form` feature.
+        pub fn set_form_body<T: JsonSchema>(operation: &mut Operation) {
+            let json_schema = aide::generate::in_context(|ctx| ctx.schema.subschema_for::<T>());
+            let mut body = RequestBody {
+                required: true,
+                ..Default::default()
+            };
+            body.content.insert(
+                "application/x-www-form-urlencoded".to_string(),
+                MediaType {
+                    schema: Some(SchemaObject {
+                        json_schema,
+                        external_docs: None,
+                        example: None,
+                    }),
+                    ..Default::default()
+                },
+            );
+            operation.request_body = Some(ReferenceOr::Item(body));
+        }
+
+        /// Replaces the request body of the operation with a `multipart/form-data` body.
+        pub fn set_multipart_body(operation: &mut Operation, fields: Vec<MultipartField>) {
+            let mut object = ObjectValidation::default();
+            for (name, schema, required) in fields {
+                if required {
+                    object.required.insert(name.to_string());
+                }
+                object.properties.insert(name.to_string(), schema);
+            }
+            let schema = Schema::Object(JsonSchemaObject {
+                instance_type: Some(InstanceType::Object.into()),
+                object: Some(Box::new(object)),
+                ..Default::default()
+            });
+
+            let mut body = RequestBody {
+                required: true,
+                ..Default::default()
+            };
+            body.content.insert(
+                "multipart/form-data".to_string(),
+                MediaType {
+                    schema: Some(SchemaObject {
+                        json_schema: schema,
+                        external_docs: None,
+                        example: None,
+                    }),
+                    ..Default::default()
+                },
+            );
+            operation.request_body = Some(ReferenceOr::Item(body));
+        }
+
+        /// Sets the `style` of the documented query parameter `name` to `deepObject`, and its
+        /// `explode` if specified.
+        pub fn set_query_style(
+            operation: &mut Operation,
+            name: &str,
+            deep_object: bool,
+            explode: Option<bool>,
+        ) {
+            let param = operation
+                .parameters
+                .iter_mut()
+                .find_map(|param| match param {
+                    ReferenceOr::Item(param @ Parameter::Query { .. })
+                        if param.parameter_data_ref().name == name =>
+                    {
+                        Some(param)
+                    }
+                    _ => None,
+                });
+            if let Some(Parameter::Query {
+                parameter_data,
+                style,
+                ..
+            }) = param
+            {
+                if deep_object {
+                    *style = QueryStyle::DeepObject;
+                }
+                if explode.is_some() {
+                    parameter_data.explode = explode;
+                }
+            }
+        }
+
+        /// Adds the `media_types` of the `accepts` option to the successful responses of the
+        /// operation, with the schema of their JSON content, which is removed if JSON isn't one
+        /// of them.
+        pub fn set_response_media_types(operation: &mut Operation, media_types: &[&str]) {
+            let Some(responses) = operation.responses.as_mut() else {
+                return;
+            };
+            let responses = responses
+                .responses
+                .iter_mut()
+                .filter(|(status, _response)| {
+                    matches!(status, StatusCode::Code(200..=299) | StatusCode::Range(2))
+                });
+            for (_status, response) in responses {
+                let ReferenceOr::Item(response) = response else {
+                    continue;
+                };
+                let Some(json) = response.content.get("application/json").cloned() else {
+                    continue;
+                };
+                if !media_types.contains(&"application/json") {
+                    response.content.shift_remove("application/json");
+                }
+                for media_type in media_types {
+                    response
+                        .content
+                        .entry(media_type.to_string())
+                        .or_insert_with(|| json.clone());
+                }
+            }
+        }
+
+        /// Documents the response `R` for the status code `N` like `response_with`, but merges it
+        /// into the response already documented for `N`, for a status code repeated in the
+        /// `responses` option. Its media types are added, and a schema for a media type that's
+        /// already documented is added to its `oneOf`.
+        pub fn merge_response<const N: u16, R, F>(
+            mut operation: TransformOperation,
+            transform: F,
+        ) -> TransformOperation
+        where
+            R: aide::operation::OperationOutput,
+            F: FnOnce(
+                aide::transform::TransformResponse<R::Inner>,
+            ) -> aide::transform::TransformResponse<R::Inner>,
+        {
+            let existing = operation
+                .inner_mut()
+                .responses
+                .as_mut()
+                .and_then(|responses| responses.responses.shift_remove_full(&StatusCode::Code(N)));
+            let mut operation = operation.response_with::<N, R, F>(transform);
+            let Some((index, _status, ReferenceOr::Item(mut existing))) = existing else {
+                return operation;
+            };
+            let responses = operation
+                .inner_mut()
+                .responses
+                .get_or_insert_with(Default::default);
+            if let Some(ReferenceOr::Item(response)) =
+                responses.responses.shift_remove(&StatusCode::Code(N))
+            {
+                if existing.description.is_empty() {
+                    existing.description = response.description;
+                }
+                for (name, header) in response.headers {
+                    existing.headers.entry(name).or_insert(header);
+                }
+                for (media_type, content) in response.content {
+                    match existing.content.get_mut(&media_type) {
+                        Some(MediaType {
+                            schema: Some(schema),
+                            ..
+                        }) => add_one_of(schema, content.schema),
+                        Some(_) => {}
+                        None => {
+                            existing.content.insert(media_type, content);
+                        }
+                    }
+                }
+            }
+            responses.responses.shift_insert(
+                index.min(responses.responses.len()),
+                StatusCode::Code(N),
+                ReferenceOr::Item(existing),
+            );
+            operation
+        }
+
+        /// Adds `other` to the `oneOf` of `schema`, which becomes one if it isn't one yet.
+        fn add_one_of(schema: &mut SchemaObject, other: Option<SchemaObject>) {
+            let Some(other) = other else {
+                return;
+            };
+            let is_one_of = match &schema.json_schema {
+                Schema::Object(object) => {
+                    object.reference.is_none()
+                        && object.instance_type.is_none()
+                        && object
+                            .subschemas
+                            .as_ref()
+                            .is_some_and(|subschemas| subschemas.one_of.is_some())
+                }
+                Schema::Bool(_) => false,
+            };
+            if !is_one_of {
+                let first = std::mem::replace(&mut schema.json_schema, Schema::Bool(true));
+                schema.json_schema = Schema::Object(JsonSchemaObject {
+                    subschemas: Some(Box::new(SubschemaValidation {
+                        one_of: Some(vec![first]),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                });
+            }
+            if let Schema::Object(JsonSchemaObject {
+                subschemas: Some(subschemas),
+                ..
+            }) = &mut schema.json_schema
+            {
+                subschemas
+                    .one_of
+                    .get_or_insert_with(Vec::new)
+                    .push(other.json_schema);
+            }
+        }
+
+        /// Replaces the request body of the operation with a raw body of the `media_types`, e.g.
+        /// for a `Bytes` or `Body` argument.
+        pub fn set_binary_body(operation: &mut Operation, media_types: &[&str]) {
+            let schema = Schema::Object(JsonSchemaObject {
+                instance_type: Some(InstanceType::String.into()),
+                format: Some("binary".to_string()),
+                ..Default::default()
+            });
+
+            let mut body = RequestBody {
+                required: true,
+                ..Default::default()
+            };
+            for media_type in media_types {
+                body.content.insert(
+                    media_type.to_string(),
+                    MediaType {
+                        schema: Some(SchemaObject {
+                            json_schema: schema.clone(),
+                            external_docs: None,
+                            example: None,
+                        }),
+                        ..Default::default()
+                    },
+                );
+            }
+            operation.request_body = Some(ReferenceOr::Item(body));
+        }
+    }
+
+    #[cfg(any(
+        feature = "templates",
+        feature = "axum-test",
+        feature = "fuzz",
+        feature = "client"
+    ))]
+    pub use templates::*;
+    #[cfg(any(
+        feature = "templates",
+        feature = "axum-test",
+        feature = "fuzz",
+        feature = "client"
+    ))]
+    mod templates {
+        use crate::PathSegmentEncode;
+        use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+        /// Characters that must be escaped inside a single path segment.
+        const SEGMENT: &AsciiSet = &CONTROLS
+            .add(b' ')
+            .add(b'"')
+            .add(b'#')
+            .add(b'%')
+            .add(b'/')
+            .add(b'<')
+            .add(b'>')
+            .add(b'?')
+            .add(b'\\')
+            .add(b'`')
+            .add(b'{')
+            .add(b'}');
+
+        /// Same as [`SEGMENT`], but allows `/` for wildcard captures.
+        const WILDCARD: &AsciiSet = &SEGMENT.remove(b'/');
+
+        /// Characters that must be escaped inside the value of a query parameter.
+        const QUERY_VALUE: &AsciiSet = &CONTROLS
+            .add(b' ')
+            .add(b'"')
+            .add(b'#')
+            .add(b'%')
+            .add(b'&')
+            .add(b'+')
+            .add(b'<')
+            .add(b'=')
+            .add(b'>')
+            .add(b'`')
+            .add(b'{')
+            .add(b'}');
+
+        pub fn push_path_segment(path: &mut String, value: &(impl PathSegmentEncode + ?Sized)) {
+            path.push('/');
+            path.extend(utf8_percent_encode(&value.to_path_segment(), SEGMENT));
+        }
+
+        pub fn push_path_wildcard(path: &mut String, value: &(impl PathSegmentEncode + ?Sized)) {
+            path.push('/');
+            path.extend(utf8_percent_encode(&value.to_path_segment(), WILDCARD));
+        }
+
+        /// Pushes a wildcard of segments, encoding the slashes inside of a segment.
+        pub fn push_path_segments<T: PathSegmentEncode>(path: &mut String, segments: &[T]) {
+            if segments.is_empty() {
+                path.push('/');
+            }
+            for segment in segments {
+                push_path_segment(path, segment);
+            }
+        }
+
+        /// Appends `name=value` to the query of `path`, starting the query if needed.
+        pub fn push_query_param(path: &mut String, name: &str, value: &dyn std::fmt::Display) {
+            path.push(if path.contains('?') { '&' } else { '?' });
+            path.push_str(name);
+            path.push('=');
+            path.extend(utf8_percent_encode(&value.to_string(), QUERY_VALUE));
         }
     }
 }
@@ -0,0 +1,152 @@
+//! Routes that moved to another typed route, with the `moved_to` option of the route macros.
+
+use crate::TypedRouteValue;
+use axum::{
+    extract::{FromRequestParts, OriginalUri, RawPathParams, Request},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use std::cell::RefCell;
+
+thread_local! {
+    /// The paths of the routes whose `moved_to` target is being looked up, to report a loop
+    /// instead of overflowing the stack.
+    static MOVING: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The path parameters of the axum `path`, e.g. `id` and `rest` of `/items/{id}/{*rest}`, and
+/// whether they're wildcards.
+fn captures(path: &str) -> impl Iterator<Item = (&str, bool)> {
+    path.split('/').filter_map(|segment| {
+        let capture = segment.strip_prefix('{')?.strip_suffix('}')?;
+        Some(match capture.strip_prefix('*') {
+            Some(name) => (name, true),
+            None => (capture, false),
+        })
+    })
+}
+
+/// The path of the `target` of a route at `path` with `moved_to`, called when the route is
+/// added to a router.
+///
+/// # Panics
+/// If the target has a path parameter the route doesn't have, since its value couldn't be
+/// carried over, or if the targets of `moved_to` lead back to the route.
+pub fn moved_to_path<V: TypedRouteValue>(path: &'static str, target: fn() -> V) -> &'static str {
+    let looped = MOVING.with(|moving| {
+        let mut moving = moving.borrow_mut();
+        let looped = moving.contains(&path);
+        moving.push(path);
+        looped
+    });
+    assert!(
+        !looped,
+        "the route `{path}` is moved to itself by `moved_to`"
+    );
+    let (target_path, _method_router, _description) = target().into_parts();
+    MOVING.with(|moving| moving.borrow_mut().pop());
+
+    for (name, _wildcard) in captures(target_path) {
+        assert!(
+            captures(path).any(|(param, _wildcard)| param == name),
+            "the route `{path}` is moved to `{target_path}`, whose path parameter `{name}` it doesn't have"
+        );
+    }
+    target_path
+}
+
+/// Pushes the percent-encoded `value` of a path parameter, keeping the slashes of a wildcard.
+fn push_encoded(location: &mut String, value: &str, wildcard: bool) {
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                location.push(char::from(byte))
+            }
+            b'/' if wildcard => location.push('/'),
+            _ => location.push_str(&format!("%{byte:02X}")),
+        }
+    }
+}
+
+/// Redirects a request with `308 Permanent Redirect` to the `target` path, with the path
+/// parameters of the request and its query. The prefix of a nested router is kept.
+pub async fn moved_to(target: &'static str, request: Request) -> Response {
+    let (mut parts, _body) = request.into_parts();
+    let params = RawPathParams::from_request_parts(&mut parts, &()).await;
+    let (mut location, query) = match parts.extensions.get::<OriginalUri>() {
+        Some(OriginalUri(uri)) => {
+            let prefix = uri.path().strip_suffix(parts.uri.path());
+            (prefix.unwrap_or_default().to_string(), uri.query())
+        }
+        None => (String::new(), parts.uri.query()),
+    };
+    for segment in target.split('/').skip(1) {
+        location.push('/');
+        let Some(capture) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) else {
+            location.push_str(segment);
+            continue;
+        };
+        let (name, wildcard) = match capture.strip_prefix('*') {
+            Some(name) => (name, true),
+            None => (capture, false),
+        };
+        let value = params
+            .iter()
+            .flatten()
+            .find_map(|(param, value)| (param == name).then_some(value));
+        // Only the aliases of the route can lack a parameter, which is checked for its path.
+        let Some(value) = value else {
+            let message = format!("The path parameter `{name}` of `{target}` is missing");
+            return (StatusCode::INTERNAL_SERVER_ERROR, message).into_response();
+        };
+        push_encoded(&mut location, value, wildcard);
+    }
+    if location.is_empty() {
+        location.push('/');
+    }
+    if let Some(query) = query {
+        location.push('?');
+        location.push_str(query);
+    }
+    Redirect::permanent(&location).into_response()
+}
+
+#[cfg(feature = "aide")]
+pub use openapi::document_moved_to;
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use aide::openapi::{
+        Header, HeaderStyle, Operation, ParameterSchemaOrContent, ReferenceOr, Response, Responses,
+        SchemaObject, StatusCode,
+    };
+
+    /// Documents the `308 Permanent Redirect` to the `target` path as the only response, since
+    /// the handler isn't called.
+    pub fn document_moved_to(operation: &mut Operation, target: &str) {
+        let json_schema = aide::generate::in_context(|ctx| ctx.schema.subschema_for::<String>());
+        let location = Header {
+            description: Some(format!("The path of `{target}`, with the same parameters")),
+            style: HeaderStyle::Simple,
+            required: true,
+            deprecated: None,
+            format: ParameterSchemaOrContent::Schema(SchemaObject {
+                json_schema,
+                example: None,
+                external_docs: None,
+            }),
+            example: None,
+            examples: Default::default(),
+            extensions: Default::default(),
+        };
+        let response = Response {
+            description: format!("The route moved to `{target}`"),
+            headers: [("Location".to_string(), ReferenceOr::Item(location))].into(),
+            ..Default::default()
+        };
+        operation.responses = Some(Responses {
+            responses: [(StatusCode::Code(308), ReferenceOr::Item(response))].into(),
+            ..Default::default()
+        });
+    }
+}
@@ -0,0 +1,125 @@
+use axum::{
+    body::Bytes,
+    extract::{
+        multipart::{Field, MultipartError},
+        FromRequest, Multipart, Request,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use std::future::Future;
+
+/// A value of a field of a `multipart(..)` clause, read from a field of a `multipart/form-data`
+/// body.
+///
+/// This is implemented for [`Bytes`], `Vec<u8>` and [`MultipartFile`] for files, and for
+/// `String`, `bool`, `char` and the numbers for text fields, which are parsed with `FromStr`.
+/// A field that can't be read is rejected with `400 Bad Request`.
+#[diagnostic::on_unimplemented(
+    message = "the multipart field type `{Self}` doesn't implement `FromMultipartField`",
+    label = "multipart fields must implement `axum_typed_routing::FromMultipartField`"
+)]
+pub trait FromMultipartField: Sized {
+    /// Reads the value from the field, or returns the rejection of the request.
+    fn from_multipart_field(
+        field: Field<'_>,
+    ) -> impl Future<Output = Result<Self, Response>> + Send;
+}
+
+impl FromMultipartField for Bytes {
+    async fn from_multipart_field(field: Field<'_>) -> Result<Self, Response> {
+        field.bytes().await.map_err(IntoResponse::into_response)
+    }
+}
+
+impl FromMultipartField for Vec<u8> {
+    async fn from_multipart_field(field: Field<'_>) -> Result<Self, Response> {
+        Bytes::from_multipart_field(field).await.map(Vec::from)
+    }
+}
+
+impl FromMultipartField for String {
+    async fn from_multipart_field(field: Field<'_>) -> Result<Self, Response> {
+        field.text().await.map_err(IntoResponse::into_response)
+    }
+}
+
+macro_rules! from_str_fields {
+    ($($ty:ty),*) => {
+        $(
+            impl FromMultipartField for $ty {
+                async fn from_multipart_field(field: Field<'_>) -> Result<Self, Response> {
+                    let name = field.name().unwrap_or_default().to_string();
+                    let text = String::from_multipart_field(field).await?;
+                    text.trim().parse().map_err(|err| {
+                        let message = format!("Invalid multipart field `{name}`: {err}");
+                        (StatusCode::BAD_REQUEST, message).into_response()
+                    })
+                }
+            }
+        )*
+    };
+}
+
+from_str_fields!(
+    bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+/// An uploaded file of a `multipart(..)` clause, with its file name and content type.
+#[derive(Debug, Clone, Default)]
+pub struct MultipartFile {
+    /// The file name sent by the client, which shouldn't be trusted as a path.
+    pub file_name: Option<String>,
+    pub content_type: Option<String>,
+    pub bytes: Bytes,
+}
+
+impl FromMultipartField for MultipartFile {
+    async fn from_multipart_field(field: Field<'_>) -> Result<Self, Response> {
+        let file_name = field.file_name().map(str::to_string);
+        let content_type = field.content_type().map(str::to_string);
+        let bytes = Bytes::from_multipart_field(field).await?;
+        Ok(MultipartFile {
+            file_name,
+            content_type,
+            bytes,
+        })
+    }
+}
+
+/// Extracts the `Multipart` body of a route with a `multipart(..)` clause.
+pub async fn multipart<S: Send + Sync>(request: Request, state: &S) -> Result<Multipart, Response> {
+    Multipart::from_request(request, state)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// The next field of the body.
+pub async fn next_multipart_field(
+    multipart: &mut Multipart,
+) -> Result<Option<Field<'_>>, Response> {
+    multipart
+        .next_field()
+        .await
+        .map_err(|err: MultipartError| err.into_response())
+}
+
+/// Reads a field into its `slot`, rejecting a field that was already read.
+pub async fn read_multipart_field<T: FromMultipartField>(
+    slot: &mut Option<T>,
+    field: Field<'_>,
+) -> Result<(), Response> {
+    if slot.is_some() {
+        let name = field.name().unwrap_or_default();
+        let message = format!("Duplicate multipart field `{name}`");
+        return Err((StatusCode::BAD_REQUEST, message).into_response());
+    }
+    *slot = Some(T::from_multipart_field(field).await?);
+    Ok(())
+}
+
+/// The rejection of a body without the required field `name`.
+pub fn missing_multipart_field(name: &str) -> Response {
+    let message = format!("Missing multipart field `{name}`");
+    (StatusCode::BAD_REQUEST, message).into_response()
+}
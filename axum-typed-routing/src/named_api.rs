@@ -0,0 +1,177 @@
+//! Splitting the documentation of a router into the named documents of its routes, assigned with
+//! the `api_doc` option.
+
+use aide::{
+    axum::ApiRouter,
+    openapi::{OpenApi, Operation, PathItem, ReferenceOr, SchemaObject},
+};
+use schemars::{schema::Schema, Map};
+use std::collections::BTreeSet;
+
+/// The extension marking the operations of a named document, e.g. `"x-api-doc": "internal"`.
+const API_DOC_EXTENSION: &str = "x-api-doc";
+
+/// The prefix of the references to the component schemas.
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+
+/// A router whose routes with `api_doc` are documented in separate documents, created with
+/// [`TypedApiRouter::finish_named_api`](crate::TypedApiRouter::finish_named_api):
+/// ```ignore
+/// let (mut public, mut internal) = (OpenApi::default(), OpenApi::default());
+/// let router = ApiRouter::new()
+///     .typed_api_route(list_items)
+///     .typed_api_route(reindex_items) // api_doc: "internal"
+///     .finish_named_api("internal", &mut internal)
+///     .finish_api(&mut public);
+/// ```
+/// The routes of a document that isn't finished with `finish_named_api` stay in the main
+/// documentation.
+pub struct NamedApiRouter<'a, S> {
+    router: ApiRouter<S>,
+    apis: Vec<(&'a str, &'a mut OpenApi)>,
+}
+
+impl<'a, S> NamedApiRouter<'a, S>
+where
+    S: Send + Sync + Clone + 'static,
+{
+    pub(crate) fn new(router: ApiRouter<S>) -> Self {
+        Self {
+            router,
+            apis: Vec::new(),
+        }
+    }
+
+    /// Documents the routes with `api_doc: "<name>"` into `api` instead of the main
+    /// documentation, when the router is finished.
+    pub fn finish_named_api(mut self, name: &'a str, api: &'a mut OpenApi) -> Self {
+        self.apis.push((name, api));
+        self
+    }
+
+    /// Finish the documentation of the router, with the routes without a named document into
+    /// `api`, like `ApiRouter::finish_api`. Every document gets the component schemas that its
+    /// operations reference, and the schemas only referenced by named documents are left out of
+    /// `api`.
+    pub fn finish_api(mut self, api: &mut OpenApi) -> axum::Router<S> {
+        self.router.finish_api_with(api, |mut transform| {
+            split_named_apis(transform.inner_mut(), &mut self.apis);
+            transform
+        })
+    }
+}
+
+/// Moves the operations of the named documents `apis` out of `api`, which is finished except for
+/// its component schemas, which are still in the generation context.
+fn split_named_apis(api: &mut OpenApi, apis: &mut [(&str, &mut OpenApi)]) {
+    let Some(paths) = &mut api.paths else {
+        return;
+    };
+    for (path, item) in &mut paths.paths {
+        let ReferenceOr::Item(item) = item else {
+            continue;
+        };
+        for (i, slot) in operation_slots(item).into_iter().enumerate() {
+            let Some(operation) = slot else {
+                continue;
+            };
+            let name = operation.extensions.shift_remove(API_DOC_EXTENSION);
+            let named = name.and_then(|name| {
+                apis.iter_mut()
+                    .find(|(api_name, _api)| name.as_str() == Some(*api_name))
+            });
+            if let Some((_name, named)) = named {
+                let named_paths = named.paths.get_or_insert_with(Default::default);
+                let named_item = named_paths
+                    .paths
+                    .entry(path.clone())
+                    .or_insert_with(|| ReferenceOr::Item(PathItem::default()));
+                if let ReferenceOr::Item(named_item) = named_item {
+                    *operation_slots(named_item).swap_remove(i) = slot.take();
+                }
+            }
+        }
+    }
+    paths.paths.retain(|_path, item| match item {
+        ReferenceOr::Item(item) => item.iter().next().is_some(),
+        ReferenceOr::Reference { .. } => true,
+    });
+
+    aide::generate::in_context(|ctx| {
+        let definitions = ctx.schema.definitions_mut();
+        let mut named_schemas = BTreeSet::new();
+        for (_name, named) in apis.iter_mut() {
+            let schemas = referenced_schemas(&named.paths, definitions);
+            let components = named.components.get_or_insert_with(Default::default);
+            for name in schemas {
+                let json_schema = definitions[&name].clone();
+                let schema = SchemaObject {
+                    json_schema,
+                    example: None,
+                    external_docs: None,
+                };
+                components.schemas.insert(name.clone(), schema);
+                named_schemas.insert(name);
+            }
+        }
+        let schemas =
+            referenced_schemas(&(&api.paths, &api.webhooks, &api.components), definitions);
+        definitions.retain(|name, _schema| schemas.contains(name) || !named_schemas.contains(name));
+    });
+}
+
+/// The operations of `item`, in a fixed order of methods.
+fn operation_slots(item: &mut PathItem) -> Vec<&mut Option<Operation>> {
+    vec![
+        &mut item.get,
+        &mut item.put,
+        &mut item.post,
+        &mut item.delete,
+        &mut item.options,
+        &mut item.head,
+        &mut item.patch,
+        &mut item.trace,
+    ]
+}
+
+/// The names of the component schemas referenced by `value`, including the schemas referenced
+/// by those in `definitions`.
+fn referenced_schemas(
+    value: &impl serde::Serialize,
+    definitions: &Map<String, Schema>,
+) -> BTreeSet<String> {
+    let value = serde_json::to_value(value).expect("failed to serialize the OpenApi documentation");
+    let mut pending = Vec::new();
+    collect_refs(&value, &mut pending);
+    let mut schemas = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        let Some(schema) = definitions.get(&name) else {
+            continue;
+        };
+        if schemas.insert(name) {
+            let schema = serde_json::to_value(schema)
+                .expect("failed to serialize the OpenApi documentation");
+            collect_refs(&schema, &mut pending);
+        }
+    }
+    schemas
+}
+
+fn collect_refs(value: &serde_json::Value, refs: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("$ref", serde_json::Value::String(reference)) => {
+                        if let Some(name) = reference.strip_prefix(SCHEMA_REF_PREFIX) {
+                            refs.push(name.to_string());
+                        }
+                    }
+                    _ => collect_refs(value, refs),
+                }
+            }
+        }
+        serde_json::Value::Array(array) => array.iter().for_each(|value| collect_refs(value, refs)),
+        _ => {}
+    }
+}
@@ -0,0 +1,183 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{
+        header::{ACCEPT, VARY},
+        request::Parts,
+        HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use std::convert::Infallible;
+
+/// An encoding of the `accepts` option of a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    #[cfg(feature = "msgpack")]
+    MsgPack,
+}
+
+impl Encoding {
+    /// Whether the media type of an `Accept` header selects this encoding.
+    fn matches(self, media_type: &str) -> bool {
+        match self {
+            Encoding::Json => media_type == "application/json",
+            #[cfg(feature = "msgpack")]
+            Encoding::MsgPack => matches!(
+                media_type,
+                "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack"
+            ),
+        }
+    }
+
+    fn encode<T: Serialize>(self, value: T) -> Response {
+        match self {
+            Encoding::Json => axum::Json(value).into_response(),
+            #[cfg(feature = "msgpack")]
+            Encoding::MsgPack => match rmp_serde::to_vec_named(&value) {
+                Ok(body) => {
+                    let content_type = [(
+                        axum::http::header::CONTENT_TYPE,
+                        HeaderValue::from_static("application/msgpack"),
+                    )];
+                    (content_type, body).into_response()
+                }
+                Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+            },
+        }
+    }
+}
+
+/// The `Accept` header of a request, extracted by routes with the `accepts` option.
+#[derive(Debug, Clone, Default)]
+pub struct AcceptHeader(Option<HeaderValue>);
+
+impl<S: Send + Sync> FromRequestParts<S> for AcceptHeader {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(AcceptHeader(parts.headers.get(ACCEPT).cloned()))
+    }
+}
+
+/// The return value of a handler with the `accepts` option, encoded with the encoding the
+/// `Accept` header of the request prefers, or rejected with `406 Not Acceptable` if it accepts
+/// none of them.
+pub struct Negotiated<T> {
+    accept: AcceptHeader,
+    encodings: &'static [Encoding],
+    value: T,
+}
+
+impl<T> Negotiated<T> {
+    pub fn new(accept: AcceptHeader, encodings: &'static [Encoding], value: T) -> Self {
+        Self {
+            accept,
+            encodings,
+            value,
+        }
+    }
+
+    /// The preferred encoding of the `Accept` header. Without the header, or if it can't be
+    /// parsed, this is the first encoding.
+    fn encoding(&self) -> Option<Encoding> {
+        let first = self.encodings.first().copied();
+        let Some(accept) = self.accept.0.as_ref().and_then(|h| h.to_str().ok()) else {
+            return first;
+        };
+        let mut ranges = accept
+            .split(',')
+            .map(|range| {
+                let mut parts = range.split(';');
+                let media_type = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+                let quality = parts
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (media_type, quality)
+            })
+            .filter(|(_media_type, quality)| *quality > 0.0)
+            .collect::<Vec<_>>();
+        // Stable, so ranges with the same quality keep the order of the header.
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranges
+            .iter()
+            .find_map(|(media_type, _quality)| match media_type.as_str() {
+                "*/*" | "application/*" => first,
+                media_type => self
+                    .encodings
+                    .iter()
+                    .copied()
+                    .find(|encoding| encoding.matches(media_type)),
+            })
+    }
+}
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        let mut response = match self.encoding() {
+            Some(encoding) => encoding.encode(self.value),
+            None => StatusCode::NOT_ACCEPTABLE.into_response(),
+        };
+        response
+            .headers_mut()
+            .append(VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use super::*;
+    use aide::{
+        generate::GenContext,
+        openapi::{MediaType, Operation, Response, SchemaObject},
+        operation::{OperationInput, OperationOutput},
+    };
+    use schemars::JsonSchema;
+
+    impl OperationInput for AcceptHeader {}
+
+    /// Documented as JSON, the media types of the other encodings are added by the macros.
+    impl<T: JsonSchema> OperationOutput for Negotiated<T> {
+        type Inner = T;
+
+        fn operation_response(
+            ctx: &mut GenContext,
+            _operation: &mut Operation,
+        ) -> Option<Response> {
+            let schema = ctx.schema.subschema_for::<T>().into_object();
+            let description = ctx
+                .resolve_schema(&schema)
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.description.clone())
+                .unwrap_or_default();
+            let media_type = MediaType {
+                schema: Some(SchemaObject {
+                    json_schema: schema.into(),
+                    example: None,
+                    external_docs: None,
+                }),
+                ..Default::default()
+            };
+            Some(Response {
+                description,
+                content: [("application/json".to_string(), media_type)]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            })
+        }
+
+        fn inferred_responses(
+            ctx: &mut GenContext,
+            operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            Self::operation_response(ctx, operation)
+                .map(|response| vec![(Some(200), response)])
+                .unwrap_or_default()
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Paginated list routes, with the `paginated` option of the route macros.
+
+use axum::{
+    extract::{FromRequestParts, OriginalUri},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+/// The page requested from a paginated route, extracted from its `page` and `per_page` query
+/// parameters, which the `paginated: true` option adds to the route:
+/// ```ignore
+/// #[route(GET "/items" { paginated: true })]
+/// async fn list_items(pagination: Pagination, State(db): State<Db>) -> Paginated<Item> {
+///     let items = db.items(pagination.offset(), pagination.limit()).await;
+///     Paginated::new(items, db.count_items().await, &pagination)
+/// }
+/// ```
+/// Pages start at `1`, and `per_page` is clamped to [`MAX_PER_PAGE`](Self::MAX_PER_PAGE).
+/// Values that aren't numbers are rejected with `400 Bad Request`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pagination {
+    /// The page, starting at `1`.
+    pub page: u32,
+    /// The number of items per page.
+    pub per_page: u32,
+    /// The path of the request, as it was received.
+    path: String,
+    /// The query parameters of the request besides `page` and `per_page`, still encoded.
+    query: Vec<String>,
+}
+
+impl Pagination {
+    /// The `per_page` of a request without it.
+    pub const DEFAULT_PER_PAGE: u32 = 20;
+    /// The largest `per_page`.
+    pub const MAX_PER_PAGE: u32 = 100;
+
+    /// The number of items before the page.
+    pub fn offset(&self) -> u64 {
+        u64::from(self.page - 1) * u64::from(self.per_page)
+    }
+
+    /// The number of items on the page.
+    pub fn limit(&self) -> u64 {
+        u64::from(self.per_page)
+    }
+
+    /// The path and query of the same request for another `page`, with the same `per_page` and
+    /// other query parameters, e.g. `/items?sort=name&page=2&per_page=20`.
+    pub fn href(&self, page: u32) -> String {
+        let mut href = self.path.clone();
+        for (i, param) in self.query.iter().enumerate() {
+            href.push(if i == 0 { '?' } else { '&' });
+            href.push_str(param);
+        }
+        let separator = if self.query.is_empty() { '?' } else { '&' };
+        href.push_str(&format!(
+            "{separator}page={page}&per_page={}",
+            self.per_page
+        ));
+        href
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Pagination {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // The path of a nested router is stripped of its prefix, which the links need.
+        let uri = match parts.extensions.get::<OriginalUri>() {
+            Some(OriginalUri(uri)) => uri,
+            None => &parts.uri,
+        };
+        let mut pagination = Self {
+            page: 1,
+            per_page: Self::DEFAULT_PER_PAGE,
+            path: uri.path().to_string(),
+            query: Vec::new(),
+        };
+        let params = uri.query().unwrap_or_default().split('&');
+        for param in params.filter(|param| !param.is_empty()) {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            let field = match name {
+                "page" => &mut pagination.page,
+                "per_page" => &mut pagination.per_page,
+                _ => {
+                    pagination.query.push(param.to_string());
+                    continue;
+                }
+            };
+            *field = value.parse().map_err(|_| {
+                let message = format!("Invalid query parameter `{name}`: expected a number");
+                (StatusCode::BAD_REQUEST, message).into_response()
+            })?;
+        }
+        pagination.page = pagination.page.max(1);
+        pagination.per_page = pagination.per_page.clamp(1, Self::MAX_PER_PAGE);
+        Ok(pagination)
+    }
+}
+
+/// A page of items of a paginated route, served as JSON with the total number of items, and
+/// links to the next and previous pages.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "aide", derive(schemars::JsonSchema))]
+pub struct Paginated<T> {
+    /// The items on the page.
+    pub items: Vec<T>,
+    /// The number of items on all pages.
+    pub total: u64,
+    /// The page, starting at `1`.
+    pub page: u32,
+    /// The number of items per page.
+    pub per_page: u32,
+    /// The path of the next page, if there is one.
+    pub next: Option<String>,
+    /// The path of the previous page, if there is one.
+    pub prev: Option<String>,
+}
+
+impl<T> Paginated<T> {
+    /// The page of `pagination`, with its `items` out of `total`.
+    pub fn new(items: Vec<T>, total: u64, pagination: &Pagination) -> Self {
+        let Pagination { page, per_page, .. } = *pagination;
+        let has_next = pagination.offset() + u64::from(per_page) < total;
+        Self {
+            items,
+            total,
+            page,
+            per_page,
+            next: has_next.then(|| pagination.href(page + 1)),
+            prev: (page > 1).then(|| pagination.href(page - 1)),
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Paginated<T> {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use super::*;
+    use aide::{
+        generate::GenContext,
+        openapi::{Operation, Response},
+        operation::{OperationInput, OperationOutput},
+    };
+    use schemars::JsonSchema;
+
+    impl OperationInput for Pagination {}
+
+    /// Documented as the JSON envelope.
+    impl<T: JsonSchema> OperationOutput for Paginated<T> {
+        type Inner = Self;
+
+        fn operation_response(ctx: &mut GenContext, operation: &mut Operation) -> Option<Response> {
+            Json::<Self>::operation_response(ctx, operation)
+        }
+
+        fn inferred_responses(
+            ctx: &mut GenContext,
+            operation: &mut Operation,
+        ) -> Vec<(Option<u16>, Response)> {
+            Json::<Self>::inferred_responses(ctx, operation)
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use axum::{
+    extract::{FromRequestParts, RawPathParams, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// The pattern of a path parameter, as in `/:slug(regex = "[a-z0-9-]+")`, which is compiled
+/// when it's first matched. The macro already checked that it's valid.
+#[derive(Debug)]
+pub struct PathPattern {
+    /// The name of the parameter in the path.
+    pub name: &'static str,
+    pub pattern: &'static str,
+    regex: OnceLock<Regex>,
+}
+
+impl PathPattern {
+    pub const fn new(name: &'static str, pattern: &'static str) -> Self {
+        Self {
+            name,
+            pattern,
+            regex: OnceLock::new(),
+        }
+    }
+
+    /// Whether the pattern matches the whole `value`.
+    fn is_match(&self, value: &str) -> bool {
+        self.regex
+            .get_or_init(|| {
+                Regex::new(&format!("^(?:{})$", self.pattern))
+                    .expect("the pattern is checked by the route macro")
+            })
+            .is_match(value)
+    }
+}
+
+/// Rejects a request with `400 Bad Request` if the (percent-decoded) value of a path parameter
+/// doesn't match its pattern.
+pub async fn path_patterns(
+    patterns: &'static [PathPattern],
+    request: Request,
+    next: Next,
+) -> Response {
+    let (mut parts, body) = request.into_parts();
+    if let Ok(params) = RawPathParams::from_request_parts(&mut parts, &()).await {
+        for (name, value) in &params {
+            let pattern = patterns.iter().find(|pattern| pattern.name == name);
+            if let Some(pattern) = pattern.filter(|pattern| !pattern.is_match(value)) {
+                let message = format!(
+                    "Invalid path parameter `{name}`: expected a match of `{}`",
+                    pattern.pattern
+                );
+                return (StatusCode::BAD_REQUEST, message).into_response();
+            }
+        }
+    }
+    next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(feature = "aide")]
+pub use openapi::document_path_patterns;
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use super::*;
+    use aide::openapi::{
+        Operation, Parameter, ParameterData, ParameterSchemaOrContent, PathStyle, ReferenceOr,
+        Response, SchemaObject, StatusCode,
+    };
+    use schemars::schema::Schema;
+
+    /// Documents the `pattern` in the schema of every path parameter with one, adding the
+    /// parameter if it isn't documented yet, and the `400 Bad Request` response to a mismatch.
+    pub fn document_path_patterns(operation: &mut Operation, patterns: &[PathPattern]) {
+        for pattern in patterns {
+            let documented =
+                operation
+                    .parameters
+                    .iter_mut()
+                    .find_map(|parameter| match parameter {
+                        ReferenceOr::Item(Parameter::Path { parameter_data, .. })
+                            if parameter_data.name == pattern.name =>
+                        {
+                            Some(parameter_data)
+                        }
+                        _ => None,
+                    });
+            let parameter_data = match documented {
+                Some(parameter_data) => parameter_data,
+                None => {
+                    let json_schema =
+                        aide::generate::in_context(|ctx| ctx.schema.subschema_for::<String>());
+                    operation
+                        .parameters
+                        .push(ReferenceOr::Item(Parameter::Path {
+                            parameter_data: ParameterData {
+                                name: pattern.name.to_string(),
+                                description: None,
+                                required: true,
+                                format: ParameterSchemaOrContent::Schema(SchemaObject {
+                                    json_schema,
+                                    example: None,
+                                    external_docs: None,
+                                }),
+                                extensions: Default::default(),
+                                deprecated: None,
+                                example: None,
+                                examples: Default::default(),
+                                explode: None,
+                            },
+                            style: PathStyle::Simple,
+                        }));
+                    match operation.parameters.last_mut() {
+                        Some(ReferenceOr::Item(Parameter::Path { parameter_data, .. })) => {
+                            parameter_data
+                        }
+                        _ => unreachable!("the parameter was just added"),
+                    }
+                }
+            };
+            if let ParameterSchemaOrContent::Schema(SchemaObject {
+                json_schema: Schema::Object(schema),
+                ..
+            }) = &mut parameter_data.format
+            {
+                schema.string().pattern = Some(pattern.pattern.to_string());
+            }
+        }
+
+        let responses = operation.responses.get_or_insert_with(Default::default);
+        responses
+            .responses
+            .entry(StatusCode::Code(400))
+            .or_insert_with(|| {
+                ReferenceOr::Item(Response {
+                    description: "A path parameter doesn't match its pattern".to_string(),
+                    ..Default::default()
+                })
+            });
+    }
+}
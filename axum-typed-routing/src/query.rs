@@ -0,0 +1,339 @@
+use axum::{extract::FromRequestParts, http::request::Parts};
+#[cfg(feature = "deep-object")]
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+#[cfg(feature = "deep-object")]
+use serde::de::DeserializeOwned;
+use serde::de::{
+    Deserialize, DeserializeSeed, Deserializer, Error, IgnoredAny, MapAccess, Visitor,
+};
+use std::{convert::Infallible, fmt, marker::PhantomData};
+
+/// Extracts the query like axum's `Query`, but also understands the bracketed objects of
+/// `deepObject` parameters, e.g. `filter[name]=x&filter[age]=3`.
+///
+/// It is used instead of `Query` by routes with a `#[route_param(style = deepObject)]`
+/// parameter, with the `deep-object` feature.
+#[cfg(feature = "deep-object")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeepObjectQuery<T>(pub T);
+
+#[cfg(feature = "deep-object")]
+impl<T: DeserializeOwned, S: Send + Sync> FromRequestParts<S> for DeepObjectQuery<T> {
+    type Rejection = DeepObjectQueryRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = parts.uri.query().unwrap_or_default();
+        // Not strict, so that percent-encoded brackets are accepted too.
+        serde_qs::Config::new(5, false)
+            .deserialize_str(query)
+            .map(DeepObjectQuery)
+            .map_err(DeepObjectQueryRejection)
+    }
+}
+
+/// The rejection of [`DeepObjectQuery`]: a `400 Bad Request` with the deserialization error.
+#[cfg(feature = "deep-object")]
+#[derive(Debug)]
+pub struct DeepObjectQueryRejection(serde_qs::Error);
+
+#[cfg(feature = "deep-object")]
+impl fmt::Display for DeepObjectQueryRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to deserialize query string: {}", self.0)
+    }
+}
+
+#[cfg(feature = "deep-object")]
+impl std::error::Error for DeepObjectQueryRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[cfg(feature = "deep-object")]
+impl IntoResponse for DeepObjectQueryRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+#[cfg(all(feature = "deep-object", feature = "aide"))]
+impl<T: schemars::JsonSchema> aide::operation::OperationInput for DeepObjectQuery<T> {
+    fn operation_input(
+        ctx: &mut aide::generate::GenContext,
+        operation: &mut aide::openapi::Operation,
+    ) {
+        use aide::operation::{add_parameters, parameters_from_schema, ParamLocation};
+
+        let schema = ctx.schema.subschema_for::<T>().into_object();
+        let params = parameters_from_schema(ctx, schema, ParamLocation::Query);
+        add_parameters(ctx, operation, params);
+    }
+}
+
+/// Extracts the query with `E`, or defaults it if `E` rejects the query, e.g. because it's
+/// missing or isn't valid UTF-8.
+///
+/// It wraps the query extractor of routes with `lenient_query: true`, whose query parameters
+/// are all optional, so a broken query string extracts them all as `None`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LenientQuery<E>(pub E);
+
+impl<E, S> FromRequestParts<S> for LenientQuery<E>
+where
+    E: FromRequestParts<S> + Default,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(
+            E::from_request_parts(parts, state)
+                .await
+                .unwrap_or_default(),
+        ))
+    }
+}
+
+/// The parameters are documented like those of `E`, but without the `400` response of a
+/// rejected query.
+#[cfg(feature = "aide")]
+impl<E: aide::operation::OperationInput> aide::operation::OperationInput for LenientQuery<E> {
+    fn operation_input(
+        ctx: &mut aide::generate::GenContext,
+        operation: &mut aide::openapi::Operation,
+    ) {
+        E::operation_input(ctx, operation);
+    }
+}
+
+/// The name of the query parameter extracted by [`SingleQueryParam`].
+pub trait QueryParamName {
+    const NAME: &'static str;
+}
+
+/// The query parameter `N::NAME`, deserialized by axum's `Query` like a struct with a single
+/// field `T`, ignoring the other parameters.
+///
+/// Routes with a single query parameter extract it with `Query<SingleQueryParam<N, T>>`, instead
+/// of generating a struct deriving `Deserialize`, which is a lot more code to compile.
+pub struct SingleQueryParam<N, T>(pub T, pub PhantomData<N>);
+
+impl<N, T: Default> Default for SingleQueryParam<N, T> {
+    fn default() -> Self {
+        Self(T::default(), PhantomData)
+    }
+}
+
+impl<'de, N: QueryParamName, T: Deserialize<'de>> Deserialize<'de> for SingleQueryParam<N, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(SingleQueryParamVisitor(PhantomData))
+    }
+}
+
+struct SingleQueryParamVisitor<N, T>(PhantomData<(N, T)>);
+
+impl<'de, N: QueryParamName, T: Deserialize<'de>> Visitor<'de> for SingleQueryParamVisitor<N, T> {
+    type Value = SingleQueryParam<N, T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a query with the parameter `{}`", N::NAME)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut value = None;
+        while let Some(matches) = map.next_key_seed(KeyIs(N::NAME))? {
+            match (matches, &value) {
+                (false, _) => drop(map.next_value::<IgnoredAny>()?),
+                (true, None) => value = Some(map.next_value()?),
+                (true, Some(_)) => return Err(A::Error::duplicate_field(N::NAME)),
+            }
+        }
+        let value = match value {
+            Some(value) => value,
+            // Like a missing field of a derived struct, this is `None` for an `Option`.
+            None => T::deserialize(MissingParam(N::NAME, PhantomData))?,
+        };
+        Ok(SingleQueryParam(value, PhantomData))
+    }
+}
+
+/// The names of the query parameters extracted by [`QueryParams`].
+pub trait QueryParamNames {
+    const NAMES: &'static [&'static str];
+}
+
+/// The query parameters `N::NAMES`, deserialized by axum's `Query` like a struct with the fields
+/// of the tuple `T`, ignoring the other parameters.
+///
+/// Like [`SingleQueryParam`], this replaces the generated struct of routes with several query
+/// parameters. The names are only looked up when deserializing, so the routes whose parameters
+/// have the same types share the code deserializing them.
+pub struct QueryParams<N, T>(pub T, pub PhantomData<N>);
+
+impl<N, T: Default> Default for QueryParams<N, T> {
+    fn default() -> Self {
+        Self(T::default(), PhantomData)
+    }
+}
+
+impl<'de, N: QueryParamNames, T: QueryParamTypes<'de>> Deserialize<'de> for QueryParams<N, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let visitor = QueryParamsVisitor(N::NAMES, PhantomData);
+        let params = deserializer.deserialize_map(visitor)?;
+        Ok(QueryParams(params, PhantomData))
+    }
+}
+
+struct QueryParamsVisitor<T>(&'static [&'static str], PhantomData<T>);
+
+impl<'de, T: QueryParamTypes<'de>> Visitor<'de> for QueryParamsVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a query with the parameters `{}`", self.0.join("`, `"))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let mut values = T::Values::default();
+        while let Some(index) = map.next_key_seed(KeyIndex(self.0))? {
+            match index {
+                Some(index) => T::next_value(&mut values, index, self.0, &mut map)?,
+                None => drop(map.next_value::<IgnoredAny>()?),
+            }
+        }
+        T::from_values(values, self.0)
+    }
+}
+
+/// The tuple of the types of the parameters extracted by [`QueryParams`].
+pub trait QueryParamTypes<'de>: Sized {
+    /// The values of the parameters found in the query so far.
+    type Values: Default;
+
+    /// Deserializes the next value of the query as the parameter at `index`.
+    fn next_value<A: MapAccess<'de>>(
+        values: &mut Self::Values,
+        index: usize,
+        names: &'static [&'static str],
+        map: &mut A,
+    ) -> Result<(), A::Error>;
+
+    /// The parameters, once the whole query is deserialized.
+    fn from_values<E: Error>(
+        values: Self::Values,
+        names: &'static [&'static str],
+    ) -> Result<Self, E>;
+}
+
+macro_rules! impl_query_param_types {
+    ($($ty:ident $index:tt),*) => {
+        impl<'de, $($ty: Deserialize<'de>),*> QueryParamTypes<'de> for ($($ty,)*) {
+            type Values = ($(Option<$ty>,)*);
+
+            fn next_value<A: MapAccess<'de>>(
+                values: &mut Self::Values,
+                index: usize,
+                names: &'static [&'static str],
+                map: &mut A,
+            ) -> Result<(), A::Error> {
+                match index {
+                    $($index => match values.$index {
+                        Some(_) => return Err(A::Error::duplicate_field(names[$index])),
+                        None => values.$index = Some(map.next_value()?),
+                    },)*
+                    _ => drop(map.next_value::<IgnoredAny>()?),
+                }
+                Ok(())
+            }
+
+            fn from_values<E: Error>(
+                values: Self::Values,
+                names: &'static [&'static str],
+            ) -> Result<Self, E> {
+                Ok(($(match values.$index {
+                    Some(value) => value,
+                    None => $ty::deserialize(MissingParam(names[$index], PhantomData))?,
+                },)*))
+            }
+        }
+    };
+}
+
+impl_query_param_types!(T0 0, T1 1);
+impl_query_param_types!(T0 0, T1 1, T2 2);
+impl_query_param_types!(T0 0, T1 1, T2 2, T3 3);
+impl_query_param_types!(T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_query_param_types!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_query_param_types!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_query_param_types!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+
+/// Deserializes a key of the query as the index of the parameter with its name, if any.
+struct KeyIndex(&'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for KeyIndex {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl Visitor<'_> for KeyIndex {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a query parameter name")
+    }
+
+    fn visit_str<E: Error>(self, key: &str) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|name| *name == key))
+    }
+}
+
+/// Deserializes a key of the query as whether it's the name of the parameter.
+struct KeyIs(&'static str);
+
+impl<'de> DeserializeSeed<'de> for KeyIs {
+    type Value = bool;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl Visitor<'_> for KeyIs {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a query parameter name")
+    }
+
+    fn visit_str<E: Error>(self, key: &str) -> Result<bool, E> {
+        Ok(key == self.0)
+    }
+}
+
+/// The value of a missing parameter, which is `None` as an `Option` and an error otherwise.
+struct MissingParam<E>(&'static str, PhantomData<E>);
+
+impl<'de, E: Error> Deserializer<'de> for MissingParam<E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, E> {
+        Err(E::missing_field(self.0))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        visitor.visit_none()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
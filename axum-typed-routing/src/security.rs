@@ -0,0 +1,162 @@
+//! Definitions of OpenAPI security schemes, enabled with the `aide` feature.
+//!
+//! The `security` option of [`api_route`](crate::api_route) only references security schemes by
+//! name. [`SecuritySchemes`] (usually created with [`define_security_schemes!`]) registers the
+//! schemes on the [`OpenApi`] document, and checks that every security requirement of the
+//! document refers to one of them, so a typo in a scheme name is caught when the documentation
+//! is built:
+//! ```ignore
+//! let mut api = OpenApi::default();
+//! let router = ApiRouter::new().typed_api_route(item_handler).finish_api(&mut api);
+//!
+//! define_security_schemes! {
+//!     "bearer": http_bearer(),
+//!     "api_key": header("X-Api-Key"),
+//! }
+//! .register(&mut api)
+//! .expect("unknown security scheme");
+//! ```
+
+use aide::openapi::{ApiKeyLocation, OpenApi, ReferenceOr, SecurityScheme};
+use std::fmt;
+
+/// Creates [`SecuritySchemes`] from a list of `"<NAME>": <SCHEME>` pairs.
+///
+/// The helpers of the [`security`](crate::security) module, such as [`http_bearer`] and
+/// [`header`], are in scope for the schemes.
+#[macro_export]
+macro_rules! define_security_schemes {
+    ($($name:literal: $scheme:expr),* $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::security::*;
+        $crate::security::SecuritySchemes::new()$(.scheme($name, $scheme))*
+    }};
+}
+
+/// A set of named security schemes, which can be registered on an [`OpenApi`] document.
+#[derive(Debug, Clone, Default)]
+pub struct SecuritySchemes {
+    schemes: Vec<(String, SecurityScheme)>,
+}
+
+impl SecuritySchemes {
+    /// Create an empty set of security schemes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a security scheme with the given name.
+    pub fn scheme(mut self, name: impl Into<String>, scheme: SecurityScheme) -> Self {
+        self.schemes.push((name.into(), scheme));
+        self
+    }
+
+    /// Add the security schemes to the components of `api`, and check that all security
+    /// requirements of the document refer to a defined scheme.
+    pub fn register(&self, api: &mut OpenApi) -> Result<(), UnknownSecurityScheme> {
+        let components = api.components.get_or_insert_with(Default::default);
+        for (name, scheme) in &self.schemes {
+            components
+                .security_schemes
+                .insert(name.clone(), ReferenceOr::Item(scheme.clone()));
+        }
+        let defined = &components.security_schemes;
+
+        let global = api.security.iter().map(|requirement| (None, requirement));
+        let operations = api
+            .paths
+            .iter()
+            .flat_map(|paths| paths.iter())
+            .filter_map(|(path, item)| Some((path, item.as_item()?)))
+            .flat_map(|(path, item)| {
+                item.iter().flat_map(move |(method, operation)| {
+                    operation
+                        .security
+                        .iter()
+                        .map(move |requirement| (Some((path, method)), requirement))
+                })
+            });
+
+        for (operation, requirement) in global.chain(operations) {
+            for scheme in requirement.keys() {
+                if !defined.contains_key(scheme) {
+                    return Err(UnknownSecurityScheme {
+                        scheme: scheme.clone(),
+                        operation: operation
+                            .map(|(path, method)| (path.clone(), method.to_uppercase())),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A security requirement refers to a security scheme that is not defined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownSecurityScheme {
+    /// The name of the undefined scheme.
+    pub scheme: String,
+    /// The `(path, method)` of the operation with the requirement, or `None` for a requirement
+    /// of the whole document.
+    pub operation: Option<(String, String)>,
+}
+
+impl fmt::Display for UnknownSecurityScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.operation {
+            Some((path, method)) => write!(
+                f,
+                "security scheme `{}` used by `{} {}` is not defined",
+                self.scheme, method, path
+            ),
+            None => write!(f, "security scheme `{}` is not defined", self.scheme),
+        }
+    }
+}
+
+impl std::error::Error for UnknownSecurityScheme {}
+
+/// HTTP bearer authentication, using the `Authorization: Bearer <TOKEN>` header.
+pub fn http_bearer() -> SecurityScheme {
+    http("bearer")
+}
+
+/// HTTP basic authentication.
+pub fn http_basic() -> SecurityScheme {
+    http("basic")
+}
+
+/// HTTP authentication with the given scheme, e.g. `"bearer"` or `"basic"`.
+pub fn http(scheme: &str) -> SecurityScheme {
+    SecurityScheme::Http {
+        scheme: scheme.to_string(),
+        bearer_format: None,
+        description: None,
+        extensions: Default::default(),
+    }
+}
+
+/// An API key sent in the header with the given name.
+pub fn header(name: &str) -> SecurityScheme {
+    api_key(ApiKeyLocation::Header, name)
+}
+
+/// An API key sent in the query parameter with the given name.
+pub fn query(name: &str) -> SecurityScheme {
+    api_key(ApiKeyLocation::Query, name)
+}
+
+/// An API key sent in the cookie with the given name.
+pub fn cookie(name: &str) -> SecurityScheme {
+    api_key(ApiKeyLocation::Cookie, name)
+}
+
+fn api_key(location: ApiKeyLocation, name: &str) -> SecurityScheme {
+    SecurityScheme::ApiKey {
+        location,
+        name: name.to_string(),
+        description: None,
+        extensions: Default::default(),
+    }
+}
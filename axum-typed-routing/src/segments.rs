@@ -0,0 +1,125 @@
+use serde::{Deserialize, Deserializer};
+use std::{fmt, ops::Deref};
+
+/// The segments of a wildcard path parameter, as in `/files/*path`, split at the slashes.
+///
+/// The [`route`](crate::route) macro splits a wildcard of this type, or of `Vec<T>`, into its
+/// segments, leaving out empty ones. A request to `/files/docs/2024/report.pdf` is extracted as
+/// `["docs", "2024", "report.pdf"]`:
+/// ```ignore
+/// #[route(GET "/files/*path")]
+/// async fn browse(path: Segments) -> String {
+///     match path.last() {
+///         Some(name) => format!("{name} in {} folders", path.len() - 1),
+///         None => "the root folder".to_string(),
+///     }
+/// }
+/// ```
+/// The segments are percent-decoded, so an encoded `%2F` splits them like a `/`. Dot segments,
+/// such as `..`, are kept as they are.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Segments(Vec<String>);
+
+impl Segments {
+    pub fn into_inner(self) -> Vec<String> {
+        self.0
+    }
+}
+
+impl Deref for Segments {
+    type Target = [String];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromIterator<String> for Segments {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Segments {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Segments {
+    type Item = &'a String;
+    type IntoIter = std::slice::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// The segments joined with slashes, e.g. `docs/2024/report.pdf`.
+impl fmt::Display for Segments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join("/"))
+    }
+}
+
+impl<'de> Deserialize<'de> for Segments {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        path_segments(deserializer)
+    }
+}
+
+/// Deserializes a wildcard path parameter into its segments, parsing every segment with
+/// `FromStr`.
+pub fn path_segments<'de, D, C, T>(deserializer: D) -> Result<C, D::Error>
+where
+    D: Deserializer<'de>,
+    C: FromIterator<T>,
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    let path = String::deserialize(deserializer)?;
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
+#[cfg(feature = "aide")]
+mod openapi {
+    use super::*;
+    use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+
+    /// Documented like the wildcard it's extracted from.
+    impl JsonSchema for Segments {
+        fn schema_name() -> String {
+            String::schema_name()
+        }
+
+        fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+            String::json_schema(gen)
+        }
+
+        fn is_referenceable() -> bool {
+            false
+        }
+    }
+}
+
+#[cfg(feature = "fuzz")]
+impl proptest::arbitrary::Arbitrary for Segments {
+    type Parameters = <Vec<String> as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = proptest::strategy::Map<
+        <Vec<String> as proptest::arbitrary::Arbitrary>::Strategy,
+        fn(Vec<String>) -> Self,
+    >;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        proptest::strategy::Strategy::prop_map(
+            proptest::arbitrary::any_with::<Vec<String>>(args),
+            Segments,
+        )
+    }
+}
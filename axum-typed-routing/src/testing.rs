@@ -0,0 +1,214 @@
+//! Requests to typed routes in tests, enabled with the `axum-test` feature.
+//!
+//! [`TypedTestServer`] derives the method and path of a request from the typed handler, so tests
+//! don't repeat the route definitions:
+//! ```ignore
+//! #[route(GET "/item/:id?amount")]
+//! async fn item_handler(id: u32, amount: Option<u32>) -> String {
+//!     todo!("handle request")
+//! }
+//!
+//! let server = TestServer::new(Router::new().typed_route(item_handler)).unwrap();
+//! let response = server
+//!     .typed_request(item_handler, (1,))
+//!     .add_query_param("amount", 2)
+//!     .await;
+//! ```
+
+use crate::{
+    __private::{push_path_segment, push_path_wildcard},
+    builder::typed_handler_route,
+    PathSegmentEncode, TypedRouteValue,
+};
+use axum::http::Method;
+use axum_test::{TestRequest, TestServer};
+
+/// Creates requests to typed routes on an `axum_test::TestServer`.
+pub trait TypedTestServer {
+    /// Create a request to the route of `handler`, using the method of the route, and its path
+    /// with the path parameters replaced by `params`, in order.
+    ///
+    /// # Panics
+    /// If the handler wasn't created by the macros, or the number of `params` doesn't match the
+    /// path parameters of the route.
+    fn typed_request<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest;
+
+    /// Same as [`typed_request`](Self::typed_request), but checks that the route is a `GET`
+    /// route.
+    fn typed_get<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest;
+
+    /// Same as [`typed_request`](Self::typed_request), but checks that the route is a `POST`
+    /// route.
+    fn typed_post<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest;
+
+    /// Same as [`typed_request`](Self::typed_request), but checks that the route is a `PUT`
+    /// route.
+    fn typed_put<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest;
+
+    /// Same as [`typed_request`](Self::typed_request), but checks that the route is a `PATCH`
+    /// route.
+    fn typed_patch<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest;
+
+    /// Same as [`typed_request`](Self::typed_request), but checks that the route is a `DELETE`
+    /// route.
+    fn typed_delete<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest;
+}
+
+impl TypedTestServer for TestServer {
+    fn typed_request<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest {
+        let (path, method) = typed_handler_route(handler);
+        let method = method.unwrap_or_else(|| {
+            panic!("the handler of `{path}` wasn't created by the typed routing macros")
+        });
+        let method = Method::from_bytes(method.as_bytes()).expect("invalid HTTP method");
+        self.method(method, &format_path(path, params.into_params()))
+    }
+
+    fn typed_get<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest {
+        expect_method(handler, "GET");
+        self.typed_request(handler, params)
+    }
+
+    fn typed_post<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest {
+        expect_method(handler, "POST");
+        self.typed_request(handler, params)
+    }
+
+    fn typed_put<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest {
+        expect_method(handler, "PUT");
+        self.typed_request(handler, params)
+    }
+
+    fn typed_patch<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest {
+        expect_method(handler, "PATCH");
+        self.typed_request(handler, params)
+    }
+
+    fn typed_delete<V: TypedRouteValue>(
+        &self,
+        handler: fn() -> V,
+        params: impl PathParams,
+    ) -> TestRequest {
+        expect_method(handler, "DELETE");
+        self.typed_request(handler, params)
+    }
+}
+
+fn expect_method<V: TypedRouteValue>(handler: fn() -> V, expected: &str) {
+    let (path, method) = typed_handler_route(handler);
+    if let Some(method) = method {
+        assert_eq!(
+            method, expected,
+            "the route `{path}` is a {method} route, not a {expected} route"
+        );
+    }
+}
+
+/// Replaces the path parameters of the axum `path` by `params`, percent-encoded.
+fn format_path(path: &str, params: Vec<Box<dyn PathSegmentEncode + '_>>) -> String {
+    let mut formatted = String::new();
+    let mut params = params.into_iter();
+    let mut count = 0;
+    for segment in path.split('/').skip(1) {
+        let is_capture = segment.starts_with('{') && segment.ends_with('}');
+        if !is_capture {
+            formatted.push('/');
+            formatted.push_str(segment);
+            continue;
+        }
+        count += 1;
+        let param = params.next().unwrap_or_else(|| {
+            panic!("not enough path parameters for `{path}`, expected at least {count}")
+        });
+        match segment.starts_with("{*") {
+            true => push_path_wildcard(&mut formatted, &*param),
+            false => push_path_segment(&mut formatted, &*param),
+        }
+    }
+    assert!(
+        params.next().is_none(),
+        "too many path parameters for `{path}`, expected {count}"
+    );
+    if formatted.is_empty() {
+        formatted.push('/');
+    }
+    formatted
+}
+
+/// The path parameters of a request, as a tuple of values implementing [`PathSegmentEncode`]
+/// (such as any `Display` type), e.g. `()`, `(42,)` or `(42, "name")`.
+pub trait PathParams {
+    /// The path parameters, in order.
+    fn into_params<'a>(self) -> Vec<Box<dyn PathSegmentEncode + 'a>>
+    where
+        Self: 'a;
+}
+
+macro_rules! impl_path_params {
+    ($($param:ident),*) => {
+        impl<$($param: PathSegmentEncode),*> PathParams for ($($param,)*) {
+            #[allow(non_snake_case)]
+            fn into_params<'a>(self) -> Vec<Box<dyn PathSegmentEncode + 'a>>
+            where
+                Self: 'a,
+            {
+                let ($($param,)*) = self;
+                vec![$(Box::new($param)),*]
+            }
+        }
+    };
+}
+
+impl_path_params!();
+impl_path_params!(T1);
+impl_path_params!(T1, T2);
+impl_path_params!(T1, T2, T3);
+impl_path_params!(T1, T2, T3, T4);
+impl_path_params!(T1, T2, T3, T4, T5);
+impl_path_params!(T1, T2, T3, T4, T5, T6);
+impl_path_params!(T1, T2, T3, T4, T5, T6, T7);
+impl_path_params!(T1, T2, T3, T4, T5, T6, T7, T8);
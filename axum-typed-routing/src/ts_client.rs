@@ -0,0 +1,209 @@
+//! Generation of TypeScript route definitions, enabled with the `ts-client` feature.
+//!
+//! Every handler annotated with [`route`](crate::route) (or `api_route`) registers a [`TsRoute`]
+//! describing its method, path, parameters and body. A [`TsClient`] collects all registered
+//! routes and renders them into a single `.ts` file, so frontend code can share the route
+//! definitions of the backend:
+//! ```ignore
+//! #[test]
+//! fn export_typescript() {
+//!     TsClient::new()
+//!         .import_types_from("./bindings")
+//!         .write("frontend/src/routes.ts")
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! For the route `#[route(GET "/item/:id?amount")]` on `item_handler`, this generates:
+//! ```ts
+//! export const item_handler = {
+//!   method: "GET",
+//!   path: (params: { id: number }): string => `/item/${encodeURIComponent(String(params.id))}`,
+//! } as const;
+//! export type ItemHandlerQuery = { amount: number };
+//! ```
+//!
+//! Rust primitives are mapped to their TypeScript equivalents, `Option<T>` makes a query
+//! parameter optional and `Vec<T>` becomes `T[]`. Other types (such as `Json<T>` bodies) are
+//! referenced by name; these can be generated with `ts-rs` and imported using
+//! [`TsClient::import_types_from`].
+
+use std::{fmt::Write, io, path::Path};
+
+/// A route registered for TypeScript generation. Created by the macros.
+#[derive(Debug, Clone, Copy)]
+pub struct TsRoute {
+    /// The name of the handler.
+    pub name: &'static str,
+    /// The HTTP method, in uppercase.
+    pub method: &'static str,
+    /// The `base` option of the route, which is prepended to the `path`.
+    pub base: &'static str,
+    /// The axum path of the route, e.g. `/item/{id}`.
+    pub path: &'static str,
+    /// The path parameters, in order.
+    pub path_params: &'static [TsParam],
+    /// The query parameters.
+    pub query_params: &'static [TsParam],
+    /// The TypeScript type of the `Json` body, if any.
+    pub body: Option<&'static str>,
+}
+
+/// A path- or query-parameter of a [`TsRoute`].
+#[derive(Debug, Clone, Copy)]
+pub struct TsParam {
+    /// The name of the parameter.
+    pub name: &'static str,
+    /// The TypeScript type of the parameter.
+    pub ty: &'static str,
+    /// Whether the parameter is an `Option<T>`.
+    pub optional: bool,
+}
+
+inventory::collect!(TsRoute);
+
+/// Renders all registered [`TsRoute`]s into a TypeScript module.
+#[derive(Debug, Clone, Default)]
+pub struct TsClient {
+    types_path: Option<String>,
+}
+
+impl TsClient {
+    /// Create a new generator for all registered routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Import the non-primitive body types from `<path>/<Type>`, the layout used by `ts-rs`.
+    pub fn import_types_from(mut self, path: impl Into<String>) -> Self {
+        self.types_path = Some(path.into());
+        self
+    }
+
+    /// All registered routes, sorted by path and method.
+    pub fn routes(&self) -> Vec<&'static TsRoute> {
+        let mut routes = inventory::iter::<TsRoute>.into_iter().collect::<Vec<_>>();
+        routes.sort_by_key(|route| (route.base, route.path, route.method, route.name));
+        routes
+    }
+
+    /// Render the TypeScript module.
+    pub fn generate(&self) -> String {
+        let routes = self.routes();
+        let mut out =
+            String::from("// This file is generated by axum-typed-routing, do not edit.\n");
+
+        if let Some(types_path) = &self.types_path {
+            let mut types = routes
+                .iter()
+                .filter_map(|route| route.body)
+                .filter(|ty| is_type_reference(ty))
+                .collect::<Vec<_>>();
+            types.sort_unstable();
+            types.dedup();
+            for ty in types {
+                let _ = writeln!(out, "import type {{ {ty} }} from \"{types_path}/{ty}\";");
+            }
+        }
+
+        for route in routes {
+            out.push('\n');
+            write_route(&mut out, route);
+        }
+        out
+    }
+
+    /// Render the TypeScript module and write it to `path`.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.generate())
+    }
+}
+
+fn write_route(out: &mut String, route: &TsRoute) {
+    let type_name = pascal_case(route.name);
+
+    let _ = writeln!(out, "export const {} = {{", route.name);
+    let _ = writeln!(out, "  method: \"{}\",", route.method);
+    let params = match route.path_params {
+        [] => String::new(),
+        params => {
+            let fields = params
+                .iter()
+                .map(|param| format!("{}: {}", param.name, param.ty))
+                .collect::<Vec<_>>();
+            format!("params: {{ {} }}", fields.join("; "))
+        }
+    };
+    let _ = writeln!(
+        out,
+        "  path: ({params}): string => `{}`,",
+        path_template(route)
+    );
+    out.push_str("} as const;\n");
+
+    if !route.query_params.is_empty() {
+        let fields = route
+            .query_params
+            .iter()
+            .map(|param| {
+                let optional = if param.optional { "?" } else { "" };
+                format!("{}{optional}: {}", param.name, param.ty)
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(
+            out,
+            "export type {type_name}Query = {{ {} }};",
+            fields.join("; ")
+        );
+    }
+    if let Some(body) = route.body {
+        let _ = writeln!(out, "export type {type_name}Body = {body};");
+    }
+}
+
+/// Converts the path of a route into the body of a TypeScript template literal.
+fn path_template(route: &TsRoute) -> String {
+    let mut template = route.base.to_string();
+    let mut params = route.path_params.iter();
+    for segment in route.path.split('/').skip(1) {
+        template.push('/');
+        let param = match segment.starts_with('{') && segment.ends_with('}') {
+            true => params.next(),
+            false => None,
+        };
+        match param {
+            Some(param) if segment.starts_with("{*") => {
+                let _ = write!(template, "${{encodeURI(String(params.{}))}}", param.name);
+            }
+            Some(param) => {
+                let _ = write!(
+                    template,
+                    "${{encodeURIComponent(String(params.{}))}}",
+                    param.name
+                );
+            }
+            None => template.push_str(segment),
+        }
+    }
+    template
+}
+
+fn is_type_reference(ty: &str) -> bool {
+    !matches!(
+        ty,
+        "string" | "number" | "bigint" | "boolean" | "unknown" | "null"
+    ) && ty.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
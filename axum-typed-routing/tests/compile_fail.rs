@@ -0,0 +1,11 @@
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    #[cfg(feature = "unique-operation-ids")]
+    {
+        t.compile_fail("tests/ui/unique-operation-ids/duplicate.rs");
+        t.pass("tests/ui/unique-operation-ids/escaped.rs");
+        t.pass("tests/ui/unique-operation-ids/modules.rs");
+    }
+}
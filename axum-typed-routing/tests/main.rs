@@ -9,8 +9,8 @@ use axum::{
     Form, Json,
 };
 use axum_test::TestServer;
-use axum_typed_routing::TypedRouter;
-use axum_typed_routing_macros::route;
+use axum_typed_routing::{Locale, RouteInfo, TypedHandler, TypedRouter, TypedRouterBuilder};
+use axum_typed_routing_macros::{inline_route, route, route_service};
 
 /// This is a handler that is documented!
 #[route(GET "/hello/:id?user_id&name")]
@@ -30,12 +30,12 @@ async fn one(state: State<String>) -> String {
     String::from("Hello!")
 }
 
-#[route(POST "/two")]
+#[route(POST "/two" { module: true })]
 async fn two() -> String {
     String::from("Hello!")
 }
 
-#[route(GET "/three/:id")]
+#[route(GET "/three/:id" { module: true })]
 async fn three(id: u32) -> String {
     format!("Hello {id}!")
 }
@@ -87,148 +87,5760 @@ async fn test_normal() {
     response.assert_status_ok();
     response.assert_text("Hello, 123 - 321 - John!");
 
-    let (path, method_router) = generic_handler_with_complex_options::<u32>();
+    let (path, method_router, _) = generic_handler_with_complex_options::<u32>();
     assert_eq!(path, "/hello/{id}");
 }
 
-#[route(GET "/*")]
-async fn wildcard() {}
+#[route(GET "/typed/:user:u32/:id:u32")]
+async fn typed_path(id: u32) -> String {
+    format!("Hello {id}!")
+}
 
-#[route(GET "/*capture")]
-async fn wildcard_capture(capture: String) -> Json<String> {
-    Json(capture)
+#[tokio::test]
+async fn test_typed_path() {
+    let router: axum::Router = axum::Router::new().typed_route(typed_path);
+
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/typed/1/2").await;
+    response.assert_status_ok();
+    response.assert_text("Hello 2!");
+
+    let response = server.get("/typed/foo/2").await;
+    response.assert_status_bad_request();
+
+    let (path, _, _) = typed_path();
+    assert_eq!(path, "/typed/{user}/{id}");
 }
 
-#[route(GET "/")]
-async fn root() {}
+#[route(GET "/renamed/:item-id as item_id:u32")]
+async fn renamed_path(item_id: u32) -> String {
+    format!("Hello {item_id}!")
+}
 
 #[tokio::test]
-async fn test_wildcard() {
-    let router: axum::Router = axum::Router::new().typed_route(wildcard_capture);
+async fn test_renamed_path() {
+    let router: axum::Router = axum::Router::new().typed_route(renamed_path);
 
     let server = TestServer::new(router).unwrap();
 
-    let response = server.get("/foo/bar").await;
+    let response = server.get("/renamed/3").await;
     response.assert_status_ok();
-    assert_eq!(response.json::<String>(), "foo/bar");
+    response.assert_text("Hello 3!");
+
+    let (path, _, _) = renamed_path();
+    assert_eq!(path, "/renamed/{item-id}");
 }
 
+struct StrictPath<T>(T);
 
-#[cfg(feature = "aide")]
-mod aide_support {
-    use super::*;
-    use aide::{axum::ApiRouter, openapi::OpenApi, transform::TransformOperation};
-    use axum_typed_routing::TypedApiRouter;
-    use axum_typed_routing_macros::api_route;
+impl<T, S> axum::extract::FromRequestParts<S> for StrictPath<T>
+where
+    T: serde::de::DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = (axum::http::StatusCode, String);
 
-    /// get-summary
-    ///
-    /// get-description
-    #[api_route(GET "/hello")]
-    async fn get_hello(state: State<String>) -> String {
-        String::from("Hello!")
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        match Path::<T>::from_request_parts(parts, state).await {
+            Ok(Path(value)) => Ok(StrictPath(value)),
+            Err(rejection) => Err((
+                axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+                format!("invalid path: {}", rejection.body_text()),
+            )),
+        }
     }
+}
 
-    /// post-summary
-    ///
-    /// post-description
-    #[api_route(POST "/hello")]
-    async fn post_hello(state: State<String>) -> String {
-        String::from("Hello!")
-    }
+#[route(GET "/strict/:id" { path_extractor: StrictPath })]
+async fn custom_path_extractor(id: u32) -> String {
+    format!("Hello {id}!")
+}
 
-    #[test]
-    fn test_aide() {
-        let router: aide::axum::ApiRouter = aide::axum::ApiRouter::new()
-            .typed_route(one)
-            .typed_api_route(get_hello)
-            .with_state("state".to_string());
+#[tokio::test]
+async fn test_custom_path_extractor() {
+    let router: axum::Router = axum::Router::new().typed_route(custom_path_extractor);
 
-        let (path, method_router) = get_hello();
-        assert_eq!(path, "/hello");
+    let server = TestServer::new(router).unwrap();
 
-        let (path, method_router) = post_hello();
-        assert_eq!(path, "/hello");
-    }
+    let response = server.get("/strict/1").await;
+    response.assert_status_ok();
+    response.assert_text("Hello 1!");
 
-    #[test]
-    fn summary_and_description_are_generated_from_doc_comments() {
-        let router = ApiRouter::new()
-            .typed_api_route(get_hello)
-            .typed_api_route(post_hello);
-        let mut api = OpenApi::default();
-        router.finish_api(&mut api);
+    let response = server.get("/strict/foo").await;
+    response.assert_status(axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(response.text().starts_with("invalid path: "));
+}
 
-        let get_op = path_item(&api, "/hello").get.as_ref().unwrap();
-        let post_op = path_item(&api, "/hello").post.as_ref().unwrap();
+struct ApiError(String);
 
-        assert_eq!(get_op.summary, Some(" get-summary".to_string()));
-        assert_eq!(get_op.description, Some(" get-description".to_string()));
-        assert!(get_op.tags.is_empty());
+impl From<axum::extract::rejection::PathRejection> for ApiError {
+    fn from(rejection: axum::extract::rejection::PathRejection) -> Self {
+        Self(format!("path: {}", rejection.body_text()))
+    }
+}
 
-        assert_eq!(post_op.summary, Some(" post-summary".to_string()));
-        assert_eq!(post_op.description, Some(" post-description".to_string()));
-        assert!(post_op.tags.is_empty());
+impl From<axum::extract::rejection::QueryRejection> for ApiError {
+    fn from(rejection: axum::extract::rejection::QueryRejection) -> Self {
+        Self(format!("query: {}", rejection.body_text()))
     }
+}
 
-    /// unused-summary
-    ///
-    /// unused-description
-    #[api_route(GET "/hello" {
-        summary: "MySummary",
-        description: "MyDescription",
-        hidden: false,
-        id: "MyRoute",
-        tags: ["MyTag1", "MyTag2"],
-        security: {
-            "MySecurity1": ["MyScope1", "MyScope2"],
-            "MySecurity2": [],
-        },
-        responses: {
-            300: String,
-        },
-        transform: |x| x.summary("OverriddenSummary"),
-    })]
-    async fn get_gello_with_attributes(state: State<String>) -> String {
-        String::from("Hello!")
+impl axum::response::IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        (axum::http::StatusCode::IM_A_TEAPOT, self.0).into_response()
     }
+}
 
-    #[test]
-    fn generated_from_attributes() {
-        let router = ApiRouter::new().typed_api_route(get_gello_with_attributes);
-        let mut api = OpenApi::default();
-        router.finish_api(&mut api);
+#[route(GET "/rejection/:id?amount" { rejection: ApiError })]
+async fn custom_rejection(id: u32, amount: u32) -> String {
+    format!("Hello {id} {amount}!")
+}
 
-        let get_op = path_item(&api, "/hello").get.as_ref().unwrap();
+#[tokio::test]
+async fn test_custom_rejection() {
+    let router: axum::Router = axum::Router::new().typed_route(custom_rejection);
 
-        assert_eq!(get_op.summary, Some("OverriddenSummary".to_string()));
-        assert_eq!(get_op.description, Some("MyDescription".to_string()));
-        assert_eq!(
-            get_op.tags,
-            vec!["MyTag1".to_string(), "MyTag2".to_string()]
-        );
-        assert_eq!(get_op.operation_id, Some("MyRoute".to_string()));
-    }
+    let server = TestServer::new(router).unwrap();
 
-    /// summary
-    ///
-    /// description
-    /// description
-    #[api_route(GET "/hello")]
-    async fn get_gello_without_attributes(state: State<String>) -> String {
-        String::from("Hello!")
-    }
+    let response = server
+        .get("/rejection/1")
+        .add_query_param("amount", 2)
+        .await;
+    response.assert_status_ok();
+    response.assert_text("Hello 1 2!");
 
-    fn path_item<'a>(api: &'a OpenApi, path: &str) -> &'a aide::openapi::PathItem {
-        api.paths
-            .as_ref()
-            .unwrap()
-            .iter()
-            .find(|(p, _)| *p == path)
-            .unwrap()
-            .1
-            .as_item()
-            .unwrap()
-    }
+    let response = server
+        .get("/rejection/foo")
+        .add_query_param("amount", 2)
+        .await;
+    response.assert_status(axum::http::StatusCode::IM_A_TEAPOT);
+    assert!(response.text().starts_with("path: "));
+
+    let response = server.get("/rejection/1").await;
+    response.assert_status(axum::http::StatusCode::IM_A_TEAPOT);
+    assert!(response.text().starts_with("query: "));
+}
+
+#[route(GET "/strict-query?id" { deny_unknown_query: true })]
+async fn strict_query(id: u32) -> String {
+    format!("Hello {id}!")
+}
+
+#[route(GET "/strict-no-query" { deny_unknown_query: true })]
+async fn strict_no_query() {}
+
+#[tokio::test]
+async fn test_deny_unknown_query() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(strict_query)
+        .typed_route(strict_no_query);
+
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/strict-query").add_query_param("id", 1).await;
+    response.assert_status_ok();
+
+    let response = server
+        .get("/strict-query")
+        .add_query_param("id", 1)
+        .add_query_param("idd", 1)
+        .await;
+    response.assert_status_bad_request();
+
+    let response = server.get("/strict-no-query").await;
+    response.assert_status_ok();
+
+    let response = server
+        .get("/strict-no-query")
+        .add_query_param("id", 1)
+        .await;
+    response.assert_status_bad_request();
+}
+
+#[route(POST "/created" { status: 201 })]
+async fn created() -> Json<u32> {
+    Json(1)
+}
+
+#[route(DELETE "/deleted" { status: 204 })]
+async fn deleted() {}
+
+#[tokio::test]
+async fn test_status() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(created)
+        .typed_route(deleted);
+
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.post("/created").await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    response.assert_json(&1);
+
+    let response = server.delete("/deleted").await;
+    response.assert_status(axum::http::StatusCode::NO_CONTENT);
+}
+
+#[route(GET "/with-head" { head: auto })]
+async fn with_head() -> &'static str {
+    "Hello!"
+}
+
+#[tokio::test]
+async fn test_auto_head() {
+    let router: axum::Router = axum::Router::new().typed_route(with_head);
+
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.method(axum::http::Method::HEAD, "/with-head").await;
+    response.assert_status_ok();
+    response.assert_text("");
+
+    let response = server.post("/with-head").await;
+    response.assert_status(axum::http::StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.header("allow"), "GET,HEAD");
+}
+
+#[route_service(GET "/metrics/*rest")]
+fn metrics() -> axum::Router {
+    axum::Router::new().fallback(|| async { "metrics" })
+}
+
+#[tokio::test]
+async fn test_route_service() {
+    let router: axum::Router<String> = axum::Router::new().typed_route(metrics);
+    let server = TestServer::new(router.with_state("state".to_string())).unwrap();
+
+    let response = server.get("/metrics/foo/bar").await;
+    response.assert_status_ok();
+    response.assert_text("metrics");
+
+    let response = server.post("/metrics/foo").await;
+    response.assert_status(axum::http::StatusCode::METHOD_NOT_ALLOWED);
+
+    let (path, _, _) = metrics::<()>();
+    assert_eq!(path, "/metrics/{*rest}");
+}
+
+#[tokio::test]
+async fn test_inline_route() {
+    let echo = inline_route!(POST "/echo/:id" with String => |State(state): State<String>| async {
+        state
+    });
+    let builder = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(inline_route!(GET "/ping" => || async { "pong" }))
+        .typed_route(echo);
+    assert_eq!(builder.routes()[0].path, "/ping");
+    assert_eq!(builder.routes()[0].handler, "main::inline_route!");
+    assert_eq!(builder.routes()[1].method, "POST");
+
+    let router = builder.into_router().with_state("state".to_string());
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/ping").await;
+    response.assert_status_ok();
+    response.assert_text("pong");
+
+    let response = server.post("/echo/1").await;
+    response.assert_status_ok();
+    response.assert_text("state");
+}
+
+#[route(GET "/versioned/:id" { version: 2 })]
+async fn versioned(id: u32) -> String {
+    format!("Version 2: {id}")
+}
+
+#[route(GET "/versioned/:id" { version: 3, version_format: "/api/v{n}" })]
+async fn versioned_format(id: u32) -> String {
+    format!("Version 3: {id}")
+}
+
+#[tokio::test]
+async fn test_version() {
+    assert_eq!(versioned().0, "/v2/versioned/{id}");
+    assert_eq!(versioned_format().0, "/api/v3/versioned/{id}");
+
+    let router: axum::Router = axum::Router::new()
+        .typed_route(versioned)
+        .typed_route(versioned_format);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/v2/versioned/1").await;
+    response.assert_status_ok();
+    response.assert_text("Version 2: 1");
+
+    let response = server.get("/api/v3/versioned/1").await;
+    response.assert_status_ok();
+    response.assert_text("Version 3: 1");
+}
+
+#[route(GET "/items/:id" { alias: "/old/items/:id" })]
+async fn aliased(id: u32) -> String {
+    format!("Item {id}")
+}
+
+#[route(GET "/users/:id" { alias: ["/old/users/:id", "/legacy/users/:id"], deprecate_alias: true })]
+async fn deprecated_alias(id: u32) -> String {
+    format!("User {id}")
+}
+
+#[tokio::test]
+async fn test_alias() {
+    let builder = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(aliased)
+        .typed_route(deprecated_alias);
+    let paths = builder
+        .routes()
+        .iter()
+        .map(|route| route.path)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "/items/{id}",
+            "/old/items/{id}",
+            "/users/{id}",
+            "/old/users/{id}",
+            "/legacy/users/{id}"
+        ]
+    );
+
+    let server = TestServer::new(builder.into_router()).unwrap();
+
+    let response = server.get("/old/items/1").await;
+    response.assert_status_ok();
+    response.assert_text("Item 1");
+    assert!(response.maybe_header("deprecation").is_none());
+
+    let response = server.get("/users/1").await;
+    response.assert_text("User 1");
+    assert!(response.maybe_header("deprecation").is_none());
+
+    let response = server.get("/legacy/users/1").await;
+    response.assert_text("User 1");
+    assert_eq!(response.header("deprecation"), "true");
+}
+
+#[route(GET "/retired" { deprecated: true, sunset: "Wed, 31 Dec 2025 23:59:59 GMT" })]
+async fn retired() -> &'static str {
+    "retired"
+}
+
+#[tokio::test]
+async fn test_deprecated_route() {
+    let router: axum::Router = axum::Router::new().typed_route(retired);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/retired").await;
+    response.assert_text("retired");
+    assert_eq!(response.header("deprecation"), "true");
+    assert_eq!(response.header("sunset"), "Wed, 31 Dec 2025 23:59:59 GMT");
+}
+
+#[route(GET "/kept/:id" { keep_fn: true, module: true })]
+async fn kept(id: u32) -> String {
+    format!("Kept {id}")
+}
+
+#[route(GET "/named/:id" { name: named_route, keep_fn: true })]
+async fn named(id: u32) -> String {
+    format!("Named {id}")
+}
+
+#[route(GET "/renamed-only" { name: renamed_only_route, module: true })]
+async fn renamed_only() -> &'static str {
+    "Renamed"
+}
+
+#[tokio::test]
+async fn test_name_and_keep_fn() {
+    assert_eq!(kept(1).await, "Kept 1");
+    assert_eq!(named(2).await, "Named 2");
+
+    let router: axum::Router = axum::Router::new()
+        .typed_route(kept_route)
+        .typed_route(named_route)
+        .typed_route(renamed_only_route);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/kept/1").await;
+    response.assert_text("Kept 1");
+
+    let response = server.get("/named/2").await;
+    response.assert_text("Named 2");
+
+    let response = server.get("/renamed-only").await;
+    response.assert_text("Renamed");
+}
+
+const API_BASE: &str = "/api";
+
+#[route(GET concat!("/concat", "/items/:id"))]
+async fn concat_path(id: u32) -> String {
+    format!("Concat {id}")
+}
+
+#[route(GET "/items/:id" { base: API_BASE })]
+async fn based(id: u32) -> String {
+    format!("Based {id}")
+}
+
+#[tokio::test]
+async fn test_path_composition() {
+    assert_eq!(concat_path().0, "/concat/items/{id}");
+    assert_eq!(based().0, "/api/items/{id}");
+
+    let router: axum::Router = axum::Router::new()
+        .typed_route(concat_path)
+        .typed_route(based);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/concat/items/1").await;
+    response.assert_text("Concat 1");
+
+    let response = server.get("/api/items/2").await;
+    response.assert_text("Based 2");
+}
+
+#[derive(serde::Deserialize)]
+struct Rename {
+    name: String,
+}
+
+#[route(POST "/rename/:id?suffix")]
+async fn destructured(
+    mut id: u32,
+    suffix: Option<String>,
+    State((prefix, _)): State<(&'static str, u32)>,
+    Json(Rename { name }): Json<Rename>,
+) -> String {
+    id += 1;
+    format!("{prefix}{id} {name}{}", suffix.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_destructuring_patterns() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(destructured)
+        .with_state(("item", 0));
+    let server = TestServer::new(router).unwrap();
+
+    let response = server
+        .post("/rename/1?suffix=!")
+        .json(&serde_json::json!({ "name": "Bob" }))
+        .await;
+    response.assert_text("item2 Bob!");
+}
+
+/// An extractor without OpenApi documentation.
+struct Session(&'static str);
+
+impl<S: Send + Sync> axum::extract::FromRequestParts<S> for Session {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        _parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Session("session"))
+    }
+}
+
+#[route(GET "/session")]
+async fn session(#[api_ignore] Session(user): Session) -> &'static str {
+    user
+}
+
+#[tokio::test]
+async fn test_api_ignore_is_removed() {
+    let router: axum::Router = axum::Router::new().typed_route(session);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/session").await;
+    response.assert_text("session");
+}
+
+#[derive(serde::Deserialize)]
+#[cfg_attr(feature = "aide", derive(schemars::JsonSchema))]
+#[serde(tag = "sort", rename_all = "snake_case")]
+enum SortOrder {
+    Name,
+    Date { since: Option<String> },
+}
+
+#[route(GET "/sorted?page&sort")]
+async fn sorted(page: Option<u32>, #[route_param(flatten)] sort: SortOrder) -> String {
+    let sort = match sort {
+        SortOrder::Name => "name".to_string(),
+        SortOrder::Date { since } => format!("date since {}", since.unwrap_or_default()),
+    };
+    format!("page {} by {sort}", page.unwrap_or(1))
+}
+
+#[tokio::test]
+async fn test_flattened_query() {
+    let router: axum::Router = axum::Router::new().typed_route(sorted);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/sorted?page=2&sort=name").await;
+    response.assert_text("page 2 by name");
+
+    let response = server.get("/sorted?sort=date&since=2024").await;
+    response.assert_text("page 1 by date since 2024");
+
+    let response = server.get("/sorted?sort=size").await;
+    response.assert_status_bad_request();
+}
+
+fn from_csv<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<u32>, D::Error> {
+    let csv = <String as serde::Deserialize>::deserialize(deserializer)?;
+    csv.split(',')
+        .map(|item| item.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn from_hex<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let hex = <String as serde::Deserialize>::deserialize(deserializer)?;
+    u32::from_str_radix(&hex, 16).map_err(serde::de::Error::custom)
+}
+
+#[route(GET "/csv/:id?ids")]
+async fn csv(
+    #[route_param(deserialize_with = "from_hex")] id: u32,
+    #[route_param(deserialize_with = "from_csv")] ids: Vec<u32>,
+) -> String {
+    format!("{id} {ids:?}")
+}
+
+#[tokio::test]
+async fn test_deserialize_with() {
+    let router: axum::Router = axum::Router::new().typed_route(csv);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/csv/ff?ids=3,4,5").await;
+    response.assert_text("255 [3, 4, 5]");
+
+    let response = server.get("/csv/ff?ids=3,x").await;
+    response.assert_status_bad_request();
+
+    let response = server.get("/csv/xx?ids=3").await;
+    response.assert_status_bad_request();
+}
+
+#[derive(Debug)]
+struct UserId(u64);
+
+impl TryFrom<u64> for UserId {
+    type Error = &'static str;
+
+    fn try_from(id: u64) -> Result<Self, Self::Error> {
+        match id {
+            0 => Err("user ids start at 1"),
+            id => Ok(UserId(id)),
+        }
+    }
+}
+
+impl std::fmt::Display for UserId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[route(GET "/users/:id?referrer")]
+async fn user(
+    #[allow(unused_mut)]
+    #[route_param(try_from = u64)]
+    mut id: UserId,
+    #[route_param(try_from = u64)] referrer: Option<UserId>,
+) -> String {
+    format!("{id:?} {referrer:?}")
+}
+
+#[tokio::test]
+async fn test_try_from_params() {
+    let router: axum::Router = axum::Router::new().typed_route(user);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/users/3?referrer=4").await;
+    response.assert_text("UserId(3) Some(UserId(4))");
+
+    let response = server.get("/users/3").await;
+    response.assert_text("UserId(3) None");
+
+    let response = server.get("/users/0").await;
+    response.assert_status_bad_request();
+
+    let response = server.get("/users/3?referrer=0").await;
+    response.assert_status_bad_request();
+}
+
+#[derive(Debug)]
+struct Sku(String);
+
+impl std::str::FromStr for Sku {
+    type Err = &'static str;
+
+    fn from_str(sku: &str) -> Result<Self, Self::Err> {
+        match sku.strip_prefix("sku-") {
+            Some(code) if !code.is_empty() => Ok(Sku(code.to_uppercase())),
+            _ => Err("expected a sku like `sku-abc`"),
+        }
+    }
+}
+
+impl std::fmt::Display for Sku {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sku-{}", self.0.to_lowercase())
+    }
+}
+
+#[route(GET "/products/:sku?related")]
+async fn product(
+    #[route_param(from_str)] sku: Sku,
+    #[route_param(from_str)] related: Option<Sku>,
+) -> String {
+    format!("{sku:?} {related:?}")
+}
+
+#[tokio::test]
+async fn test_from_str_params() {
+    let router: axum::Router = axum::Router::new().typed_route(product);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/products/sku-ab?related=sku-cd").await;
+    response.assert_text(r#"Sku("AB") Some(Sku("CD"))"#);
+
+    let response = server.get("/products/sku-ab").await;
+    response.assert_text(r#"Sku("AB") None"#);
+
+    let response = server.get("/products/ab").await;
+    response.assert_status_bad_request();
+
+    let response = server.get("/products/sku-ab?related=cd").await;
+    response.assert_status_bad_request();
+}
+
+#[derive(serde::Serialize)]
+struct Track {
+    title: &'static str,
+}
+
+#[route(GET "/tracks/:id" { accepts: [json] })]
+async fn track(id: u32) -> Result<Track, axum::http::StatusCode> {
+    match id {
+        1 => Ok(Track { title: "Intro" }),
+        _ => Err(axum::http::StatusCode::NOT_FOUND),
+    }
+}
+
+#[tokio::test]
+async fn test_accepts() {
+    let router: axum::Router = axum::Router::new().typed_route(track);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/tracks/1").await;
+    response.assert_header("content-type", "application/json");
+    response.assert_header("vary", "accept");
+    response.assert_text(r#"{"title":"Intro"}"#);
+
+    let response = server
+        .get("/tracks/1")
+        .add_header("accept", "text/html, application/json;q=0.5")
+        .await;
+    response.assert_header("content-type", "application/json");
+
+    let response = server
+        .get("/tracks/1")
+        .add_header("accept", "text/html")
+        .await;
+    response.assert_status(axum::http::StatusCode::NOT_ACCEPTABLE);
+
+    let response = server.get("/tracks/2").await;
+    response.assert_status_not_found();
+}
+
+#[cfg(feature = "msgpack")]
+#[route(GET "/tracks" { accepts: [json, msgpack] })]
+async fn tracks() -> Vec<Track> {
+    vec![Track { title: "Intro" }]
+}
+
+#[cfg(feature = "msgpack")]
+#[tokio::test]
+async fn test_accepts_msgpack() {
+    let router: axum::Router = axum::Router::new().typed_route(tracks);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server
+        .get("/tracks")
+        .add_header("accept", "application/json;q=0.5, application/msgpack")
+        .await;
+    response.assert_header("content-type", "application/msgpack");
+    assert_eq!(
+        response.as_bytes().as_ref(),
+        b"\x91\x81\xa5title\xa5Intro".as_slice()
+    );
+
+    let response = server.get("/tracks").add_header("accept", "*/*").await;
+    response.assert_header("content-type", "application/json");
+}
+
+#[route(GET "/internal-metrics" { host: "internal.example.com" })]
+async fn internal_metrics() -> &'static str {
+    "up 1"
+}
+
+#[route(GET "/status" { host: "internal.example.com:8080", guards: [RequireToken] })]
+async fn internal_status() -> &'static str {
+    "ok"
+}
+
+#[tokio::test]
+async fn test_host() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(internal_metrics)
+        .typed_route(internal_status);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server
+        .get("/internal-metrics")
+        .add_header("host", "internal.example.com")
+        .await;
+    response.assert_text("up 1");
+
+    let response = server
+        .get("/internal-metrics")
+        .add_header("host", "Internal.Example.com:3000")
+        .await;
+    response.assert_text("up 1");
+
+    let response = server
+        .get("/internal-metrics")
+        .add_header("host", "www.example.com")
+        .await;
+    response.assert_status_not_found();
+
+    let response = server
+        .get("/status")
+        .add_header("host", "internal.example.com")
+        .await;
+    response.assert_status_not_found();
+
+    let response = server
+        .get("/status")
+        .add_header("host", "internal.example.com:8080")
+        .await;
+    response.assert_status_unauthorized();
+}
+
+#[route(GET "/debugged/:id?verbose" { debug_handler: true })]
+async fn debugged(id: u32, verbose: Option<bool>, State(prefix): State<String>) -> String {
+    format!("{prefix}{id} {verbose:?}")
+}
+
+#[tokio::test]
+async fn test_debug_handler() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(debugged)
+        .with_state("debugged ".to_string());
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/debugged/3?verbose=true").await;
+    response.assert_text("debugged 3 Some(true)");
+}
+
+#[route(GET "/single?r#type")]
+async fn single_query(r#type: Option<String>) -> String {
+    format!("{type:?}")
+}
+
+#[route(GET "/single-required?limit")]
+async fn single_required_query(limit: u32) -> String {
+    format!("{limit}")
+}
+
+#[tokio::test]
+async fn test_single_query_param() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(single_query)
+        .typed_route(single_required_query);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/single?other=1&type=a%20b").await;
+    response.assert_text(r#"Some("a b")"#);
+
+    let response = server.get("/single").await;
+    response.assert_text("None");
+
+    let response = server.get("/single?type=a&type=b").await;
+    response.assert_status_bad_request();
+
+    let response = server.get("/single-required?limit=3").await;
+    response.assert_text("3");
+
+    let response = server.get("/single-required").await;
+    response.assert_status_bad_request();
+    assert!(response.text().contains("missing field `limit`"));
+
+    let response = server.get("/single-required?limit=x").await;
+    response.assert_status_bad_request();
+}
+
+#[route(GET "/lenient?page&q" { lenient_query: true })]
+async fn lenient_search(page: Option<u32>, q: Option<String>) -> String {
+    format!("{page:?} {q:?}")
+}
+
+#[route(GET "/lenient-single?page" { lenient_query: true })]
+async fn lenient_single(page: Option<u32>) -> String {
+    format!("{page:?}")
+}
+
+#[tokio::test]
+async fn test_lenient_query() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(lenient_search)
+        .typed_route(lenient_single);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/lenient?page=2&q=a").await;
+    response.assert_text(r#"Some(2) Some("a")"#);
+
+    let response = server.get("/lenient").await;
+    response.assert_text("None None");
+
+    let response = server.get("/lenient?page=x&q=a").await;
+    response.assert_status_ok();
+    response.assert_text("None None");
+
+    let response = server.get("/lenient-single?page=x").await;
+    response.assert_text("None");
+}
+
+#[route(GET "/shared?r#type&limit&page")]
+async fn shared_query(r#type: Option<String>, limit: u32, page: Option<u32>) -> String {
+    format!("{type:?} {limit} {page:?}")
+}
+
+#[route(GET "/shared-same-shape?q&per_page&offset")]
+async fn shared_query_same_shape(q: Option<String>, per_page: u32, offset: Option<u32>) -> String {
+    format!("{q:?} {per_page} {offset:?}")
+}
+
+#[tokio::test]
+async fn test_shared_query_params() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(shared_query)
+        .typed_route(shared_query_same_shape);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server
+        .get("/shared?page=2&other=1&type=a%20b&limit=3")
+        .await;
+    response.assert_text(r#"Some("a b") 3 Some(2)"#);
+
+    let response = server.get("/shared?limit=3").await;
+    response.assert_text("None 3 None");
+
+    let response = server.get("/shared?limit=3&limit=4").await;
+    response.assert_status_bad_request();
+
+    let response = server.get("/shared?type=a").await;
+    response.assert_status_bad_request();
+    assert!(response.text().contains("missing field `limit`"));
+
+    let response = server.get("/shared?limit=x").await;
+    response.assert_status_bad_request();
+
+    let response = server.get("/shared-same-shape?per_page=10&offset=20").await;
+    response.assert_text("None 10 Some(20)");
+}
+
+#[route(GET "/unexploded?ids&tags")]
+async fn unexploded(
+    #[route_param(explode = false)] ids: Vec<u32>,
+    #[route_param(style = form, explode = false)] tags: Option<Vec<String>>,
+) -> String {
+    format!("{ids:?} {tags:?}")
+}
+
+#[tokio::test]
+async fn test_unexploded_query() {
+    let router: axum::Router = axum::Router::new().typed_route(unexploded);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/unexploded?ids=1,2,3&tags=a,b").await;
+    response.assert_text(r#"[1, 2, 3] Some(["a", "b"])"#);
+
+    let response = server.get("/unexploded?ids=").await;
+    response.assert_text("[] None");
+
+    let response = server.get("/unexploded?ids=1,x").await;
+    response.assert_status_bad_request();
+}
+
+#[route(GET "/csv?ids(csv):Vec<u32>&tags(csv)")]
+async fn csv_query(ids: Vec<u32>, tags: Option<Vec<String>>) -> String {
+    format!("{ids:?} {tags:?}")
+}
+
+#[tokio::test]
+async fn test_csv_query() {
+    let router: axum::Router = axum::Router::new().typed_route(csv_query);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/csv?ids=1,2,3&tags=a,b").await;
+    response.assert_text(r#"[1, 2, 3] Some(["a", "b"])"#);
+
+    let response = server.get("/csv?ids=1,x").await;
+    response.assert_status_bad_request();
+}
+
+#[derive(serde::Deserialize)]
+#[cfg_attr(feature = "aide", derive(schemars::JsonSchema))]
+struct Filter {
+    name: String,
+    age: Option<u32>,
+}
+
+#[cfg(feature = "deep-object")]
+#[route(GET "/deep?page&filter")]
+async fn deep(page: Option<u32>, #[route_param(style = deepObject)] filter: Filter) -> String {
+    format!("{page:?} {} {:?}", filter.name, filter.age)
+}
+
+#[cfg(feature = "deep-object")]
+#[tokio::test]
+async fn test_deep_object_query() {
+    let router: axum::Router = axum::Router::new().typed_route(deep);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server
+        .get("/deep?page=2&filter[name]=x&filter[age]=3")
+        .await;
+    response.assert_text("Some(2) x Some(3)");
+
+    let response = server.get("/deep?filter%5Bname%5D=y").await;
+    response.assert_text("None y None");
+
+    let response = server.get("/deep?filter[age]=3").await;
+    response.assert_status_bad_request();
+}
+
+#[route(GET "/three/:id")]
+async fn three_again(id: u32) {}
+
+#[route(GET "/three/:other")]
+async fn three_renamed(other: u32) {}
+
+#[route(POST "/three/:id")]
+async fn three_post(id: u32) {}
+
+#[tokio::test]
+async fn test_merge_method_routers() {
+    let (path, method_router) = axum_typed_routing::merge_method_routers!(three, three_post);
+    assert_eq!(path, "/three/{id}");
+    let router: axum::Router = axum::Router::new().route(path, method_router);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/three/1").await;
+    response.assert_text("Hello 1!");
+    let response = server.post("/three/1").await;
+    response.assert_status_ok();
+    let response = server.delete("/three/1").await;
+    response.assert_status(axum::http::StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[test]
+#[should_panic(expected = "different paths")]
+fn merge_method_routers_with_different_paths() {
+    let _ = axum_typed_routing::merge_method_routers!(three, csv);
+}
+
+#[test]
+fn test_route_conflicts() {
+    let builder = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(three)
+        .typed_route(three_post)
+        .typed_route(three_again)
+        .typed_route(three_renamed);
+    assert_eq!(builder.routes().len(), 2);
+
+    let err = builder.build().unwrap_err();
+    let conflicts = err
+        .conflicts()
+        .map(|conflict| (conflict.existing.handler, conflict.conflicting.handler))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        conflicts,
+        [
+            ("main::three", "main::three_again"),
+            ("main::three", "main::three_renamed"),
+            ("main::three_post", "main::three_renamed"),
+        ]
+    );
+    assert!(err.to_string().contains(
+        "GET /three/{id} (main::three_again) conflicts with GET /three/{id} (main::three)"
+    ));
+}
+
+#[test]
+#[should_panic(expected = "invalid typed routes")]
+fn test_route_conflicts_panic() {
+    TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(three)
+        .typed_route(three_again)
+        .into_router();
+}
+
+#[tokio::test]
+async fn test_merge_typed() {
+    let items = TypedRouterBuilder::new(axum::Router::new()).typed_route(three);
+    let other = TypedRouterBuilder::new(axum::Router::new()).typed_route(three_post);
+    let router = items.clone().merge_typed(other).build().unwrap();
+    let server = TestServer::new(router).unwrap();
+    server.get("/three/1").await.assert_text("Hello 1!");
+    server.post("/three/1").await.assert_status_ok();
+
+    let conflicting = TypedRouterBuilder::new(axum::Router::new()).typed_route(three_renamed);
+    let err = items.merge_typed(conflicting).build().unwrap_err();
+    let conflicts = err.conflicts().collect::<Vec<_>>();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].existing.handler, "main::three");
+    assert_eq!(conflicts[0].conflicting.handler, "main::three_renamed");
+}
+
+#[test]
+fn invalid_paths_are_reported() {
+    use axum_typed_routing::Error;
+
+    let err = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(three)
+        .typed_route(|| ("items/{id}", get(|| async {})))
+        .typed_route(|| ("/items/:id", get(|| async {})))
+        .typed_route(|| ("/items/{id", get(|| async {})))
+        .typed_route(|| ("/files/{*path}/raw", get(|| async {})))
+        .build()
+        .unwrap_err();
+    let reasons = err
+        .errors
+        .iter()
+        .map(|error| match error {
+            Error::InvalidPath { route, reason } => (route.path, *reason),
+            error => panic!("expected an invalid path: {error}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reasons,
+        [
+            ("items/{id}", "paths must start with a `/`"),
+            (
+                "/items/:id",
+                "path parameters are written as `{name}` or `{*name}`, not `:name` or `*name`"
+            ),
+            ("/items/{id", "unclosed `{`, escape it as `{{`"),
+            (
+                "/files/{*path}/raw",
+                "a wildcard must be the whole last segment of the path"
+            ),
+        ]
+    );
+    assert!(err
+        .to_string()
+        .contains("* /items/{id (<unknown>) has an invalid path"));
+
+    // Escaped braces are literal.
+    TypedRouterBuilder::new(axum::Router::<()>::new())
+        .typed_route(|| ("/{{literal}}/{*path}", get(|| async {})))
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn test_route_name() {
+    assert_eq!(three::NAME, "main::three");
+    assert_eq!(kept::NAME, "main::kept");
+    assert_eq!(renamed_only::NAME, "main::renamed_only");
+}
+
+/// Without a route module, a handler can have the name of a module.
+#[cfg(not(any(feature = "templates", feature = "fuzz", feature = "client")))]
+mod without_route_module {
+    use super::*;
+
+    mod page {
+        pub const SIZE: u32 = 20;
+    }
+
+    #[route(GET "/page")]
+    async fn page() -> String {
+        page::SIZE.to_string()
+    }
+
+    #[tokio::test]
+    async fn test_handler_named_like_a_module() {
+        let router: axum::Router = axum::Router::new().typed_route(page);
+        let server = TestServer::new(router).unwrap();
+        server.get("/page").await.assert_text("20");
+    }
+}
+
+trait AuthProvider {
+    fn current_user(&self) -> String;
+}
+
+#[derive(Clone)]
+struct AdminState;
+
+impl AuthProvider for AdminState {
+    fn current_user(&self) -> String {
+        "admin".to_string()
+    }
+}
+
+#[derive(Clone)]
+struct GuestState;
+
+impl AuthProvider for GuestState {
+    fn current_user(&self) -> String {
+        "guest".to_string()
+    }
+}
+
+#[route(GET "/me/:id" with S where S: AuthProvider)]
+async fn generic_state(id: u32, State(auth): State<S>) -> String {
+    format!("{} {id}", auth.current_user())
+}
+
+#[tokio::test]
+async fn test_generic_state() {
+    let admin = axum::Router::new()
+        .typed_route(generic_state)
+        .with_state(AdminState);
+    let server = TestServer::new(admin).unwrap();
+    server.get("/me/1").await.assert_text("admin 1");
+
+    let guest = axum::Router::new()
+        .typed_route(generic_state)
+        .with_state(GuestState);
+    let server = TestServer::new(guest).unwrap();
+    server.get("/me/2").await.assert_text("guest 2");
+}
+
+trait Backend: Send + Sync + 'static {
+    const NAME: &'static str;
+}
+
+#[derive(Clone)]
+struct Postgres;
+
+impl Backend for Postgres {
+    const NAME: &'static str = "postgres";
+}
+
+#[derive(Clone)]
+struct Repo<T>(std::marker::PhantomData<T>);
+
+#[route(GET "/repo/:id" with Repo<T> where T: Backend)]
+async fn repo_item(id: u32, State(_repo): State<Repo<T>>) -> String {
+    format!("{} {id}", T::NAME)
+}
+
+#[route(GET "/repo-declared/:id" with Repo<T> { status: 201 })]
+async fn repo_declared<T: Backend + Clone>(id: u32, State(_repo): State<Repo<T>>) -> String {
+    format!("{} {id}", T::NAME)
+}
+
+#[tokio::test]
+async fn test_generic_state_type() {
+    let router = axum::Router::new()
+        .typed_route(repo_item::<Postgres>)
+        .typed_route(repo_declared::<Postgres>)
+        .with_state(Repo::<Postgres>(std::marker::PhantomData));
+    let server = TestServer::new(router).unwrap();
+    server.get("/repo/1").await.assert_text("postgres 1");
+
+    let response = server.get("/repo-declared/2").await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    response.assert_text("postgres 2");
+}
+
+const ADMIN_ENABLED: bool = false;
+
+#[route(GET "/admin" { enabled_if: ADMIN_ENABLED })]
+async fn admin() -> &'static str {
+    "admin"
+}
+
+#[tokio::test]
+async fn test_conditional_routes() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(admin)
+        .typed_route_if(true, created)
+        .typed_route_if(false, session);
+    let server = TestServer::new(router).unwrap();
+    server.get("/admin").await.assert_status_not_found();
+    server.get("/session").await.assert_status_not_found();
+    server
+        .post("/created")
+        .await
+        .assert_status(axum::http::StatusCode::CREATED);
+
+    let builder = TypedRouterBuilder::new(axum::Router::<()>::new()).typed_route(admin);
+    assert!(builder.routes().is_empty());
+}
+
+#[route(GET "/orders/:order-id as order_id/lines/:line" { path_struct: true })]
+async fn order_line(order_id: u32, line: u32) -> String {
+    format!("{order_id} {line}")
+}
+
+#[derive(serde::Deserialize)]
+struct ShelfPath {
+    shelf: String,
+    slot: u32,
+}
+
+#[route(GET "/shelves/:shelf/:slot:u32" { path_struct: ShelfPath })]
+async fn shelf_slot(path: ShelfPath) -> String {
+    format!("{} {}", path.shelf, path.slot)
+}
+
+#[tokio::test]
+async fn test_path_struct() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(order_line)
+        .typed_route(shelf_slot);
+    let server = TestServer::new(router).unwrap();
+    server.get("/orders/7/lines/2").await.assert_text("7 2");
+    server.get("/shelves/top/3").await.assert_text("top 3");
+
+    let response = server.get("/orders/7/lines/two").await;
+    response.assert_status_bad_request();
+    assert!(response.text().contains("`line`"), "{}", response.text());
+    let response = server.get("/shelves/top/left").await;
+    response.assert_status_bad_request();
+    assert!(response.text().contains("`slot`"), "{}", response.text());
+}
+
+#[route(PROPFIND "/dav/:name")]
+async fn propfind(name: String) -> String {
+    format!("properties of {name}")
+}
+
+#[route(GET "/dav/:name")]
+async fn dav_get(name: String) -> String {
+    format!("contents of {name}")
+}
+
+#[tokio::test]
+async fn test_custom_method() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(propfind)
+        .typed_route(dav_get);
+    let server = TestServer::new(router).unwrap();
+    let propfind_method = axum::http::Method::from_bytes(b"PROPFIND").unwrap();
+    server
+        .method(propfind_method, "/dav/notes")
+        .await
+        .assert_text("properties of notes");
+    server
+        .get("/dav/notes")
+        .await
+        .assert_text("contents of notes");
+    server
+        .delete("/dav/notes")
+        .await
+        .assert_status(axum::http::StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[route(GET "/logo" { cache: { max_age: 300, public: true } })]
+async fn logo() -> &'static str {
+    "logo"
+}
+
+#[route(GET "/account" { cache: { no_store: true } })]
+async fn account() -> impl axum::response::IntoResponse {
+    ([(axum::http::header::CACHE_CONTROL, "no-cache")], "account")
+}
+
+#[tokio::test]
+async fn test_cache_control() {
+    let router: axum::Router = axum::Router::new().typed_route(logo).typed_route(account);
+    let server = TestServer::new(router).unwrap();
+    server
+        .get("/logo")
+        .await
+        .assert_header("cache-control", "public, max-age=300");
+    server
+        .get("/account")
+        .await
+        .assert_header("cache-control", "no-cache");
+}
+
+#[route(GET "/events" { compression: off })]
+async fn events() -> &'static str {
+    "data: event"
+}
+
+#[tokio::test]
+async fn test_compression_off() {
+    let router: axum::Router = axum::Router::new().typed_route(events);
+    let server = TestServer::new(router).unwrap();
+    server
+        .get("/events")
+        .await
+        .assert_header("content-encoding", "identity");
+}
+
+trait Greeting: Clone + Send + Sync + 'static {
+    fn greeting(&self) -> &'static str;
+}
+
+#[derive(Clone)]
+struct English;
+
+impl Greeting for English {
+    fn greeting(&self) -> &'static str {
+        "hello"
+    }
+}
+
+struct GreetingPlugin<S>(std::marker::PhantomData<S>);
+
+impl<S: Greeting> GreetingPlugin<S> {
+    #[route(GET "/greet/:name" { in_impl: true })]
+    async fn greet(name: String, State(state): State<S>) -> String {
+        format!("{} {name}", state.greeting())
+    }
+
+    fn routes() -> Vec<TypedHandler<S>> {
+        vec![Self::greet_route]
+    }
+}
+
+#[tokio::test]
+async fn test_routes_in_impl() {
+    let router = GreetingPlugin::<English>::routes()
+        .into_iter()
+        .fold(axum::Router::new(), TypedRouter::typed_route)
+        .with_state(English);
+    let server = TestServer::new(router).unwrap();
+    server.get("/greet/world").await.assert_text("hello world");
+}
+
+#[route(GET "/en/items/:id" {
+    locale: "en",
+    localized: { "de": "/de/artikel/:id", "fr": "/fr/articles/:id" },
+})]
+async fn localized_item(id: u32, Locale(locale): Locale) -> String {
+    format!("{locale} {id}")
+}
+
+#[tokio::test]
+async fn test_localized_paths() {
+    let builder = TypedRouterBuilder::new(axum::Router::<()>::new()).typed_route(localized_item);
+    let paths = builder
+        .routes()
+        .iter()
+        .map(|route| route.path)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec!["/en/items/{id}", "/de/artikel/{id}", "/fr/articles/{id}"]
+    );
+
+    let server = TestServer::new(builder.build().unwrap()).unwrap();
+    server.get("/en/items/1").await.assert_text("en 1");
+    server.get("/de/artikel/2").await.assert_text("de 2");
+    server.get("/fr/articles/3").await.assert_text("fr 3");
+}
+
+#[test]
+fn test_route_map() {
+    let builder = TypedRouterBuilder::new(axum::Router::<()>::new())
+        .typed_route(three)
+        .typed_route(localized_item);
+    let route_map = builder.route_map();
+    assert_eq!(route_map.len(), 2);
+
+    let route = route_map.get(three).unwrap();
+    assert_eq!((route.method, route.path), ("GET", "/three/{id}"));
+    assert!(route.handler.ends_with("::three"));
+
+    let paths = route_map
+        .routes(localized_item)
+        .iter()
+        .map(|route| route.path)
+        .collect::<Vec<_>>();
+    assert_eq!(paths[0], "/en/items/{id}");
+    assert_eq!(paths.len(), 3);
+
+    assert!(route_map.get(created).is_none());
+    assert!(route_map.routes(created).is_empty());
+}
+
+#[derive(Clone)]
+struct Flags(&'static [&'static str]);
+
+impl axum_typed_routing::FeatureGate for Flags {
+    fn is_enabled(&self, flag: &str, parts: &axum::http::request::Parts) -> bool {
+        self.0.contains(&flag) || parts.headers.contains_key("x-beta")
+    }
+
+    fn disabled_status(&self, flag: &str) -> axum::http::StatusCode {
+        match flag {
+            "maintenance" => axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            _ => axum::http::StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+#[route(GET "/checkout/:id" with Flags { feature_flag: "new-checkout" })]
+async fn new_checkout(id: u32) -> String {
+    format!("checkout {id}")
+}
+
+#[route(GET "/reports" with Flags { feature_flag: "maintenance" })]
+async fn reports(State(flags): State<Flags>) -> String {
+    String::from("reports")
+}
+
+#[tokio::test]
+async fn test_feature_flag() {
+    let router = axum::Router::new()
+        .typed_route(new_checkout)
+        .typed_route(reports);
+    let server = TestServer::new(router.clone().with_state(Flags(&["new-checkout"]))).unwrap();
+    server.get("/checkout/1").await.assert_text("checkout 1");
+    server
+        .get("/reports")
+        .await
+        .assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+
+    let server = TestServer::new(router.with_state(Flags(&[]))).unwrap();
+    // A disabled route is rejected before its path is extracted.
+    server.get("/checkout/x").await.assert_status_not_found();
+    let response = server.get("/checkout/2").add_header("x-beta", "1").await;
+    response.assert_text("checkout 2");
+}
+
+/// A response cache counting the calls of the handlers, to see which responses were cached.
+#[derive(Clone, Default)]
+struct Responses {
+    cache: std::sync::Arc<
+        std::sync::Mutex<std::collections::HashMap<String, axum_typed_routing::CachedResponse>>,
+    >,
+    calls: std::sync::Arc<std::sync::atomic::AtomicU32>,
+}
+
+impl axum_typed_routing::ResponseCache for Responses {
+    async fn get(&self, key: &str) -> Option<axum_typed_routing::CachedResponse> {
+        self.cache.lock().unwrap().get(key).cloned()
+    }
+
+    async fn insert(
+        &self,
+        key: String,
+        response: axum_typed_routing::CachedResponse,
+        ttl: std::time::Duration,
+    ) {
+        assert_eq!(ttl, std::time::Duration::from_secs(60));
+        self.cache.lock().unwrap().insert(key, response);
+    }
+}
+
+#[route(GET "/prices/:id?amount&currency" { cached: { ttl: 60s, key: [id, amount] } })]
+async fn price(
+    id: u32,
+    amount: Option<u32>,
+    currency: Option<String>,
+    State(responses): State<Responses>,
+) -> Result<String, axum::http::StatusCode> {
+    let call = responses
+        .calls
+        .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    match id {
+        0 => Err(axum::http::StatusCode::NOT_FOUND),
+        _ => Ok(format!("{id} {amount:?} {currency:?} call {call}")),
+    }
+}
+
+#[tokio::test]
+async fn test_cached_responses() {
+    let responses = Responses::default();
+    let router = axum::Router::new()
+        .typed_route(price)
+        .with_state(responses.clone());
+    let server = TestServer::new(router).unwrap();
+
+    server
+        .get("/prices/1?amount=2&currency=EUR")
+        .await
+        .assert_text("1 Some(2) Some(\"EUR\") call 0");
+    // The currency isn't part of the key.
+    server
+        .get("/prices/1?amount=2&currency=USD")
+        .await
+        .assert_text("1 Some(2) Some(\"EUR\") call 0");
+    server
+        .get("/prices/1")
+        .await
+        .assert_text("1 None None call 1");
+
+    // Errors aren't cached.
+    server.get("/prices/0").await.assert_status_not_found();
+    server.get("/prices/0").await.assert_status_not_found();
+    assert_eq!(responses.calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+
+    let mut keys = responses
+        .cache
+        .lock()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            "main::price?id=1&amount=None",
+            "main::price?id=1&amount=Some(2)"
+        ]
+    );
+}
+
+/// The user of a request, from its `Authorization` header.
+#[derive(Debug)]
+struct AuthUser(String);
+
+impl<S: Send + Sync> axum::extract::FromRequestParts<S> for AuthUser {
+    type Rejection = axum::http::StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        match parts.headers.get(axum::http::header::AUTHORIZATION) {
+            Some(value) => Ok(AuthUser(value.to_str().unwrap().to_owned())),
+            None => Err(axum::http::StatusCode::UNAUTHORIZED),
+        }
+    }
+}
+
+#[route(GET "/balances/:id" { cached: { ttl: 60s, key: [id, user] } })]
+async fn balance(id: u32, user: AuthUser, State(responses): State<Responses>) -> String {
+    let call = responses
+        .calls
+        .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    format!("{id} {} call {call}", user.0)
+}
+
+#[tokio::test]
+async fn test_cached_responses_of_users() {
+    let router = axum::Router::new()
+        .typed_route(balance)
+        .with_state(Responses::default());
+    let server = TestServer::new(router).unwrap();
+    let response = |user: &'static str| {
+        server
+            .get("/balances/1")
+            .add_header(axum::http::header::AUTHORIZATION, user)
+    };
+    response("alice").await.assert_text("1 alice call 0");
+    // The response of another user isn't shared.
+    response("bob").await.assert_text("1 bob call 1");
+    response("alice").await.assert_text("1 alice call 0");
+}
+
+impl axum_typed_routing::IdempotencyStore for Responses {
+    async fn get(&self, key: &str) -> Option<axum_typed_routing::CachedResponse> {
+        self.cache.lock().unwrap().get(key).cloned()
+    }
+
+    async fn insert(
+        &self,
+        key: String,
+        response: axum_typed_routing::CachedResponse,
+        ttl: std::time::Duration,
+    ) {
+        assert_eq!(ttl, std::time::Duration::from_secs(24 * 60 * 60));
+        self.cache.lock().unwrap().insert(key, response);
+    }
+}
+
+#[route(POST "/orders/:id" { idempotent: { ttl: 24h } })]
+async fn create_order(
+    id: u32,
+    key: axum_typed_routing::IdempotencyKey,
+    State(responses): State<Responses>,
+) -> Result<String, axum::http::StatusCode> {
+    let call = responses
+        .calls
+        .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    match id {
+        0 => Err(axum::http::StatusCode::SERVICE_UNAVAILABLE),
+        _ => Ok(format!("order {id} with {} call {call}", key.0)),
+    }
+}
+
+#[route(POST "/payments" with Responses { idempotent: true })]
+async fn create_payment() -> &'static str {
+    "paid"
+}
+
+#[tokio::test]
+async fn test_idempotent_routes() {
+    let responses = Responses::default();
+    let router = axum::Router::new()
+        .typed_route(create_order)
+        .typed_route(create_payment)
+        .with_state(responses.clone());
+    let server = TestServer::new(router).unwrap();
+
+    let response = server
+        .post("/orders/1")
+        .add_header("idempotency-key", "abc")
+        .await;
+    response.assert_text("order 1 with abc call 0");
+    assert!(response.maybe_header("idempotent-replayed").is_none());
+    // A retry is replayed, and a new key is handled again.
+    let response = server
+        .post("/orders/1")
+        .add_header("idempotency-key", "abc")
+        .await;
+    response.assert_text("order 1 with abc call 0");
+    response.assert_header("idempotent-replayed", "true");
+    server
+        .post("/orders/1")
+        .add_header("idempotency-key", "def")
+        .await
+        .assert_text("order 1 with def call 1");
+
+    // Server errors aren't stored.
+    for _ in 0..2 {
+        server
+            .post("/orders/0")
+            .add_header("idempotency-key", "ghi")
+            .await
+            .assert_status_service_unavailable();
+    }
+    assert_eq!(responses.calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+    assert!(responses
+        .cache
+        .lock()
+        .unwrap()
+        .contains_key("main::create_order#abc"));
+
+    server.post("/orders/1").await.assert_status_bad_request();
+    server
+        .post("/payments")
+        .add_header("idempotency-key", "with space")
+        .await
+        .assert_status_bad_request();
+    server
+        .post("/payments")
+        .add_header("idempotency-key", "abc")
+        .await
+        .assert_text("paid");
+}
+
+#[route(GET "/accounts/:account_id/files/*path")]
+async fn get_account_file(account_id: u32, path: String) -> String {
+    format!("{path} of {account_id}")
+}
+
+#[route(GET "/users/:account_id/:version/*path" { moved_to: get_account_file })]
+async fn get_user_file(account_id: u32, version: u32, path: String) -> String {
+    unreachable!("{account_id} {version} {path}")
+}
+
+#[route(GET "/users/:id" { moved_to: get_account_file })]
+async fn get_user_without_path(id: u32) {}
+
+#[tokio::test]
+async fn test_moved_routes() {
+    let router = axum::Router::new()
+        .typed_route(get_account_file)
+        .typed_route(get_user_file);
+    let server = TestServer::new(axum::Router::new().nest("/api", router)).unwrap();
+
+    let response = server.get("/api/users/7/2/docs/a%20b.txt?download=1").await;
+    response.assert_status(axum::http::StatusCode::PERMANENT_REDIRECT);
+    response.assert_header(
+        "location",
+        "/api/accounts/7/files/docs/a%20b.txt?download=1",
+    );
+    server
+        .get("/api/accounts/7/files/docs/a%20b.txt")
+        .await
+        .assert_text("docs/a b.txt of 7");
+
+    let moved =
+        std::panic::catch_unwind(|| axum::Router::<()>::new().typed_route(get_user_without_path));
+    assert!(moved.is_err());
+}
+
+#[cfg(feature = "trace")]
+mod trace {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+
+    /// Records the fields of the spans created while it's the default subscriber.
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Vec<String>>>);
+
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Vec::new();
+            span.record(
+                &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                    fields.push(format!("{field}={value:?}"))
+                },
+            );
+            self.0.lock().unwrap().push(fields.join(" "));
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &tracing::Event<'_>) {}
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn spans_are_named_after_typed_routes() {
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let builder = TypedRouterBuilder::new(axum::Router::<()>::new()).typed_route(three);
+        let trace_layer = builder.trace_layer();
+        let router = builder.build().unwrap().layer(trace_layer);
+        let server = TestServer::new(router).unwrap();
+        server.get("/three/1").await.assert_status_ok();
+
+        let spans = recorder.0.lock().unwrap().clone();
+        assert_eq!(spans.len(), 1);
+        assert!(spans[0].starts_with(r#"method="GET" route="/three/{id}" handler="#));
+        assert!(spans[0].contains(r#"::three" otel.name="GET /three/{id}""#));
+    }
+}
+
+#[tokio::test]
+async fn test_closure_routes() {
+    // E.g. a path read from the configuration at startup.
+    let prefix: &'static str = String::from("/status").leak();
+    let status = move || (prefix, get(|| async { "ok" }));
+    let pointer: TypedHandler = three;
+    let builder = TypedRouterBuilder::new(axum::Router::<()>::new())
+        .typed_route(status)
+        .typed_route(pointer);
+    let route_map = builder.route_map();
+    assert_eq!(route_map.get(status).unwrap().path, "/status");
+    assert_eq!(route_map.get(pointer).unwrap().path, "/three/{id}");
+
+    let server = TestServer::new(builder.build().unwrap()).unwrap();
+    server.get("/status").await.assert_text("ok");
+    server.get("/three/1").await.assert_status_ok();
+}
+
+#[test]
+fn test_descriptions_are_returned_by_handlers() {
+    // The description is part of the value, so a closure returning it keeps the route described,
+    // and a closure calling a typed handler without returning its description doesn't.
+    let wrapped = || {
+        let (path, method_router, description) = three();
+        (
+            path,
+            method_router.layer(axum::extract::DefaultBodyLimit::max(16)),
+            description,
+        )
+    };
+    let forwarded = || {
+        let (path, method_router, _description) = three_post();
+        (path, method_router)
+    };
+    let builder = TypedRouterBuilder::new(axum::Router::<()>::new())
+        .typed_route(wrapped)
+        .typed_route(forwarded);
+    assert_eq!(builder.routes()[0].method, "GET");
+    assert!(builder.routes()[0].handler.ends_with("::three"));
+    assert_eq!(builder.routes()[1].method, "*");
+    assert_eq!(builder.routes()[1].handler, "<unknown>");
+}
+
+struct RequireToken;
+
+impl<S: Send + Sync> axum::extract::FromRequestParts<S> for RequireToken {
+    type Rejection = axum::http::StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        match parts.headers.get("x-token") {
+            Some(token) if token == "secret" => Ok(RequireToken),
+            _ => Err(axum::http::StatusCode::UNAUTHORIZED),
+        }
+    }
+}
+
+#[route(GET "/guarded/:id" { guards: [RequireToken] })]
+async fn guarded(id: u32) -> String {
+    format!("guarded {id}")
+}
+
+#[tokio::test]
+async fn test_guards() {
+    let router: axum::Router = axum::Router::new().typed_route(guarded);
+    let server = TestServer::new(router).unwrap();
+    server
+        .get("/guarded/1")
+        .await
+        .assert_status(axum::http::StatusCode::UNAUTHORIZED);
+    server
+        .get("/guarded/1")
+        .add_header("x-token", "secret")
+        .await
+        .assert_text("guarded 1");
+}
+
+// The arguments passed through to the handler keep their names in the generated code, unless
+// they'd clash with the handler or a parameter.
+#[route(GET "/passthrough/:id")]
+async fn passthrough(
+    id: u32,
+    State(passthrough): State<String>,
+    _: axum::http::HeaderMap,
+    r#type: axum::http::Method,
+    mut uri: axum::http::Uri,
+) -> String {
+    uri = uri.path().parse().unwrap();
+    format!("{passthrough} {id} {type} {uri}")
+}
+
+#[tokio::test]
+async fn test_passthrough_arguments() {
+    let router = axum::Router::new()
+        .typed_route(passthrough)
+        .with_state("state".to_string());
+    let server = TestServer::new(router).unwrap();
+    server
+        .get("/passthrough/1?x=y")
+        .await
+        .assert_text("state 1 GET /passthrough/1");
+}
+
+mod route_visibility {
+    use super::*;
+
+    mod handlers {
+        use super::*;
+
+        #[route(GET "/modular/:id" { in_module: true, vis: pub(crate) })]
+        pub async fn modular(id: u32) -> String {
+            format!("modular {id}")
+        }
+
+        // The handler stays private, but its route can be registered by the parent module.
+        #[route(GET "/restricted" { keep_fn: true, vis: pub(super), module: true })]
+        async fn restricted() -> &'static str {
+            "restricted"
+        }
+    }
+
+    #[tokio::test]
+    async fn routes_have_their_own_visibility() {
+        assert_eq!(handlers::modular(1).await, "modular 1");
+        assert_eq!(
+            handlers::modular::NAME,
+            "main::route_visibility::handlers::modular"
+        );
+        assert_eq!(
+            handlers::restricted::NAME,
+            "main::route_visibility::handlers::restricted"
+        );
+
+        let router: axum::Router = axum::Router::new()
+            .typed_route(handlers::modular::route)
+            .typed_route(handlers::restricted_route);
+        let server = TestServer::new(router).unwrap();
+        server.get("/modular/2").await.assert_text("modular 2");
+        server.get("/restricted").await.assert_text("restricted");
+    }
+}
+
+mod hooks {
+    use super::*;
+    use std::sync::Mutex;
+
+    static CALLS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    async fn log_entry(id: &u32, label: &Option<String>) {
+        CALLS.lock().unwrap().push(format!("before {id} {label:?}"));
+    }
+
+    async fn audit(id: &u32) {
+        CALLS.lock().unwrap().push(format!("after {id}"));
+    }
+
+    #[route(GET "/hooked/:id?label" { before: [log_entry], after: [audit(id)] })]
+    async fn hooked(id: u32, label: Option<String>) -> String {
+        CALLS.lock().unwrap().push(format!("handler {id}"));
+        format!("{id} {}", label.unwrap_or_default())
+    }
+
+    #[route(GET "/erased-hooked/:id" { erased: true, before: [audit(id)], after: [audit] })]
+    async fn erased_hooked(id: u32) -> String {
+        id.to_string()
+    }
+
+    #[tokio::test]
+    async fn hooks_run_around_the_handler() {
+        let router: axum::Router = axum::Router::new()
+            .typed_route(hooked)
+            .typed_route(erased_hooked);
+        let server = TestServer::new(router).unwrap();
+
+        server.get("/hooked/7?label=x").await.assert_text("7 x");
+        assert_eq!(
+            std::mem::take(&mut *CALLS.lock().unwrap()),
+            ["before 7 Some(\"x\")", "handler 7", "after 7"]
+        );
+
+        // Hooks don't run for requests rejected by the extractors.
+        server
+            .get("/hooked/seven")
+            .await
+            .assert_status_bad_request();
+        assert!(CALLS.lock().unwrap().is_empty());
+
+        server.get("/erased-hooked/3").await.assert_text("3");
+        assert_eq!(
+            std::mem::take(&mut *CALLS.lock().unwrap()),
+            ["after 3", "after 3"]
+        );
+    }
+}
+
+// Generates the test `no_route_conflicts`.
+axum_typed_routing::assert_no_conflicts!(logo, account, events, erased_shape::<Circle>);
+
+#[test]
+#[should_panic(expected = "GET /events (main::events) conflicts with GET /events")]
+fn conflicting_handlers_are_reported() {
+    #[route(GET "/events")]
+    async fn other_events() {}
+
+    use axum_typed_routing::__private::{assert_no_conflicts, describe_handler};
+    assert_no_conflicts(vec![
+        describe_handler(other_events),
+        describe_handler(logo),
+        describe_handler(events),
+    ]);
+}
+
+trait Shape: Send + Sync + 'static {
+    const NAME: &'static str;
+}
+
+struct Circle;
+impl Shape for Circle {
+    const NAME: &'static str = "circle";
+}
+
+struct Square;
+impl Shape for Square {
+    const NAME: &'static str = "square";
+}
+
+#[route(GET "/shapes/:id" { erased: true, status: 201, deprecated: true, cache: { max_age: 60 } })]
+async fn erased_shape<T: Shape>(id: u32, State(prefix): State<String>) -> String {
+    format!("{prefix} {} {id}", T::NAME)
+}
+
+#[tokio::test]
+async fn test_erased_route() {
+    let router = axum::Router::new()
+        .nest(
+            "/circle",
+            axum::Router::new().typed_route(erased_shape::<Circle>),
+        )
+        .nest(
+            "/square",
+            axum::Router::new().typed_route(erased_shape::<Square>),
+        )
+        .with_state("shape".to_string());
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/circle/shapes/1").await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    response.assert_text("shape circle 1");
+    response.assert_header("deprecation", "true");
+    response.assert_header("cache-control", "max-age=60");
+    server
+        .get("/square/shapes/2")
+        .await
+        .assert_text("shape square 2");
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Shelf {
+    Top,
+    Bottom,
+}
+
+impl std::fmt::Display for Shelf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shelf::Top => f.write_str("top"),
+            Shelf::Bottom => f.write_str("bottom"),
+        }
+    }
+}
+
+impl axum_typed_routing::SampleParam for Shelf {
+    fn sample() -> String {
+        "top".to_string()
+    }
+}
+
+// Generates the test `sampled_handler::extracts_params`.
+#[route(GET "/sampled/:id/:shelf/*rest?amount&flag" { generate_test: true })]
+async fn sampled_handler(
+    id: u32,
+    shelf: Shelf,
+    rest: String,
+    amount: Option<f64>,
+    flag: bool,
+    State(state): State<String>,
+) -> String {
+    unreachable!("the generated test doesn't call the handler")
+}
+
+#[tokio::test]
+#[should_panic(expected = "weren't extracted: 400 Bad Request")]
+async fn assert_extracts_rejects_mismatches() {
+    let router = axum::Router::new().route(
+        "/sampled/{id}",
+        axum::routing::any(|Path(id): Path<u32>| async {}),
+    );
+    axum_typed_routing::__private::assert_extracts(router, "/sampled/sample").await;
+}
+
+#[tokio::test]
+async fn assert_extracts_waits_for_timers() {
+    let router = axum::Router::new().route(
+        "/sampled/{id}",
+        axum::routing::any(|Path(id): Path<u32>| async {}).layer(axum::middleware::from_fn(
+            |request: axum::extract::Request, next: axum::middleware::Next| async move {
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                next.run(request).await
+            },
+        )),
+    );
+    axum_typed_routing::__private::assert_extracts(router, "/sampled/1").await;
+}
+
+#[cfg(feature = "multipart")]
+mod multipart {
+    use super::*;
+    use axum::body::Bytes;
+    use axum_test::multipart::{MultipartForm, Part};
+    use axum_typed_routing::MultipartFile;
+
+    #[route(POST "/upload/:folder" with String multipart(file: MultipartFile, title: String, size: Option<u32>))]
+    async fn upload(
+        folder: String,
+        file: MultipartFile,
+        title: String,
+        size: Option<u32>,
+    ) -> String {
+        let name = file.file_name.unwrap_or_default();
+        format!("{folder}/{name} {title} {size:?} {}", file.bytes.len())
+    }
+
+    #[route(POST "/avatar" multipart(image: Bytes, caption: String))]
+    async fn avatar(image: Bytes, state: State<String>) -> String {
+        format!("{} {}", image.len(), state.0)
+    }
+
+    fn server() -> TestServer {
+        let router = axum::Router::new()
+            .typed_route(upload)
+            .typed_route(avatar)
+            .with_state("state".to_string());
+        TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fields_are_extracted() {
+        let server = server();
+        let form = MultipartForm::new()
+            .add_text("title", "Notes")
+            .add_text("ignored", "x")
+            .add_part("file", Part::bytes(b"abc".to_vec()).file_name("notes.txt"))
+            .add_text("size", "3");
+        let response = server.post("/upload/docs").multipart(form).await;
+        response.assert_status_ok();
+        response.assert_text("docs/notes.txt Notes Some(3) 3");
+
+        let form = MultipartForm::new()
+            .add_part("image", Part::bytes(vec![0; 4]))
+            .add_text("caption", "Me");
+        let response = server.post("/avatar").multipart(form).await;
+        response.assert_text("4 state");
+    }
+
+    #[tokio::test]
+    async fn invalid_fields_are_rejected() {
+        let server = server();
+        let form = MultipartForm::new().add_part("file", Part::bytes(b"abc".to_vec()));
+        let response = server.post("/upload/docs").multipart(form).await;
+        response.assert_status_bad_request();
+        response.assert_text("Missing multipart field `title`");
+
+        let form = MultipartForm::new()
+            .add_text("title", "a")
+            .add_text("title", "b")
+            .add_part("file", Part::bytes(b"abc".to_vec()));
+        let response = server.post("/upload/docs").multipart(form).await;
+        response.assert_status_bad_request();
+        response.assert_text("Duplicate multipart field `title`");
+
+        let form = MultipartForm::new()
+            .add_text("title", "a")
+            .add_text("size", "big")
+            .add_part("file", Part::bytes(b"abc".to_vec()));
+        let response = server.post("/upload/docs").multipart(form).await;
+        response.assert_status_bad_request();
+        assert!(response
+            .text()
+            .starts_with("Invalid multipart field `size`"));
+    }
+}
+
+#[cfg(feature = "deadline")]
+mod deadline {
+    use super::*;
+    use axum_typed_routing::Deadline;
+    use std::time::Duration;
+
+    #[route(GET "/slow/:millis" { deadline: { header: "grpc-timeout", default: 50ms, max: 1s } })]
+    async fn slow(millis: u64, deadline: Deadline) -> String {
+        let remaining = deadline.remaining().unwrap();
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+        format!("{}", remaining <= Duration::from_secs(1))
+    }
+
+    #[route(GET "/erased-slow/:millis" { deadline: { header: "x-request-deadline" }, erased: true })]
+    async fn erased_slow(millis: u64, deadline: Deadline) -> String {
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+        format!("{:?}", deadline.instant().is_some())
+    }
+
+    fn server() -> TestServer {
+        let router: axum::Router = axum::Router::new()
+            .typed_route(slow)
+            .typed_route(erased_slow);
+        TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_time_out() {
+        let server = server();
+        server.get("/slow/0").await.assert_text("true");
+
+        let response = server.get("/slow/200").await;
+        response.assert_status(axum::http::StatusCode::GATEWAY_TIMEOUT);
+
+        let response = server
+            .get("/slow/100")
+            .add_header("grpc-timeout", "500m")
+            .await;
+        response.assert_text("true");
+
+        // The header can't extend the deadline beyond the `max`.
+        let response = server.get("/slow/0").add_header("grpc-timeout", "1H").await;
+        response.assert_text("true");
+
+        let response = server
+            .get("/slow/0")
+            .add_header("grpc-timeout", "soon")
+            .await;
+        response.assert_status_bad_request();
+        response.assert_text("Invalid `grpc-timeout` header");
+    }
+
+    #[tokio::test]
+    async fn erased_requests_time_out() {
+        let server = server();
+        server.get("/erased-slow/0").await.assert_text("false");
+
+        let response = server
+            .get("/erased-slow/200")
+            .add_header("x-request-deadline", "20")
+            .await;
+        response.assert_status(axum::http::StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[cfg(feature = "aide")]
+    #[axum_typed_routing::api_route(GET "/api-slow" { deadline: { header: "grpc-timeout" } })]
+    async fn api_slow(deadline: Deadline) -> String {
+        format!("{:?}", deadline.remaining())
+    }
+
+    #[cfg(feature = "aide")]
+    #[test]
+    fn deadline_is_documented() {
+        use aide::axum::ApiRouter;
+        use axum_typed_routing::TypedApiRouter;
+
+        let mut api = aide::openapi::OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_slow)
+            .finish_api(&mut api);
+        let paths = api.paths.as_ref().unwrap();
+        let op = paths.paths["/api-slow"]
+            .as_item()
+            .unwrap()
+            .get
+            .as_ref()
+            .unwrap();
+        let header = op.parameters[0].as_item().unwrap();
+        assert!(matches!(header, aide::openapi::Parameter::Header { .. }));
+        assert_eq!(header.parameter_data_ref().name, "grpc-timeout");
+        assert!(!header.parameter_data_ref().required);
+        let responses = &op.responses.as_ref().unwrap().responses;
+        assert!(responses.contains_key(&aide::openapi::StatusCode::Code(504)));
+    }
+}
+
+#[cfg(feature = "regex")]
+mod path_patterns {
+    use super::*;
+
+    #[route(GET "/posts/:slug(regex = \"[a-z0-9-]+\")/:page:u32")]
+    async fn get_post(slug: String, page: u32) -> String {
+        format!("{slug} {page}")
+    }
+
+    // The pattern may contain the separators of the path and query.
+    #[route(GET r#"/versions/:version(regex = "v[0-9]+([.][0-9]+)?/?")?exact:bool"# { erased: true })]
+    async fn get_version(version: String, exact: bool) -> String {
+        format!("{version} {exact}")
+    }
+
+    fn server() -> TestServer {
+        let router: axum::Router = axum::Router::new()
+            .typed_route(get_post)
+            .typed_route(get_version);
+        TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn values_are_matched() {
+        let server = server();
+        server
+            .get("/posts/hello-world-2/3")
+            .await
+            .assert_text("hello-world-2 3");
+
+        let response = server.get("/posts/Hello_World/3").await;
+        response.assert_status_bad_request();
+        response.assert_text("Invalid path parameter `slug`: expected a match of `[a-z0-9-]+`");
+
+        // The whole value must match, after percent-decoding.
+        server
+            .get("/posts/hello%20world/3")
+            .await
+            .assert_status_bad_request();
+
+        server
+            .get("/versions/v1.2?exact=true")
+            .await
+            .assert_text("v1.2 true");
+        server
+            .get("/versions/v1.2.3?exact=true")
+            .await
+            .assert_status_bad_request();
+    }
+
+    #[test]
+    fn paths_leave_out_patterns() {
+        assert_eq!(get_post().0, "/posts/{slug}/{page}");
+    }
+}
+
+#[cfg(feature = "dynamic")]
+mod dynamic {
+    use super::*;
+    use axum_typed_routing::{DynamicTypedRouter, Error};
+
+    #[route(GET "/dynamic/items/:id" { alias: "/dynamic/item/:id" })]
+    async fn get_item(id: u32) -> String {
+        format!("item {id}")
+    }
+
+    #[route(GET "/dynamic/items/:id")]
+    async fn get_item_v2(id: u32) -> String {
+        format!("item {id} v2")
+    }
+
+    #[route(POST "/dynamic/items/:id")]
+    async fn update_item(id: u32) -> String {
+        format!("updated {id}")
+    }
+
+    #[tokio::test]
+    async fn routes_can_be_disabled_and_repointed() {
+        let router = DynamicTypedRouter::new()
+            .typed_route(get_item)
+            .typed_route(update_item);
+        let routes = router.routes();
+        let server = TestServer::new(router.into_router()).unwrap();
+        let route = routes.route_map().get(get_item).unwrap();
+        assert_eq!(routes.routes().len(), 2);
+
+        server.get("/dynamic/items/1").await.assert_text("item 1");
+        assert!(routes.disable(&route));
+        assert!(!routes.is_enabled(&route));
+        server
+            .get("/dynamic/items/1")
+            .await
+            .assert_status_service_unavailable();
+        // Aliases follow their route, and the other methods of the path are still served.
+        server
+            .get("/dynamic/item/1")
+            .await
+            .assert_status_service_unavailable();
+        server
+            .post("/dynamic/items/1")
+            .await
+            .assert_text("updated 1");
+
+        routes.repoint(&route, get_item_v2).unwrap();
+        assert!(routes.enable(&route));
+        server
+            .get("/dynamic/items/1")
+            .await
+            .assert_text("item 1 v2");
+        server.get("/dynamic/item/1").await.assert_text("item 1 v2");
+
+        assert!(routes.reset(&route));
+        server.get("/dynamic/items/1").await.assert_text("item 1");
+    }
+
+    #[test]
+    fn repointed_routes_must_match() {
+        let router = DynamicTypedRouter::<()>::new().typed_route(get_item);
+        let routes = router.routes();
+        let route = routes.route_map().get(get_item).unwrap();
+
+        let err = routes.repoint(&route, update_item).unwrap_err();
+        assert!(
+            matches!(err, Error::MismatchedRoute { replacement, .. } if replacement.method == "POST")
+        );
+
+        let alias = routes.route_map().routes(get_item)[1];
+        assert_eq!(alias.path, "/dynamic/item/{id}");
+        assert_eq!(
+            routes.repoint(&alias, get_item_v2),
+            Err(Error::UnknownRoute(alias))
+        );
+        assert!(!routes.disable(&alias));
+    }
+}
+
+#[cfg(feature = "pagination")]
+mod paginated_routes {
+    use super::*;
+    use axum_typed_routing::{Paginated, Pagination};
+
+    #[route(GET "/paginated/:shelf?sort:Option<String>" { paginated: true })]
+    async fn list_books(shelf: u32, pagination: Pagination) -> Paginated<u32> {
+        let total = 45;
+        let items = (pagination.offset()..total.min(pagination.offset() + pagination.limit()))
+            .map(|i| shelf * 100 + i as u32)
+            .collect();
+        Paginated::new(items, total, &pagination)
+    }
+
+    fn server() -> TestServer {
+        let router: axum::Router = axum::Router::new().typed_route(list_books);
+        TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn pages_link_to_each_other() {
+        let server = server();
+        let page = server
+            .get("/paginated/1?sort=title&page=2&per_page=20")
+            .await
+            .json::<Paginated<u32>>();
+        assert_eq!(page.items.len(), 20);
+        assert_eq!(page.items[0], 120);
+        assert_eq!((page.total, page.page, page.per_page), (45, 2, 20));
+        assert_eq!(
+            page.next.as_deref(),
+            Some("/paginated/1?sort=title&page=3&per_page=20")
+        );
+        assert_eq!(
+            page.prev.as_deref(),
+            Some("/paginated/1?sort=title&page=1&per_page=20")
+        );
+
+        let last = server.get(page.next.as_deref().unwrap()).await;
+        let last = last.json::<Paginated<u32>>();
+        assert_eq!(last.items.len(), 5);
+        assert!(last.next.is_none());
+    }
+
+    #[tokio::test]
+    async fn defaults_are_applied() {
+        let server = server();
+        let page = server.get("/paginated/1").await.json::<Paginated<u32>>();
+        assert_eq!(
+            (page.page, page.per_page),
+            (1, Pagination::DEFAULT_PER_PAGE)
+        );
+        assert!(page.prev.is_none());
+        assert_eq!(
+            page.next.as_deref(),
+            Some("/paginated/1?page=2&per_page=20")
+        );
+
+        let page = server
+            .get("/paginated/1?per_page=1000")
+            .await
+            .json::<Paginated<u32>>();
+        assert_eq!(page.per_page, Pagination::MAX_PER_PAGE);
+
+        server
+            .get("/paginated/1?page=x")
+            .await
+            .assert_status_bad_request();
+    }
+}
+
+#[cfg(feature = "async-graphql")]
+mod graphql {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+    use axum_typed_routing::graphql_route;
+    use serde_json::json;
+
+    pub struct Query;
+
+    #[Object]
+    impl Query {
+        async fn add(&self, a: i32, b: i32) -> i32 {
+            a + b
+        }
+    }
+
+    #[graphql_route("/graphql")]
+    type AddSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+    fn server() -> TestServer {
+        let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+        let router: axum::Router = axum::Router::new()
+            .typed_route(add_schema)
+            .with_state(schema);
+        TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_are_executed() {
+        let server = server();
+        server
+            .post("/graphql")
+            .json(&json!({
+                "query": "query Add($a: Int!) { add(a: $a, b: 2) }",
+                "variables": { "a": 1 },
+            }))
+            .await
+            .assert_json(&json!({ "data": { "add": 3 } }));
+
+        // Errors of the operation are part of the response.
+        let response = server
+            .post("/graphql")
+            .json(&json!({ "query": "{ missing }" }))
+            .await;
+        response.assert_status_ok();
+        let body = response.json::<serde_json::Value>();
+        assert!(body["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("missing"));
+    }
+
+    #[tokio::test]
+    async fn get_serves_graphiql() {
+        let response = server().get("/graphql").await;
+        response.assert_status_ok();
+        response.assert_text_contains("GraphiQL.createFetcher");
+    }
+}
+
+#[cfg(feature = "juniper")]
+mod juniper_graphql {
+    use super::*;
+    use axum::extract::FromRef;
+    use axum_typed_routing::graphql_route;
+    use juniper::{graphql_object, EmptyMutation, EmptySubscription};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    pub struct Context {
+        offset: i32,
+    }
+
+    impl juniper::Context for Context {}
+
+    pub struct Query;
+
+    #[graphql_object(context = Context)]
+    impl Query {
+        fn add(context: &Context, a: i32, b: i32) -> i32 {
+            a + b + context.offset
+        }
+    }
+
+    type RootNode =
+        juniper::RootNode<'static, Query, EmptyMutation<Context>, EmptySubscription<Context>>;
+
+    #[derive(Clone)]
+    struct AppState {
+        schema: Arc<RootNode>,
+        offset: i32,
+    }
+
+    impl FromRef<AppState> for Arc<RootNode> {
+        fn from_ref(state: &AppState) -> Self {
+            state.schema.clone()
+        }
+    }
+
+    impl FromRef<AppState> for Context {
+        fn from_ref(state: &AppState) -> Self {
+            Context {
+                offset: state.offset,
+            }
+        }
+    }
+
+    #[graphql_route("/graphql" with AppState)]
+    type OffsetSchema = Arc<RootNode>;
+
+    #[tokio::test]
+    async fn context_is_taken_from_state() {
+        let schema = RootNode::new(Query, EmptyMutation::new(), EmptySubscription::new());
+        let state = AppState {
+            schema: Arc::new(schema),
+            offset: 10,
+        };
+        let router: axum::Router = axum::Router::new()
+            .typed_route(offset_schema)
+            .with_state(state);
+        let server = TestServer::new(router).unwrap();
+        server
+            .post("/graphql")
+            .json(&json!({ "query": "{ add(a: 1, b: 2) }" }))
+            .await
+            .assert_json(&json!({ "data": { "add": 13 } }));
+    }
+}
+
+#[cfg(feature = "matched-route")]
+#[tokio::test]
+async fn test_matched_typed_route() {
+    use axum_typed_routing::MatchedTypedRoute;
+
+    #[route(GET "/audited/:id" { alias: "/audit/:id", permissions: ["audit:read"] })]
+    async fn audited(id: u32, route: MatchedTypedRoute) -> String {
+        let permissions = route.permissions.join(",");
+        format!(
+            "{} {} {} {permissions}",
+            route.method, route.template, route.name
+        )
+    }
+
+    let router: axum::Router =
+        axum::Router::new()
+            .typed_route(audited)
+            .layer(axum::middleware::map_response(
+                |mut response: axum::response::Response| async {
+                    let route = response.extensions().get::<MatchedTypedRoute>().copied();
+                    let name = axum::http::HeaderValue::from_static(route.unwrap().name);
+                    response.headers_mut().insert("x-handler", name);
+                    response
+                },
+            ));
+    let server = TestServer::new(router).unwrap();
+    let response = server.get("/audit/1").await;
+    response.assert_text("GET /audited/{id} main::audited audit:read");
+    assert_eq!(response.header("x-handler"), "main::audited");
+}
+
+#[route(GET "/restricted/:id" { alias: "/protected/:id", permissions: ["items:read", "items:list"], module: true })]
+async fn restricted(id: u32) -> String {
+    format!("Item {id}")
+}
+
+#[tokio::test]
+async fn test_route_permissions() {
+    use axum::{extract::Request, middleware::Next, response::IntoResponse};
+    use axum_typed_routing::RoutePermissions;
+
+    async fn authorize(
+        State(permissions): State<RoutePermissions>,
+        request: Request,
+        next: Next,
+    ) -> axum::response::Response {
+        let granted = request
+            .headers()
+            .get("x-permissions")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .split(',')
+            .collect::<Vec<_>>();
+        let required = permissions.required(&request);
+        if !required
+            .iter()
+            .all(|permission| granted.contains(permission))
+        {
+            return axum::http::StatusCode::FORBIDDEN.into_response();
+        }
+        next.run(request).await
+    }
+
+    let builder = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(restricted)
+        .typed_route(three);
+    let routes = builder.routes();
+    assert_eq!(routes[0].permissions, ["items:read", "items:list"]);
+    assert_eq!(routes[1].permissions, ["items:read", "items:list"]);
+    assert!(routes[2].permissions.is_empty());
+    assert_eq!(restricted::ROUTE.permissions, ["items:read", "items:list"]);
+
+    let permissions = builder.route_permissions();
+    assert_eq!(
+        permissions.get("HEAD", "/protected/{id}"),
+        ["items:read", "items:list"]
+    );
+    let router: axum::Router = builder
+        .build()
+        .unwrap()
+        .layer(axum::middleware::from_fn_with_state(permissions, authorize));
+    let server = TestServer::new(router).unwrap();
+    server.get("/restricted/1").await.assert_status_forbidden();
+    server
+        .get("/protected/1")
+        .add_header("x-permissions", "items:read")
+        .await
+        .assert_status_forbidden();
+    server
+        .get("/protected/1")
+        .add_header("x-permissions", "items:list,items:read")
+        .await
+        .assert_text("Item 1");
+    server.get("/three/1").await.assert_text("Hello 1!");
+}
+
+axum_typed_routing::route_params! {
+    /// Pagination of list endpoints.
+    pub(crate) pagination: "?page:u32&per_page:Option<u32>" {
+        page: { description: "The page, starting at 0", example: 0 },
+    };
+    sorting: "?sort:Option<String>";
+}
+
+#[route(GET "/mixed?q" { include: [pagination, sorting] })]
+async fn mixed(q: String, page: u32, sort: Option<String>) -> String {
+    format!("{q} {page} {sort:?}")
+}
+
+#[tokio::test]
+async fn test_included_params() {
+    let router: axum::Router = axum::Router::new().typed_route(mixed);
+    let server = TestServer::new(router).unwrap();
+    server
+        .get("/mixed?q=a&page=2&per_page=10&sort=name")
+        .await
+        .assert_text("a 2 Some(\"name\")");
+    // The included parameters are extracted, even without an argument for `per_page`.
+    server
+        .get("/mixed?q=a&page=2&per_page=x")
+        .await
+        .assert_status_bad_request();
+    server.get("/mixed?q=a").await.assert_status_bad_request();
+}
+
+#[route(GET "/paged?page:u32&per_page:Option<u32>")]
+async fn typed_query(page: u32) -> String {
+    format!("Page {page}!")
+}
+
+#[tokio::test]
+async fn test_typed_query() {
+    let router: axum::Router = axum::Router::new().typed_route(typed_query);
+
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/paged").add_query_param("page", 2).await;
+    response.assert_status_ok();
+    response.assert_text("Page 2!");
+
+    let response = server
+        .get("/paged")
+        .add_query_param("page", 2)
+        .add_query_param("per_page", "foo")
+        .await;
+    response.assert_status_bad_request();
+
+    let response = server.get("/paged").await;
+    response.assert_status_bad_request();
+}
+
+#[route(GET "/*")]
+async fn wildcard() {}
+
+#[route(GET "/*capture" { module: true })]
+async fn wildcard_capture(capture: String) -> Json<String> {
+    Json(capture)
+}
+
+#[route(GET "/")]
+async fn root() {}
+
+#[tokio::test]
+async fn test_wildcard() {
+    let router: axum::Router = axum::Router::new().typed_route(wildcard_capture);
+
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/foo/bar").await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<String>(), "foo/bar");
+}
+
+#[route(GET "/browse/*path")]
+async fn browse_segments(path: axum_typed_routing::Segments) -> Json<Vec<String>> {
+    assert_eq!(path.to_string(), path.join("/"));
+    Json(path.into_inner())
+}
+
+#[route(GET "/versions/*versions")]
+async fn version_segments(versions: Vec<u32>) -> Json<Vec<u32>> {
+    Json(versions)
+}
+
+#[tokio::test]
+async fn test_wildcard_segments() {
+    let router: axum::Router = axum::Router::new()
+        .typed_route(browse_segments)
+        .typed_route(version_segments);
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.get("/browse/docs//2024/a%20report.pdf").await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<Vec<String>>(),
+        ["docs", "2024", "a report.pdf"]
+    );
+
+    let response = server.get("/versions/1/2/3").await;
+    assert_eq!(response.json::<Vec<u32>>(), [1, 2, 3]);
+    server
+        .get("/versions/1/latest")
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_router_builder() {
+    fn untyped() -> (&'static str, axum::routing::MethodRouter) {
+        ("/untyped", get(|| async {}))
+    }
+
+    let builder = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(three)
+        .typed_route(two)
+        .typed_route(untyped);
+
+    let routes = builder.routes();
+    assert_eq!(
+        routes,
+        [
+            RouteInfo {
+                method: "GET",
+                path: "/three/{id}",
+                handler: "main::three",
+                permissions: &[],
+            },
+            RouteInfo {
+                method: "POST",
+                path: "/two",
+                handler: "main::two",
+                permissions: &[],
+            },
+            RouteInfo {
+                method: "*",
+                path: "/untyped",
+                handler: "<unknown>",
+                permissions: &[],
+            },
+        ]
+    );
+    assert_eq!(
+        builder.route_table(),
+        "METHOD  PATH         HANDLER\n\
+         GET     /three/{id}  main::three\n\
+         POST    /two         main::two\n\
+         *       /untyped     <unknown>\n"
+    );
+
+    let server = TestServer::new(builder.into_router()).unwrap();
+    let response = server.get("/three/123").await;
+    response.assert_status_ok();
+    response.assert_text("Hello 123!");
+}
+
+#[route(PUT "/three/:id")]
+async fn put_three(id: u32) {}
+
+#[tokio::test]
+async fn test_auto_options() {
+    let router = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(three)
+        .typed_route(put_three)
+        .typed_route(two)
+        .auto_options()
+        .into_router();
+
+    let server = TestServer::new(router).unwrap();
+
+    let response = server.method(axum::http::Method::OPTIONS, "/three/1").await;
+    response.assert_status(axum::http::StatusCode::NO_CONTENT);
+    assert_eq!(response.header("allow"), "GET,PUT,HEAD,OPTIONS");
+
+    let response = server.method(axum::http::Method::OPTIONS, "/two").await;
+    assert_eq!(response.header("allow"), "POST,OPTIONS");
+
+    let response = server.get("/three/1").await;
+    response.assert_status_ok();
+}
+
+#[route(GET "/cors/items" {
+    cors: {
+        origins: ["https://app.example.com"],
+        headers: ["Content-Type", "x-request-id"],
+        max_age: 600,
+        credentials: true,
+    },
+})]
+async fn cors_items() -> &'static str {
+    "items"
+}
+
+#[route(POST "/cors/items" { cors: { origins: ["https://app.example.com"] } })]
+async fn cors_create() {}
+
+#[route(GET "/cors/public" { erased: true, cors: { origins: ["*"] } })]
+async fn cors_public() -> &'static str {
+    "public"
+}
+
+fn preflight(
+    server: &TestServer,
+    path: &str,
+    origin: &str,
+    method: &str,
+) -> axum_test::TestRequest {
+    server
+        .method(axum::http::Method::OPTIONS, path)
+        .add_header("origin", origin)
+        .add_header("access-control-request-method", method)
+}
+
+#[tokio::test]
+async fn test_cors() {
+    let router = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(cors_items)
+        .typed_route(cors_create)
+        .typed_route(two)
+        .auto_options()
+        .into_router();
+    let server = TestServer::new(router).unwrap();
+
+    let response = preflight(&server, "/cors/items", "https://app.example.com", "POST").await;
+    response.assert_status(axum::http::StatusCode::NO_CONTENT);
+    assert_eq!(
+        response.header("access-control-allow-origin"),
+        "https://app.example.com"
+    );
+    assert_eq!(
+        response.header("access-control-allow-methods"),
+        "GET,POST,HEAD"
+    );
+    assert!(response
+        .maybe_header("access-control-allow-headers")
+        .is_none());
+    assert!(response
+        .maybe_header("access-control-allow-credentials")
+        .is_none());
+
+    let response = preflight(&server, "/cors/items", "https://app.example.com", "GET").await;
+    assert_eq!(
+        response.header("access-control-allow-headers"),
+        "content-type,x-request-id"
+    );
+    assert_eq!(response.header("access-control-max-age"), "600");
+    assert_eq!(response.header("access-control-allow-credentials"), "true");
+
+    let response = preflight(&server, "/cors/items", "https://evil.example.com", "GET").await;
+    response.assert_status(axum::http::StatusCode::NO_CONTENT);
+    assert!(response
+        .maybe_header("access-control-allow-origin")
+        .is_none());
+
+    let response = server
+        .get("/cors/items")
+        .add_header("origin", "https://app.example.com")
+        .await;
+    response.assert_text("items");
+    assert_eq!(
+        response.header("access-control-allow-origin"),
+        "https://app.example.com"
+    );
+    assert_eq!(response.header("vary"), "origin");
+
+    let response = server
+        .get("/cors/items")
+        .add_header("origin", "https://evil.example.com")
+        .await;
+    assert!(response
+        .maybe_header("access-control-allow-origin")
+        .is_none());
+
+    // Paths without `cors` still get the `OPTIONS` route of `auto_options`.
+    let response = server.method(axum::http::Method::OPTIONS, "/two").await;
+    assert_eq!(response.header("allow"), "POST,OPTIONS");
+}
+
+#[tokio::test]
+async fn test_cors_without_builder() {
+    let (path, method_router) = axum_typed_routing::merge_method_routers!(cors_items, cors_create);
+    let router: axum::Router = axum::Router::new()
+        .route(path, method_router)
+        .typed_route(cors_public);
+    let server = TestServer::new(router).unwrap();
+
+    let response = preflight(&server, "/cors/items", "https://app.example.com", "POST").await;
+    assert_eq!(
+        response.header("access-control-allow-methods"),
+        "GET,POST,HEAD"
+    );
+
+    let response = preflight(&server, "/cors/public", "https://any.example.com", "GET").await;
+    assert_eq!(response.header("access-control-allow-origin"), "*");
+    assert_eq!(response.header("access-control-allow-methods"), "GET,HEAD");
+
+    let response = server
+        .get("/cors/public")
+        .add_header("origin", "https://any.example.com")
+        .await;
+    response.assert_text("public");
+    assert_eq!(response.header("access-control-allow-origin"), "*");
+}
+
+#[tokio::test]
+async fn test_cors_routes_of_routers() {
+    let items: axum::Router = axum::Router::new().typed_route(cors_items);
+    let create: axum::Router = axum::Router::new().typed_route(cors_create);
+    let items = TestServer::new(items).unwrap();
+    let create = TestServer::new(create).unwrap();
+
+    // The routers of the same path only answer for their own routes.
+    let response = preflight(&items, "/cors/items", "https://app.example.com", "GET").await;
+    response.assert_status(axum::http::StatusCode::NO_CONTENT);
+    assert_eq!(response.header("access-control-allow-methods"), "GET,HEAD");
+    assert_eq!(response.header("access-control-max-age"), "600");
+    let response = preflight(&items, "/cors/items", "https://app.example.com", "POST").await;
+    assert!(response
+        .maybe_header("access-control-allow-origin")
+        .is_none());
+
+    let response = preflight(&create, "/cors/items", "https://app.example.com", "POST").await;
+    assert_eq!(response.header("access-control-allow-methods"), "POST");
+    assert!(response
+        .maybe_header("access-control-allow-credentials")
+        .is_none());
+    let response = preflight(&create, "/cors/items", "https://app.example.com", "GET").await;
+    assert!(response
+        .maybe_header("access-control-allow-origin")
+        .is_none());
+    create
+        .delete("/cors/items")
+        .await
+        .assert_status(axum::http::StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[route(GET "/about" { sitemap: { priority: 0.8, changefreq: monthly } })]
+async fn about() {}
+
+#[route(GET "/drafts" { sitemap: false })]
+async fn drafts() {}
+
+#[tokio::test]
+async fn test_sitemap() {
+    let builder = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(about)
+        .typed_route(drafts)
+        .typed_route(three)
+        .typed_route(two)
+        .typed_route(events)
+        .sitemap("https://example.com/");
+    assert_eq!(builder.sitemap_entries().len(), 2);
+
+    let server = TestServer::new(builder.into_router()).unwrap();
+    let response = server.get("/sitemap.xml").await;
+    assert_eq!(response.header("content-type"), "application/xml");
+    response.assert_text(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        "  <url>\n",
+        "    <loc>https://example.com/about</loc>\n",
+        "    <changefreq>monthly</changefreq>\n",
+        "    <priority>0.8</priority>\n",
+        "  </url>\n",
+        "  <url>\n",
+        "    <loc>https://example.com/events</loc>\n",
+        "  </url>\n",
+        "</urlset>\n",
+    ));
+}
+
+#[tokio::test]
+async fn test_routes_debug_endpoint() {
+    let builder = TypedRouterBuilder::new(axum::Router::new())
+        .typed_route(three)
+        .typed_route(two);
+    let endpoint = builder.routes_debug_endpoint();
+    let router = builder.into_router().route("/routes", endpoint);
+
+    let server = TestServer::new(router).unwrap();
+    let response = server.get("/routes").await;
+    assert_eq!(response.header("content-type"), "application/json");
+    assert_eq!(
+        response.json::<serde_json::Value>(),
+        serde_json::json!([
+            {
+                "method": "GET",
+                "path": "/three/{id}",
+                "handler": "main::three",
+                "operation_id": null,
+                "tags": [],
+            },
+            {
+                "method": "POST",
+                "path": "/two",
+                "handler": "main::two",
+                "operation_id": null,
+                "tags": [],
+            },
+        ])
+    );
+}
+
+mod route_constants {
+    use super::*;
+    use axum_typed_routing::route_index;
+
+    #[route(PUT "/local/:id" { module: true })]
+    async fn local(id: u32) {}
+
+    route_index! {
+        two,
+        CAPTURE = super::wildcard_capture,
+        self::local,
+    }
+
+    #[test]
+    fn route_index_has_constants() {
+        assert_eq!(
+            routes::TWO,
+            RouteInfo {
+                method: "POST",
+                path: "/two",
+                handler: "main::two",
+                permissions: &[],
+            }
+        );
+        assert_eq!(routes::CAPTURE.path, "/{*capture}");
+        assert_eq!(routes::LOCAL.method, "PUT");
+        assert_eq!(routes::LOCAL.path, "/local/{id}");
+        assert_eq!(routes::LOCAL.handler, "main::route_constants::local");
+        assert_eq!(routes::ALL, [routes::TWO, routes::CAPTURE, routes::LOCAL]);
+    }
+
+    #[test]
+    fn route_index_has_operation_ids() {
+        use routes::OperationId;
+
+        fn is_public(id: OperationId) -> bool {
+            match id {
+                OperationId::Two | OperationId::Capture => true,
+                OperationId::Local => false,
+            }
+        }
+
+        assert_eq!(
+            OperationId::ALL,
+            [OperationId::Two, OperationId::Capture, OperationId::Local]
+        );
+        assert_eq!(OperationId::Local.as_str(), "main::route_constants::local");
+        assert_eq!(
+            OperationId::Local.to_string(),
+            "main::route_constants::local"
+        );
+        assert_eq!(OperationId::Local.method(), "PUT");
+        assert_eq!(OperationId::Local.path(), "/local/{id}");
+        assert_eq!(OperationId::Capture.route(), routes::CAPTURE);
+        assert_eq!(
+            OperationId::find("PUT", "/local/{id}"),
+            Some(OperationId::Local)
+        );
+        assert_eq!(OperationId::find("GET", "/local/{id}"), None);
+        assert!(!is_public(OperationId::Local));
+    }
+}
+
+#[cfg(feature = "templates")]
+mod templates {
+    use super::*;
+
+    #[route(GET "/files/:folder/*path")]
+    async fn file(folder: String, path: String) {}
+
+    #[route(GET "/tree/*path")]
+    async fn tree(path: Vec<String>) {}
+
+    #[test]
+    fn href_formats_path() {
+        assert_eq!(root::href(), "/");
+        assert_eq!(three::href(&123), "/three/123");
+        assert_eq!(four::href(), "/four");
+        assert_eq!(
+            file::href(&"a b".to_string(), &"c/d?".to_string()),
+            "/files/a%20b/c/d%3F"
+        );
+        assert_eq!(
+            file::href(&"a\\b".to_string(), &"c\\d".to_string()),
+            "/files/a%5Cb/c%5Cd"
+        );
+        let path = vec!["a/b".to_string(), "c d".to_string()];
+        assert_eq!(tree::href(&path), "/tree/a%2Fb/c%20d");
+        assert_eq!(based::href(&1), "/api/items/1");
+        assert_eq!(order_line::href(&7, &2), "/orders/7/lines/2");
+        let shelf = ShelfPath {
+            shelf: "top left".to_string(),
+            slot: 3,
+        };
+        assert_eq!(shelf_slot::href(&shelf), "/shelves/top%20left/3");
+    }
+
+    #[route(GET "/search/:category?q:String&page:Option<u32>")]
+    async fn search(category: String, q: String, page: Option<u32>) {}
+
+    #[test]
+    fn link_formats_query() {
+        assert_eq!(
+            search::link()
+                .category("a/b".to_string())
+                .q("x & y=z".to_string())
+                .build(),
+            "/search/a%2Fb?q=x%20%26%20y%3Dz"
+        );
+        assert_eq!(
+            search::link()
+                .page(2)
+                .q("rust".to_string())
+                .category("books".to_string())
+                .build(),
+            "/search/books?q=rust&page=2"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "missing path parameter `category` of the link")]
+    fn link_requires_path_params() {
+        search::link().q("rust".to_string()).build();
+    }
+
+    /// A parameter that can't be formatted into a path.
+    #[derive(serde::Deserialize)]
+    struct Opaque(#[allow(dead_code)] u32);
+
+    #[route(GET "/opaque/:id")]
+    async fn opaque(id: Opaque) -> &'static str {
+        "opaque"
+    }
+
+    #[tokio::test]
+    async fn helpers_only_need_formatting_when_called() {
+        let router: axum::Router = axum::Router::new().typed_route(opaque);
+        let server = TestServer::new(router).unwrap();
+        server.get("/opaque/1").await.assert_text("opaque");
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Tag(String);
+
+    impl axum_typed_routing::PathSegmentEncode for Tag {
+        fn to_path_segment(&self) -> std::borrow::Cow<'_, str> {
+            std::borrow::Cow::Owned(self.0.to_lowercase())
+        }
+    }
+
+    #[cfg(feature = "fuzz")]
+    impl proptest::arbitrary::Arbitrary for Tag {
+        type Parameters = ();
+        type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+        fn arbitrary_with(_args: ()) -> Self::Strategy {
+            proptest::strategy::Strategy::boxed(proptest::strategy::Strategy::prop_map(
+                proptest::arbitrary::any::<String>(),
+                Tag,
+            ))
+        }
+    }
+
+    #[route(GET "/tags/:tag")]
+    async fn tagged(tag: Tag) {}
+
+    #[test]
+    fn href_uses_path_segment_encode() {
+        assert_eq!(tagged::href(&Tag("Rust Lang".into())), "/tags/rust%20lang");
+        assert_eq!(tagged::href(&Tag("../Admin".into())), "/tags/..%2Fadmin");
+    }
+
+    #[test]
+    fn attrs_contain_method() {
+        assert_eq!(one::attrs(), ("/one".to_string(), "POST"));
+        assert_eq!(three::attrs(&1), ("/three/1".to_string(), "GET"));
+    }
+}
+
+#[cfg(feature = "fuzz")]
+mod fuzzing {
+    use super::*;
+    use proptest::{
+        arbitrary::Arbitrary,
+        strategy::{BoxedStrategy, Just, Strategy, ValueTree},
+        test_runner::TestRunner,
+    };
+
+    // The parameters of every route need a strategy with the `fuzz` feature.
+    impl Arbitrary for UserId {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_args: ()) -> Self::Strategy {
+            (1..u64::MAX).prop_map(UserId).boxed()
+        }
+    }
+
+    impl Arbitrary for Sku {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_args: ()) -> Self::Strategy {
+            "[A-Z0-9]{1,8}".prop_map(Sku).boxed()
+        }
+    }
+
+    impl Arbitrary for Shelf {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_args: ()) -> Self::Strategy {
+            proptest::prop_oneof![Just(Shelf::Top), Just(Shelf::Bottom)].boxed()
+        }
+    }
+
+    #[route(GET "/fuzzed/:name?page:Option<u32>")]
+    async fn fuzzed(name: String, page: Option<u32>) -> String {
+        format!("{name} {page:?}")
+    }
+
+    #[route(GET "/fuzzed/versions/*versions")]
+    async fn fuzzed_versions(versions: Vec<u32>) -> String {
+        format!("{versions:?}")
+    }
+
+    /// Requests `samples` paths of the `strategy` from the `router`, which must all succeed.
+    async fn request_samples(
+        router: axum::Router,
+        strategy: impl Strategy<Value = String>,
+        prefix: &str,
+    ) {
+        let server = TestServer::new(router).unwrap();
+        let mut runner = TestRunner::deterministic();
+        for _ in 0..64 {
+            let path = strategy.new_tree(&mut runner).unwrap().current();
+            assert!(path.starts_with(prefix), "{path}");
+            server.get(&path).await.assert_status_ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn param_strategy_generates_matching_paths() {
+        let router = axum::Router::new().typed_route(fuzzed);
+        request_samples(router, fuzzed::param_strategy(), "/fuzzed/").await;
+
+        let router = axum::Router::new().typed_route(fuzzed_versions);
+        let strategy = fuzzed_versions::param_strategy();
+        request_samples(router, strategy, "/fuzzed/versions/").await;
+
+        let router = axum::Router::new().typed_route(user);
+        request_samples(router, user::param_strategy(), "/users/").await;
+    }
+}
+
+#[cfg(feature = "axum-test")]
+mod testing {
+    use super::*;
+    use axum_typed_routing::testing::TypedTestServer;
+
+    #[route(GET "/files/:folder/*path")]
+    async fn file(folder: String, path: String) -> String {
+        format!("{folder} {path}")
+    }
+
+    #[route(POST "/create")]
+    async fn create() -> &'static str {
+        "Created!"
+    }
+
+    fn server() -> TestServer {
+        let router = axum::Router::new()
+            .typed_route(create)
+            .typed_route(three)
+            .typed_route(file)
+            .typed_route(root);
+        TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_use_method_and_path_of_route() {
+        let server = server();
+
+        server.typed_request(root, ()).await.assert_status_ok();
+        server.typed_post(create, ()).await.assert_text("Created!");
+        server.typed_get(three, (7,)).await.assert_text("Hello 7!");
+        server
+            .typed_get(file, ("a b", "c/d?"))
+            .await
+            .assert_text("a b c/d?");
+    }
+
+    #[test]
+    #[should_panic(expected = "is a GET route, not a POST route")]
+    fn wrong_method_panics() {
+        server().typed_post(three, (7,));
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough path parameters")]
+    fn missing_params_panic() {
+        server().typed_get(three, ());
+    }
+}
+
+#[cfg(feature = "ts-client")]
+mod ts_client {
+    use super::*;
+    use axum_typed_routing::ts_client::TsClient;
+
+    #[derive(serde::Deserialize)]
+    struct Item {}
+
+    #[route(PUT "/items/:id?dry_run")]
+    async fn put_item(id: u32, dry_run: Option<bool>, Json(item): Json<Item>) {}
+
+    #[test]
+    fn generates_route_definitions() {
+        let ts = TsClient::new().import_types_from("./bindings").generate();
+
+        assert!(ts.contains("import type { Item } from \"./bindings/Item\";"));
+        assert!(ts.contains(
+            "export const put_item = {
+  method: \"PUT\",
+  path: (params: { id: number }): string => `/items/${encodeURIComponent(String(params.id))}`,
+} as const;
+export type PutItemQuery = { dry_run?: boolean };
+export type PutItemBody = Item;
+"
+        ));
+        assert!(ts.contains(
+            "export const four = {
+  method: \"GET\",
+  path: (): string => `/four`,
+} as const;
+export type FourQuery = { id: number };
+"
+        ));
+        assert!(ts.contains("path: (params: { capture: string }): string => `/${encodeURI(String(params.capture))}`"));
+        assert!(ts.contains("path: (params: { item_id: number }): string => `/renamed/${encodeURIComponent(String(params.item_id))}`"));
+    }
+}
+
+#[cfg(feature = "normalize-path")]
+mod normalize_path {
+    use super::*;
+    use axum::body::Body;
+    use tower_http::normalize_path::NormalizePath;
+    use tower_service::Service;
+
+    #[route(GET "/normalized/:id")]
+    async fn normalized(id: u32) -> String {
+        format!("normalized {id}")
+    }
+
+    #[route(GET "/disabled")]
+    async fn disabled() {}
+
+    #[tokio::test]
+    async fn typed_routes_are_added_to_the_wrapped_router() {
+        let mut app = NormalizePath::trim_trailing_slash(axum::Router::new())
+            .typed_route(normalized)
+            .typed_route_if(false, disabled);
+
+        let request = axum::http::Request::get("/normalized/1/")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "normalized 1");
+
+        let request = axum::http::Request::get("/disabled")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+}
+
+#[cfg(feature = "client")]
+mod client {
+    use super::*;
+    use tower_service::Service;
+
+    #[route(GET "/client/:category?q:String&page:Option<u32>")]
+    async fn client_search(category: String, q: String, page: Option<u32>) -> String {
+        format!("{category} {q} {page:?}")
+    }
+
+    #[test]
+    fn request_formats_method_and_uri() {
+        let request = client_search::request(&"a/b".to_string())
+            .q("x y".to_string())
+            .build();
+        assert_eq!(request.method(), "GET");
+        assert_eq!(request.uri(), "/client/a%2Fb?q=x%20y");
+        assert_eq!(root::request().build().uri(), "/");
+    }
+
+    #[test]
+    #[should_panic(expected = "missing query parameter `q` of the request")]
+    fn request_requires_query_params() {
+        client_search::request(&"books".to_string()).page(2).build();
+    }
+
+    #[tokio::test]
+    async fn request_is_handled_by_the_route() {
+        let mut router: axum::Router = axum::Router::new().typed_route(client_search);
+
+        let request = client_search::request(&"books".to_string())
+            .page(2)
+            .q("rust".to_string())
+            .build();
+        let response = router.call(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "books rust Some(2)");
+
+        let mut request = destructured::request(&1)
+            .suffix("!".to_string())
+            .body(r#"{"name": "x"}"#)
+            .build();
+        assert_eq!(request.method(), "POST");
+        request.headers_mut().insert(
+            axum::http::header::CONTENT_TYPE,
+            axum::http::HeaderValue::from_static("application/json"),
+        );
+        let mut router: axum::Router = axum::Router::new()
+            .typed_route(destructured)
+            .with_state(("item", 0));
+        let response = router.call(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "item2 x!");
+
+        let mut router: axum::Router = axum::Router::new().typed_route(internal_metrics);
+        let request = internal_metrics::request().build();
+        assert_eq!(request.headers()["host"], "internal.example.com");
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+    }
+}
+
+#[cfg(feature = "aide")]
+mod aide_support {
+    use super::*;
+    use aide::{axum::ApiRouter, openapi::OpenApi, transform::TransformOperation};
+    use axum_typed_routing::TypedApiRouter;
+    use axum_typed_routing_macros::api_route;
+
+    /// get-summary
+    ///
+    /// get-description
+    #[api_route(GET "/hello" { module: true })]
+    async fn get_hello(state: State<String>) -> String {
+        String::from("Hello!")
+    }
+
+    /// post-summary
+    ///
+    /// post-description
+    #[api_route(POST "/hello")]
+    async fn post_hello(state: State<String>) -> String {
+        String::from("Hello!")
+    }
+
+    #[test]
+    fn test_aide() {
+        let router: aide::axum::ApiRouter = aide::axum::ApiRouter::new()
+            .typed_route(one)
+            .typed_api_route(get_hello)
+            .with_state("state".to_string());
+
+        let (path, method_router, _) = get_hello();
+        assert_eq!(path, "/hello");
+
+        let (path, method_router, _) = post_hello();
+        assert_eq!(path, "/hello");
+    }
+
+    #[api_route(POST "/status" { status: 201, responses: { 400: String } })]
+    async fn status_route() -> Json<u32> {
+        Json(1)
+    }
+
+    #[test]
+    fn status_is_documented_instead_of_200() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(status_route)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/status").post.as_ref().unwrap();
+        let responses = op.responses.as_ref().unwrap();
+        let codes = responses
+            .responses
+            .keys()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(codes, ["201", "400"]);
+    }
+
+    #[api_route(GET "/described-responses" {
+        responses: {
+            200: Json<u32> |r| r.description("the count").example(3u32),
+            404: String |r| r.description("no count"),
+            500: String,
+        },
+    })]
+    async fn described_responses() -> Json<u32> {
+        Json(1)
+    }
+
+    #[api_route(GET "/merged-responses" {
+        responses: {
+            200: Json<u32> |r| r.description("the count"),
+            404: Json<String>,
+            200: axum::response::Html<String>,
+            404: Json<u32>,
+            404: Json<bool>,
+        },
+        infer_responses: false,
+    })]
+    async fn merged_responses() -> Json<u32> {
+        Json(1)
+    }
+
+    #[test]
+    fn repeated_responses_are_merged() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(merged_responses)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/merged-responses").get.as_ref().unwrap();
+        let responses = &op.responses.as_ref().unwrap().responses;
+        let codes = responses
+            .keys()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(codes, ["200", "404"]);
+
+        let response = |code| match responses.get(&aide::openapi::StatusCode::Code(code)) {
+            Some(aide::openapi::ReferenceOr::Item(response)) => response,
+            _ => panic!("expected a {code} response"),
+        };
+        let ok = response(200);
+        assert_eq!(ok.description, "the count");
+        let media_types = ok.content.keys().collect::<Vec<_>>();
+        assert_eq!(media_types, ["application/json", "text/html"]);
+
+        let not_found = response(404);
+        let schema = &not_found.content["application/json"]
+            .schema
+            .as_ref()
+            .unwrap();
+        let schema = serde_json::to_value(&schema.json_schema).unwrap();
+        assert_eq!(
+            schema,
+            serde_json::json!({
+                "oneOf": [{ "type": "string" }, { "type": "integer", "format": "uint32", "minimum": 0.0 }, { "type": "boolean" }]
+            })
+        );
+    }
+
+    const TEAPOT: axum::http::StatusCode = axum::http::StatusCode::IM_A_TEAPOT;
+
+    #[api_route(GET "/constant-responses" {
+        responses: {
+            axum::http::StatusCode::CREATED: Json<u32>,
+            TEAPOT: String,
+            201: Json<bool>,
+        },
+        infer_responses: false,
+    })]
+    async fn constant_responses() -> Json<u32> {
+        Json(1)
+    }
+
+    #[test]
+    fn response_codes_can_be_constants() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(constant_responses)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/constant-responses").get.as_ref().unwrap();
+        let codes = op
+            .responses
+            .as_ref()
+            .unwrap()
+            .responses
+            .keys()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        // `StatusCode::CREATED` is resolved to `201`, so the repeated code is merged.
+        assert_eq!(codes, ["201", "418"]);
+    }
+
+    #[api_route(POST "/explicit-responses" {
+        responses: { 201: Json<u32> },
+        infer_responses: false,
+    })]
+    async fn explicit_responses() -> Result<Json<u32>, String> {
+        Ok(Json(1))
+    }
+
+    #[api_route(POST "/inferred-responses" { responses: { 201: Json<u32> } })]
+    async fn inferred_responses() -> Result<Json<u32>, String> {
+        Ok(Json(1))
+    }
+
+    #[test]
+    fn inferred_responses_can_be_disabled() {
+        let router = ApiRouter::new()
+            .typed_api_route(explicit_responses)
+            .typed_api_route(inferred_responses);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let responses = |path| {
+            let post_op = path_item(&api, path).post.as_ref().unwrap();
+            post_op.responses.clone().unwrap()
+        };
+        let explicit = responses("/explicit-responses");
+        assert!(explicit.default.is_none());
+        let codes = explicit.responses.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(codes, vec![aide::openapi::StatusCode::Code(201)]);
+        // Without the option, the response of the return type is inferred.
+        let inferred = responses("/inferred-responses");
+        assert!(inferred
+            .responses
+            .contains_key(&aide::openapi::StatusCode::Code(200)));
+    }
+
+    #[test]
+    fn responses_can_be_transformed() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(described_responses)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/described-responses")
+            .get
+            .as_ref()
+            .unwrap();
+        let response = |code| {
+            op.responses.as_ref().unwrap().responses[&aide::openapi::StatusCode::Code(code)]
+                .as_item()
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(response(200).description, "the count");
+        let example = response(200).content["application/json"].example.clone();
+        assert_eq!(example, Some(serde_json::json!(3)));
+        assert_eq!(response(404).description, "no count");
+        assert_eq!(response(500).description, "plain text");
+    }
+
+    // Duplicate ids are a compile error with `unique-operation-ids`.
+    #[cfg(not(feature = "unique-operation-ids"))]
+    #[api_route(GET "/first-count" { id: "count" })]
+    async fn first_count() -> Json<u32> {
+        Json(1)
+    }
+
+    #[cfg(not(feature = "unique-operation-ids"))]
+    #[api_route(GET "/second-count" { id: "count" })]
+    async fn second_count() -> Json<u32> {
+        Json(2)
+    }
+
+    #[test]
+    #[cfg(not(feature = "unique-operation-ids"))]
+    #[should_panic(expected = "operation id `count` of GET /second-count")]
+    fn duplicate_operation_ids_are_reported() {
+        use axum_typed_routing::__private::{assert_no_conflicts, describe_handler};
+        assert_no_conflicts(vec![
+            describe_handler(first_count),
+            describe_handler(second_count),
+        ]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "unique-operation-ids"))]
+    fn builder_reports_duplicate_operation_ids() {
+        use axum_typed_routing::Error;
+
+        let err = TypedRouterBuilder::new(ApiRouter::new())
+            .typed_api_route(first_count)
+            .typed_api_route(second_count)
+            .build()
+            .unwrap_err();
+        let [Error::DuplicateOperationId {
+            operation_id,
+            existing,
+            duplicate,
+        }] = err.errors[..]
+        else {
+            panic!("expected a duplicate operation id: {err}");
+        };
+        assert_eq!(operation_id, "count");
+        assert_eq!(existing.handler, "main::aide_support::first_count");
+        assert_eq!(duplicate.path, "/second-count");
+    }
+
+    #[api_route(GET "/api-head" { head: auto, tags: ["head"] })]
+    async fn api_head() -> Json<u32> {
+        Json(1)
+    }
+
+    #[test]
+    fn auto_head_is_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_head)
+            .finish_api(&mut api);
+
+        let item = path_item(&api, "/api-head");
+        let get_op = item.get.as_ref().unwrap();
+        let head_op = item.head.as_ref().unwrap();
+        assert_eq!(get_op.operation_id.as_deref(), Some("api_head"));
+        assert_eq!(head_op.operation_id.as_deref(), Some("api_head_head"));
+        assert_eq!(head_op.tags, ["head"]);
+
+        let response = |op: &aide::openapi::Operation| {
+            op.responses.as_ref().unwrap().responses[&aide::openapi::StatusCode::Code(200)]
+                .as_item()
+                .unwrap()
+                .content
+                .len()
+        };
+        assert_eq!(response(get_op), 1);
+        assert_eq!(response(head_op), 0);
+    }
+
+    #[api_route(GET "/versioned" { version: 2, tags: ["items"] })]
+    async fn api_versioned() {}
+
+    #[test]
+    fn version_is_tagged() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_versioned)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/v2/versioned").get.as_ref().unwrap();
+        assert_eq!(op.tags, ["items", "v2"]);
+    }
+
+    #[api_route(GET "/api-aliased" { alias: "/old-api-aliased" })]
+    async fn api_aliased() {}
+
+    #[test]
+    fn aliases_are_not_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_aliased)
+            .finish_api(&mut api);
+
+        let paths = api.paths.unwrap();
+        assert!(paths.paths.contains_key("/api-aliased"));
+        assert!(!paths.paths.contains_key("/old-api-aliased"));
+    }
+
+    #[api_route(POST "/ignored/:id")]
+    async fn api_ignored(
+        id: u32,
+        #[api_ignore] Session(user): Session,
+        Json(body): Json<String>,
+    ) -> String {
+        format!("{user} {id} {body}")
+    }
+
+    #[tokio::test]
+    async fn ignored_arguments_are_extracted() {
+        let mut api = OpenApi::default();
+        let router = ApiRouter::new()
+            .typed_api_route(api_ignored)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/ignored/{id}").post.as_ref().unwrap();
+        assert!(op.request_body.is_some());
+
+        let server = TestServer::new(router).unwrap();
+        let response = server.post("/ignored/1").json(&"body").await;
+        response.assert_text("session 1 body");
+    }
+
+    /// An extractor for data inserted by middleware, which doesn't implement `OperationInput`.
+    mod middleware {
+        #[derive(Clone)]
+        pub struct Extension<T>(pub T);
+
+        impl<S: Send + Sync, T: Clone + Send + Sync + 'static> axum::extract::FromRequestParts<S>
+            for Extension<T>
+        {
+            type Rejection = axum::http::StatusCode;
+
+            async fn from_request_parts(
+                parts: &mut axum::http::request::Parts,
+                _state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                let value = parts.extensions.get::<T>().cloned();
+                value
+                    .map(Extension)
+                    .ok_or(axum::http::StatusCode::UNAUTHORIZED)
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct CurrentUser(&'static str);
+
+    #[api_route(GET "/whoami" { infer_extensions: true })]
+    async fn whoami(middleware::Extension(user): middleware::Extension<CurrentUser>) -> String {
+        user.0.to_string()
+    }
+
+    #[tokio::test]
+    async fn extensions_are_inferred() {
+        let mut api = OpenApi::default();
+        let router = ApiRouter::new()
+            .typed_api_route(whoami)
+            .finish_api(&mut api)
+            .layer(axum::Extension(CurrentUser("admin")));
+
+        let op = path_item(&api, "/whoami").get.as_ref().unwrap();
+        assert!(op.parameters.is_empty());
+        assert!(op.request_body.is_none());
+
+        let server = TestServer::new(router).unwrap();
+        server.get("/whoami").await.assert_text("admin");
+    }
+
+    #[api_route(GET "/examples/:id?amount&page&per_page" {
+        params: {
+            id: { example: 42, description: "item id" },
+            amount: { example: 3, deprecated: true },
+            page: { required: true },
+            per_page: { required: false },
+        },
+    })]
+    async fn param_examples(
+        id: u32,
+        amount: Option<u32>,
+        page: Option<u32>,
+        per_page: u32,
+    ) -> String {
+        format!("{id} {amount:?}")
+    }
+
+    #[test]
+    fn param_examples_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(param_examples)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/examples/{id}").get.as_ref().unwrap();
+        let param = |name: &str| {
+            op.parameters
+                .iter()
+                .map(|param| param.as_item().unwrap())
+                .find(|param| param.parameter_data_ref().name == name)
+                .unwrap()
+        };
+
+        let id = param("id");
+        assert!(matches!(id, aide::openapi::Parameter::Path { .. }));
+        assert_eq!(id.parameter_data_ref().example, Some(serde_json::json!(42)));
+        assert_eq!(
+            id.parameter_data_ref().description.as_deref(),
+            Some("item id")
+        );
+
+        let amount = param("amount");
+        assert!(matches!(amount, aide::openapi::Parameter::Query { .. }));
+        assert_eq!(
+            amount.parameter_data_ref().example,
+            Some(serde_json::json!(3))
+        );
+        assert!(amount.parameter_data_ref().description.is_none());
+        assert!(!amount.parameter_data_ref().required);
+        assert_eq!(amount.parameter_data_ref().deprecated, Some(true));
+        assert!(param("page").parameter_data_ref().required);
+        assert!(param("page").parameter_data_ref().deprecated.is_none());
+        assert!(!param("per_page").parameter_data_ref().required);
+    }
+
+    #[api_route(POST "/idempotent-orders" { idempotent: true })]
+    async fn create_idempotent_order(key: axum_typed_routing::IdempotencyKey) -> String {
+        key.0
+    }
+
+    #[test]
+    fn idempotency_keys_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(create_idempotent_order)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/idempotent-orders").post.as_ref().unwrap();
+        // The handler takes the key too, but it's documented once.
+        assert_eq!(op.parameters.len(), 1);
+        let header = op.parameters[0].as_item().unwrap();
+        assert!(matches!(header, aide::openapi::Parameter::Header { .. }));
+        assert_eq!(header.parameter_data_ref().name, "Idempotency-Key");
+        assert!(header.parameter_data_ref().required);
+        let responses = serde_json::to_value(op.responses.as_ref().unwrap()).unwrap();
+        assert!(responses["400"].is_object());
+    }
+
+    #[api_route(GET "/raw-items/:id?page")]
+    async fn raw_item(
+        id: u32,
+        page: Option<u32>,
+        axum::extract::RawQuery(query): axum::extract::RawQuery,
+        params: axum::extract::RawPathParams,
+        uri: axum::extract::OriginalUri,
+    ) -> String {
+        format!(
+            "{id} {page:?} {query:?} {} {}",
+            params.iter().count(),
+            uri.0
+        )
+    }
+
+    #[test]
+    fn raw_extractors_are_not_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(raw_item)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/raw-items/{id}").get.as_ref().unwrap();
+        let names = op
+            .parameters
+            .iter()
+            .map(|param| param.as_item().unwrap().parameter_data_ref().name.as_str())
+            .collect::<Vec<_>>();
+        // aide doesn't document the tuple of the path parameters.
+        assert_eq!(names, ["page"]);
+    }
+
+    #[api_route(GET "/accounts/:id")]
+    async fn get_api_account(id: u32) -> String {
+        id.to_string()
+    }
+
+    #[api_route(GET "/users/:id" { moved_to: get_api_account })]
+    async fn get_api_user(id: u32) -> String {
+        id.to_string()
+    }
+
+    #[test]
+    fn moved_routes_document_the_redirect() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(get_api_account)
+            .typed_api_route(get_api_user)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/users/{id}").get.as_ref().unwrap();
+        let responses = serde_json::to_value(op.responses.as_ref().unwrap()).unwrap();
+        assert_eq!(responses.as_object().unwrap().len(), 1);
+        assert_eq!(
+            responses["308"]["description"],
+            "The route moved to `/accounts/{id}`"
+        );
+        assert!(responses["308"]["headers"]["Location"].is_object());
+    }
+
+    #[api_route(GET "/gateway/:id?page" {
+        parameters: [
+            { name: "x-request-id", in: header, required: true, description: "set by the gateway" },
+            { name: "page", in: query, schema: u64, required: true, example: 2 },
+            { name: "session", in: cookie, deprecated: true },
+            { name: "id", in: path, schema: u32 },
+        ],
+        params: { page: { description: "the page" } },
+    })]
+    async fn gateway_parameters(id: u32, page: Option<u32>) -> String {
+        format!("{id} {page:?}")
+    }
+
+    #[test]
+    fn parameters_are_overridden() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(gateway_parameters)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/gateway/{id}").get.as_ref().unwrap();
+        assert_eq!(op.parameters.len(), 4);
+        let param = |name: &str| {
+            op.parameters
+                .iter()
+                .map(|param| param.as_item().unwrap())
+                .find(|param| param.parameter_data_ref().name == name)
+                .unwrap()
+        };
+
+        let request_id = param("x-request-id");
+        assert!(matches!(
+            request_id,
+            aide::openapi::Parameter::Header { .. }
+        ));
+        assert!(request_id.parameter_data_ref().required);
+        assert_eq!(
+            request_id.parameter_data_ref().description.as_deref(),
+            Some("set by the gateway")
+        );
+
+        let page = param("page");
+        assert!(matches!(page, aide::openapi::Parameter::Query { .. }));
+        assert!(page.parameter_data_ref().required);
+        assert_eq!(
+            page.parameter_data_ref().example,
+            Some(serde_json::json!(2))
+        );
+        assert_eq!(
+            page.parameter_data_ref().description.as_deref(),
+            Some("the page")
+        );
+        let schema = serde_json::to_value(&page.parameter_data_ref().format).unwrap();
+        assert_eq!(schema["schema"]["format"], "uint64");
+
+        let session = param("session");
+        assert!(matches!(session, aide::openapi::Parameter::Cookie { .. }));
+        assert!(!session.parameter_data_ref().required);
+        assert_eq!(session.parameter_data_ref().deprecated, Some(true));
+        let id = param("id");
+        assert!(matches!(id, aide::openapi::Parameter::Path { .. }));
+        assert!(id.parameter_data_ref().required);
+    }
+
+    #[api_route(GET "/mixed-docs" {
+        include: pagination,
+        params: { page: { example: 1 } },
+    })]
+    async fn mixed_docs(page: u32) -> String {
+        page.to_string()
+    }
+
+    #[test]
+    fn included_params_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(mixed_docs)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/mixed-docs").get.as_ref().unwrap();
+        let params = op
+            .parameters
+            .iter()
+            .map(|param| param.as_item().unwrap().parameter_data_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(params.len(), 2);
+        let page = params.iter().find(|param| param.name == "page").unwrap();
+        assert!(page.required);
+        assert_eq!(page.description.as_deref(), Some("The page, starting at 0"));
+        // The `params` of the route take precedence over those of the mixin.
+        assert_eq!(page.example, Some(serde_json::json!(1)));
+        let per_page = params
+            .iter()
+            .find(|param| param.name == "per_page")
+            .unwrap();
+        assert!(!per_page.required);
+    }
+
+    #[api_route(GET "/field-attrs?page&per_page")]
+    async fn field_attrs(
+        #[serde(default)] page: u32,
+        #[schemars(range(min = 1, max = 100))] per_page: u32,
+    ) -> String {
+        format!("{page} {per_page}")
+    }
+
+    #[tokio::test]
+    async fn query_field_attrs_are_forwarded() {
+        let mut api = OpenApi::default();
+        let router = ApiRouter::new()
+            .typed_api_route(field_attrs)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/field-attrs").get.as_ref().unwrap();
+        let per_page = op
+            .parameters
+            .iter()
+            .map(|param| param.as_item().unwrap().parameter_data_ref())
+            .find(|param| param.name == "per_page")
+            .unwrap();
+        let aide::openapi::ParameterSchemaOrContent::Schema(schema) = &per_page.format else {
+            panic!("expected a schema");
+        };
+        assert_eq!(
+            schema.json_schema.clone().into_object().number().maximum,
+            Some(100.0)
+        );
+
+        let server = TestServer::new(router).unwrap();
+        server
+            .get("/field-attrs?per_page=5")
+            .await
+            .assert_text("0 5");
+    }
+
+    const ITEM_SUMMARY: &str = "Get an item";
+
+    #[api_route(GET "/described" {
+        summary: ITEM_SUMMARY,
+        description: concat!("# Items\n", "Returns the item."),
+    })]
+    async fn described() {}
+
+    #[test]
+    fn summary_and_description_can_be_expressions() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(described)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/described").get.as_ref().unwrap();
+        assert_eq!(op.summary.as_deref(), Some("Get an item"));
+        assert_eq!(
+            op.description.as_deref(),
+            Some("# Items\nReturns the item.")
+        );
+    }
+
+    #[api_route(GET "/api-sorted?sort")]
+    async fn api_sorted(#[route_param(flatten)] sort: SortOrder) {}
+
+    #[test]
+    fn flattened_query_is_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_sorted)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-sorted").get.as_ref().unwrap();
+        let names = op
+            .parameters
+            .iter()
+            .map(|param| param.as_item().unwrap().parameter_data_ref().name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["sort", "since"]);
+
+        let sort = op.parameters[0].as_item().unwrap().parameter_data_ref();
+        let aide::openapi::ParameterSchemaOrContent::Schema(schema) = &sort.format else {
+            panic!("expected a schema");
+        };
+        let schema = serde_json::to_value(&schema.json_schema).unwrap();
+        assert_eq!(schema["enum"], serde_json::json!(["name", "date"]));
+    }
+
+    #[api_route(GET "/api-csv/:id?ids")]
+    async fn api_csv(
+        #[route_param(deserialize_with = "from_hex")] id: u32,
+        #[route_param(deserialize_with = "from_csv")] ids: Vec<u32>,
+    ) {
+    }
+
+    #[test]
+    fn deserialize_with_is_documented_as_string() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_csv)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-csv/{id}").get.as_ref().unwrap();
+        let query = op
+            .parameters
+            .iter()
+            .map(|param| param.as_item().unwrap().parameter_data_ref())
+            .find(|param| param.name == "ids")
+            .unwrap();
+        let aide::openapi::ParameterSchemaOrContent::Schema(schema) = &query.format else {
+            panic!("expected a schema");
+        };
+        let schema = serde_json::to_value(&schema.json_schema).unwrap();
+        assert_eq!(schema["type"], "string");
+    }
+
+    #[api_route(GET "/api-users/:id?referrer" {
+        params: { id: { example: 1 } },
+    })]
+    async fn api_user(
+        #[route_param(try_from = u64)] id: UserId,
+        #[route_param(try_from = u64)] referrer: Option<UserId>,
+    ) {
+    }
+
+    #[test]
+    fn try_from_params_are_documented_as_raw_type() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_user)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-users/{id}").get.as_ref().unwrap();
+        for param in &op.parameters {
+            let param = param.as_item().unwrap().parameter_data_ref();
+            let aide::openapi::ParameterSchemaOrContent::Schema(schema) = &param.format else {
+                panic!("expected a schema");
+            };
+            let schema = serde_json::to_value(&schema.json_schema).unwrap();
+            assert!(
+                schema["type"].to_string().contains("integer"),
+                "{}",
+                param.name
+            );
+            assert_eq!(param.required, param.name == "id");
+        }
+    }
+
+    #[api_route(GET "/api-unexploded?ids")]
+    async fn api_unexploded(#[route_param(explode = false)] ids: Vec<u32>) {}
+
+    #[api_route(GET "/api-csv-list?ids(csv):Vec<u32>")]
+    async fn api_csv_list() {}
+
+    #[cfg(feature = "deep-object")]
+    #[api_route(GET "/api-deep?filter")]
+    async fn api_deep(#[route_param(style = deepObject)] filter: Filter) {}
+
+    #[api_route(GET "/api-debugged/:id" { debug_handler: true })]
+    async fn api_debugged(id: u32) -> Json<u32> {
+        Json(id)
+    }
+
+    #[test]
+    fn debug_handler_is_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_debugged)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-debugged/{id}").get.as_ref().unwrap();
+        let responses = &op.responses.as_ref().unwrap().responses;
+        assert!(responses.contains_key(&aide::openapi::StatusCode::Code(200)));
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[derive(serde::Serialize, schemars::JsonSchema)]
+    struct Album {
+        title: String,
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[api_route(GET "/api-albums" { accepts: [json, msgpack] })]
+    async fn api_albums() -> Vec<Album> {
+        Vec::new()
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn accepted_media_types_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_albums)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-albums").get.as_ref().unwrap();
+        let Some(aide::openapi::ReferenceOr::Item(response)) = op
+            .responses
+            .as_ref()
+            .unwrap()
+            .responses
+            .get(&aide::openapi::StatusCode::Code(200))
+        else {
+            panic!("expected a 200 response");
+        };
+        let media_types = response.content.keys().collect::<Vec<_>>();
+        assert_eq!(media_types, ["application/json", "application/msgpack"]);
+    }
+
+    #[test]
+    fn query_styles_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_unexploded)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-unexploded").get.as_ref().unwrap();
+        let ids = op.parameters[0].as_item().unwrap();
+        assert_eq!(ids.parameter_data_ref().explode, Some(false));
+
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_csv_list)
+            .finish_api(&mut api);
+        let op = path_item(&api, "/api-csv-list").get.as_ref().unwrap();
+        let Some(aide::openapi::ReferenceOr::Item(aide::openapi::Parameter::Query {
+            parameter_data,
+            style,
+            ..
+        })) = op.parameters.first()
+        else {
+            panic!("expected a query parameter");
+        };
+        assert_eq!(parameter_data.name, "ids");
+        assert_eq!(parameter_data.explode, Some(false));
+        assert!(matches!(style, aide::openapi::QueryStyle::Form));
+
+        #[cfg(feature = "deep-object")]
+        {
+            let mut api = OpenApi::default();
+            ApiRouter::new()
+                .typed_api_route(api_deep)
+                .finish_api(&mut api);
+
+            let op = path_item(&api, "/api-deep").get.as_ref().unwrap();
+            let Some(aide::openapi::ReferenceOr::Item(aide::openapi::Parameter::Query {
+                parameter_data,
+                style,
+                ..
+            })) = op.parameters.first()
+            else {
+                panic!("expected a query parameter");
+            };
+            assert_eq!(parameter_data.name, "filter");
+            assert!(matches!(style, aide::openapi::QueryStyle::DeepObject));
+        }
+    }
+
+    #[test]
+    fn snapshot_is_sorted() {
+        let mut first = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_versioned)
+            .typed_api_route(api_aliased)
+            .finish_api(&mut first);
+        let mut second = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_aliased)
+            .typed_api_route(api_versioned)
+            .finish_api(&mut second);
+
+        let snapshot = axum_typed_routing::openapi_snapshot(&first);
+        assert_eq!(snapshot, axum_typed_routing::openapi_snapshot(&second));
+        assert!(snapshot.ends_with("}\n"));
+        let aliased = snapshot.find("\"/api-aliased\"").unwrap();
+        let versioned = snapshot.find("\"/v2/versioned\"").unwrap();
+        assert!(aliased < versioned);
+
+        let yaml = axum_typed_routing::openapi_yaml(&first);
+        assert_eq!(yaml, axum_typed_routing::openapi_yaml(&second));
+        let aliased = yaml.find("\"/api-aliased\":").unwrap();
+        let versioned = yaml.find("\"/v2/versioned\":").unwrap();
+        assert!(aliased < versioned);
+    }
+
+    #[test]
+    fn api_is_exported() {
+        let (_router, json) = ApiRouter::new()
+            .typed_api_route(api_versioned)
+            .finish_api_to_json(OpenApi::default());
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(value["info"]["title"], "API");
+        assert_eq!(value["info"]["version"], "0.1.0");
+
+        let (_router, yaml) = TypedRouterBuilder::new(ApiRouter::new())
+            .typed_api_route(api_versioned)
+            .finish_api_to_yaml(OpenApi::default());
+        let expected = r#""components": {}
+"info":
+  "title": "API"
+  "version": "0.1.0"
+"openapi": "3.1.0"
+"paths":
+  "/v2/versioned":
+    "get":
+      "operationId": "api_versioned"
+      "responses":
+        "200":
+          "description": "no content"
+      "tags":
+        - "items"
+        - "v2"
+"#;
+        assert_eq!(yaml, expected);
+
+        let path = std::env::temp_dir().join("axum_typed_routing_openapi.yaml");
+        ApiRouter::new()
+            .typed_api_route(api_versioned)
+            .write_openapi(OpenApi::default(), &path)
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), yaml);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn router_builder_documents_api_routes() {
+        let builder = TypedRouterBuilder::new(ApiRouter::new())
+            .typed_api_route(get_hello)
+            .typed_route(one);
+        let handlers = builder
+            .routes()
+            .iter()
+            .map(|route| (route.method, route.handler))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            handlers,
+            [
+                ("GET", "main::aide_support::get_hello"),
+                ("POST", "main::one")
+            ]
+        );
+
+        let mut api = OpenApi::default();
+        builder.into_router().finish_api(&mut api);
+        assert!(path_item(&api, "/hello").get.is_some());
+        assert!(api.paths.unwrap().paths.get("/one").is_none());
+    }
+
+    #[test]
+    fn route_details_include_operation_ids_and_tags() {
+        let builder = TypedRouterBuilder::new(ApiRouter::new())
+            .typed_api_route(api_versioned)
+            .typed_route(two);
+        let details = builder
+            .route_details()
+            .iter()
+            .map(|details| (details.route.path, details.operation_id, details.tags))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            details,
+            [
+                ("/v2/versioned", Some("api_versioned"), &["items", "v2"][..]),
+                ("/two", None, &[][..]),
+            ]
+        );
+        let json = serde_json::from_str::<serde_json::Value>(&builder.routes_json()).unwrap();
+        assert_eq!(json[0]["tags"], serde_json::json!(["items", "v2"]));
+    }
+
+    const TAG_ITEMS: &str = "items";
+
+    fn admin_tag() -> &'static str {
+        "admin"
+    }
+
+    #[api_route(DELETE "/tagged/:id" {
+        id: concat!(module_path!(), "::delete_tagged"),
+        tags: [TAG_ITEMS, admin_tag()],
+        module: true,
+    })]
+    async fn delete_tagged(id: u32) {}
+
+    #[test]
+    fn tags_and_id_accept_expressions() {
+        let builder = TypedRouterBuilder::new(ApiRouter::new()).typed_api_route(delete_tagged);
+        let details = &builder.route_details()[0];
+        assert_eq!(details.tags, ["items", "admin"]);
+        assert_eq!(
+            delete_tagged::operation_id(),
+            Some("main::aide_support::delete_tagged")
+        );
+
+        let mut api = OpenApi::default();
+        builder.into_router().finish_api(&mut api);
+        let op = path_item(&api, "/tagged/{id}").delete.as_ref().unwrap();
+        assert_eq!(op.tags, ["items", "admin"]);
+        assert_eq!(
+            op.operation_id.as_deref(),
+            Some("main::aide_support::delete_tagged")
+        );
+    }
+
+    #[test]
+    fn summary_and_description_are_generated_from_doc_comments() {
+        let router = ApiRouter::new()
+            .typed_api_route(get_hello)
+            .typed_api_route(post_hello);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let get_op = path_item(&api, "/hello").get.as_ref().unwrap();
+        let post_op = path_item(&api, "/hello").post.as_ref().unwrap();
+
+        assert_eq!(get_op.summary, Some("get-summary".to_string()));
+        assert_eq!(get_op.description, Some("get-description".to_string()));
+        assert!(get_op.tags.is_empty());
+
+        assert_eq!(post_op.summary, Some("post-summary".to_string()));
+        assert_eq!(post_op.description, Some("post-description".to_string()));
+        assert!(post_op.tags.is_empty());
+    }
+
+    /// Lists the items
+    /// of a shelf.
+    ///
+    /// The items are sorted
+    /// by name:
+    /// - books
+    ///   and magazines
+    /// - records
+    ///
+    /// ```
+    /// let shelf = 1;
+    /// let items = 2;
+    /// ```
+    #[api_route(GET "/shelf" { doc_format: unwrapped })]
+    async fn shelf() {}
+
+    /**
+        Shelf summary
+
+        Shelf
+          description
+    */
+    #[api_route(GET "/block-shelf")]
+    async fn block_shelf() {}
+
+    /// raw-summary
+    /// raw-description
+    #[api_route(GET "/raw-shelf" { doc_format: raw })]
+    async fn raw_shelf() {}
+
+    #[test]
+    fn doc_comments_can_be_unwrapped_or_raw() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(shelf)
+            .typed_api_route(block_shelf)
+            .typed_api_route(raw_shelf)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/shelf").get.as_ref().unwrap();
+        assert_eq!(op.summary.as_deref(), Some("Lists the items of a shelf."));
+        assert_eq!(
+            op.description.as_deref(),
+            Some(
+                "The items are sorted by name:\n- books and magazines\n- records\n\n```\nlet shelf = 1;\nlet items = 2;\n```"
+            )
+        );
+
+        let op = path_item(&api, "/block-shelf").get.as_ref().unwrap();
+        assert_eq!(op.summary.as_deref(), Some("Shelf summary"));
+        assert_eq!(op.description.as_deref(), Some("Shelf\n  description"));
+
+        let op = path_item(&api, "/raw-shelf").get.as_ref().unwrap();
+        assert_eq!(op.summary.as_deref(), Some(" raw-summary"));
+        assert_eq!(op.description, None);
+    }
+
+    /// unused-summary
+    ///
+    /// unused-description
+    #[api_route(GET "/hello" {
+        summary: "MySummary",
+        description: "MyDescription",
+        hidden: false,
+        id: "MyRoute",
+        tags: ["MyTag1", "MyTag2"],
+        security: {
+            "MySecurity1": ["MyScope1", "MyScope2"],
+            "MySecurity2": [],
+        },
+        responses: {
+            300: String,
+        },
+        transform: |x| x.summary("OverriddenSummary"),
+    })]
+    async fn get_gello_with_attributes(state: State<String>) -> String {
+        String::from("Hello!")
+    }
+
+    #[test]
+    fn generated_from_attributes() {
+        let router = ApiRouter::new().typed_api_route(get_gello_with_attributes);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let get_op = path_item(&api, "/hello").get.as_ref().unwrap();
+
+        assert_eq!(get_op.summary, Some("OverriddenSummary".to_string()));
+        assert_eq!(get_op.description, Some("MyDescription".to_string()));
+        assert_eq!(
+            get_op.tags,
+            vec!["MyTag1".to_string(), "MyTag2".to_string()]
+        );
+        assert_eq!(get_op.operation_id, Some("MyRoute".to_string()));
+    }
+
+    fn common_transform(op: TransformOperation) -> TransformOperation {
+        op.tag("common").response::<500, String>()
+    }
+
+    #[api_route(GET "/transformed" { transform: common_transform })]
+    async fn transformed_by_fn() {}
+
+    #[test]
+    fn transform_can_be_a_function() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(transformed_by_fn)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/transformed").get.as_ref().unwrap();
+        assert_eq!(op.tags, vec!["common".to_string()]);
+        let responses = op.responses.as_ref().unwrap();
+        assert!(responses
+            .responses
+            .contains_key(&aide::openapi::StatusCode::Code(500)));
+    }
+
+    /// summary
+    ///
+    /// description
+    /// description
+    #[api_route(GET "/hello")]
+    async fn get_gello_without_attributes(state: State<String>) -> String {
+        String::from("Hello!")
+    }
+
+    #[api_route(POST "/upload" {
+        request_body: multipart { file: binary, description: Option<String> },
+    })]
+    async fn upload(multipart: axum::extract::Multipart) {}
+
+    #[api_route(PUT "/avatar" { request_body: binary("image/png", "image/*") })]
+    async fn upload_avatar(body: axum::body::Body) {}
+
+    #[api_route(PUT "/blob" { request_body: binary })]
+    async fn upload_blob(body: axum::body::Bytes) {}
+
+    #[test]
+    fn binary_request_body() {
+        let router = ApiRouter::new()
+            .typed_api_route(upload_avatar)
+            .typed_api_route(upload_blob);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let media_types = |path| {
+            let put_op = path_item(&api, path).put.as_ref().unwrap();
+            let body = match put_op.request_body.as_ref().unwrap() {
+                aide::openapi::ReferenceOr::Item(body) => body,
+                _ => panic!("expected an inline request body"),
+            };
+            body.content
+                .iter()
+                .map(|(media_type, content)| {
+                    let schema = &content.schema.as_ref().unwrap().json_schema;
+                    (media_type.clone(), serde_json::to_value(schema).unwrap())
+                })
+                .collect::<Vec<_>>()
+        };
+        let binary = serde_json::json!({ "type": "string", "format": "binary" });
+        assert_eq!(
+            media_types("/avatar"),
+            vec![
+                ("image/png".to_string(), binary.clone()),
+                ("image/*".to_string(), binary.clone()),
+            ]
+        );
+        assert_eq!(
+            media_types("/blob"),
+            vec![("application/octet-stream".to_string(), binary)]
+        );
+    }
+
+    #[api_route(POST "/upload-elsewhere" {
+        servers: ["https://uploads.example.com", "https://uploads.example.org"],
+    })]
+    async fn upload_elsewhere() {}
+
+    #[test]
+    fn servers_are_set() {
+        let router = ApiRouter::new().typed_api_route(upload_elsewhere);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let post_op = path_item(&api, "/upload-elsewhere").post.as_ref().unwrap();
+        let urls = post_op
+            .servers
+            .iter()
+            .map(|server| server.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            vec!["https://uploads.example.com", "https://uploads.example.org"]
+        );
+    }
+
+    #[api_route(GET "/internal" {
+        extensions: { "x-internal": true, "x-owner": "payments", "x-rate-limit": 10 * 6 },
+    })]
+    async fn internal() {}
+
+    #[test]
+    fn extensions_are_set() {
+        let router = ApiRouter::new().typed_api_route(internal);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let get_op = path_item(&api, "/internal").get.as_ref().unwrap();
+        assert_eq!(get_op.extensions["x-internal"], serde_json::json!(true));
+        assert_eq!(get_op.extensions["x-owner"], serde_json::json!("payments"));
+        assert_eq!(get_op.extensions["x-rate-limit"], serde_json::json!(60));
+    }
+
+    #[api_route(DELETE "/internal/:id" { permissions: ["items:write"] })]
+    async fn delete_internal(id: u32) {}
+
+    #[test]
+    fn permissions_are_documented() {
+        let router = ApiRouter::new().typed_api_route(delete_internal);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let op = path_item(&api, "/internal/{id}").delete.as_ref().unwrap();
+        let permissions = &op.extensions["x-permissions"];
+        assert_eq!(*permissions, serde_json::json!(["items:write"]));
+    }
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct NewWidget {
+        id: u32,
+    }
+
+    #[api_route(POST "/widgets" {
+        status: 201,
+        examples: {
+            "happy path": {
+                request: serde_json::json!({ "id": 1 }),
+                response: serde_json::json!({ "name": "x" }),
+            },
+            "minimal": { request: serde_json::json!({ "id": 2 }) },
+        },
+    })]
+    async fn create_widget(Json(widget): Json<NewWidget>) -> Json<serde_json::Value> {
+        Json(serde_json::json!({ "name": "x" }))
+    }
+
+    #[test]
+    fn examples_are_documented() {
+        let router = ApiRouter::new().typed_api_route(create_widget);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let post_op = path_item(&api, "/widgets").post.as_ref().unwrap();
+        let body = post_op.request_body.as_ref().unwrap().as_item().unwrap();
+        let examples = &body.content["application/json"].examples;
+        let example = |name: &str| examples[name].as_item().unwrap().value.clone();
+        assert_eq!(example("happy path"), Some(serde_json::json!({ "id": 1 })));
+        assert_eq!(example("minimal"), Some(serde_json::json!({ "id": 2 })));
+
+        let responses = &post_op.responses.as_ref().unwrap().responses;
+        let created = responses[&aide::openapi::StatusCode::Code(201)]
+            .as_item()
+            .unwrap();
+        let examples = &created.content["application/json"].examples;
+        assert_eq!(examples.len(), 1);
+        assert_eq!(
+            examples["happy path"].as_item().unwrap().value,
+            Some(serde_json::json!({ "name": "x" }))
+        );
+    }
+
+    #[test]
+    fn multipart_request_body() {
+        let router = ApiRouter::new().typed_api_route(upload);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let post_op = path_item(&api, "/upload").post.as_ref().unwrap();
+        let body = match post_op.request_body.as_ref().unwrap() {
+            aide::openapi::ReferenceOr::Item(body) => body,
+            _ => panic!("expected an inline request body"),
+        };
+        let schema = serde_json::to_value(
+            &body.content["multipart/form-data"]
+                .schema
+                .as_ref()
+                .unwrap()
+                .json_schema,
+        )
+        .unwrap();
+
+        assert_eq!(
+            schema,
+            serde_json::json!({
+                "type": "object",
+                "required": ["file"],
+                "properties": {
+                    "file": { "type": "string", "format": "binary" },
+                    "description": { "type": "string" },
+                },
+            })
+        );
+    }
+
+    #[cfg(feature = "multipart")]
+    #[api_route(POST "/api-upload" multipart(file: axum::body::Bytes, size: Option<u32>))]
+    async fn api_upload(file: axum::body::Bytes, size: Option<u32>) {}
+
+    #[cfg(feature = "multipart")]
+    #[test]
+    fn multipart_fields_are_documented() {
+        let router = ApiRouter::new().typed_api_route(api_upload);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let post_op = path_item(&api, "/api-upload").post.as_ref().unwrap();
+        let body = match post_op.request_body.as_ref().unwrap() {
+            aide::openapi::ReferenceOr::Item(body) => body,
+            _ => panic!("expected an inline request body"),
+        };
+        let schema = serde_json::to_value(
+            &body.content["multipart/form-data"]
+                .schema
+                .as_ref()
+                .unwrap()
+                .json_schema,
+        )
+        .unwrap();
+
+        assert_eq!(
+            schema,
+            serde_json::json!({
+                "type": "object",
+                "required": ["file"],
+                "properties": {
+                    "file": { "type": "string", "format": "binary" },
+                    "size": { "type": "integer", "format": "uint32", "minimum": 0.0 },
+                },
+            })
+        );
+    }
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct Signup {
+        email: String,
+        newsletter: Option<bool>,
+    }
+
+    #[api_route(POST "/signup")]
+    async fn signup(axum::Form(signup): axum::Form<Signup>) -> String {
+        signup.email
+    }
+
+    #[test]
+    fn form_request_body() {
+        let router = ApiRouter::new().typed_api_route(signup);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let post_op = path_item(&api, "/signup").post.as_ref().unwrap();
+        let body = match post_op.request_body.as_ref().unwrap() {
+            aide::openapi::ReferenceOr::Item(body) => body,
+            _ => panic!("expected an inline request body"),
+        };
+        assert!(body.required);
+        let schema = serde_json::to_value(
+            &body.content["application/x-www-form-urlencoded"]
+                .schema
+                .as_ref()
+                .unwrap()
+                .json_schema,
+        )
+        .unwrap();
+        assert_eq!(
+            schema,
+            serde_json::json!({ "$ref": "#/components/schemas/Signup" })
+        );
+        let components = api.components.as_ref().unwrap();
+        assert!(components.schemas.contains_key("Signup"));
+    }
+
+    #[api_route(GET "/retired" { deprecated: true })]
+    async fn retired_api() -> &'static str {
+        "retired"
+    }
+
+    #[tokio::test]
+    async fn deprecated_routes_are_documented() {
+        let mut api = OpenApi::default();
+        let router = ApiRouter::new()
+            .typed_api_route(retired_api)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/retired").get.as_ref().unwrap();
+        assert!(op.deprecated);
+
+        let server = TestServer::new(router).unwrap();
+        let response = server.get("/retired").await;
+        assert_eq!(response.header("deprecation"), "true");
+        assert!(response.maybe_header("sunset").is_none());
+    }
+
+    const SCHEME_BEARER: &str = "bearer";
+
+    enum Scope {
+        ReadItems,
+    }
+
+    impl AsRef<str> for Scope {
+        fn as_ref(&self) -> &str {
+            match self {
+                Scope::ReadItems => "read:items",
+            }
+        }
+    }
+
+    #[api_route(GET "/secured" { security: { SCHEME_BEARER: [Scope::ReadItems, "admin"] } })]
+    async fn secured_by_consts() {}
+
+    #[test]
+    fn security_can_use_constants() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(secured_by_consts)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/secured").get.as_ref().unwrap();
+        assert_eq!(op.security.len(), 1);
+        assert_eq!(
+            op.security[0]["bearer"],
+            vec!["read:items".to_string(), "admin".to_string()]
+        );
+    }
+
+    #[test]
+    fn security_schemes_are_registered() {
+        let router = ApiRouter::new().typed_api_route(get_gello_with_attributes);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let result = axum_typed_routing::define_security_schemes! {
+            "MySecurity1": http_bearer(),
+            "MySecurity2": header("X-Api-Key"),
+        }
+        .register(&mut api);
+        assert_eq!(result, Ok(()));
+
+        let schemes = &api.components.as_ref().unwrap().security_schemes;
+        assert_eq!(
+            schemes.keys().collect::<Vec<_>>(),
+            vec!["MySecurity1", "MySecurity2"]
+        );
+    }
+
+    #[test]
+    fn unknown_security_schemes_are_reported() {
+        let router = ApiRouter::new().typed_api_route(get_gello_with_attributes);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let error = axum_typed_routing::define_security_schemes! {
+            "MySecurity1": http_bearer(),
+        }
+        .register(&mut api)
+        .unwrap_err();
+        assert_eq!(error.scheme, "MySecurity2");
+        assert_eq!(
+            error.to_string(),
+            "security scheme `MySecurity2` used by `GET /hello` is not defined"
+        );
+    }
+
+    #[axum_typed_routing::api_defaults(tags: ["items"], security: { "bearer": [] })]
+    mod items {
+        use super::*;
+
+        #[api_route(GET "/items")]
+        pub async fn list_items() {}
+
+        pub mod nested {
+            use super::*;
+
+            #[api_route(GET "/items/:id" { tags: ["public"], security: { "api_key": [] } })]
+            pub async fn get_item(id: u32) {}
+        }
+    }
+
+    #[test]
+    fn api_defaults_are_merged() {
+        let router = ApiRouter::new()
+            .typed_api_route(items::list_items)
+            .typed_api_route(items::nested::get_item);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let list_op = path_item(&api, "/items").get.as_ref().unwrap();
+        assert_eq!(list_op.tags, vec!["items".to_string()]);
+        assert_eq!(list_op.security.len(), 1);
+        assert!(list_op.security[0].contains_key("bearer"));
+
+        let get_op = path_item(&api, "/items/{id}").get.as_ref().unwrap();
+        assert_eq!(get_op.tags, vec!["items".to_string(), "public".to_string()]);
+        assert_eq!(get_op.security.len(), 2);
+        assert!(get_op.security[0].contains_key("bearer"));
+        assert!(get_op.security[1].contains_key("api_key"));
+    }
+
+    #[axum_typed_routing::api_defaults(id_strategy: camelCase, id_module_prefix: true)]
+    mod ids {
+        use super::*;
+
+        #[api_route(GET "/ids/item" { module: true })]
+        pub async fn get_item_v2() {}
+
+        #[api_route(GET "/ids/raw" { id_strategy: PascalCase, id_module_prefix: false, module: true })]
+        pub async fn raw_item() {}
+    }
+
+    #[test]
+    fn id_strategy_is_applied() {
+        let router = ApiRouter::new()
+            .typed_api_route(ids::get_item_v2)
+            .typed_api_route(ids::raw_item);
+        let mut api = OpenApi::default();
+        router.finish_api(&mut api);
+
+        let item_op = path_item(&api, "/ids/item").get.as_ref().unwrap();
+        assert_eq!(item_op.operation_id.as_deref(), Some("ids.getItemV2"));
+        let raw_op = path_item(&api, "/ids/raw").get.as_ref().unwrap();
+        assert_eq!(raw_op.operation_id.as_deref(), Some("RawItem"));
+    }
+
+    mod operation_ids {
+        use super::*;
+        use axum_typed_routing::route_index;
+
+        route_index! {
+            super::ids::get_item_v2,
+            super::ids::raw_item,
+            super::get_hello,
+        }
+    }
+
+    #[test]
+    fn route_index_has_api_operation_ids() {
+        use operation_ids::routes::OperationId;
+
+        assert_eq!(OperationId::GetItemV2.as_str(), "ids.getItemV2");
+        assert_eq!(OperationId::RawItem.as_str(), "RawItem");
+        assert_eq!(OperationId::GetItemV2.path(), "/ids/item");
+        assert_eq!(
+            OperationId::find("GET", "/ids/raw"),
+            Some(OperationId::RawItem)
+        );
+        assert_eq!(OperationId::GetHello.as_str(), "get_hello");
+    }
+
+    #[cfg(feature = "scalar")]
+    #[tokio::test]
+    async fn docs_are_served() {
+        use axum_typed_routing::DocsUi;
+
+        let router = ApiRouter::new()
+            .typed_api_route(get_hello)
+            .serve_docs(OpenApi::default(), "/docs", DocsUi::Scalar)
+            .with_state("state".to_string());
+
+        let server = TestServer::new(router).unwrap();
+
+        let response = server.get("/docs").await;
+        response.assert_status_ok();
+        assert!(response.text().contains("/openapi.json"));
+
+        let response = server.get("/openapi.json").await;
+        response.assert_status_ok();
+        let spec = response.json::<serde_json::Value>();
+        assert!(spec["paths"]["/hello"]["get"].is_object());
+        assert!(spec["paths"].get("/docs").is_none());
+    }
+
+    #[api_route(GET "/api-lenient?page&q" { lenient_query: true })]
+    async fn api_lenient(page: Option<u32>, q: Option<String>) -> String {
+        format!("{page:?} {q:?}")
+    }
+
+    #[test]
+    fn lenient_query_params_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_lenient)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-lenient").get.as_ref().unwrap();
+        let names = op
+            .parameters
+            .iter()
+            .map(|param| param.as_item().unwrap().parameter_data_ref().name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["page", "q"]);
+    }
+
+    #[api_route(GET "/api-flagged/:id" with Flags { feature_flag: "beta" })]
+    async fn api_flagged(id: u32) -> String {
+        format!("{id}")
+    }
+
+    #[test]
+    fn feature_flags_are_not_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_flagged)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-flagged/{id}").get.as_ref().unwrap();
+        assert!(op.parameters.iter().all(|param| {
+            let param = param.as_item().unwrap().parameter_data_ref();
+            param.name == "id"
+        }));
+    }
+
+    /// A query parameter type without a `JsonSchema`.
+    #[derive(serde::Deserialize)]
+    struct Opaque(String);
+
+    fn opaque_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        gen.subschema_for::<String>()
+    }
+
+    #[api_route(GET "/api-opaque?filter&raw")]
+    async fn api_opaque(
+        #[route_param(schema_with = "opaque_schema")] filter: Opaque,
+        #[route_param(skip_schema)] raw: Option<Opaque>,
+    ) -> String {
+        filter.0
+    }
+
+    #[test]
+    fn query_params_without_schema_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_opaque)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-opaque").get.as_ref().unwrap();
+        let params = op
+            .parameters
+            .iter()
+            .map(|param| param.as_item().unwrap().parameter_data_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(params.len(), 1);
+        assert_eq!(params[0].name, "filter");
+        assert!(params[0].required);
+    }
+
+    #[api_route(GET "/api-cached/:id" with Responses { cached: { ttl: 1m } })]
+    async fn api_cached(id: u32) -> Json<u32> {
+        Json(id)
+    }
+
+    #[test]
+    fn cached_responses_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_cached)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/api-cached/{id}").get.as_ref().unwrap();
+        let responses = op.responses.as_ref().unwrap();
+        let ok = responses.responses[&aide::openapi::StatusCode::Code(200)]
+            .as_item()
+            .unwrap();
+        assert!(ok.content.contains_key("application/json"));
+    }
+
+    #[test]
+    fn contract_drift_is_reported() {
+        use aide::openapi::{Operation, PathItem, ReferenceOr};
+        use axum_typed_routing::{contract_drift, openapi_snapshot, ContractOperation};
+
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(fallible_item)
+            .typed_api_route(fallible_create)
+            .finish_api(&mut api);
+
+        // The contract is checked in as JSON, so it must match after a round trip.
+        let contract: OpenApi = serde_json::from_str(&openapi_snapshot(&api)).unwrap();
+        assert_eq!(contract_drift(&api, &contract), Ok(()));
+
+        let mut contract = contract;
+        let paths = &mut contract.paths.as_mut().unwrap().paths;
+        let ReferenceOr::Item(create) = paths.get_mut("/fallible").unwrap() else {
+            panic!("expected an inline path item");
+        };
+        create.post = None;
+        let ReferenceOr::Item(item) = paths.get_mut("/fallible/{id}").unwrap() else {
+            panic!("expected an inline path item");
+        };
+        item.get.as_mut().unwrap().summary = Some("changed".to_string());
+        let gone = PathItem {
+            delete: Some(Operation {
+                operation_id: Some("deleteGone".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        paths.insert("/gone".to_string(), ReferenceOr::Item(gone));
+        let schemas = &mut contract.components.as_mut().unwrap().schemas;
+        schemas.shift_remove("ErrorBody");
+
+        let drift = contract_drift(&api, &contract).unwrap_err();
+        let operation = |method: &str, path: &str, id: &str| ContractOperation {
+            method: method.to_string(),
+            path: path.to_string(),
+            operation_id: Some(id.to_string()),
+        };
+        assert_eq!(
+            drift.missing,
+            vec![operation("DELETE", "/gone", "deleteGone")]
+        );
+        assert_eq!(
+            drift.undocumented,
+            vec![operation("POST", "/fallible", "fallible_create")]
+        );
+        assert_eq!(
+            drift.changed,
+            vec![operation("GET", "/fallible/{id}", "fallible_item")]
+        );
+        assert_eq!(drift.changed_schemas, vec!["ErrorBody".to_string()]);
+        assert_eq!(
+            drift.to_string(),
+            "the routes don't match the OpenApi contract:\n  \
+             missing:      DELETE /gone (deleteGone)\n  \
+             undocumented: POST /fallible (fallible_create)\n  \
+             changed:      GET /fallible/{id} (fallible_item)\n  \
+             schema:       ErrorBody"
+        );
+    }
+
+    #[derive(serde::Serialize, schemars::JsonSchema)]
+    struct ErrorBody {
+        message: String,
+    }
+
+    enum ApiError {
+        NotFound,
+        Unauthorized,
+    }
+
+    impl axum::response::IntoResponse for ApiError {
+        fn into_response(self) -> axum::response::Response {
+            use axum::{http::StatusCode, response::IntoResponse};
+
+            match self {
+                ApiError::NotFound => {
+                    let message = "not found".to_string();
+                    (StatusCode::NOT_FOUND, Json(ErrorBody { message })).into_response()
+                }
+                ApiError::Unauthorized => StatusCode::UNAUTHORIZED.into_response(),
+            }
+        }
+    }
+
+    impl axum_typed_routing::ErrorResponses for ApiError {
+        fn error_responses(docs: &mut axum_typed_routing::ErrorResponseDocs<'_>) {
+            docs.response::<Json<ErrorBody>>(404, "The item doesn't exist")
+                .response::<()>(401, "The request isn't authenticated");
+        }
+    }
+
+    #[api_route(GET "/fallible/:id")]
+    async fn fallible_item(id: u32) -> Result<Json<u32>, ApiError> {
+        match id {
+            0 => Err(ApiError::NotFound),
+            _ => Ok(Json(id)),
+        }
+    }
+
+    #[api_route(POST "/fallible" { status: 201 })]
+    async fn fallible_create() -> Result<impl aide::axum::IntoApiResponse, ApiError> {
+        Err::<Json<u32>, _>(ApiError::Unauthorized)
+    }
+
+    #[test]
+    fn error_responses_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(fallible_item)
+            .typed_api_route(fallible_create)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/fallible/{id}").get.as_ref().unwrap();
+        let responses = &op.responses.as_ref().unwrap().responses;
+        let code = aide::openapi::StatusCode::Code;
+        let ok = responses[&code(200)].as_item().unwrap();
+        assert!(ok.content.contains_key("application/json"));
+        let not_found = responses[&code(404)].as_item().unwrap();
+        assert_eq!(not_found.description, "The item doesn't exist");
+        assert!(not_found.content.contains_key("application/json"));
+        let unauthorized = responses[&code(401)].as_item().unwrap();
+        assert_eq!(unauthorized.description, "The request isn't authenticated");
+        assert!(unauthorized.content.is_empty());
+
+        let op = path_item(&api, "/fallible").post.as_ref().unwrap();
+        let responses = &op.responses.as_ref().unwrap().responses;
+        assert!(responses.contains_key(&code(404)));
+        assert!(responses.contains_key(&code(401)));
+    }
+
+    #[tokio::test]
+    async fn error_responses_are_returned() {
+        let router: axum::Router = ApiRouter::new()
+            .typed_api_route(fallible_item)
+            .typed_api_route(fallible_create)
+            .into();
+        let server = TestServer::new(router).unwrap();
+
+        server.get("/fallible/1").await.assert_json(&1);
+        server
+            .get("/fallible/0")
+            .expect_failure()
+            .await
+            .assert_status_not_found();
+        server
+            .post("/fallible")
+            .expect_failure()
+            .await
+            .assert_status_unauthorized();
+    }
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct PlacedOrder {
+        id: u32,
+    }
+
+    /// An order was placed.
+    #[axum_typed_routing::api_webhook("order.created" { tags: ["orders"] })]
+    async fn order_created(Json(order): Json<PlacedOrder>) {}
+
+    /// An order was cancelled.
+    #[axum_typed_routing::api_webhook(PUT "order.cancelled" with String)]
+    async fn order_cancelled(State(state): State<String>, Json(order): Json<PlacedOrder>) {}
+
+    #[test]
+    fn webhooks_are_documented() {
+        use axum_typed_routing::TypedWebhooks;
+
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(get_hello)
+            .finish_api_with(&mut api, |api| {
+                api.typed_webhook(order_created)
+                    .typed_webhook(order_cancelled)
+            });
+
+        let created = api.webhooks["order.created"].as_item().unwrap();
+        let op = created.post.as_ref().unwrap();
+        assert_eq!(op.summary.as_deref(), Some("An order was placed."));
+        assert_eq!(op.tags, ["orders"]);
+        assert!(op.request_body.is_some());
+        let cancelled = api.webhooks["order.cancelled"].as_item().unwrap();
+        assert!(cancelled.put.is_some());
+
+        // The schema of the request body is a component, like those of the routes.
+        let schemas = &api.components.as_ref().unwrap().schemas;
+        assert!(schemas.contains_key("PlacedOrder"));
+        // A webhook isn't a path.
+        let paths = &api.paths.as_ref().unwrap().paths;
+        assert_eq!(paths.len(), 1);
+        assert!(paths.contains_key("/hello"));
+    }
+
+    #[api_route(GET "/tree/*path" { params: { path: { description: "folders" } } })]
+    async fn api_tree(path: Vec<String>) -> String {
+        path.join("/")
+    }
+
+    #[test]
+    fn wildcard_segments_are_documented_as_string() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_tree)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/tree/{*path}").get.as_ref().unwrap();
+        let path = serde_json::to_value(&op.parameters[0]).unwrap();
+        assert_eq!(path["in"], "path");
+        assert_eq!(path["schema"]["type"], "string");
+    }
+
+    #[cfg(feature = "regex")]
+    #[api_route(GET "/articles/:slug(regex = \"[a-z0-9-]+\")/:id" { params: { id: { example: 1 } } })]
+    async fn api_article(slug: String, id: u32) -> String {
+        format!("{slug} {id}")
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn path_patterns_are_documented() {
+        let mut api = OpenApi::default();
+        ApiRouter::new()
+            .typed_api_route(api_article)
+            .finish_api(&mut api);
+
+        let op = path_item(&api, "/articles/{slug}/{id}")
+            .get
+            .as_ref()
+            .unwrap();
+        let params = serde_json::to_value(&op.parameters).unwrap();
+        let slug = params
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|param| param["name"] == "slug")
+            .unwrap();
+        assert_eq!(slug["in"], "path");
+        assert_eq!(slug["required"], true);
+        assert_eq!(slug["schema"]["pattern"], "[a-z0-9-]+");
+        let responses = &op.responses.as_ref().unwrap().responses;
+        assert!(responses.contains_key(&aide::openapi::StatusCode::Code(400)));
+    }
+
+    #[derive(serde::Serialize, schemars::JsonSchema)]
+    struct CatalogItem {
+        name: String,
+    }
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct PurgeRequest {
+        reason: String,
+    }
+
+    #[api_route(GET "/catalog")]
+    async fn list_catalog() -> Json<Vec<CatalogItem>> {
+        Json(Vec::new())
+    }
+
+    #[api_route(DELETE "/catalog" { api_doc: "internal" })]
+    async fn purge_catalog(Json(request): Json<PurgeRequest>) -> String {
+        request.reason
+    }
+
+    #[api_route(POST "/catalog/reindex" { api_doc: "internal" })]
+    async fn reindex_catalog() -> Json<Vec<CatalogItem>> {
+        Json(Vec::new())
+    }
+
+    #[api_route(GET "/catalog/audit" { api_doc: "audit" })]
+    async fn audit_catalog() -> String {
+        String::new()
+    }
+
+    #[tokio::test]
+    async fn named_apis_are_documented_separately() {
+        let (mut public, mut internal) = (OpenApi::default(), OpenApi::default());
+        let router = ApiRouter::new()
+            .typed_api_route(list_catalog)
+            .typed_api_route(purge_catalog)
+            .typed_api_route(reindex_catalog)
+            .typed_api_route(audit_catalog)
+            .finish_named_api("internal", &mut internal)
+            .finish_api(&mut public);
+
+        let public_paths = &public.paths.as_ref().unwrap().paths;
+        assert_eq!(
+            public_paths.keys().collect::<Vec<_>>(),
+            ["/catalog", "/catalog/audit"]
+        );
+        let catalog = path_item(&public, "/catalog");
+        assert!(catalog.get.is_some() && catalog.delete.is_none());
+        // A document that isn't finished separately stays in the main documentation, without
+        // the extension.
+        let audit = path_item(&public, "/catalog/audit").get.as_ref().unwrap();
+        assert!(audit.extensions.is_empty());
+
+        let internal_paths = &internal.paths.as_ref().unwrap().paths;
+        assert_eq!(
+            internal_paths.keys().collect::<Vec<_>>(),
+            ["/catalog", "/catalog/reindex"]
+        );
+        let purge = path_item(&internal, "/catalog").delete.as_ref().unwrap();
+        assert!(purge.extensions.is_empty());
+
+        // Every document has the schemas it references, and only the internal one has those of
+        // the internal routes.
+        let schemas = |api: &OpenApi| {
+            let components = api.components.as_ref().unwrap();
+            components.schemas.keys().cloned().collect::<Vec<_>>()
+        };
+        assert_eq!(schemas(&public), ["CatalogItem"]);
+        assert_eq!(schemas(&internal), ["CatalogItem", "PurgeRequest"]);
+
+        // All routes are still served.
+        let server = TestServer::new(router).unwrap();
+        server.post("/catalog/reindex").await.assert_status_ok();
+        server.get("/catalog/audit").await.assert_status_ok();
+    }
+
+    /// Only uses the prelude and the re-exported `aide` and `schemars`.
+    mod with_prelude {
+        use axum::Json;
+        use axum_typed_routing::aide::{axum::ApiRouter, openapi::OpenApi};
+        use axum_typed_routing::prelude::*;
+        use axum_typed_routing::schemars::JsonSchema;
+        use serde::Deserialize;
+
+        #[derive(Deserialize, JsonSchema)]
+        #[schemars(crate = "axum_typed_routing::schemars")]
+        struct Label {
+            name: String,
+        }
+
+        #[api_route(POST "/labels/:id?force" { tags: ["labels"] })]
+        async fn put_label(id: u32, force: Option<bool>, Json(label): Json<Label>) -> String {
+            label.name
+        }
+
+        #[route(GET "/labels")]
+        async fn list_labels() -> String {
+            String::new()
+        }
+
+        #[test]
+        fn prelude_routes_are_documented() {
+            let mut api = OpenApi::default();
+            ApiRouter::new()
+                .typed_api_route(put_label)
+                .typed_route(list_labels)
+                .finish_api(&mut api);
+
+            let op = super::path_item(&api, "/labels/{id}")
+                .post
+                .as_ref()
+                .unwrap();
+            assert_eq!(op.tags, ["labels"]);
+            let force = op.parameters[0].as_item().unwrap();
+            assert_eq!(force.parameter_data_ref().name, "force");
+            let schemas = &api.components.as_ref().unwrap().schemas;
+            assert!(schemas.contains_key("Label"));
+        }
+    }
+
+    #[cfg(feature = "pagination")]
+    mod pagination {
+        use super::*;
+        use axum_typed_routing::{Paginated, Pagination};
+
+        #[api_route(GET "/paginated-items" { paginated: true })]
+        async fn list_paged_items(pagination: Pagination) -> Paginated<String> {
+            Paginated::new(Vec::new(), 0, &pagination)
+        }
+
+        #[test]
+        fn paginated_routes_are_documented() {
+            let mut api = OpenApi::default();
+            ApiRouter::new()
+                .typed_api_route(list_paged_items)
+                .finish_api(&mut api);
+
+            let op = super::path_item(&api, "/paginated-items")
+                .get
+                .as_ref()
+                .unwrap();
+            let params = op
+                .parameters
+                .iter()
+                .map(|param| param.as_item().unwrap().parameter_data_ref())
+                .collect::<Vec<_>>();
+            assert_eq!(params.len(), 2);
+            assert!(params.iter().all(|param| !param.required));
+            assert_eq!(params[0].name, "page");
+            assert_eq!(params[0].example, Some(serde_json::json!(1)));
+
+            let response = serde_json::to_value(op.responses.as_ref().unwrap()).unwrap();
+            let schema = &response["200"]["content"]["application/json"]["schema"];
+            let schema = serde_json::to_string(schema).unwrap();
+            assert!(schema.contains("Paginated"), "{schema}");
+        }
+    }
+
+    #[cfg(feature = "async-graphql")]
+    mod graphql {
+        use super::*;
+        use crate::graphql::Query;
+        use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+        use axum_typed_routing::prelude::api_graphql_route;
+
+        /// Adds numbers.
+        #[api_graphql_route("/graphql")]
+        type ApiSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+        #[test]
+        fn graphql_routes_are_documented() {
+            let mut api = OpenApi::default();
+            ApiRouter::new()
+                .typed_api_route(api_schema)
+                .finish_api(&mut api);
+
+            let item = super::path_item(&api, "/graphql");
+            let post = item.post.as_ref().unwrap();
+            assert_eq!(post.operation_id.as_deref(), Some("api_schema"));
+            assert_eq!(post.summary.as_deref(), Some("Adds numbers."));
+            let body = serde_json::to_value(&post.request_body).unwrap();
+            assert_eq!(
+                body["content"]["application/json"]["schema"]["$ref"],
+                "#/components/schemas/GraphQLRequest"
+            );
+            let get = item.get.as_ref().unwrap();
+            assert_eq!(get.operation_id.as_deref(), Some("api_schema_graphiql"));
+            let responses = serde_json::to_value(&get.responses).unwrap();
+            assert!(responses["200"]["content"]["text/html"].is_object());
+        }
+    }
+
+    fn path_item<'a>(api: &'a OpenApi, path: &str) -> &'a aide::openapi::PathItem {
+        api.paths
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|(p, _)| *p == path)
+            .unwrap()
+            .1
+            .as_item()
+            .unwrap()
+    }
+}
+
+struct Realms;
+
+impl Realms {
+    #[route(GET "/realms/admin/:id" inject(realm = "admin", level = 2u8) { in_impl: true, name: admin_realm })]
+    #[route(GET "/realms/guest/:id" inject(realm = "guest", level = 0u8) { in_impl: true, name: guest_realm })]
+    async fn realm_item(id: u32, realm: &'static str, State(_): State<()>, level: u8) -> String {
+        format!("{realm} {level} {id}")
+    }
+}
+
+#[tokio::test]
+async fn test_injected_arguments() {
+    let router = axum::Router::new()
+        .typed_route(Realms::admin_realm)
+        .typed_route(Realms::guest_realm);
+    let server = TestServer::new(router).unwrap();
+
+    server.get("/realms/admin/3").await.assert_text("admin 2 3");
+    server.get("/realms/guest/4").await.assert_text("guest 0 4");
+}
+
+#[route(GET "/raw/:id?page")]
+async fn raw_extractors(
+    id: u32,
+    page: Option<u32>,
+    axum::extract::RawQuery(query): axum::extract::RawQuery,
+    params: axum::extract::RawPathParams,
+    axum::extract::OriginalUri(uri): axum::extract::OriginalUri,
+) -> String {
+    let params = params.iter().map(|(name, value)| format!("{name}={value}"));
+    format!(
+        "{id} {page:?} {query:?} {:?} {}",
+        params.collect::<Vec<_>>(),
+        uri.path_and_query().unwrap()
+    )
+}
+
+#[tokio::test]
+async fn test_raw_extractors() {
+    let router = axum::Router::new().typed_route(raw_extractors);
+    let server = TestServer::new(axum::Router::new().nest("/api", router)).unwrap();
+
+    server
+        .get("/api/raw/5?page=2&filter=new")
+        .await
+        .assert_text(
+            r#"5 Some(2) Some("page=2&filter=new") ["id=5"] /api/raw/5?page=2&filter=new"#,
+        );
 }
 
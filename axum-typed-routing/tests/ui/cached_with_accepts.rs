@@ -0,0 +1,11 @@
+use axum_typed_routing::route;
+
+#[derive(serde::Serialize)]
+struct Price(u32);
+
+#[route(GET "/prices/:id" { cached: { ttl: 60s }, accepts: [json] })]
+async fn price(id: u32) -> Price {
+    Price(id)
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use axum::http::HeaderMap;
+use axum_typed_routing::route;
+
+#[route(GET "/balances/:id" { cached: { ttl: 60s } })]
+async fn balance(id: u32, headers: HeaderMap) -> String {
+    format!("{id} {headers:?}")
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use axum_typed_routing::api_route;
+
+#[api_route(GET "/items" { id: "list-items" })]
+async fn list_items() {}
+
+#[api_route(GET "/products" { id: "list-items" })]
+async fn list_products() {}
+
+fn main() {}
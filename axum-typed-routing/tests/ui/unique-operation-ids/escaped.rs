@@ -0,0 +1,13 @@
+use axum_typed_routing::api_route;
+
+// Different ids, which would share a name if `_` wasn't escaped like `-`.
+#[api_route(GET "/items" { id: "a-b" })]
+async fn dashed() {}
+
+#[api_route(GET "/products" { id: "a_2d_b" })]
+async fn escaped() {}
+
+#[api_route(GET "/orders" { id: "a_x2d_b" })]
+async fn hex_escaped() {}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use axum_typed_routing::api_route;
+
+// The ids of different modules are checked when the routes are added to a router.
+mod items {
+    use super::*;
+
+    #[api_route(GET "/items" { id: "list" })]
+    pub async fn list() {}
+}
+
+mod products {
+    use super::*;
+
+    #[api_route(GET "/products" { id: "list" })]
+    pub async fn list() {}
+}
+
+fn main() {}